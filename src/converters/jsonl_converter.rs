@@ -2,6 +2,7 @@
 
 
 // src/converters/jsonl_converter.rs
+use crate::error::Result;
 use crate::parsers::documents_types::DocumentElement;
 use crate::converters::converter_types::{TrainingRecord, RecordMetadata};
 
@@ -10,191 +11,134 @@ pub fn export_to_jsonl(
     elements: &[DocumentElement], 
     source_file: &str,
     document_type: &str
-) -> Result<String, Box<dyn std::error::Error>> {
+) -> Result<String> {
     let mut jsonl_lines = Vec::new();
-    
+    // Location markers apply to every element that follows them
+    let mut location = Location::default();
+
     for element in elements {
-        let record = match element {
-            DocumentElement::Title { text } => {
-                TrainingRecord {
-                    text: text.clone(),
-                    element_type: "title".to_string(),
-                    metadata: RecordMetadata {
-                        source_file: source_file.to_string(),
-                        document_type: document_type.to_string(),
-                        content_length: text.len(),
-                        language: None,
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    },
-                }
+        let (text, element_type, language) = match element {
+            DocumentElement::Page { number } => {
+                location.page = Some(*number);
+                continue;
             },
-            DocumentElement::Description { text } => {
-                TrainingRecord {
-                    text: text.clone(),
-                    element_type: "description".to_string(),
-                    metadata: RecordMetadata {
-                        source_file: source_file.to_string(),
-                        document_type: document_type.to_string(),
-                        content_length: text.len(),
-                        language: None,
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    },
-                }
+            DocumentElement::Slide { number } => {
+                location.slide = Some(*number);
+                continue;
             },
-            DocumentElement::Heading { level, text } => {
-                TrainingRecord {
-                    text: text.clone(),
-                    element_type: format!("heading_{}", level),
-                    metadata: RecordMetadata {
-                        source_file: source_file.to_string(),
-                        document_type: document_type.to_string(),
-                        content_length: text.len(),
-                        language: None,
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    },
-                }
+            DocumentElement::Sheet { name, range } => {
+                location.sheet = Some((name.clone(), range.clone()));
+                continue;
             },
-            DocumentElement::Paragraph { text } => {
-                TrainingRecord {
-                    text: text.clone(),
-                    element_type: "paragraph".to_string(),
-                    metadata: RecordMetadata {
-                        source_file: source_file.to_string(),
-                        document_type: document_type.to_string(),
-                        content_length: text.len(),
-                        language: None,
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    },
-                }
+            DocumentElement::Chapter { number, title } => {
+                location.chapter = Some((*number, title.clone()));
+                continue;
             },
-            DocumentElement::Blockquote { text } => {
-                TrainingRecord {
-                    text: text.clone(),
-                    element_type: "blockquote".to_string(),
-                    metadata: RecordMetadata {
-                        source_file: source_file.to_string(),
-                        document_type: document_type.to_string(),
-                        content_length: text.len(),
-                        language: None,
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    },
-                }
+            DocumentElement::Cell { index } => {
+                location.cell = Some(*index);
+                continue;
+            },
+            DocumentElement::CodeItem { kind, name, start_line, end_line } => {
+                location.item = Some((kind.clone(), name.clone(), *start_line, *end_line));
+                continue;
+            },
+            DocumentElement::Cue { start_ms, end_ms, speaker } => {
+                location.cue = Some((*start_ms, *end_ms, speaker.clone()));
+                continue;
+            },
+            // Messages, wiki pages and captures start a new document inside the file:
+            // nothing located in the previous one carries over
+            DocumentElement::Message { index, id, date, in_reply_to, references } => {
+                location = Location::default();
+                location.message = Some((*index, id.clone(), date.clone(), in_reply_to.clone(), references.clone()));
+                continue;
             },
+            DocumentElement::WikiPage { title, namespace, revision_id } => {
+                location = Location::default();
+                location.wiki_page = Some((title.clone(), *namespace, *revision_id));
+                continue;
+            },
+            DocumentElement::Capture { uri, date } => {
+                location = Location::default();
+                location.capture = Some((uri.clone(), date.clone()));
+                continue;
+            },
+            DocumentElement::Title { text } => (text.clone(), "title".to_string(), None),
+            DocumentElement::Description { text } => (text.clone(), "description".to_string(), None),
+            DocumentElement::Heading { level, text } => (text.clone(), format!("heading_{}", level), None),
+            DocumentElement::Paragraph { text } => (text.clone(), "paragraph".to_string(), None),
+            DocumentElement::Blockquote { text } => (text.clone(), "blockquote".to_string(), None),
             DocumentElement::List { items, ordered } => {
-                let list_text = items.join(" | ");
-                TrainingRecord {
-                    text: list_text.clone(),
-                    element_type: if *ordered { "ordered_list".to_string() } else { "unordered_list".to_string() },
-                    metadata: RecordMetadata {
-                        source_file: source_file.to_string(),
-                        document_type: document_type.to_string(),
-                        content_length: list_text.len(),
-                        language: None,
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    },
-                }
+                let element_type = if *ordered { "ordered_list" } else { "unordered_list" };
+                (items.join(" | "), element_type.to_string(), None)
             },
             DocumentElement::Code { code, language, inline } => {
-                TrainingRecord {
-                    text: code.clone(),
-                    element_type: if *inline { "inline_code".to_string() } else { "code_block".to_string() },
-                    metadata: RecordMetadata {
-                        source_file: source_file.to_string(),
-                        document_type: document_type.to_string(),
-                        content_length: code.len(),
-                        language: language.clone(),
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    },
-                }
-            },
-            DocumentElement::Link { text, url } => {
-                let link_text = format!("{} -> {}", text, url);
-                TrainingRecord {
-                    text: link_text.clone(),
-                    element_type: "link".to_string(),
-                    metadata: RecordMetadata {
-                        source_file: source_file.to_string(),
-                        document_type: document_type.to_string(),
-                        content_length: link_text.len(),
-                        language: None,
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    },
-                }
+                let element_type = if *inline { "inline_code" } else { "code_block" };
+                (code.clone(), element_type.to_string(), language.clone())
             },
+            DocumentElement::Link { text, url } => (format!("{} -> {}", text, url), "link".to_string(), None),
             DocumentElement::Image { alt, url } => {
                 let image_text = if let Some(url) = url {
                     format!("{} ({})", alt, url)
                 } else {
                     alt.clone()
                 };
-                TrainingRecord {
-                    text: image_text.clone(),
-                    element_type: "image".to_string(),
-                    metadata: RecordMetadata {
-                        source_file: source_file.to_string(),
-                        document_type: document_type.to_string(),
-                        content_length: image_text.len(),
-                        language: None,
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    },
-                }
+                (image_text, "image".to_string(), None)
             },
+            DocumentElement::SpeakerNotes { text } => (text.clone(), "speaker_notes".to_string(), None),
+            DocumentElement::CodeOutput { text, kind } => (text.clone(), format!("output_{}", kind), None),
+            DocumentElement::QuotedText { text } => (text.clone(), "quoted_text".to_string(), None),
+            DocumentElement::Math { tex, .. } => (tex.clone(), "math".to_string(), None),
             DocumentElement::Table { headers, rows } => {
                 let mut table_text = format!("Headers: {} | ", headers.join(", "));
                 for row in rows {
                     table_text.push_str(&format!("Row: {} | ", row.join(", ")));
                 }
-                TrainingRecord {
-                    text: table_text.clone(),
-                    element_type: "table".to_string(),
-                    metadata: RecordMetadata {
-                        source_file: source_file.to_string(),
-                        document_type: document_type.to_string(),
-                        content_length: table_text.len(),
-                        language: None,
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    },
-                }
-            },
-            DocumentElement::Keywords { text } => {
-                TrainingRecord {
-                    text: text.clone(),
-                    element_type: "keywords".to_string(),
-                    metadata: RecordMetadata {
-                        source_file: source_file.to_string(),
-                        document_type: document_type.to_string(),
-                        content_length: text.len(),
-                        language: None,
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    },
-                }
-            },
-            DocumentElement::Author { text } => {
-                TrainingRecord {
-                    text: text.clone(),
-                    element_type: "author".to_string(),
-                    metadata: RecordMetadata {
-                        source_file: source_file.to_string(),
-                        document_type: document_type.to_string(),
-                        content_length: text.len(),
-                        language: None,
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    },
-                }
+                (table_text, "table".to_string(), None)
             },
-            DocumentElement::Language { text } => {
-                TrainingRecord {
-                    text: text.clone(),
-                    element_type: "language".to_string(),
-                    metadata: RecordMetadata {
-                        source_file: source_file.to_string(),
-                        document_type: document_type.to_string(),
-                        content_length: text.len(),
-                        language: Some(text.clone()),
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                    },
-                }
+            DocumentElement::Keywords { text } => (text.clone(), "keywords".to_string(), None),
+            DocumentElement::Author { text } => (text.clone(), "author".to_string(), None),
+            DocumentElement::Language { text } => (text.clone(), "language".to_string(), Some(text.clone())),
+            DocumentElement::Creator { text } => (text.clone(), "creator".to_string(), None),
+            DocumentElement::Producer { text } => (text.clone(), "producer".to_string(), None),
+            DocumentElement::CreationDate { text } => (text.clone(), "creation_date".to_string(), None),
+            DocumentElement::ModificationDate { text } => (text.clone(), "modification_date".to_string(), None),
+        };
+
+        let record = TrainingRecord {
+            metadata: RecordMetadata {
+                source_file: source_file.to_string(),
+                document_type: document_type.to_string(),
+                content_length: text.len(),
+                language,
+                page: location.page,
+                slide: location.slide,
+                sheet: location.sheet.as_ref().map(|(name, _)| name.clone()),
+                cell_range: location.sheet.as_ref().map(|(_, range)| range.clone()),
+                chapter: location.chapter.as_ref().map(|(number, _)| *number),
+                chapter_title: location.chapter.as_ref().and_then(|(_, title)| title.clone()),
+                cell: location.cell,
+                item_kind: location.item.as_ref().map(|(kind, _, _, _)| kind.clone()),
+                item_name: location.item.as_ref().and_then(|(_, name, _, _)| name.clone()),
+                line_start: location.item.as_ref().map(|(_, _, start, _)| *start),
+                line_end: location.item.as_ref().map(|(_, _, _, end)| *end),
+                message: location.message.as_ref().map(|(index, _, _, _, _)| *index),
+                message_id: location.message.as_ref().and_then(|(_, id, _, _, _)| id.clone()),
+                message_date: location.message.as_ref().and_then(|(_, _, date, _, _)| date.clone()),
+                in_reply_to: location.message.as_ref().and_then(|(_, _, _, in_reply_to, _)| in_reply_to.clone()),
+                references: location.message.as_ref().map(|(_, _, _, _, references)| references.clone()).unwrap_or_default(),
+                start_ms: location.cue.as_ref().map(|(start, _, _)| *start),
+                end_ms: location.cue.as_ref().map(|(_, end, _)| *end),
+                speaker: location.cue.as_ref().and_then(|(_, _, speaker)| speaker.clone()),
+                page_title: location.wiki_page.as_ref().map(|(title, _, _)| title.clone()),
+                namespace: location.wiki_page.as_ref().map(|(_, namespace, _)| *namespace),
+                revision_id: location.wiki_page.as_ref().and_then(|(_, _, revision_id)| *revision_id),
+                target_uri: location.capture.as_ref().map(|(uri, _)| uri.clone()),
+                capture_date: location.capture.as_ref().and_then(|(_, date)| date.clone()),
+                timestamp: chrono::Utc::now().to_rfc3339(),
             },
+            text,
+            element_type,
         };
         
         let json_line = serde_json::to_string(&record)?;
@@ -203,4 +147,68 @@ pub fn export_to_jsonl(
     
     Ok(jsonl_lines.join("\n"))
 }
-    
\ No newline at end of file
+
+// Message index, Message-ID, date, In-Reply-To and References
+type MessageMarker = (u32, Option<String>, Option<String>, Option<String>, Vec<String>);
+
+// Markers in effect at the current element, as the fields they fill in
+#[derive(Default)]
+struct Location {
+    page: Option<u32>,
+    slide: Option<u32>,
+    sheet: Option<(String, String)>,
+    chapter: Option<(u32, Option<String>)>,
+    cell: Option<u32>,
+    item: Option<(String, Option<String>, u32, u32)>,
+    message: Option<MessageMarker>,
+    cue: Option<(u64, u64, Option<String>)>,
+    wiki_page: Option<(String, i32, Option<u64>)>,
+    capture: Option<(String, Option<String>)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(elements: &[DocumentElement]) -> Vec<serde_json::Value> {
+        export_to_jsonl(elements, "archive", "warc").unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn markers_reset_at_each_capture() {
+        let paragraph = |text: &str| DocumentElement::Paragraph { text: text.to_string() };
+        let capture = |uri: &str| DocumentElement::Capture { uri: uri.to_string(), date: None };
+        let records = records(&[
+            capture("http://a.example/"),
+            DocumentElement::Page { number: 2 },
+            paragraph("first"),
+            capture("http://b.example/"),
+            paragraph("second"),
+        ]);
+
+        assert_eq!(records[0]["metadata"]["page"], 2);
+        assert_eq!(records[1]["metadata"]["target_uri"], "http://b.example/");
+        assert!(records[1]["metadata"].get("page").is_none());
+    }
+
+    #[test]
+    fn markers_reset_at_each_wiki_page_and_message() {
+        let paragraph = |text: &str| DocumentElement::Paragraph { text: text.to_string() };
+        let records = records(&[
+            DocumentElement::Message { index: 0, id: Some("a@x".to_string()), date: None, in_reply_to: None, references: Vec::new() },
+            DocumentElement::Cue { start_ms: 0, end_ms: 10, speaker: None },
+            paragraph("mail"),
+            DocumentElement::WikiPage { title: "Page".to_string(), namespace: 0, revision_id: Some(7) },
+            paragraph("wiki"),
+        ]);
+
+        assert_eq!(records[0]["metadata"]["message_id"], "a@x");
+        assert_eq!(records[0]["metadata"]["start_ms"], 0);
+        let metadata = &records[1]["metadata"];
+        assert_eq!(metadata["page_title"], "Page");
+        assert!(metadata.get("message_id").is_none() && metadata.get("start_ms").is_none());
+    }
+}
@@ -1,17 +1,25 @@
 // txt_converter.rs
 
+use crate::error::Result;
 use crate::parsers::documents_types::DocumentElement;
 
 pub fn export_to_txt(
     elements: &[DocumentElement], 
     source_file: &str
-) -> Result<String, Box<dyn std::error::Error>> {
-    let mut txt_lines = Vec::new();
-    
+) -> Result<String> {
     // Add header
-    txt_lines.push(format!("# Document: {}", source_file));
-    txt_lines.push("".to_string()); // Empty line
-    
+    Ok(format!("{}{}", txt_header(source_file), export_elements_to_txt(elements)?))
+}
+
+// First lines of the text export of `source_file`
+pub fn txt_header(source_file: &str) -> String {
+    format!("# Document: {}\n\n", source_file)
+}
+
+// Text of the elements alone, without the header: records of a streamed file are exported one after another
+pub fn export_elements_to_txt(elements: &[DocumentElement]) -> Result<String> {
+    let mut txt_lines = Vec::new();
+
     for element in elements {
         let txt_line = match element {
             DocumentElement::Title { text } => {
@@ -84,6 +92,90 @@ pub fn export_to_txt(
             DocumentElement::Language { text } => {
                 format!("LANGUAGE: {}", text)
             },
+            DocumentElement::Page { number } => {
+                format!("[PAGE {}]", number)
+            },
+            DocumentElement::Slide { number } => {
+                format!("[SLIDE {}]", number)
+            },
+            DocumentElement::Sheet { name, range } => {
+                format!("[SHEET {}!{}]", name, range)
+            },
+            DocumentElement::Cell { index } => {
+                format!("[CELL {}]", index)
+            },
+            DocumentElement::Message { index, id, .. } => {
+                if let Some(id) = id {
+                    format!("[MESSAGE {}: <{}>]", index, id)
+                } else {
+                    format!("[MESSAGE {}]", index)
+                }
+            },
+            DocumentElement::Cue { start_ms, end_ms, speaker } => {
+                let timing = format!("{} --> {}", format_milliseconds(*start_ms), format_milliseconds(*end_ms));
+                if let Some(speaker) = speaker {
+                    format!("[{}] {}:", timing, speaker)
+                } else {
+                    format!("[{}]", timing)
+                }
+            },
+            DocumentElement::Math { tex, environment } => {
+                if let Some(environment) = environment {
+                    format!("MATH ({}):\n{}", environment, tex)
+                } else {
+                    format!("MATH:\n{}", tex)
+                }
+            },
+            DocumentElement::QuotedText { text } => {
+                let quoted: Vec<String> = text.lines().map(|line| format!("> {}", line)).collect();
+                quoted.join("\n")
+            },
+            DocumentElement::CodeItem { kind, name, start_line, end_line } => {
+                if let Some(name) = name {
+                    format!("[ITEM {} {}, lines {}-{}]", kind, name, start_line, end_line)
+                } else {
+                    format!("[ITEM {}, lines {}-{}]", kind, start_line, end_line)
+                }
+            },
+            DocumentElement::WikiPage { title, namespace, revision_id } => {
+                if let Some(revision_id) = revision_id {
+                    format!("[WIKI PAGE {} (namespace {}, revision {})]", title, namespace, revision_id)
+                } else {
+                    format!("[WIKI PAGE {} (namespace {})]", title, namespace)
+                }
+            },
+            DocumentElement::Capture { uri, date } => {
+                if let Some(date) = date {
+                    format!("[CAPTURE {} ({})]", uri, date)
+                } else {
+                    format!("[CAPTURE {}]", uri)
+                }
+            },
+            DocumentElement::Chapter { number, title } => {
+                if let Some(title) = title {
+                    format!("[CHAPTER {}: {}]", number, title)
+                } else {
+                    format!("[CHAPTER {}]", number)
+                }
+            },
+            DocumentElement::SpeakerNotes { text } => {
+                format!("NOTES: {}", text)
+            },
+            DocumentElement::CodeOutput { text, kind } => {
+                format!("OUTPUT ({}):\n{}", kind, text)
+            },
+            DocumentElement::Creator { text } => {
+                format!("CREATOR: {}", text)
+            },
+            DocumentElement::Producer { text } => {
+                format!("PRODUCER: {}", text)
+            },
+            DocumentElement::CreationDate { text } => {
+                format!("CREATED: {}", text)
+            },
+            DocumentElement::ModificationDate { text } => {
+                format!("MODIFIED: {}", text)
+            },
         };
         
         txt_lines.push(txt_line);
@@ -92,3 +184,9 @@ pub fn export_to_txt(
     
     Ok(txt_lines.join("\n"))
 }
+
+// 3723500 -> "01:02:03.500"
+fn format_milliseconds(milliseconds: u64) -> String {
+    let seconds = milliseconds / 1000;
+    format!("{:02}:{:02}:{:02}.{:03}", seconds / 3600, seconds / 60 % 60, seconds % 60, milliseconds % 1000)
+}
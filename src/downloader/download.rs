@@ -1,9 +1,12 @@
-use std::fs;
+use std::fs::{self, File};
+use std::io;
 use std::path::PathBuf;
+use crate::error::{OxidocError, Result};
 
-pub fn save_to_downloads_jsonl(jsonl_content: &str, filename: &str) -> Result<String, Box<dyn std::error::Error>> {
+pub fn save_to_downloads_jsonl(jsonl_content: &str, filename: &str) -> Result<String> {
     // Get Downloads directory
-    let home_dir = std::env::var("HOME")?;
+    let home_dir = std::env::var("HOME")
+        .map_err(|_| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))?;
     let downloads_path = PathBuf::from(home_dir).join("Downloads");
     
     // Create filename with .jsonl extension if not present
@@ -16,15 +19,16 @@ pub fn save_to_downloads_jsonl(jsonl_content: &str, filename: &str) -> Result<St
     let file_path = downloads_path.join(&filename);
     
     // Save file
-    fs::write(&file_path, jsonl_content)?;
+    fs::write(&file_path, jsonl_content).map_err(|source| OxidocError::io(&file_path, source))?;
     
     // Return path
     Ok(file_path.to_string_lossy().to_string())
 }
 
-pub fn save_to_downloads_txt(txt_content: &str, filename: &str) -> Result<String, Box<dyn std::error::Error>> {
+pub fn save_to_downloads_txt(txt_content: &str, filename: &str) -> Result<String> {
     // Get Downloads directory
-    let home_dir = std::env::var("HOME")?;
+    let home_dir = std::env::var("HOME")
+        .map_err(|_| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))?;
     let downloads_path = PathBuf::from(home_dir).join("Downloads");
     
     // Create filename with .txt extension if not present
@@ -37,8 +41,26 @@ pub fn save_to_downloads_txt(txt_content: &str, filename: &str) -> Result<String
     let file_path = downloads_path.join(&filename);
     
     // Save file
-    fs::write(&file_path, txt_content)?;
+    fs::write(&file_path, txt_content).map_err(|source| OxidocError::io(&file_path, source))?;
     
     // Return path
     Ok(file_path.to_string_lossy().to_string())
-}
\ No newline at end of file
+}
+
+// Create (or truncate) `filename.extension` in Downloads for output written as it is produced
+pub fn create_in_downloads(filename: &str, extension: &str) -> Result<(File, PathBuf)> {
+    let home_dir = std::env::var("HOME")
+        .map_err(|_| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))?;
+    let downloads_path = PathBuf::from(home_dir).join("Downloads");
+
+    let suffix = format!(".{}", extension);
+    let filename = if filename.ends_with(&suffix) {
+        filename.to_string()
+    } else {
+        format!("{}{}", filename, suffix)
+    };
+
+    let file_path = downloads_path.join(&filename);
+    let file = File::create(&file_path).map_err(|source| OxidocError::io(&file_path, source))?;
+    Ok((file, file_path))
+}
@@ -0,0 +1,150 @@
+// error.rs
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub type Result<T> = std::result::Result<T, OxidocError>;
+
+// Every failure oxidoc can report. `path` is the source file when one is known
+// (documents parsed from bytes or readers have none).
+#[derive(Debug)]
+pub enum OxidocError {
+    // Reading or writing a file failed (a missing input file ends up here)
+    Io { path: Option<PathBuf>, source: io::Error },
+    // No parser handles this format
+    UnsupportedFormat { path: Option<PathBuf>, format: String },
+    // The input is not a valid document of its format
+    MalformedInput { path: Option<PathBuf>, message: String },
+    // Text could not be decoded or encoded
+    Encoding { path: Option<PathBuf>, message: String },
+    // pdf-extract could not read the PDF
+    Pdf { path: Option<PathBuf>, source: pdf_extract::OutputError },
+    // A zip container (DOCX, PPTX, EPUB...) could not be read
+    Archive { path: Option<PathBuf>, source: zip::result::ZipError },
+    // A record could not be serialized for export
+    Serialization { source: serde_json::Error },
+}
+
+impl OxidocError {
+    pub fn io(path: &Path, source: io::Error) -> OxidocError {
+        OxidocError::Io { path: Some(path.to_path_buf()), source }
+    }
+
+    pub fn unsupported_format(format: impl Into<String>) -> OxidocError {
+        OxidocError::UnsupportedFormat { path: None, format: format.into() }
+    }
+
+    pub fn malformed(message: impl Into<String>) -> OxidocError {
+        OxidocError::MalformedInput { path: None, message: message.into() }
+    }
+
+    pub fn encoding(message: impl Into<String>) -> OxidocError {
+        OxidocError::Encoding { path: None, message: message.into() }
+    }
+
+    // Attach the source file, keeping one that was already set
+    pub fn with_path(mut self, source_path: &Path) -> OxidocError {
+        let slot = match &mut self {
+            OxidocError::Io { path, .. }
+            | OxidocError::UnsupportedFormat { path, .. }
+            | OxidocError::MalformedInput { path, .. }
+            | OxidocError::Encoding { path, .. }
+            | OxidocError::Pdf { path, .. }
+            | OxidocError::Archive { path, .. } => path,
+            OxidocError::Serialization { .. } => return self,
+        };
+        if slot.is_none() {
+            *slot = Some(source_path.to_path_buf());
+        }
+        self
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            OxidocError::Io { path, .. }
+            | OxidocError::UnsupportedFormat { path, .. }
+            | OxidocError::MalformedInput { path, .. }
+            | OxidocError::Encoding { path, .. }
+            | OxidocError::Pdf { path, .. }
+            | OxidocError::Archive { path, .. } => path.as_deref(),
+            OxidocError::Serialization { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for OxidocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OxidocError::Io { source, .. } => write!(f, "I/O error: {}", source)?,
+            OxidocError::UnsupportedFormat { format, .. } => write!(f, "unsupported format: {}", format)?,
+            OxidocError::MalformedInput { message, .. } => write!(f, "malformed input: {}", message)?,
+            OxidocError::Encoding { message, .. } => write!(f, "encoding error: {}", message)?,
+            OxidocError::Pdf { source, .. } => write!(f, "PDF extraction failed: {}", source)?,
+            OxidocError::Archive { source, .. } => write!(f, "invalid archive: {}", source)?,
+            OxidocError::Serialization { source } => write!(f, "serialization failed: {}", source)?,
+        }
+        if let Some(path) = self.path() {
+            write!(f, " ({})", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for OxidocError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OxidocError::Io { source, .. } => Some(source),
+            OxidocError::Pdf { source, .. } => Some(source),
+            OxidocError::Archive { source, .. } => Some(source),
+            OxidocError::Serialization { source } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for OxidocError {
+    fn from(source: io::Error) -> OxidocError {
+        OxidocError::Io { path: None, source }
+    }
+}
+
+impl From<pdf_extract::OutputError> for OxidocError {
+    fn from(source: pdf_extract::OutputError) -> OxidocError {
+        OxidocError::Pdf { path: None, source }
+    }
+}
+
+impl From<zip::result::ZipError> for OxidocError {
+    fn from(source: zip::result::ZipError) -> OxidocError {
+        OxidocError::Archive { path: None, source }
+    }
+}
+
+impl From<serde_json::Error> for OxidocError {
+    fn from(source: serde_json::Error) -> OxidocError {
+        OxidocError::Serialization { source }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::registry::ParserRegistry;
+
+    #[test]
+    fn missing_files_are_io_errors_naming_the_file() {
+        let path = Path::new("test_files/does_not_exist.md");
+        let error = ParserRegistry::with_defaults().parse_path(path, &()).unwrap_err();
+        assert!(matches!(error, OxidocError::Io { .. }));
+        assert_eq!(error.path(), Some(path));
+        assert!(error.to_string().ends_with("(test_files/does_not_exist.md)"));
+    }
+
+    #[test]
+    fn the_first_path_attached_is_kept() {
+        let error = OxidocError::malformed("bad").with_path(Path::new("inner.xml")).with_path(Path::new("outer.docx"));
+        assert_eq!(error.path(), Some(Path::new("inner.xml")));
+        assert_eq!(error.to_string(), "malformed input: bad (inner.xml)");
+    }
+}
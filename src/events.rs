@@ -0,0 +1,174 @@
+// events.rs
+
+use std::ops::AddAssign;
+use std::path::PathBuf;
+use crate::parsers::documents_types::DocumentElement;
+
+// What happens while a document is parsed. The library never prints; it reports these
+// to an EventSink so the CLI can render them and services can log or ignore them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseEvent {
+    Started { path: Option<PathBuf>, format: String },
+    // Something was skipped or degraded, but parsing went on
+    Warning { path: Option<PathBuf>, message: String },
+    Finished { path: Option<PathBuf>, format: String, counts: ElementCounts },
+}
+
+// Receives parse events. `()` discards them; closures work as sinks too.
+pub trait EventSink {
+    fn emit(&self, event: ParseEvent);
+}
+
+impl EventSink for () {
+    fn emit(&self, _event: ParseEvent) {}
+}
+
+impl<F: Fn(ParseEvent)> EventSink for F {
+    fn emit(&self, event: ParseEvent) {
+        self(event)
+    }
+}
+
+// Number of parsed elements per kind
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ElementCounts {
+    pub metadata: usize,
+    pub headings: usize,
+    pub paragraphs: usize,
+    pub lists: usize,
+    pub tables: usize,
+    pub code_blocks: usize,
+    pub links: usize,
+    pub images: usize,
+    pub blockquotes: usize,
+    pub speaker_notes: usize,
+    pub code_outputs: usize,
+    pub quoted_texts: usize,
+    pub math: usize,
+    pub pages: usize,
+    pub slides: usize,
+    pub sheets: usize,
+    pub chapters: usize,
+    pub cells: usize,
+    pub code_items: usize,
+    pub messages: usize,
+    pub cues: usize,
+    pub wiki_pages: usize,
+    pub captures: usize,
+    pub total: usize,
+}
+
+impl ElementCounts {
+    pub fn from_elements(elements: &[DocumentElement]) -> ElementCounts {
+        let mut counts = ElementCounts { total: elements.len(), ..ElementCounts::default() };
+        // A sheet split into several tables has one marker per table
+        let mut last_sheet = None;
+
+        for element in elements {
+            match element {
+                DocumentElement::Title { .. } |
+                DocumentElement::Description { .. } |
+                DocumentElement::Keywords { .. } |
+                DocumentElement::Author { .. } |
+                DocumentElement::Language { .. } |
+                DocumentElement::Creator { .. } |
+                DocumentElement::Producer { .. } |
+                DocumentElement::CreationDate { .. } |
+                DocumentElement::ModificationDate { .. } => counts.metadata += 1,
+                DocumentElement::Heading { .. } => counts.headings += 1,
+                DocumentElement::Paragraph { .. } => counts.paragraphs += 1,
+                DocumentElement::List { .. } => counts.lists += 1,
+                DocumentElement::Table { .. } => counts.tables += 1,
+                DocumentElement::Code { .. } => counts.code_blocks += 1,
+                DocumentElement::Link { .. } => counts.links += 1,
+                DocumentElement::Image { .. } => counts.images += 1,
+                DocumentElement::Blockquote { .. } => counts.blockquotes += 1,
+                DocumentElement::SpeakerNotes { .. } => counts.speaker_notes += 1,
+                DocumentElement::CodeOutput { .. } => counts.code_outputs += 1,
+                DocumentElement::QuotedText { .. } => counts.quoted_texts += 1,
+                DocumentElement::Math { .. } => counts.math += 1,
+                DocumentElement::Page { .. } => counts.pages += 1,
+                DocumentElement::Slide { .. } => counts.slides += 1,
+                DocumentElement::Sheet { name, .. } => {
+                    if last_sheet != Some(name) {
+                        counts.sheets += 1;
+                        last_sheet = Some(name);
+                    }
+                },
+                DocumentElement::Chapter { .. } => counts.chapters += 1,
+                DocumentElement::Cell { .. } => counts.cells += 1,
+                DocumentElement::CodeItem { .. } => counts.code_items += 1,
+                DocumentElement::Message { .. } => counts.messages += 1,
+                DocumentElement::Cue { .. } => counts.cues += 1,
+                DocumentElement::WikiPage { .. } => counts.wiki_pages += 1,
+                DocumentElement::Capture { .. } => counts.captures += 1,
+            }
+        }
+
+        counts
+    }
+}
+
+// Counts of a document parsed record by record are the sums of the records' counts
+impl AddAssign for ElementCounts {
+    fn add_assign(&mut self, other: ElementCounts) {
+        self.metadata += other.metadata;
+        self.headings += other.headings;
+        self.paragraphs += other.paragraphs;
+        self.lists += other.lists;
+        self.tables += other.tables;
+        self.code_blocks += other.code_blocks;
+        self.links += other.links;
+        self.images += other.images;
+        self.blockquotes += other.blockquotes;
+        self.speaker_notes += other.speaker_notes;
+        self.code_outputs += other.code_outputs;
+        self.quoted_texts += other.quoted_texts;
+        self.math += other.math;
+        self.pages += other.pages;
+        self.slides += other.slides;
+        self.sheets += other.sheets;
+        self.chapters += other.chapters;
+        self.cells += other.cells;
+        self.code_items += other.code_items;
+        self.messages += other.messages;
+        self.cues += other.cues;
+        self.wiki_pages += other.wiki_pages;
+        self.captures += other.captures;
+        self.total += other.total;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::registry::ParserRegistry;
+    use std::cell::RefCell;
+    use std::path::Path;
+
+    #[test]
+    fn parsing_reports_start_and_counts_to_the_sink() {
+        let events = RefCell::new(Vec::new());
+        let sink = |event: ParseEvent| events.borrow_mut().push(event);
+        let elements = ParserRegistry::with_defaults().parse_path(Path::new("test_files/sample.md"), &sink).unwrap();
+
+        let events = events.into_inner();
+        assert!(matches!(&events[0], ParseEvent::Started { format, .. } if format == "markdown"));
+        match events.last() {
+            Some(ParseEvent::Finished { counts, .. }) => assert_eq!(*counts, ElementCounts::from_elements(&elements)),
+            other => panic!("expected Finished, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn counts_of_records_add_up() {
+        let sheet = |name: &str| DocumentElement::Sheet { name: name.to_string(), range: "A1:B2".to_string() };
+        let table = DocumentElement::Table { headers: Vec::new(), rows: Vec::new() };
+        let elements = vec![sheet("One"), table.clone(), sheet("One"), table.clone(), sheet("Two"), table];
+
+        let mut counts = ElementCounts::from_elements(&elements[..4]);
+        counts += ElementCounts::from_elements(&elements[4..]);
+        assert_eq!((counts.sheets, counts.tables, counts.total), (2, 3, 6));
+        assert_eq!(counts, ElementCounts::from_elements(&elements));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod error;
+pub mod events;
 pub mod parsers;
 pub mod converters;
 pub mod downloader;
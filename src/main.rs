@@ -1,10 +1,12 @@
 // main.rs
 
-use oxidoc::parsers::html_parser::parse_html_file;
-use oxidoc::parsers::pdf_parser::parse_pdf_file;
+use oxidoc::events::ParseEvent;
+use oxidoc::parsers::registry::ParserRegistry;
 use oxidoc::converters::jsonl_converter::export_to_jsonl;
-use oxidoc::converters::txt_converter::export_to_txt;
-use oxidoc::downloader::download::{save_to_downloads_jsonl, save_to_downloads_txt};
+use oxidoc::converters::txt_converter::{export_elements_to_txt, txt_header};
+use oxidoc::downloader::download::create_in_downloads;
+use oxidoc::error::OxidocError;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -21,58 +23,108 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 \x1b[0m"
     );
 
-    // Test HTML parsing
-    println!("\n🔍 Testing HTML Parser...");
-    let html_file_path = Path::new("test_files/sample.html");
-    let html_elements = parse_html_file(html_file_path)?;
-    
-    // Export HTML to JSONL
-    let html_jsonl_content = export_to_jsonl(&html_elements, "sample.html", "html")?;
-    let html_jsonl_path = save_to_downloads_jsonl(&html_jsonl_content, "oxidoc_html_sample")?;
-    
-    // Export HTML to TXT
-    let html_txt_content = export_to_txt(&html_elements, "sample.html")?;
-    let html_txt_path = save_to_downloads_txt(&html_txt_content, "oxidoc_html_sample")?;
-    
-    println!("\n✅ HTML Files saved:");
-    println!("   📄 JSONL: {}", html_jsonl_path);
-    println!("   📝 TXT: {}", html_txt_path);
-    
-    // Test PDF parsing
-    println!("\n🔍 Testing PDF Parser...");
-    let pdf_file_path = Path::new("test_files/pdf_test.pdf");
-    let pdf_elements = parse_pdf_file(pdf_file_path)?;
-    
-    // Export PDF to JSONL
-    let pdf_jsonl_content = export_to_jsonl(&pdf_elements, "pdf_test.pdf", "pdf")?;
-    let pdf_jsonl_path = save_to_downloads_jsonl(&pdf_jsonl_content, "oxidoc_pdf_sample")?;
-    
-    // Export PDF to TXT
-    let pdf_txt_content = export_to_txt(&pdf_elements, "pdf_test.pdf")?;
-    let pdf_txt_path = save_to_downloads_txt(&pdf_txt_content, "oxidoc_pdf_sample")?;
-    
-    println!("\n✅ PDF Files saved:");
-    println!("   📄 JSONL: {}", pdf_jsonl_path);
-    println!("   📝 TXT: {}", pdf_txt_path);
-    
-    // Show previews
-    println!("\n📄 HTML JSONL Preview (first 3 lines):");
-    let html_lines: Vec<&str> = html_jsonl_content.lines().take(3).collect();
-    for (i, line) in html_lines.iter().enumerate() {
-        println!("{}. {}", i + 1, line);
-    }
-    
-    println!("\n📄 PDF JSONL Preview (first 3 lines):");
-    let pdf_lines: Vec<&str> = pdf_jsonl_content.lines().take(3).collect();
-    for (i, line) in pdf_lines.iter().enumerate() {
-        println!("{}. {}", i + 1, line);
-    }
-    
-    println!("\n📝 PDF TXT Preview (first 10 lines):");
-    let pdf_txt_lines: Vec<&str> = pdf_txt_content.lines().take(10).collect();
-    for (i, line) in pdf_txt_lines.iter().enumerate() {
-        println!("{}. {}", i + 1, line);
+    // Parse every sample through the registry, which picks the parser from the file itself
+    let registry = ParserRegistry::with_defaults();
+    let sample_files = ["test_files/sample.html", "test_files/pdf_test.pdf", "test_files/sample.txt", "test_files/sample.md"];
+
+    for sample_file in sample_files {
+        let file_path = Path::new(sample_file);
+        let Some(parser) = registry.detect_path(file_path)? else {
+            println!("\n⚠️  No parser for {}", sample_file);
+            continue;
+        };
+        let format = parser.name().to_string();
+        let source_file = file_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+        println!("\n🔍 Testing {} Parser...", format.to_uppercase());
+        // Write each record (a wiki page, a web capture...) as soon as it is parsed,
+        // so archives never have to fit in memory
+        let (jsonl_file, jsonl_path) = create_in_downloads(&format!("oxidoc_{}_sample", format), "jsonl")?;
+        let (txt_file, txt_path) = create_in_downloads(&format!("oxidoc_{}_sample", format), "txt")?;
+        let mut jsonl_writer = BufWriter::new(jsonl_file);
+        let mut txt_writer = BufWriter::new(txt_file);
+        let mut jsonl_preview = Vec::new();
+        let mut txt_preview: Vec<String> = txt_header(&source_file).lines().map(str::to_string).collect();
+        txt_writer.write_all(txt_header(&source_file).as_bytes())
+            .map_err(|source| OxidocError::io(&txt_path, source))?;
+
+        registry.parse_path_records(file_path, &render_event, &mut |elements| {
+            let jsonl_content = export_to_jsonl(&elements, &source_file, &format)?;
+            for line in jsonl_content.lines() {
+                writeln!(jsonl_writer, "{}", line).map_err(|source| OxidocError::io(&jsonl_path, source))?;
+                if jsonl_preview.len() < 3 {
+                    jsonl_preview.push(line.to_string());
+                }
+            }
+
+            let txt_content = export_elements_to_txt(&elements)?;
+            writeln!(txt_writer, "{}", txt_content).map_err(|source| OxidocError::io(&txt_path, source))?;
+            txt_preview.extend(txt_content.lines().take(10usize.saturating_sub(txt_preview.len())).map(str::to_string));
+            Ok(())
+        })?;
+        jsonl_writer.flush().map_err(|source| OxidocError::io(&jsonl_path, source))?;
+        txt_writer.flush().map_err(|source| OxidocError::io(&txt_path, source))?;
+
+        println!("\n✅ {} Files saved:", format.to_uppercase());
+        println!("   📄 JSONL: {}", jsonl_path.display());
+        println!("   📝 TXT: {}", txt_path.display());
+
+        // Show previews
+        println!("\n📄 {} JSONL Preview (first 3 lines):", format.to_uppercase());
+        for (i, line) in jsonl_preview.iter().enumerate() {
+            println!("{}. {}", i + 1, line);
+        }
+
+        println!("\n📝 {} TXT Preview (first 10 lines):", format.to_uppercase());
+        for (i, line) in txt_preview.iter().enumerate() {
+            println!("{}. {}", i + 1, line);
+        }
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+// Print parser progress the way the CLI always has
+fn render_event(event: ParseEvent) {
+    match event {
+        ParseEvent::Started { path, format } => {
+            println!("\nOxidoc {} Parser", format.to_uppercase());
+            if let Some(path) = path {
+                println!("Parsing {} file: {:?}", format.to_uppercase(), path);
+            }
+        },
+        ParseEvent::Warning { path, message } => {
+            match path {
+                Some(path) => eprintln!("⚠️  {}: {}", path.display(), message),
+                None => eprintln!("⚠️  {}", message),
+            }
+        },
+        ParseEvent::Finished { counts, .. } => {
+            println!("\nParsing Results:");
+            println!("Metadata: {}", counts.metadata);
+            println!("Headings: {}", counts.headings);
+            println!("Paragraphs: {}", counts.paragraphs);
+            println!("Lists: {}", counts.lists);
+            println!("Tables: {}", counts.tables);
+            println!("Code blocks: {}", counts.code_blocks);
+            println!("Links: {}", counts.links);
+            println!("Images: {}", counts.images);
+            println!("Blockquotes: {}", counts.blockquotes);
+            println!("Speaker notes: {}", counts.speaker_notes);
+            println!("Code outputs: {}", counts.code_outputs);
+            println!("Quoted texts: {}", counts.quoted_texts);
+            println!("Math: {}", counts.math);
+            println!("Pages: {}", counts.pages);
+            println!("Slides: {}", counts.slides);
+            println!("Sheets: {}", counts.sheets);
+            println!("Chapters: {}", counts.chapters);
+            println!("Cells: {}", counts.cells);
+            println!("Code items: {}", counts.code_items);
+            println!("Messages: {}", counts.messages);
+            println!("Cues: {}", counts.cues);
+            println!("Wiki pages: {}", counts.wiki_pages);
+            println!("Captures: {}", counts.captures);
+            println!("Total elements: {}", counts.total);
+        },
+    }
+}
@@ -0,0 +1,1032 @@
+// asciidoc_parser.rs
+
+// AsciiDoc, as written for Asciidoctor. The header ("= Title", author and revision lines, ":name: value"
+// attribute entries) gives the metadata; "==" section titles become headings from level 1. Listing,
+// literal and fenced blocks become Code (with the language of "[source,lang]"), "|===" tables Tables,
+// "*"/"."/"::" lists Lists, quote blocks Blockquotes, and admonitions ("NOTE: ..." paragraphs and
+// "[NOTE]" blocks) Blockquotes headed by their kind. Inline markup is removed, attribute references
+// are replaced by their values, and link macros and URLs follow their block as Links.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::EventSink;
+use crate::parsers::documents_types::{fallback_title, Document, DocumentElement};
+use crate::parsers::html_parser::parse_html_str;
+use crate::parsers::markup::{
+    admonition_label, dedent, expand_tabs, indentation, is_blank, join_lines, push_list_item,
+    push_quote, split_csv, strip_emphasis, table, ADMONITIONS,
+};
+use crate::parsers::parser::Parser;
+use crate::parsers::txt_parser::{decode_text, warn_if_latin1};
+
+// Admonition paragraphs are written "NOTE: text"
+const ADMONITION_PARAGRAPHS: &[&str] = &["NOTE", "TIP", "IMPORTANT", "WARNING", "CAUTION"];
+
+// Block macros that show nothing of the document text
+const SKIPPED_MACROS: &[&str] = &["include::", "toc::", "video::", "audio::", "ifdef::", "ifndef::", "ifeval::", "endif::"];
+
+// Main parsing function
+pub fn parse_asciidoc_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    // Parse into the document tree, then flatten it in reading order
+    Ok(parse_asciidoc_document(path)?.to_elements())
+}
+
+// Parse an AsciiDoc file into the hierarchical document tree
+pub fn parse_asciidoc_document(path: &Path) -> Result<Document> {
+    parse_asciidoc_path(path, &())
+}
+
+fn parse_asciidoc_path(path: &Path, events: &dyn EventSink) -> Result<Document> {
+    // Read the file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+    let (source, encoding) = decode_text(&bytes);
+    warn_if_latin1(encoding, Some(path), events);
+
+    let mut document = parse_asciidoc_str(&source);
+    fallback_title(&mut document.metadata, path);
+    Ok(document)
+}
+
+pub fn parse_asciidoc_str(source: &str) -> Document {
+    let source = expand_tabs(source);
+    let lines: Vec<&str> = source.lines().collect();
+
+    let mut reader = Reader { attributes: HashMap::new() };
+    let (mut elements, body_start) = reader.header(&lines);
+    elements.extend(reader.blocks(&lines[body_start..]));
+
+    Document::from_elements(elements)
+}
+
+// AsciiDoc implementation of the common Parser trait
+pub struct AsciidocParser;
+
+impl Parser for AsciidocParser {
+    fn name(&self) -> &str {
+        "asciidoc"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["adoc", "asciidoc"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["text/asciidoc", "text/x-asciidoc"]
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        let (source, encoding) = decode_text(bytes);
+        warn_if_latin1(encoding, None, events);
+        Ok(parse_asciidoc_str(&source).to_elements())
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        Ok(parse_asciidoc_path(path, events)?.to_elements())
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to the header
+//-------------------------------------------------
+
+// The attribute list written above a block: "[source,python]", "[NOTE]", "[quote, Author]", "[%header,cols=2]"
+#[derive(Default)]
+struct BlockAttributes {
+    positional: Vec<String>,
+    named: HashMap<String, String>,
+    options: Vec<String>,
+}
+
+impl BlockAttributes {
+    fn parse(list: &str) -> BlockAttributes {
+        let mut attributes = BlockAttributes::default();
+        for (index, entry) in split_csv(list, ',').into_iter().enumerate() {
+            if let Some((name, value)) = entry.split_once('=') {
+                let value = value.trim().trim_matches('"').to_string();
+                match name.trim() {
+                    "options" | "opts" => attributes.options.extend(value.split(',').map(|option| option.trim().to_string())),
+                    name => {
+                        attributes.named.insert(name.to_string(), value);
+                    },
+                }
+                continue;
+            }
+            // The first positional attribute is the style, with "#id", ".role" and "%option" shorthands
+            let mut entry = entry.as_str();
+            if index == 0 {
+                let end = entry.find(['#', '.', '%']).unwrap_or(entry.len());
+                for option in entry[end..].split('%').skip(1) {
+                    attributes.options.push(option.split(['#', '.']).next().unwrap_or_default().to_string());
+                }
+                entry = &entry[..end];
+            }
+            attributes.positional.push(entry.to_string());
+        }
+        attributes
+    }
+
+    fn style(&self) -> &str {
+        self.positional.first().map_or("", String::as_str)
+    }
+
+    fn has_option(&self, option: &str) -> bool {
+        self.options.iter().any(|name| name == option)
+    }
+}
+
+struct Reader {
+    // Document attributes, for "{name}" references
+    attributes: HashMap<String, String>,
+}
+
+impl Reader {
+    // Title, author and revision lines and attribute entries up to the first blank line.
+    // Returns the metadata and the first line of the body.
+    fn header(&mut self, lines: &[&str]) -> (Vec<DocumentElement>, usize) {
+        let mut metadata = Vec::new();
+        let mut i = 0;
+        while lines.get(i).is_some_and(|line| is_blank(line) || is_comment(line)) {
+            i += 1;
+        }
+
+        let Some(title) = lines.get(i).and_then(|line| line.strip_prefix("= ").or_else(|| line.strip_prefix("# "))) else {
+            // No document title: leading attribute entries still count
+            while let Some((name, value)) = lines.get(i).and_then(|line| attribute_entry(line)) {
+                self.set_attribute(name, value);
+                i += 1;
+            }
+            return (self.attribute_metadata(), i);
+        };
+        let title = self.inline(title.trim(), &mut Vec::new());
+        self.attributes.insert("doctitle".to_string(), title.clone());
+        metadata.push(DocumentElement::Title { text: title });
+        i += 1;
+
+        let mut line_number = 0;
+        while let Some(line) = lines.get(i).filter(|line| !is_blank(line)) {
+            i += 1;
+            if is_comment(line) {
+                continue;
+            }
+            if let Some((name, value)) = attribute_entry(line) {
+                self.set_attribute(name, value);
+                continue;
+            }
+
+            line_number += 1;
+            match line_number {
+                // "Name <email>; Other Name <email>"
+                1 => {
+                    let authors: Vec<&str> = line.split(';')
+                        .map(|author| author.split('<').next().unwrap_or_default().trim())
+                        .filter(|author| !author.is_empty())
+                        .collect();
+                    self.attributes.entry("author".to_string()).or_insert_with(|| authors.join(", "));
+                },
+                // "v1.0, 2024-01-02: Remark"
+                2 => {
+                    let revision = line.split(':').next().unwrap_or_default();
+                    if let Some((_, date)) = revision.split_once(',') {
+                        self.attributes.entry("revdate".to_string()).or_insert_with(|| date.trim().to_string());
+                    } else if !revision.starts_with('v') {
+                        self.attributes.entry("revdate".to_string()).or_insert_with(|| revision.trim().to_string());
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        metadata.extend(self.attribute_metadata());
+        (metadata, i)
+    }
+
+    fn set_attribute(&mut self, name: &str, value: &str) {
+        match name.strip_suffix('!').or_else(|| name.strip_prefix('!')) {
+            Some(name) => {
+                self.attributes.remove(name);
+            },
+            None => {
+                self.attributes.insert(name.to_string(), value.to_string());
+            },
+        }
+    }
+
+    // Metadata given by the header attributes
+    fn attribute_metadata(&self) -> Vec<DocumentElement> {
+        let value = |name: &str| self.attributes.get(name).filter(|value| !value.is_empty()).cloned();
+        let mut metadata = Vec::new();
+        if let Some(text) = value("description") {
+            metadata.push(DocumentElement::Description { text });
+        }
+        if let Some(text) = value("keywords") {
+            metadata.push(DocumentElement::Keywords { text });
+        }
+        if let Some(text) = value("author").or_else(|| value("authors")) {
+            metadata.push(DocumentElement::Author { text });
+        }
+        if let Some(text) = value("lang") {
+            metadata.push(DocumentElement::Language { text });
+        }
+        if let Some(text) = value("revdate") {
+            metadata.push(DocumentElement::CreationDate { text });
+        }
+        metadata
+    }
+
+
+    //-------------------------------------------------
+    // Block of Functions related to blocks
+    //-------------------------------------------------
+
+    fn blocks(&mut self, lines: &[&str]) -> Vec<DocumentElement> {
+        let mut out = Vec::new();
+        let mut pending_attributes = BlockAttributes::default();
+        let mut pending_title: Option<String> = None;
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i].trim_end();
+            if line.is_empty() {
+                i += 1;
+                continue;
+            }
+
+            // Comment block and line comments
+            if line == "////" {
+                i = closing_delimiter(lines, i, line) + 1;
+                continue;
+            }
+            if is_comment(line) {
+                i += 1;
+                continue;
+            }
+
+            if let Some((name, value)) = attribute_entry(line) {
+                self.set_attribute(name, value);
+                i += 1;
+                continue;
+            }
+
+            // Block anchor ("[[id]]", "[#id]") and attribute list
+            if line.starts_with('[') && line.ends_with(']') && !line.starts_with("[[[") {
+                if !line.starts_with("[[") && !line.starts_with("[#") {
+                    pending_attributes = BlockAttributes::parse(&line[1..line.len() - 1]);
+                }
+                i += 1;
+                continue;
+            }
+
+            // Block title (".Title", not ". item" nor "....")
+            if let Some(text) = line.strip_prefix('.')
+                && text.starts_with(|c: char| !c.is_whitespace() && c != '.') {
+                pending_title = Some(self.inline(text, &mut Vec::new()));
+                i += 1;
+                continue;
+            }
+
+            let mut attributes = std::mem::take(&mut pending_attributes);
+            let title = pending_title.take();
+
+            if let Some(level) = section_level(line) {
+                let mut links = Vec::new();
+                let text = self.inline(line[level..].trim(), &mut links);
+                // "=" is the document title: "==" sections are level 1
+                out.push(DocumentElement::Heading { level: (level.max(2) - 1).min(6) as u8, text });
+                out.extend(links);
+                i += 1;
+                continue;
+            }
+
+            if let Some(delimiter) = delimiter(line) {
+                // A fenced block names its language: "```python"
+                if delimiter == "```" && !line[3..].trim().is_empty() {
+                    attributes.positional = vec!["source".to_string(), line[3..].trim().to_string()];
+                }
+                let end = closing_delimiter(lines, i, line);
+                let content = dedent(&lines[(i + 1).min(end)..end]);
+                self.delimited_block(delimiter, &content, &attributes, title, &mut out);
+                i = end + 1;
+                continue;
+            }
+
+            if let Some(image) = line.strip_prefix("image::") {
+                let (target, list) = macro_parts(image);
+                let list = BlockAttributes::parse(list);
+                let alt = list.positional.first().filter(|alt| !alt.is_empty()).cloned()
+                    .or_else(|| list.named.get("alt").cloned())
+                    .or(title)
+                    .unwrap_or_default();
+                out.push(DocumentElement::Image { alt, url: Some(self.substitute(target)) });
+                i += 1;
+                continue;
+            }
+
+            if SKIPPED_MACROS.iter().any(|name| line.starts_with(name)) || is_break(line) {
+                i += 1;
+                continue;
+            }
+
+            if let Some(title) = title {
+                out.push(DocumentElement::Paragraph { text: title });
+            }
+
+            if list_item(line).is_some() {
+                i = self.list(lines, i, &mut out);
+                continue;
+            }
+
+            // Literal paragraph: indented lines
+            if indentation(line) > 0 {
+                let end = paragraph_end(lines, i);
+                out.push(DocumentElement::Code { code: dedent(&lines[i..end]).join("\n"), language: None, inline: false });
+                i = end;
+                continue;
+            }
+
+            let end = paragraph_end(lines, i);
+            self.paragraph(&lines[i..end], &attributes, &mut out);
+            i = end;
+        }
+
+        out
+    }
+
+    // A paragraph, in the style its attribute list or admonition label gives
+    fn paragraph(&mut self, lines: &[&str], attributes: &BlockAttributes, out: &mut Vec<DocumentElement>) {
+        match attributes.style() {
+            "source" | "listing" | "literal" => {
+                out.push(DocumentElement::Code { code: lines.join("\n"), language: source_language(attributes), inline: false });
+                return;
+            },
+            "stem" | "latexmath" | "asciimath" => {
+                out.push(DocumentElement::Math { tex: lines.join("\n"), environment: None });
+                return;
+            },
+            _ => {},
+        }
+
+        // Hard line breaks (" +") are removed
+        let text = join_lines(&lines.iter().map(|line| line.trim_end().trim_end_matches(" +")).collect::<Vec<_>>());
+        let (label, text) = match ADMONITION_PARAGRAPHS.iter().find(|kind| text.starts_with(&format!("{}: ", kind))) {
+            Some(kind) => (Some(admonition_label(kind)), text[kind.len() + 2..].to_string()),
+            None => (self.admonition(attributes), text),
+        };
+
+        let mut links = Vec::new();
+        let text = self.inline(&text, &mut links);
+        let element = DocumentElement::Paragraph { text };
+        match attributes.style() {
+            _ if label.is_some() => push_quote(label.as_deref(), vec![element], out),
+            "quote" | "verse" => push_quote(None, vec![element], out),
+            _ => out.push(element),
+        }
+        out.extend(links);
+    }
+
+    fn delimited_block(
+        &mut self,
+        delimiter: &str,
+        content: &[&str],
+        attributes: &BlockAttributes,
+        title: Option<String>,
+        out: &mut Vec<DocumentElement>,
+    ) {
+        // Titles of code blocks and tables are captions: they come first
+        if let Some(title) = title {
+            out.push(DocumentElement::Paragraph { text: title });
+        }
+
+        match delimiter {
+            "----" if matches!(attributes.style(), "stem" | "latexmath" | "asciimath") => {
+                out.push(DocumentElement::Math { tex: content.join("\n"), environment: None });
+            },
+            "----" | "```" => {
+                out.push(DocumentElement::Code { code: content.join("\n"), language: source_language(attributes), inline: false });
+            },
+            "...." => {
+                out.push(DocumentElement::Code { code: content.join("\n"), language: None, inline: false });
+            },
+            "++++" if matches!(attributes.style(), "stem" | "latexmath" | "asciimath") => {
+                out.push(DocumentElement::Math { tex: content.join("\n"), environment: None });
+            },
+            // Passthrough HTML
+            "++++" => {
+                let document = parse_html_str(&content.join("\n"));
+                out.extend(Document { metadata: Vec::new(), nodes: document.nodes }.to_elements());
+            },
+            "____" if attributes.style() == "verse" => {
+                let text = content.join("\n");
+                out.push(DocumentElement::Blockquote { text: attribution(text, attributes) });
+            },
+            "____" => {
+                let nested = self.blocks(content);
+                let mut quote = Vec::new();
+                push_quote(None, nested, &mut quote);
+                if let Some(DocumentElement::Blockquote { text }) = quote.first_mut() {
+                    *text = attribution(std::mem::take(text), attributes);
+                }
+                out.extend(quote);
+            },
+            "|===" | ",===" | ":===" | "!===" => out.extend(self.table(delimiter, content, attributes)),
+            // Example, sidebar and open blocks: their content, or an admonition
+            _ => {
+                if attributes.style() == "source" {
+                    out.push(DocumentElement::Code { code: content.join("\n"), language: source_language(attributes), inline: false });
+                    return;
+                }
+                let nested = self.blocks(content);
+                match self.admonition(attributes) {
+                    Some(label) => push_quote(Some(&label), nested, out),
+                    None if attributes.style() == "quote" => push_quote(None, nested, out),
+                    None => out.extend(nested),
+                }
+            },
+        }
+    }
+
+    // The label of an admonition style ("[NOTE]")
+    fn admonition(&self, attributes: &BlockAttributes) -> Option<String> {
+        let style = attributes.style().to_lowercase();
+        ADMONITIONS.contains(&style.as_str()).then(|| admonition_label(&style))
+    }
+
+    // Items of consecutive list lines, whatever their depth (a list of another kind after a blank line
+    // starts a new list); returns the line after the list
+    fn list(&mut self, lines: &[&str], mut i: usize, out: &mut Vec<DocumentElement>) -> usize {
+        let first_kind = list_item(lines[i]).map(|(kind, _)| std::mem::discriminant(&kind));
+        let ordered = list_item(lines[i]).is_some_and(|(kind, _)| kind == ListKind::Ordered);
+        let mut items = Vec::new();
+        let mut trailing = Vec::new();
+        let mut after_blank = false;
+
+        while i < lines.len() {
+            if is_blank(lines[i]) {
+                after_blank = true;
+                i += 1;
+                continue;
+            }
+            let Some((kind, text)) = list_item(lines[i]) else { break };
+            if after_blank && Some(std::mem::discriminant(&kind)) != first_kind {
+                break;
+            }
+            after_blank = false;
+
+            // The item text runs on until a blank line, another item or a "+" continuation
+            let mut end = i + 1;
+            while end < lines.len()
+                && !is_blank(lines[end])
+                && list_item(lines[end]).is_none()
+                && lines[end].trim() != "+"
+                && delimiter(lines[end].trim_end()).is_none() {
+                end += 1;
+            }
+            let mut item_lines = vec![text];
+            item_lines.extend(&lines[i + 1..end]);
+
+            let mut links = Vec::new();
+            let text = match kind {
+                // "term:: definition", where the definition may start on the next line
+                ListKind::Description(term) => {
+                    let term = self.inline(term, &mut links);
+                    let definition = self.inline(&join_lines(&item_lines), &mut links);
+                    match definition.is_empty() {
+                        true => term,
+                        false => format!("{}: {}", term, definition),
+                    }
+                },
+                // Checklist items "* [x] text" keep their checkbox, written like Markdown task items
+                _ => {
+                    let (checkbox, first_line) = checkbox(item_lines[0]);
+                    item_lines[0] = first_line;
+                    format!("{}{}", checkbox, self.inline(&join_lines(&item_lines), &mut links))
+                },
+            };
+            let mut elements = vec![DocumentElement::Paragraph { text }];
+            elements.extend(links);
+
+            // "+" attaches the following block to the item
+            while lines.get(end).is_some_and(|line| line.trim() == "+") {
+                let start = end + 1;
+                end = match lines.get(start).and_then(|line| delimiter(line.trim_end())) {
+                    Some(_) => closing_delimiter(lines, start, lines[start].trim_end()) + 1,
+                    None => paragraph_end(lines, start),
+                };
+                elements.extend(self.blocks(&lines[start.min(end)..end]));
+            }
+
+            // Item paragraphs join the item text, attached blocks follow the list
+            let (paragraph, attached) = elements.split_at(1);
+            push_list_item(paragraph.to_vec(), &mut items, &mut trailing);
+            trailing.extend(attached.iter().cloned());
+            i = end;
+        }
+
+        if !items.is_empty() {
+            out.push(DocumentElement::List { items, ordered });
+        }
+        out.extend(trailing);
+        i
+    }
+
+    // Table cells are separated by "|" (or "," and ":" for CSV and DSV data); the header is the first
+    // row when the table has the header option, or when its first line is followed by a blank line
+    fn table(&mut self, delimiter: &str, content: &[&str], attributes: &BlockAttributes) -> Option<DocumentElement> {
+        let format = attributes.named.get("format").map(String::as_str).unwrap_or(match delimiter {
+            ",===" => "csv",
+            ":===" => "dsv",
+            _ => "psv",
+        });
+        let separator = attributes.named.get("separator").and_then(|separator| separator.chars().next())
+            .unwrap_or(match (format, delimiter) {
+                ("csv", _) => ',',
+                ("dsv", _) => ':',
+                (_, "!===") => '!',
+                _ => '|',
+            });
+
+        let mut cells: Vec<String> = Vec::new();
+        let mut first_line_cells = 0;
+        let mut implicit_header = false;
+        for (index, line) in content.iter().enumerate() {
+            if is_blank(line) {
+                continue;
+            }
+            let line_cells = match format {
+                "psv" => {
+                    let mut pieces = split_cells(line, separator).into_iter();
+                    // Text before the first separator continues the previous cell
+                    if let Some(continued) = pieces.next().filter(|piece| !piece.trim().is_empty())
+                        && let Some(previous) = cells.last_mut() {
+                        previous.push(' ');
+                        previous.push_str(continued.trim());
+                    }
+                    pieces.map(|piece| piece.trim().to_string()).collect::<Vec<_>>()
+                },
+                _ => split_csv(line, separator),
+            };
+            if first_line_cells == 0 && !line_cells.is_empty() {
+                first_line_cells = line_cells.len();
+                implicit_header = content.get(index + 1).is_some_and(|next| is_blank(next));
+            }
+            cells.extend(line_cells);
+        }
+
+        let columns = attributes.named.get("cols")
+            .map(|cols| match cols.split_once('*') {
+                Some((count, _)) if !cols.contains(',') => count.trim().parse().unwrap_or(1),
+                _ => cols.split(',').count(),
+            })
+            .unwrap_or(first_line_cells)
+            .max(1);
+        let header = !attributes.has_option("noheader")
+            && (attributes.has_option("header") || implicit_header);
+
+        let mut links = Vec::new();
+        let rows = cells.chunks(columns)
+            .map(|row| row.iter().map(|cell| self.inline(cell, &mut links)).collect())
+            .collect();
+        table(rows, usize::from(header))
+    }
+
+
+    //-------------------------------------------------
+    // Block of Functions related to inline markup
+    //-------------------------------------------------
+
+    // Replace "{name}" references to the document attributes
+    fn substitute(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(open) = rest.find('{') {
+            result.push_str(&rest[..open]);
+            let reference = &rest[open + 1..];
+            match reference.find('}').map(|close| (&reference[..close], close)) {
+                Some((name, close)) if self.attributes.contains_key(name) => {
+                    result.push_str(&self.attributes[name]);
+                    rest = &reference[close + 1..];
+                },
+                _ => {
+                    result.push('{');
+                    rest = reference;
+                },
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
+    // Remove inline markup: attribute references, macros (links, cross references, images, keys,
+    // footnotes, math), anchors, index terms and emphasis. Links and URLs are added to `links`.
+    fn inline(&self, text: &str, links: &mut Vec<DocumentElement>) -> String {
+        let text = self.substitute(text);
+        let mut result = String::with_capacity(text.len());
+        let mut i = 0;
+
+        while i < text.len() {
+            let rest = &text[i..];
+            let at_boundary = i == 0 || !text[..i].ends_with(|c: char| c.is_alphanumeric());
+
+            // Passthroughs: +++text+++ and pass:[text]
+            if let Some(inner) = rest.strip_prefix("+++")
+                && let Some(end) = inner.find("+++") {
+                result.push_str(&inner[..end]);
+                i += 6 + end;
+                continue;
+            }
+
+            // Cross references: <<id>> and <<id,text>>
+            if let Some(inner) = rest.strip_prefix("<<")
+                && let Some(end) = inner.find(">>") {
+                let reference = &inner[..end];
+                let shown = reference.split_once(',').map_or(reference, |(_, text)| text);
+                result.push_str(shown.trim());
+                i += 4 + end;
+                continue;
+            }
+
+            // Anchors ([[id]]) and index terms: (((hidden))) and ((visible))
+            if let Some(inner) = rest.strip_prefix("[[")
+                && let Some(end) = inner.find("]]") {
+                i += 4 + end;
+                continue;
+            }
+            if let Some(inner) = rest.strip_prefix("(((")
+                && let Some(end) = inner.find(")))") {
+                i += 6 + end;
+                continue;
+            }
+            if let Some(inner) = rest.strip_prefix("((")
+                && let Some(end) = inner.find("))") {
+                result.push_str(&inner[..end]);
+                i += 4 + end;
+                continue;
+            }
+
+            // Role shorthands before a marked phrase: [.role]#text#
+            if rest.starts_with("[.")
+                && let Some(end) = rest.find(']')
+                && rest[end + 1..].starts_with(['#', '*', '_', '`']) {
+                i += end + 1;
+                continue;
+            }
+
+            if at_boundary && let Some((consumed, shown)) = inline_macro(rest, links) {
+                result.push_str(&shown);
+                i += consumed;
+                continue;
+            }
+
+            let c = rest.chars().next().unwrap_or_default();
+            result.push(c);
+            i += c.len_utf8();
+        }
+
+        let result = strip_emphasis(&result, &["**", "__", "``", "##", "*", "_", "`", "#"]);
+        // "`+literal+`" keeps its text
+        let result = strip_emphasis(&result, &["+"]);
+        result.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+// An inline macro ("link:url[text]", "https://url[text]", "kbd:[Ctrl+C]"...) or a bare URL at the start
+// of `text`. Returns the length it spans and the text it shows.
+fn inline_macro(text: &str, links: &mut Vec<DocumentElement>) -> Option<(usize, String)> {
+    const SCHEMES: &[&str] = &["https://", "http://", "ftp://", "irc://", "mailto:"];
+
+    let name_end = text.find(':').filter(|&end| end > 0 && text[..end].chars().all(|c| c.is_ascii_alphanumeric()))?;
+    let name = &text[..name_end];
+    let scheme = SCHEMES.iter().find(|scheme| text.starts_with(*scheme));
+    if scheme.is_none() && !matches!(
+        name,
+        "link" | "xref" | "image" | "kbd" | "btn" | "menu" | "footnote" | "footnoteref" | "stem" | "latexmath"
+            | "asciimath" | "pass" | "icon" | "anchor" | "indexterm" | "indexterm2"
+    ) {
+        return None;
+    }
+
+    let target_start = if scheme.is_some() { 0 } else { name_end + 1 };
+    let target_end = text[target_start..].find(|c: char| c == '[' || c.is_whitespace())
+        .map_or(text.len(), |end| target_start + end);
+    let target = &text[target_start..target_end];
+
+    // Bare URL, without trailing punctuation
+    if !text[target_end..].starts_with('[') {
+        scheme?;
+        let url = target.trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '\'', '"']);
+        if url.len() <= scheme.map_or(0, |scheme| scheme.len()) {
+            return None;
+        }
+        links.push(DocumentElement::Link { text: url.to_string(), url: url.to_string() });
+        return Some((url.len(), url.to_string()));
+    }
+
+    let list_start = target_end + 1;
+    let list_end = list_start + text[list_start..].find(']')?;
+    let list = &text[list_start..list_end];
+    // Named attributes ("window=_blank") follow the text
+    let label = match list.contains('=') {
+        true => list.split(',').next().unwrap_or_default(),
+        false => list,
+    }.trim().trim_matches('"');
+
+    let shown = match name {
+        _ if scheme.is_some() || name == "link" => {
+            let shown = if label.is_empty() { target } else { label };
+            links.push(DocumentElement::Link { text: shown.to_string(), url: target.to_string() });
+            shown.to_string()
+        },
+        "xref" => if label.is_empty() { target.to_string() } else { label.to_string() },
+        "image" => list.split(',').next().unwrap_or_default().trim().to_string(),
+        "kbd" => list.split(['+', ',']).map(str::trim).collect::<Vec<_>>().join("+"),
+        "menu" => std::iter::once(target).chain(list.split('>').map(str::trim)).filter(|item| !item.is_empty())
+            .collect::<Vec<_>>().join(" > "),
+        "btn" | "pass" | "indexterm2" => list.to_string(),
+        "stem" | "latexmath" | "asciimath" => format!("${}$", list),
+        // Footnotes, icons, anchors and hidden index terms show nothing
+        _ => String::new(),
+    };
+    Some((list_end + 1, shown))
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to lines
+//-------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ListKind<'a> {
+    Unordered,
+    Ordered,
+    Description(&'a str),
+}
+
+// A list item line: "* text", "** text", "- text", ". text", "1. text", "term:: text".
+// Returns its kind and the text after the marker.
+fn list_item(line: &str) -> Option<(ListKind<'_>, &str)> {
+    let line = line.trim();
+    let (marker, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let is_run = |c: char| !marker.is_empty() && marker.chars().all(|m| m == c);
+
+    if !rest.trim().is_empty() {
+        let text = rest.trim_start();
+        if is_run('*') || marker == "-" {
+            return Some((ListKind::Unordered, text));
+        }
+        if is_run('.')
+            || marker.strip_suffix('.').is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+            || marker.strip_suffix('.').is_some_and(|letter| letter.len() == 1 && letter.chars().all(|c| c.is_ascii_alphabetic())) {
+            return Some((ListKind::Ordered, text));
+        }
+    }
+
+    // Description list: "term:: definition" ("term:::", "term;;" for deeper levels)
+    for separator in ["::::", ":::", "::", ";;"] {
+        if let Some(position) = line.find(separator) {
+            let term = line[..position].trim();
+            let definition = &line[position + separator.len()..];
+            if !term.is_empty() && (definition.is_empty() || definition.starts_with(' ')) {
+                return Some((ListKind::Description(term), definition.trim()));
+            }
+        }
+    }
+    None
+}
+
+// The checkbox opening a checklist item, as "[x] " or "[ ] ", and the text after it
+fn checkbox(text: &str) -> (&'static str, &str) {
+    for (marker, written) in [("[x] ", "[x] "), ("[*] ", "[x] "), ("[ ] ", "[ ] ")] {
+        if let Some(rest) = text.strip_prefix(marker) {
+            return (written, rest.trim_start());
+        }
+    }
+    ("", text)
+}
+
+// "//" line comments ("////" opens a comment block)
+fn is_comment(line: &str) -> bool {
+    line.starts_with("//") && !line.starts_with("////")
+}
+
+// ":name: value" (":name!:" unsets the attribute)
+fn attribute_entry(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line.strip_prefix(':')?.split_once(':')?;
+    (!name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || "-_!".contains(c))
+        && (value.is_empty() || value.starts_with(' ')))
+        .then(|| (name, value.trim()))
+}
+
+// Length of the "==" (or Markdown "##") marker of a section title
+fn section_level(line: &str) -> Option<usize> {
+    let marker = line.chars().next().filter(|c| *c == '=' || *c == '#')?;
+    let level = line.len() - line.trim_start_matches(marker).len();
+    (level <= 6 && line[level..].starts_with(' ') && !line[level..].trim().is_empty()).then_some(level)
+}
+
+// The kind of block a delimiter line opens, by its canonical delimiter
+fn delimiter(line: &str) -> Option<&'static str> {
+    if line.starts_with("```") {
+        return Some("```");
+    }
+    if line == "--" {
+        return Some("--");
+    }
+    for table in ["|===", ",===", ":===", "!==="] {
+        if line.starts_with(table) && line[1..].chars().all(|c| c == '=') {
+            return Some(table);
+        }
+    }
+
+    let first = line.chars().next()?;
+    let kind = match first {
+        '-' => "----",
+        '.' => "....",
+        '=' => "====",
+        '*' => "****",
+        '_' => "____",
+        '+' => "++++",
+        '/' => "////",
+        _ => return None,
+    };
+    (line.len() >= 4 && line.chars().all(|c| c == first)).then_some(kind)
+}
+
+// The line closing the block opened at `start` (the same delimiter), or the end of the lines
+fn closing_delimiter(lines: &[&str], start: usize, opening: &str) -> usize {
+    let closing = if opening.starts_with("```") { "```" } else { opening };
+    (start + 1..lines.len())
+        .find(|&i| lines[i].trim_end() == closing)
+        .unwrap_or(lines.len())
+}
+
+// Page breaks ("<<<") and thematic breaks ("\'\'\'", Markdown "---" and "***")
+fn is_break(line: &str) -> bool {
+    line == "<<<" || line == "---" || line == "***" || (line.len() >= 3 && line.chars().all(|c| c == '\''))
+}
+
+// End of the paragraph starting at `start`: a blank line or a block delimiter
+fn paragraph_end(lines: &[&str], start: usize) -> usize {
+    (start + 1..lines.len())
+        .find(|&i| is_blank(lines[i]) || delimiter(lines[i].trim_end()).is_some())
+        .unwrap_or(lines.len())
+}
+
+// "target[attributes]" of a block macro
+fn macro_parts(text: &str) -> (&str, &str) {
+    match text.split_once('[') {
+        Some((target, list)) => (target, list.rsplit_once(']').map_or(list, |(list, _)| list)),
+        None => (text, ""),
+    }
+}
+
+// Language of "[source,python]" (or "[,python]", "[source,language=python]")
+fn source_language(attributes: &BlockAttributes) -> Option<String> {
+    attributes.positional.get(1)
+        .or_else(|| attributes.named.get("language"))
+        .filter(|language| !language.is_empty())
+        .cloned()
+}
+
+// A quote followed by who said it and where: "[quote, Author, Source]"
+fn attribution(text: String, attributes: &BlockAttributes) -> String {
+    let credits: Vec<&str> = attributes.positional.iter().skip(1)
+        .map(|credit| credit.trim())
+        .filter(|credit| !credit.is_empty())
+        .collect();
+    match credits.is_empty() {
+        true => text,
+        false => format!("{}\n— {}", text, credits.join(", ")),
+    }
+}
+
+// Split a table line at its cell separators ("\\|" is a literal bar). The cell specifier written
+// right before a separator ("2+", "3*", "a", ".^") belongs to the next cell and is dropped.
+fn split_cells(line: &str, separator: char) -> Vec<String> {
+    let mut pieces = vec![String::new()];
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let piece = pieces.last_mut().unwrap();
+        if c == '\\' && chars.peek() == Some(&separator) {
+            piece.push(separator);
+            chars.next();
+        } else if c == separator {
+            pieces.push(String::new());
+        } else {
+            piece.push(c);
+        }
+    }
+
+    let count = pieces.len();
+    for piece in &mut pieces[..count - 1] {
+        let token_start = piece.rfind(char::is_whitespace).map_or(0, |space| space + 1);
+        let token = &piece[token_start..];
+        // A lone style letter is only a specifier when nothing precedes it
+        if is_cell_spec(token) && (token_start == 0 || !token.chars().all(|c| c.is_ascii_alphabetic())) {
+            piece.truncate(token_start);
+        }
+    }
+    pieces
+}
+
+// Duplication ("3*"), span ("2+", "2.3+"), alignments ("^", ".>") and style ("a") of a cell
+fn is_cell_spec(token: &str) -> bool {
+    let is_number = |text: &str| !text.is_empty() && text.chars().all(|c| c.is_ascii_digit());
+    let mut rest = token;
+    if let Some((count, after)) = rest.split_once('*')
+        && is_number(count) {
+        rest = after;
+    }
+    if let Some((span, after)) = rest.split_once('+')
+        && span.split('.').all(is_number) {
+        rest = after;
+    }
+    rest = rest.strip_prefix(['<', '^', '>']).unwrap_or(rest);
+    if let Some(after) = rest.strip_prefix('.').and_then(|after| after.strip_prefix(['<', '^', '>'])) {
+        rest = after;
+    }
+    rest = rest.strip_prefix(['a', 'd', 'e', 'h', 'l', 'm', 's', 'v']).unwrap_or(rest);
+    !token.is_empty() && rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::ParseEvent;
+
+    const SOURCE: &str = "= Guide
+Ann Lee
+
+== Install
+
+[source,rust]
+----
+fn main() {}
+----
+
+NOTE: Needs Rust.
+
+* one
+** nested
+* two
+
+. first
+. second
+
+|===
+|Name |Size
+
+|a |1
+|===
+";
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn header_blocks_lists_and_tables() {
+        assert_eq!(parse_asciidoc_str(SOURCE).to_elements(), vec![
+            DocumentElement::Title { text: "Guide".to_string() },
+            DocumentElement::Author { text: "Ann Lee".to_string() },
+            DocumentElement::Heading { level: 1, text: "Install".to_string() },
+            DocumentElement::Code { code: "fn main() {}".to_string(), language: Some("rust".to_string()), inline: false },
+            DocumentElement::Blockquote { text: "Note: Needs Rust.".to_string() },
+            DocumentElement::List { items: strings(&["one", "nested", "two"]), ordered: false },
+            DocumentElement::List { items: strings(&["first", "second"]), ordered: true },
+            DocumentElement::Table { headers: strings(&["Name", "Size"]), rows: vec![strings(&["a", "1"])] },
+        ]);
+    }
+
+    #[test]
+    fn latin1_files_are_reported() {
+        let source = b"Caf\xe9 cr\xe8me.\n";
+        let path = std::env::temp_dir().join(format!("oxidoc-latin1-{}.adoc", std::process::id()));
+        fs::write(&path, source).unwrap();
+
+        let warnings = std::cell::RefCell::new(Vec::new());
+        let sink = |event: ParseEvent| if let ParseEvent::Warning { path, .. } = event {
+            warnings.borrow_mut().push(path);
+        };
+        let from_path = AsciidocParser.parse_path(&path, &sink);
+        fs::remove_file(&path).unwrap();
+        let from_bytes = AsciidocParser.parse_bytes(source, &sink).unwrap();
+
+        // Only the filename title differs
+        assert_eq!(from_path.unwrap()[1..], from_bytes[..]);
+        assert_eq!(from_bytes, vec![DocumentElement::Paragraph { text: "Café crème.".to_string() }]);
+        assert_eq!(*warnings.borrow(), vec![Some(path), None]);
+    }
+
+    #[test]
+    fn checklist_items_keep_their_checkbox() {
+        assert_eq!(parse_asciidoc_str("* [x] Buy milk\n* [ ] Call Ann\n* [*] Pack\n").to_elements(), vec![
+            DocumentElement::List {
+                items: vec!["[x] Buy milk".to_string(), "[ ] Call Ann".to_string(), "[x] Pack".to_string()],
+                ordered: false,
+            },
+        ]);
+    }
+}
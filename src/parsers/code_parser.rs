@@ -0,0 +1,1332 @@
+// code_parser.rs
+
+// Source files split into their top-level items (functions, classes, impls...), each one
+// a Code element introduced by a CodeItem marker with its kind, name and line range.
+// Doc comments and docstrings are also emitted as prose, through the Markdown parser.
+// Splitting is lexical: strings and comments are skipped, then braces (or indentation
+// for Python) delimit the items. Languages without a splitter are kept as one block.
+
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+use crate::error::{OxidocError, Result};
+use crate::events::EventSink;
+use crate::parsers::documents_types::{Document, DocumentElement};
+use crate::parsers::markdown_parser::parse_markdown_str;
+use crate::parsers::parser::Parser;
+use crate::parsers::txt_parser::{decode_text, warn_if_latin1};
+
+// Header lines read to find the kind and name of an item
+const HEADER_MAX_LINES: usize = 5;
+
+// How a language delimits its top-level items
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Layout {
+    // Items end at the brace closing their body, or at ";" (or the end of the line
+    // when `newline_ends_statements`)
+    Braces,
+    // Items are the lines starting at column 0 and everything indented below them
+    Indentation,
+    // No splitter: the whole file is one block
+    Whole,
+}
+
+// What a single quote opens
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SingleQuote {
+    // A string ending at the next unescaped quote
+    String,
+    // A character literal ('a', '\n'); anything else (Rust lifetimes) is plain code
+    Character,
+}
+
+struct Language {
+    name: &'static str,
+    extensions: &'static [&'static str],
+    // Interpreters named in a "#!" line
+    interpreters: &'static [&'static str],
+    layout: Layout,
+    newline_ends_statements: bool,
+    // "#" lines are preprocessor directives ending at the end of the line
+    preprocessor: bool,
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    nested_block_comments: bool,
+    // Comment prefixes of doc comments; every comment above an item when empty
+    doc_comments: &'static [&'static str],
+    // Comment prefixes documenting the file itself (Rust "//!")
+    file_doc_comments: &'static [&'static str],
+    single_quote: SingleQuote,
+    backtick_strings: bool,
+    triple_quote_strings: bool,
+    // Functions are declared without a keyword (C, C++): "int main(void) {"
+    implicit_functions: bool,
+    // Words that may precede the item keyword ("pub", "export", "static"...)
+    modifiers: &'static [&'static str],
+    // Item keyword and the kind it declares
+    items: &'static [(&'static str, &'static str)],
+}
+
+const C_FAMILY_COMMENTS: &[&str] = &["//"];
+const C_ITEMS: &[(&str, &str)] = &[
+    ("struct", "struct"), ("union", "union"), ("enum", "enum"), ("typedef", "type"),
+    ("class", "class"), ("namespace", "namespace"), ("template", "template"),
+];
+const C_MODIFIERS: &[&str] = &["static", "inline", "extern", "const", "volatile", "constexpr", "virtual", "unsigned", "signed"];
+const JS_ITEMS: &[(&str, &str)] = &[
+    ("function", "function"), ("class", "class"), ("interface", "interface"), ("type", "type"),
+    ("enum", "enum"), ("namespace", "namespace"), ("module", "namespace"), ("const", "variable"),
+    ("let", "variable"), ("var", "variable"),
+];
+const JS_MODIFIERS: &[&str] = &["export", "default", "async", "declare", "abstract"];
+const JVM_MODIFIERS: &[&str] = &[
+    "public", "private", "protected", "internal", "static", "final", "abstract", "sealed", "open",
+    "data", "inline", "override", "partial", "readonly", "virtual", "unsafe", "extern", "suspend",
+    "inner", "implicit", "lazy", "case", "strictfp", "synchronized", "native", "fileprivate", "mutating",
+];
+
+static LANGUAGES: &[Language] = &[
+    Language {
+        name: "rust",
+        extensions: &["rs"],
+        interpreters: &[],
+        layout: Layout::Braces,
+        newline_ends_statements: false,
+        preprocessor: false,
+        line_comments: C_FAMILY_COMMENTS,
+        block_comment: Some(("/*", "*/")),
+        nested_block_comments: true,
+        doc_comments: &["///", "/**"],
+        file_doc_comments: &["//!", "/*!"],
+        single_quote: SingleQuote::Character,
+        backtick_strings: false,
+        triple_quote_strings: false,
+        implicit_functions: false,
+        modifiers: &["pub", "crate", "super", "self", "in", "async", "unsafe", "extern", "default"],
+        items: &[
+            ("fn", "function"), ("struct", "struct"), ("enum", "enum"), ("union", "union"),
+            ("trait", "trait"), ("impl", "impl"), ("mod", "module"), ("type", "type"),
+            ("const", "constant"), ("static", "static"), ("macro_rules", "macro"),
+        ],
+    },
+    Language {
+        name: "python",
+        extensions: &["py", "pyw", "pyi"],
+        interpreters: &["python", "python3", "python2"],
+        layout: Layout::Indentation,
+        newline_ends_statements: true,
+        preprocessor: false,
+        line_comments: &["#"],
+        block_comment: None,
+        nested_block_comments: false,
+        doc_comments: &[],
+        file_doc_comments: &[],
+        single_quote: SingleQuote::String,
+        backtick_strings: false,
+        triple_quote_strings: true,
+        implicit_functions: false,
+        modifiers: &["async"],
+        items: &[("def", "function"), ("class", "class")],
+    },
+    Language {
+        name: "javascript",
+        extensions: &["js", "mjs", "cjs", "jsx"],
+        interpreters: &["node", "nodejs", "bun"],
+        layout: Layout::Braces,
+        newline_ends_statements: true,
+        preprocessor: false,
+        line_comments: C_FAMILY_COMMENTS,
+        block_comment: Some(("/*", "*/")),
+        nested_block_comments: false,
+        doc_comments: &["/**"],
+        file_doc_comments: &[],
+        single_quote: SingleQuote::String,
+        backtick_strings: true,
+        triple_quote_strings: false,
+        implicit_functions: false,
+        modifiers: JS_MODIFIERS,
+        items: JS_ITEMS,
+    },
+    Language {
+        name: "typescript",
+        extensions: &["ts", "tsx", "mts", "cts"],
+        interpreters: &["ts-node", "deno", "tsx"],
+        layout: Layout::Braces,
+        newline_ends_statements: true,
+        preprocessor: false,
+        line_comments: C_FAMILY_COMMENTS,
+        block_comment: Some(("/*", "*/")),
+        nested_block_comments: false,
+        doc_comments: &["/**"],
+        file_doc_comments: &[],
+        single_quote: SingleQuote::String,
+        backtick_strings: true,
+        triple_quote_strings: false,
+        implicit_functions: false,
+        modifiers: JS_MODIFIERS,
+        items: JS_ITEMS,
+    },
+    Language {
+        name: "go",
+        extensions: &["go"],
+        interpreters: &[],
+        layout: Layout::Braces,
+        newline_ends_statements: true,
+        preprocessor: false,
+        line_comments: C_FAMILY_COMMENTS,
+        block_comment: Some(("/*", "*/")),
+        nested_block_comments: false,
+        doc_comments: &[],
+        file_doc_comments: &[],
+        single_quote: SingleQuote::Character,
+        backtick_strings: true,
+        triple_quote_strings: false,
+        implicit_functions: false,
+        modifiers: &[],
+        items: &[
+            ("func", "function"), ("type", "type"), ("const", "constant"), ("var", "variable"),
+            ("package", "package"),
+        ],
+    },
+    Language {
+        name: "java",
+        extensions: &["java"],
+        interpreters: &[],
+        layout: Layout::Braces,
+        newline_ends_statements: false,
+        preprocessor: false,
+        line_comments: C_FAMILY_COMMENTS,
+        block_comment: Some(("/*", "*/")),
+        nested_block_comments: false,
+        doc_comments: &["/**"],
+        file_doc_comments: &[],
+        single_quote: SingleQuote::Character,
+        backtick_strings: false,
+        triple_quote_strings: true,
+        implicit_functions: false,
+        modifiers: JVM_MODIFIERS,
+        items: &[
+            ("class", "class"), ("interface", "interface"), ("enum", "enum"), ("record", "record"),
+            ("package", "package"),
+        ],
+    },
+    Language {
+        name: "kotlin",
+        extensions: &["kt", "kts"],
+        interpreters: &["kotlin"],
+        layout: Layout::Braces,
+        newline_ends_statements: true,
+        preprocessor: false,
+        line_comments: C_FAMILY_COMMENTS,
+        block_comment: Some(("/*", "*/")),
+        nested_block_comments: true,
+        doc_comments: &["/**"],
+        file_doc_comments: &[],
+        single_quote: SingleQuote::Character,
+        backtick_strings: false,
+        triple_quote_strings: true,
+        implicit_functions: false,
+        modifiers: JVM_MODIFIERS,
+        items: &[
+            ("fun", "function"), ("class", "class"), ("interface", "interface"), ("object", "object"),
+            ("typealias", "type"), ("val", "variable"), ("var", "variable"), ("package", "package"),
+        ],
+    },
+    Language {
+        name: "scala",
+        extensions: &["scala", "sc"],
+        interpreters: &["scala"],
+        layout: Layout::Braces,
+        newline_ends_statements: true,
+        preprocessor: false,
+        line_comments: C_FAMILY_COMMENTS,
+        block_comment: Some(("/*", "*/")),
+        nested_block_comments: true,
+        doc_comments: &["/**"],
+        file_doc_comments: &[],
+        single_quote: SingleQuote::Character,
+        backtick_strings: false,
+        triple_quote_strings: true,
+        implicit_functions: false,
+        modifiers: JVM_MODIFIERS,
+        items: &[
+            ("def", "function"), ("class", "class"), ("trait", "trait"), ("object", "object"),
+            ("type", "type"), ("val", "variable"), ("var", "variable"), ("package", "package"),
+        ],
+    },
+    Language {
+        name: "swift",
+        extensions: &["swift"],
+        interpreters: &["swift"],
+        layout: Layout::Braces,
+        newline_ends_statements: true,
+        preprocessor: false,
+        line_comments: C_FAMILY_COMMENTS,
+        block_comment: Some(("/*", "*/")),
+        nested_block_comments: true,
+        doc_comments: &["///", "/**"],
+        file_doc_comments: &[],
+        single_quote: SingleQuote::String,
+        backtick_strings: false,
+        triple_quote_strings: true,
+        implicit_functions: false,
+        modifiers: JVM_MODIFIERS,
+        items: &[
+            ("func", "function"), ("class", "class"), ("struct", "struct"), ("enum", "enum"),
+            ("protocol", "protocol"), ("extension", "extension"), ("typealias", "type"),
+            ("let", "constant"), ("var", "variable"),
+        ],
+    },
+    Language {
+        name: "csharp",
+        extensions: &["cs"],
+        interpreters: &[],
+        layout: Layout::Braces,
+        newline_ends_statements: false,
+        preprocessor: true,
+        line_comments: C_FAMILY_COMMENTS,
+        block_comment: Some(("/*", "*/")),
+        nested_block_comments: false,
+        doc_comments: &["///", "/**"],
+        file_doc_comments: &[],
+        single_quote: SingleQuote::Character,
+        backtick_strings: false,
+        triple_quote_strings: true,
+        implicit_functions: false,
+        modifiers: JVM_MODIFIERS,
+        items: &[
+            ("class", "class"), ("interface", "interface"), ("struct", "struct"), ("enum", "enum"),
+            ("record", "record"), ("namespace", "namespace"), ("delegate", "type"),
+        ],
+    },
+    Language {
+        name: "c",
+        extensions: &["c", "h"],
+        interpreters: &[],
+        layout: Layout::Braces,
+        newline_ends_statements: false,
+        preprocessor: true,
+        line_comments: C_FAMILY_COMMENTS,
+        block_comment: Some(("/*", "*/")),
+        nested_block_comments: false,
+        doc_comments: &[],
+        file_doc_comments: &[],
+        single_quote: SingleQuote::Character,
+        backtick_strings: false,
+        triple_quote_strings: false,
+        implicit_functions: true,
+        modifiers: C_MODIFIERS,
+        items: C_ITEMS,
+    },
+    Language {
+        name: "cpp",
+        extensions: &["cc", "cpp", "cxx", "c++", "hpp", "hh", "hxx", "h++", "ipp"],
+        interpreters: &[],
+        layout: Layout::Braces,
+        newline_ends_statements: false,
+        preprocessor: true,
+        line_comments: C_FAMILY_COMMENTS,
+        block_comment: Some(("/*", "*/")),
+        nested_block_comments: false,
+        doc_comments: &[],
+        file_doc_comments: &[],
+        single_quote: SingleQuote::Character,
+        backtick_strings: false,
+        triple_quote_strings: false,
+        implicit_functions: true,
+        modifiers: C_MODIFIERS,
+        items: C_ITEMS,
+    },
+    Language {
+        name: "php",
+        extensions: &["php"],
+        interpreters: &["php"],
+        layout: Layout::Braces,
+        newline_ends_statements: false,
+        preprocessor: false,
+        line_comments: &["//", "#"],
+        block_comment: Some(("/*", "*/")),
+        nested_block_comments: false,
+        doc_comments: &["/**"],
+        file_doc_comments: &[],
+        single_quote: SingleQuote::String,
+        backtick_strings: false,
+        triple_quote_strings: false,
+        implicit_functions: false,
+        modifiers: &["php", "public", "private", "protected", "static", "final", "abstract", "readonly"],
+        items: &[
+            ("function", "function"), ("class", "class"), ("interface", "interface"), ("trait", "trait"),
+            ("enum", "enum"), ("namespace", "namespace"),
+        ],
+    },
+    whole_file("ruby", &["rb", "rake", "gemspec"], &["ruby"]),
+    whole_file("shell", &["sh", "bash", "zsh", "ksh", "fish"], &["sh", "bash", "zsh", "ksh", "dash", "fish"]),
+    whole_file("perl", &["pl", "pm"], &["perl"]),
+    whole_file("lua", &["lua"], &["lua", "luajit"]),
+    whole_file("r", &["r"], &["Rscript"]),
+    whole_file("sql", &["sql"], &[]),
+    whole_file("haskell", &["hs"], &["runhaskell", "runghc"]),
+    whole_file("elixir", &["ex", "exs"], &["elixir"]),
+    whole_file("julia", &["jl"], &["julia"]),
+    whole_file("powershell", &["ps1", "psm1"], &["pwsh", "powershell"]),
+    whole_file("dart", &["dart"], &["dart"]),
+    whole_file("zig", &["zig"], &[]),
+];
+
+const fn whole_file(name: &'static str, extensions: &'static [&'static str], interpreters: &'static [&'static str]) -> Language {
+    Language {
+        name,
+        extensions,
+        interpreters,
+        layout: Layout::Whole,
+        newline_ends_statements: false,
+        preprocessor: false,
+        line_comments: &[],
+        block_comment: None,
+        nested_block_comments: false,
+        doc_comments: &[],
+        file_doc_comments: &[],
+        single_quote: SingleQuote::String,
+        backtick_strings: false,
+        triple_quote_strings: false,
+        implicit_functions: false,
+        modifiers: &[],
+        items: &[],
+    }
+}
+
+static SOURCE_EXTENSIONS: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
+    LANGUAGES.iter().flat_map(|language| language.extensions.iter().copied()).collect()
+});
+
+// Main parsing function
+pub fn parse_code_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    parse_code_path(path, &())
+}
+
+fn parse_code_path(path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    // Read the source file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+    let (source, encoding) = decode_text(&bytes);
+    warn_if_latin1(encoding, Some(path), events);
+
+    // The extension decides the language, the "#!" line when there is none
+    let language = path.extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(language_for_extension)
+        .map(|language| language.name)
+        .or_else(|| language_for_shebang(&source).map(|language| language.name));
+    let mut elements = parse_code_str(&source, language);
+
+    // Source files have no metadata, so the filename is the title
+    if let Some(title_str) = path.file_name().and_then(|filename| filename.to_str()) {
+        elements.insert(0, DocumentElement::Title {
+            text: title_str.to_string()
+        });
+    }
+
+    Ok(elements)
+}
+
+// Parse source code of the named language ("rust", "python"...); unknown languages
+// and None keep the file as one block
+pub fn parse_code_str(source: &str, language: Option<&str>) -> Vec<DocumentElement> {
+    let source = source.replace("\r\n", "\n");
+    let lines: Vec<&str> = source.split('\n').collect();
+    let syntax = language.and_then(|name| LANGUAGES.iter().find(|language| language.name == name));
+    let language_name = language.map(|name| name.to_string());
+
+    let chunks = match syntax {
+        Some(syntax) if syntax.layout == Layout::Braces => scan_braces(&source, &lines, syntax),
+        Some(syntax) if syntax.layout == Layout::Indentation => scan_indentation(&source, &lines, syntax),
+        _ => whole_chunk(&lines),
+    };
+
+    let mut elements = Vec::new();
+    if let Some(syntax) = syntax {
+        elements.extend(prose(&file_documentation(&lines, &chunks, syntax)));
+    }
+
+    for chunk in merge_unnamed(classify(chunks, &lines, syntax)) {
+        let code = lines[chunk.start..=chunk.end].join("\n");
+        if code.trim().is_empty() {
+            continue;
+        }
+        elements.push(DocumentElement::CodeItem {
+            kind: chunk.kind.clone().unwrap_or_else(|| "code".to_string()),
+            name: chunk.name.clone(),
+            start_line: chunk.start as u32 + 1,
+            end_line: chunk.end as u32 + 1,
+        });
+        if let Some(documentation) = &chunk.documentation {
+            elements.extend(prose(documentation));
+        }
+        elements.push(DocumentElement::Code { code, language: language_name.clone(), inline: false });
+    }
+
+    elements
+}
+
+// Source code implementation of the common Parser trait
+pub struct CodeParser;
+
+impl Parser for CodeParser {
+    fn name(&self) -> &str {
+        "code"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &SOURCE_EXTENSIONS
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["text/x-rust", "text/x-python", "text/javascript", "text/x-go", "text/x-java", "text/x-c", "text/x-c++"]
+    }
+
+    // Scripts without an extension are recognized by their "#!" line
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(b"#!") && language_for_shebang(&String::from_utf8_lossy(&bytes[..bytes.len().min(256)])).is_some()
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        let (source, encoding) = decode_text(bytes);
+        warn_if_latin1(encoding, None, events);
+        let language = language_for_shebang(&source).map(|language| language.name);
+        Ok(parse_code_str(&source, language))
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_code_path(path, events)
+    }
+}
+
+fn language_for_extension(extension: &str) -> Option<&'static Language> {
+    let extension = extension.to_lowercase();
+    LANGUAGES.iter().find(|language| language.extensions.contains(&extension.as_str()))
+}
+
+// "#!/usr/bin/env python3", "#!/bin/bash -e"
+fn language_for_shebang(source: &str) -> Option<&'static Language> {
+    let line = source.trim_start_matches('\u{feff}').lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+    // "python3.12" names the same interpreter as "python3"
+    let program = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    LANGUAGES.iter().find(|language| {
+        language.interpreters.iter().any(|interpreter| interpreter.trim_end_matches(|c: char| c.is_ascii_digit()) == program)
+    })
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to splitting
+//-------------------------------------------------
+
+// Lines (0-based, inclusive) of one top-level statement
+#[derive(Debug, Clone)]
+struct Chunk {
+    start: usize,
+    end: usize,
+    kind: Option<String>,
+    name: Option<String>,
+    documentation: Option<String>,
+}
+
+impl Chunk {
+    fn new(start: usize, end: usize) -> Chunk {
+        Chunk { start, end, kind: None, name: None, documentation: None }
+    }
+}
+
+// What each line holds once strings and comments are told apart from code
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LineContent {
+    Blank,
+    Comment,
+    Code,
+}
+
+fn whole_chunk(lines: &[&str]) -> Vec<Chunk> {
+    let last = lines.iter().rposition(|line| !line.trim().is_empty()).unwrap_or(0);
+    vec![Chunk { kind: Some("file".to_string()), ..Chunk::new(0, last) }]
+}
+
+// Lexical state of the scanner
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Lexical {
+    Code,
+    LineComment,
+    BlockComment(usize),
+    // Closing delimiter, and whether backslashes escape
+    String(StringEnd, bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StringEnd {
+    Double,
+    Single,
+    Backtick,
+    TripleDouble,
+    TripleSingle,
+    // Rust r#"..."# with this many hashes
+    Raw(usize),
+}
+
+// Tells code, comments and strings apart in one pass and records what each line holds
+struct Scanner<'a> {
+    characters: Vec<char>,
+    syntax: &'a Language,
+    content: Vec<LineContent>,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(source: &str, line_count: usize, syntax: &'a Language) -> Scanner<'a> {
+        Scanner { characters: source.chars().collect(), syntax, content: vec![LineContent::Blank; line_count] }
+    }
+
+    fn starts_with(&self, index: usize, pattern: &str) -> bool {
+        pattern.chars().enumerate().all(|(offset, character)| self.characters.get(index + offset) == Some(&character))
+    }
+
+    fn mark(&mut self, line: usize, content: LineContent) {
+        if let Some(slot) = self.content.get_mut(line)
+            && (*slot == LineContent::Blank || content == LineContent::Code) {
+            *slot = content;
+        }
+    }
+
+    // Calls `on_code(line, character)` for code characters and `on_code(line, '\n')`
+    // at the end of every line that is not inside a multi-line string or comment
+    fn run(&mut self, mut on_code: impl FnMut(usize, char)) {
+        let mut state = Lexical::Code;
+        let mut line = 0;
+        let mut index = 0;
+
+        while index < self.characters.len() {
+            let character = self.characters[index];
+            if character == '\n' {
+                match state {
+                    Lexical::LineComment => state = Lexical::Code,
+                    // Only these strings span lines
+                    Lexical::String(StringEnd::Double | StringEnd::Single, _) => state = Lexical::Code,
+                    _ => {},
+                }
+                if state == Lexical::Code {
+                    on_code(line, '\n');
+                }
+                line += 1;
+                index += 1;
+                continue;
+            }
+
+            match state {
+                Lexical::Code => {
+                    if character.is_whitespace() {
+                        index += 1;
+                        continue;
+                    }
+                    if let Some(prefix) = self.syntax.line_comments.iter().find(|prefix| self.starts_with(index, prefix)) {
+                        // Rust attributes and PHP 8 attributes start like comments in some languages
+                        if !(*prefix == "#" && self.starts_with(index, "#[")) {
+                            state = Lexical::LineComment;
+                            self.mark(line, LineContent::Comment);
+                            index += prefix.len();
+                            continue;
+                        }
+                    }
+                    if let Some((open, _)) = self.syntax.block_comment
+                        && self.starts_with(index, open) {
+                        state = Lexical::BlockComment(1);
+                        self.mark(line, LineContent::Comment);
+                        index += open.chars().count();
+                        continue;
+                    }
+
+                    self.mark(line, LineContent::Code);
+                    let (string, length) = self.string_start(index);
+                    if let Some(string) = string {
+                        state = string;
+                        for offset in 0..length {
+                            on_code(line, self.characters[index + offset]);
+                        }
+                        index += length;
+                        continue;
+                    }
+                    if character == '\'' && self.syntax.single_quote == SingleQuote::Character {
+                        let length = self.character_literal(index);
+                        index += length;
+                        on_code(line, '\'');
+                        continue;
+                    }
+                    on_code(line, character);
+                    index += 1;
+                },
+                Lexical::LineComment => {
+                    index += 1;
+                },
+                Lexical::BlockComment(depth) => {
+                    if !character.is_whitespace() {
+                        self.mark(line, LineContent::Comment);
+                    }
+                    let (open, close) = self.syntax.block_comment.unwrap_or(("/*", "*/"));
+                    if self.starts_with(index, close) {
+                        state = if depth > 1 { Lexical::BlockComment(depth - 1) } else { Lexical::Code };
+                        index += close.chars().count();
+                    } else if self.syntax.nested_block_comments && self.starts_with(index, open) {
+                        state = Lexical::BlockComment(depth + 1);
+                        index += open.chars().count();
+                    } else {
+                        index += 1;
+                    }
+                },
+                Lexical::String(end, escapes) => {
+                    self.mark(line, LineContent::Code);
+                    if escapes && character == '\\' {
+                        // An escaped line break continues the string on the next line
+                        if self.starts_with(index + 1, "\n") || self.starts_with(index + 1, "\r\n") {
+                            let length = if self.characters[index + 1] == '\r' { 3 } else { 2 };
+                            line += 1;
+                            index += length;
+                        } else {
+                            index += 2;
+                        }
+                        continue;
+                    }
+                    let closing = match end {
+                        StringEnd::Double => "\"".to_string(),
+                        StringEnd::Single => "'".to_string(),
+                        StringEnd::Backtick => "`".to_string(),
+                        StringEnd::TripleDouble => "\"\"\"".to_string(),
+                        StringEnd::TripleSingle => "'''".to_string(),
+                        StringEnd::Raw(hashes) => format!("\"{}", "#".repeat(hashes)),
+                    };
+                    if self.starts_with(index, &closing) {
+                        state = Lexical::Code;
+                        index += closing.len();
+                        on_code(line, '"');
+                    } else {
+                        index += 1;
+                    }
+                },
+            }
+        }
+    }
+
+    // String opening at `index`, and the length of its opening delimiter
+    fn string_start(&self, index: usize) -> (Option<Lexical>, usize) {
+        let syntax = self.syntax;
+        let previous_is_word = index > 0 && (self.characters[index - 1].is_alphanumeric() || self.characters[index - 1] == '_');
+
+        if syntax.triple_quote_strings && self.starts_with(index, "\"\"\"") {
+            return (Some(Lexical::String(StringEnd::TripleDouble, true)), 3);
+        }
+        if syntax.triple_quote_strings && syntax.single_quote == SingleQuote::String && self.starts_with(index, "'''") {
+            return (Some(Lexical::String(StringEnd::TripleSingle, true)), 3);
+        }
+        // Rust raw strings: r"...", r#"..."#, br"..."
+        if syntax.name == "rust" && self.characters[index] == 'r' && !previous_is_word {
+            let hashes = self.characters[index + 1..].iter().take_while(|character| **character == '#').count();
+            if self.characters.get(index + 1 + hashes) == Some(&'"') {
+                return (Some(Lexical::String(StringEnd::Raw(hashes), false)), hashes + 2);
+            }
+        }
+        // C# verbatim strings: @"C:\path"
+        if syntax.name == "csharp" && self.starts_with(index, "@\"") {
+            return (Some(Lexical::String(StringEnd::Double, false)), 2);
+        }
+        match self.characters[index] {
+            '"' => (Some(Lexical::String(StringEnd::Double, true)), 1),
+            '\'' if syntax.single_quote == SingleQuote::String => (Some(Lexical::String(StringEnd::Single, true)), 1),
+            // Go raw strings have no escapes, JavaScript templates do
+            '`' if syntax.backtick_strings => (Some(Lexical::String(StringEnd::Backtick, syntax.name != "go")), 1),
+            _ => (None, 0),
+        }
+    }
+
+    // Length of a character literal at `index` ('a', '\n', '\u{1F600}'), 1 for a lone quote
+    fn character_literal(&self, index: usize) -> usize {
+        match self.characters.get(index + 1) {
+            Some('\\') => self.characters[index + 2..]
+                .iter()
+                .take(12)
+                .position(|character| *character == '\'')
+                .map_or(1, |position| position + 3),
+            Some(_) if self.characters.get(index + 2) == Some(&'\'') => 3,
+            _ => 1,
+        }
+    }
+}
+
+// Top-level statements end where the braces, parentheses and brackets opened since their
+// first character are closed and a ";" or a closing brace is met (or the line ends)
+fn scan_braces(source: &str, lines: &[&str], syntax: &Language) -> Vec<Chunk> {
+    let mut scanner = Scanner::new(source, lines.len(), syntax);
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut braces = 0usize;
+    let mut brackets = 0usize;
+    let mut directive = false;
+
+    scanner.run(|line, character| {
+        if character == '\n' {
+            let continued = lines[line].trim_end().ends_with('\\');
+            let ends = (directive && !continued)
+                || (syntax.newline_ends_statements && braces == 0 && brackets == 0 && !continues_on_next_line(lines[line]));
+            if let Some(first) = start
+                && ends {
+                push_chunk(&mut chunks, first, line);
+                start = None;
+                directive = false;
+            }
+            return;
+        }
+
+        if start.is_none() {
+            // A ";" left after a closing brace ("};") belongs to the statement before
+            if character == ';' && braces == 0 {
+                return;
+            }
+            start = Some(line);
+            directive = syntax.preprocessor && character == '#';
+        }
+        match character {
+            '{' => braces += 1,
+            '}' => {
+                braces = braces.saturating_sub(1);
+                if braces == 0 && brackets == 0 && !directive
+                    && let Some(first) = start.take() {
+                    push_chunk(&mut chunks, first, line);
+                }
+            },
+            '(' | '[' => brackets += 1,
+            ')' | ']' => brackets = brackets.saturating_sub(1),
+            ';' if braces == 0 && brackets == 0 && !directive => {
+                if let Some(first) = start.take() {
+                    push_chunk(&mut chunks, first, line);
+                }
+            },
+            _ => {},
+        }
+    });
+    if let Some(first) = start {
+        let last = scanner.content.iter().rposition(|content| *content == LineContent::Code).unwrap_or(first);
+        push_chunk(&mut chunks, first, last.max(first));
+    }
+
+    attach_comments(merge_attributes(chunks, lines), &scanner.content, lines, syntax)
+}
+
+// A line ending with an operator or an opening delimiter goes on (newline-terminated languages)
+fn continues_on_next_line(line: &str) -> bool {
+    let code = line.trim_end();
+    code.ends_with(['=', '+', '-', '*', '/', '.', ',', '&', '|', '(', '[', '{', ':', '?', '\\'])
+        && !code.ends_with("++") && !code.ends_with("--")
+}
+
+// Statements sharing a line are one chunk
+fn push_chunk(chunks: &mut Vec<Chunk>, start: usize, end: usize) {
+    match chunks.last_mut() {
+        Some(last) if start <= last.end => last.end = last.end.max(end),
+        _ => chunks.push(Chunk::new(start, end)),
+    }
+}
+
+// Python: a statement starts at every code line at column 0 (outside strings and brackets)
+// and runs until the next one
+fn scan_indentation(source: &str, lines: &[&str], syntax: &Language) -> Vec<Chunk> {
+    let mut scanner = Scanner::new(source, lines.len(), syntax);
+    let mut starts = Vec::new();
+    let mut brackets = 0usize;
+    let mut line_open = false;
+    let mut continued = false;
+
+    scanner.run(|line, character| {
+        if character == '\n' {
+            continued = lines[line].trim_end().ends_with('\\');
+            line_open = false;
+            return;
+        }
+        if !line_open {
+            line_open = true;
+            let at_column_zero = !lines[line].starts_with(char::is_whitespace);
+            if brackets == 0 && !continued && at_column_zero && starts.last() != Some(&line) {
+                starts.push(line);
+            }
+        }
+        match character {
+            '(' | '[' | '{' => brackets += 1,
+            ')' | ']' | '}' => brackets = brackets.saturating_sub(1),
+            _ => {},
+        }
+    });
+
+    let mut chunks = Vec::new();
+    for (index, start) in starts.iter().enumerate() {
+        let next = starts.get(index + 1).copied().unwrap_or(lines.len());
+        // Trailing blank and comment lines belong to what follows
+        let end = (*start..next).rev().find(|line| scanner.content[*line] == LineContent::Code).unwrap_or(*start);
+        chunks.push(Chunk::new(*start, end));
+    }
+
+    attach_comments(merge_attributes(chunks, lines), &scanner.content, lines, syntax)
+}
+
+// Attributes, annotations and decorators on their own lines belong to the item below them
+fn merge_attributes(chunks: Vec<Chunk>, lines: &[&str]) -> Vec<Chunk> {
+    let mut merged: Vec<Chunk> = Vec::new();
+    let mut pending: Option<usize> = None;
+
+    for mut chunk in chunks {
+        if let Some(start) = pending.take() {
+            chunk.start = start;
+        }
+        let is_attribute = lines[chunk.start..=chunk.end].iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .all(|line| line.starts_with('@') || line.starts_with("#[") || (line.starts_with('[') && line.ends_with(']')));
+        if is_attribute {
+            pending = Some(chunk.start);
+        } else {
+            merged.push(chunk);
+        }
+    }
+    if let Some(start) = pending {
+        merged.push(Chunk::new(start, lines.len().saturating_sub(1)));
+    }
+
+    merged
+}
+
+// Comment lines right above a statement (no blank line between) document it
+fn attach_comments(mut chunks: Vec<Chunk>, content: &[LineContent], lines: &[&str], syntax: &Language) -> Vec<Chunk> {
+    let mut previous_end: Option<usize> = None;
+
+    for chunk in &mut chunks {
+        while chunk.start > 0
+            && previous_end.is_none_or(|end| chunk.start - 1 > end)
+            && content[chunk.start - 1] == LineContent::Comment
+            && !is_file_documentation(lines[chunk.start - 1], syntax) {
+            chunk.start -= 1;
+        }
+        previous_end = Some(chunk.end);
+    }
+
+    chunks
+}
+
+fn is_file_documentation(line: &str, syntax: &Language) -> bool {
+    let line = line.trim_start();
+    syntax.file_doc_comments.iter().any(|prefix| line.starts_with(prefix))
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to items
+//-------------------------------------------------
+
+// Kind, name and documentation of every chunk
+fn classify(chunks: Vec<Chunk>, lines: &[&str], syntax: Option<&Language>) -> Vec<Chunk> {
+    let Some(syntax) = syntax else {
+        return chunks;
+    };
+
+    chunks.into_iter()
+        .map(|mut chunk| {
+            if chunk.kind.is_some() {
+                return chunk;
+            }
+            let header = item_header(&lines[chunk.start..=chunk.end], syntax);
+            let (kind, name) = item_kind_and_name(&header, &lines[chunk.start..=chunk.end], syntax);
+            if kind.is_some() {
+                chunk.documentation = match syntax.layout {
+                    Layout::Indentation => docstring(&lines[chunk.start..=chunk.end]),
+                    _ => doc_comment(&lines[chunk.start..=chunk.end], syntax),
+                };
+            }
+            chunk.kind = kind;
+            chunk.name = name;
+            chunk
+        })
+        .collect()
+}
+
+// Consecutive statements that are not items (imports, top-level calls) form one block
+fn merge_unnamed(chunks: Vec<Chunk>) -> Vec<Chunk> {
+    let mut merged: Vec<Chunk> = Vec::new();
+
+    for chunk in chunks {
+        match merged.last_mut() {
+            Some(last) if last.kind.is_none() && chunk.kind.is_none() => last.end = chunk.end,
+            _ => merged.push(chunk),
+        }
+    }
+
+    merged
+}
+
+// The first code lines of an item, without comments, attributes and decorators, up to its body
+fn item_header(lines: &[&str], syntax: &Language) -> String {
+    let mut header = Vec::new();
+
+    for line in lines {
+        let code = line.trim();
+        let is_comment = syntax.line_comments.iter().any(|prefix| code.starts_with(prefix) && !code.starts_with("#["))
+            || syntax.block_comment.is_some_and(|(open, _)| code.starts_with(open))
+            || code.starts_with('*');
+        if code.is_empty() || is_comment || code.starts_with('@') || code.starts_with("#[") {
+            continue;
+        }
+        header.push(code);
+        if code.contains('{') || code.contains(';') || code.ends_with(':') || header.len() >= HEADER_MAX_LINES {
+            break;
+        }
+    }
+
+    let header = header.join(" ");
+    match header.find('{') {
+        Some(position) => header[..position].to_string(),
+        None => header,
+    }
+}
+
+fn item_kind_and_name(header: &str, lines: &[&str], syntax: &Language) -> (Option<String>, Option<String>) {
+    let words = words(header);
+    let item_kind = |word: &str| syntax.items.iter().find(|(keyword, _)| *keyword == word).map(|(_, kind)| *kind);
+
+    for (index, (word, end)) in words.iter().enumerate() {
+        if syntax.modifiers.contains(word) {
+            continue;
+        }
+        let Some(kind) = item_kind(word) else {
+            break;
+        };
+        // "const fn", "enum class", "export default class": the last keyword decides
+        if *word != "typedef" && words.get(index + 1).is_some_and(|(next, _)| item_kind(next).is_some() || syntax.modifiers.contains(next)) {
+            continue;
+        }
+
+        let rest = &header[*end..];
+        let name = match kind {
+            "impl" => impl_name(rest),
+            // typedef struct { ... } Name;
+            "type" if *word == "typedef" => last_word(lines),
+            _ => item_name(rest, syntax),
+        };
+        // const handler = () => {...}
+        let kind = if kind == "variable" && (rest.contains("=>") || rest.contains("function")) { "function" } else { kind };
+        return (Some(kind.to_string()), name);
+    }
+
+    // int main(int argc, char **argv) {
+    if syntax.implicit_functions
+        && lines.iter().any(|line| line.contains('{'))
+        && let Some(parenthesis) = header.find('(') {
+        let before = header[..parenthesis].trim_end();
+        let name: String = before.chars().rev()
+            .take_while(|character| character.is_alphanumeric() || matches!(character, '_' | ':' | '~'))
+            .collect::<Vec<char>>()
+            .into_iter()
+            .rev()
+            .collect();
+        if !name.is_empty() && !before.contains('=') {
+            return (Some("function".to_string()), Some(name));
+        }
+    }
+
+    (None, None)
+}
+
+// Identifier-like words of a header, with the byte offset where each ends
+fn words(text: &str) -> Vec<(&str, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+
+    for (index, character) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        let is_word = character.is_alphanumeric() || character == '_' || character == '$';
+        match (start, is_word) {
+            (None, true) => start = Some(index),
+            (Some(first), false) => {
+                words.push((&text[first..index], index));
+                start = None;
+            },
+            _ => {},
+        }
+    }
+
+    words
+}
+
+// The identifier after the keyword; Go methods have their receiver in between
+fn item_name(rest: &str, syntax: &Language) -> Option<String> {
+    let mut rest = rest.trim_start().trim_start_matches(['!', '*']).trim_start();
+    if syntax.name == "go" && rest.starts_with('(') {
+        rest = skip_balanced(rest, '(', ')').trim_start();
+    }
+
+    let name: String = rest.chars()
+        .take_while(|character| character.is_alphanumeric() || matches!(character, '_' | '$' | '.' | ':'))
+        .collect();
+    let name = name.trim_end_matches(['.', ':']);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+// "impl<T> Display for Wrapper<T> where T: Debug" -> "Display for Wrapper<T>"
+fn impl_name(rest: &str) -> Option<String> {
+    let mut rest = rest.trim_start();
+    if rest.starts_with('<') {
+        rest = skip_balanced(rest, '<', '>');
+    }
+    let rest = rest.split(" where ").next().unwrap_or(rest);
+    let name = rest.split_whitespace().collect::<Vec<&str>>().join(" ");
+    (!name.is_empty()).then_some(name)
+}
+
+// The last identifier before the final ";" of a chunk
+fn last_word(lines: &[&str]) -> Option<String> {
+    let code = lines.join(" ");
+    let code = code.trim_end().trim_end_matches(';');
+    words(code).last().map(|(word, _)| word.to_string())
+}
+
+fn skip_balanced(text: &str, open: char, close: char) -> &str {
+    let mut depth = 0;
+    for (index, character) in text.char_indices() {
+        if character == open {
+            depth += 1;
+        } else if character == close {
+            depth -= 1;
+            if depth == 0 {
+                return &text[index + character.len_utf8()..];
+            }
+        }
+    }
+    ""
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to documentation
+//-------------------------------------------------
+
+// Doc comments at the top of the chunk, without their comment markers
+fn doc_comment(lines: &[&str], syntax: &Language) -> Option<String> {
+    let mut documentation = Vec::new();
+    let mut in_block = false;
+
+    for line in lines {
+        let code = line.trim();
+        if in_block {
+            let (text, closed) = match code.find("*/") {
+                Some(position) => (&code[..position], true),
+                None => (code, false),
+            };
+            documentation.push(strip_block_line(text));
+            in_block = !closed;
+            continue;
+        }
+
+        let line_prefix = syntax.line_comments.iter()
+            .find(|prefix| code.starts_with(**prefix) && !code.starts_with("#["))
+            .copied();
+        let block_open = syntax.block_comment.map(|(open, _)| open).filter(|open| code.starts_with(open));
+        let documents = |prefix: &str| syntax.doc_comments.is_empty() || syntax.doc_comments.iter().any(|doc| prefix.starts_with(doc));
+
+        if let Some(prefix) = line_prefix {
+            if !documents(code) {
+                continue;
+            }
+            let text = syntax.doc_comments.iter()
+                .find(|doc| code.starts_with(**doc))
+                .map_or(&code[prefix.len()..], |doc| &code[doc.len()..]);
+            documentation.push(text.strip_prefix(' ').unwrap_or(text).to_string());
+        } else if let Some(open) = block_open {
+            if !documents(code) {
+                // A plain comment: skip to its end
+                in_block = !code.contains("*/");
+                if in_block {
+                    documentation.clear();
+                }
+                continue;
+            }
+            let text = code.trim_start_matches(open).trim_start_matches('*').trim_start_matches('!');
+            let (text, closed) = match text.find("*/") {
+                Some(position) => (&text[..position], true),
+                None => (text, false),
+            };
+            documentation.push(strip_block_line(text));
+            in_block = !closed;
+        } else if code.is_empty() || code.starts_with('@') || code.starts_with("#[") {
+            continue;
+        } else {
+            break;
+        }
+    }
+
+    let documentation = documentation.join("\n");
+    (!documentation.trim().is_empty()).then(|| documentation.trim().to_string())
+}
+
+// " * text" -> "text"
+fn strip_block_line(text: &str) -> String {
+    let text = text.trim();
+    let text = text.strip_prefix('*').unwrap_or(text);
+    text.strip_prefix(' ').unwrap_or(text).trim_end().to_string()
+}
+
+// Python: the string literal opening the body of a function or class
+fn docstring(lines: &[&str]) -> Option<String> {
+    let header = lines.iter().position(|line| {
+        let code = line.trim_start();
+        code.starts_with("def ") || code.starts_with("async def ") || code.starts_with("class ")
+    })?;
+    // The header may span several lines (long parameter lists)
+    let body = (header..lines.len()).find(|index| lines[*index].split('#').next().unwrap_or("").trim_end().ends_with(':'))? + 1;
+    let first = (body..lines.len()).find(|index| !lines[*index].trim().is_empty())?;
+
+    string_literal(&lines[first..])
+}
+
+// Content of the string literal the lines start with, dedented like inspect.cleandoc
+fn string_literal(lines: &[&str]) -> Option<String> {
+    let code = lines.first()?.trim_start();
+    let code = code.trim_start_matches(['r', 'R', 'u', 'U']);
+    let quote = ["\"\"\"", "'''", "\"", "'"].into_iter().find(|quote| code.starts_with(quote))?;
+
+    let mut content = Vec::new();
+    let mut rest = &code[quote.len()..];
+    for line in lines.iter().skip(1).map(|line| Some(*line)).chain(std::iter::once(None)) {
+        if let Some(position) = rest.find(quote) {
+            content.push(rest[..position].to_string());
+            return Some(clean_docstring(&content));
+        }
+        content.push(rest.to_string());
+        rest = line?;
+        // Single-quoted strings do not span lines
+        if quote.len() == 1 {
+            return None;
+        }
+    }
+    None
+}
+
+fn clean_docstring(lines: &[String]) -> String {
+    let indentation = lines.iter()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let mut cleaned: Vec<&str> = vec![lines[0].trim()];
+    cleaned.extend(lines.iter().skip(1).map(|line| line.get(indentation..).unwrap_or("").trim_end()));
+    cleaned.join("\n").trim().to_string()
+}
+
+// Documentation of the file itself: Rust "//!" comments, or a Python module docstring
+fn file_documentation(lines: &[&str], chunks: &[Chunk], syntax: &Language) -> String {
+    if syntax.layout == Layout::Indentation {
+        // The first statement, past the "#!" line and comments attached to it
+        let first = chunks.first()
+            .and_then(|chunk| (chunk.start..=chunk.end).find(|index| !lines[*index].trim().is_empty() && !lines[*index].starts_with('#')));
+        return first
+            .and_then(|first| string_literal(&lines[first..]))
+            .unwrap_or_default();
+    }
+
+    let mut documentation = Vec::new();
+    let mut in_block = false;
+    for line in lines {
+        let code = line.trim();
+        if in_block {
+            let (text, closed) = match code.find("*/") {
+                Some(position) => (&code[..position], true),
+                None => (code, false),
+            };
+            documentation.push(strip_block_line(text));
+            in_block = !closed;
+        } else if let Some(prefix) = syntax.file_doc_comments.iter().find(|prefix| code.starts_with(**prefix)) {
+            let text = &code[prefix.len()..];
+            if prefix.starts_with("/*") {
+                let (text, closed) = match text.find("*/") {
+                    Some(position) => (&text[..position], true),
+                    None => (text, false),
+                };
+                documentation.push(strip_block_line(text));
+                in_block = !closed;
+            } else {
+                documentation.push(text.strip_prefix(' ').unwrap_or(text).to_string());
+            }
+        }
+    }
+    documentation.join("\n").trim().to_string()
+}
+
+// Doc comments are Markdown in most languages (and close enough in the others)
+fn prose(documentation: &str) -> Vec<DocumentElement> {
+    if documentation.trim().is_empty() {
+        return Vec::new();
+    }
+    let document = parse_markdown_str(documentation);
+    Document { metadata: Vec::new(), nodes: document.nodes }.to_elements()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::ParseEvent;
+
+    // (kind, name, start_line, end_line) of every item
+    fn items(source: &str, language: &str) -> Vec<(String, Option<String>, u32, u32)> {
+        parse_code_str(source, Some(language)).into_iter()
+            .filter_map(|element| match element {
+                DocumentElement::CodeItem { kind, name, start_line, end_line } => Some((kind, name, start_line, end_line)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn escaped_newline_in_c_string_keeps_line_numbers() {
+        let source = "const char *s = \"abc\\\nxyz\";\n\nint main(void) {\n    return 0;\n}\n";
+        let items = items(source, "c");
+        let main = items.iter().find(|item| item.1.as_deref() == Some("main")).unwrap();
+        assert_eq!((main.2, main.3), (4, 6));
+    }
+
+    #[test]
+    fn escaped_newline_in_python_string_keeps_line_numbers() {
+        let source = "GREETING = 'hello \\\nworld'\n\nclass Greeter:\n    def greet(self):\n        return GREETING\n";
+        let items = items(source, "python");
+        let class = items.iter().find(|item| item.1.as_deref() == Some("Greeter")).unwrap();
+        assert_eq!((class.0.as_str(), class.2, class.3), ("class", 4, 6));
+    }
+
+    #[test]
+    fn rust_items_have_their_line_ranges() {
+        let source = "use std::fmt;\n\n/// Adds\nfn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n\nstruct Point {\n    x: i32,\n}\n";
+        let items = items(source, "rust");
+        assert!(items.contains(&("function".to_string(), Some("add".to_string()), 3, 6)), "{:?}", items);
+        assert!(items.iter().any(|item| item.1.as_deref() == Some("Point") && (item.2, item.3) == (8, 10)), "{:?}", items);
+    }
+
+    #[test]
+    fn latin1_sources_are_reported() {
+        let source = b"def greet():\n    return 'caf\xe9'\n";
+        let path = std::env::temp_dir().join(format!("oxidoc-latin1-{}.py", std::process::id()));
+        fs::write(&path, source).unwrap();
+
+        let warnings = std::cell::RefCell::new(Vec::new());
+        let sink = |event: ParseEvent| if let ParseEvent::Warning { path, .. } = event {
+            warnings.borrow_mut().push(path);
+        };
+        let from_path = CodeParser.parse_path(&path, &sink);
+        fs::remove_file(&path).unwrap();
+        CodeParser.parse_bytes(source, &sink).unwrap();
+
+        assert!(from_path.unwrap().iter().any(|element| matches!(element, DocumentElement::Code { code, .. } if code.contains("café"))));
+        assert_eq!(*warnings.borrow(), vec![Some(path), None]);
+    }
+}
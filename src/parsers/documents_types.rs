@@ -1,6 +1,7 @@
+use std::path::Path;
 use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum DocumentElement {
     // Document metadata (from any document type)
     Title { text: String },
@@ -8,6 +9,10 @@ pub enum DocumentElement {
     Keywords { text: String },
     Author { text: String },
     Language { text: String },
+    Creator { text: String },
+    Producer { text: String },
+    CreationDate { text: String },
+    ModificationDate { text: String },
     
     // Content elements (generic for any document type)
     Heading { level: u8, text: String },
@@ -18,4 +23,331 @@ pub enum DocumentElement {
     Code { code: String, language: Option<String>, inline: bool },
     Link { text: String, url: String },
     Image { alt: String, url: Option<String> },
-}
\ No newline at end of file
+    // Presenter notes attached to a slide
+    SpeakerNotes { text: String },
+    // Text printed by running code ("stdout", "stderr", "result", "error")
+    CodeOutput { text: String, kind: String },
+    // Text a reply quotes from an earlier message ("> ..." lines), kept apart so it can be deduplicated
+    QuotedText { text: String },
+    // Display math with its TeX source untouched, and the environment it came from ("align"...)
+    Math { tex: String, environment: Option<String> },
+
+    // Location markers: every element that follows belongs to this location
+    Page { number: u32 },
+    Slide { number: u32 },
+    // Spreadsheet sheet and the cell range ("A1:F500") of the elements that follow
+    Sheet { name: String, range: String },
+    // Book chapter (1-based, in reading order) and its title from the table of contents
+    Chapter { number: u32, title: Option<String> },
+    // Notebook cell, by its position in the notebook (from 0)
+    Cell { index: u32 },
+    // Top-level source code item ("function", "class", "impl"...) and its lines (1-based, inclusive)
+    CodeItem { kind: String, name: Option<String>, start_line: u32, end_line: u32 },
+    // Email message (from 0 in an mbox archive), its Message-ID and date, and the
+    // Message-IDs it replies to (In-Reply-To) and belongs to (References, oldest first)
+    Message { index: u32, id: Option<String>, date: Option<String>, in_reply_to: Option<String>, references: Vec<String> },
+    // Subtitle or transcript passage: when it is spoken (milliseconds from the start) and by whom
+    Cue { start_ms: u64, end_ms: u64, speaker: Option<String> },
+    // Wiki page from a MediaWiki dump: its title, namespace number (0 for articles) and revision ID
+    WikiPage { title: String, namespace: i32, revision_id: Option<u64> },
+    // Web archive capture: the URI a crawler fetched and when it fetched it (RFC 3339)
+    Capture { uri: String, date: Option<String> },
+}
+
+impl DocumentElement {
+    // Metadata variants describe the document rather than its content
+    pub fn is_metadata(&self) -> bool {
+        matches!(
+            self,
+            DocumentElement::Title { .. }
+                | DocumentElement::Description { .. }
+                | DocumentElement::Keywords { .. }
+                | DocumentElement::Author { .. }
+                | DocumentElement::Language { .. }
+                | DocumentElement::Creator { .. }
+                | DocumentElement::Producer { .. }
+                | DocumentElement::CreationDate { .. }
+                | DocumentElement::ModificationDate { .. }
+        )
+    }
+}
+
+
+//---------------------------------------------
+// Hierarchical document model
+//---------------------------------------------
+
+// Tree view of a document: sections come from heading levels and every block keeps its nested content.
+// `to_elements` flattens it back into the same Vec<DocumentElement> the converters consume.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Document {
+    pub metadata: Vec<DocumentElement>,
+    pub nodes: Vec<Node>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Node {
+    // A heading plus everything up to the next heading of the same or higher level
+    Section { level: u8, heading: String, children: Vec<Node> },
+    Paragraph { text: String, children: Vec<Node> },
+    Blockquote { text: String, children: Vec<Node> },
+    List { ordered: bool, items: Vec<ListItem> },
+    Table { headers: Vec<TableCell>, rows: Vec<Vec<TableCell>> },
+    // Leaf content (code, links, images...) and metadata found inside the body
+    Element(DocumentElement),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ListItem {
+    pub text: String,
+    // Nested lists, code, links... inside the item
+    pub children: Vec<Node>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TableCell {
+    pub text: String,
+    pub children: Vec<Node>,
+}
+
+impl Document {
+    // Build the tree from a flat element list (leading metadata is split out, headings open sections)
+    pub fn from_elements(elements: Vec<DocumentElement>) -> Document {
+        let mut document = Document::default();
+        let mut nodes = Vec::new();
+
+        for element in elements {
+            if nodes.is_empty() && element.is_metadata() {
+                document.metadata.push(element);
+                continue;
+            }
+
+            let node = match element {
+                DocumentElement::Heading { level, text } => {
+                    Node::Section { level, heading: text, children: Vec::new() }
+                },
+                DocumentElement::Paragraph { text } => {
+                    Node::Paragraph { text, children: Vec::new() }
+                },
+                DocumentElement::Blockquote { text } => {
+                    Node::Blockquote { text, children: Vec::new() }
+                },
+                DocumentElement::List { items, ordered } => {
+                    let items = items.into_iter()
+                        .map(|text| ListItem { text, children: Vec::new() })
+                        .collect();
+                    Node::List { ordered, items }
+                },
+                DocumentElement::Table { headers, rows } => {
+                    let to_cells = |texts: Vec<String>| -> Vec<TableCell> {
+                        texts.into_iter()
+                            .map(|text| TableCell { text, children: Vec::new() })
+                            .collect()
+                    };
+                    Node::Table {
+                        headers: to_cells(headers),
+                        rows: rows.into_iter().map(to_cells).collect(),
+                    }
+                },
+                other => Node::Element(other),
+            };
+            nodes.push(node);
+        }
+
+        document.nodes = group_sections(nodes);
+        document
+    }
+
+    // Flatten the tree back into document order
+    pub fn to_elements(&self) -> Vec<DocumentElement> {
+        let mut elements = self.metadata.clone();
+        for node in &self.nodes {
+            node.flatten_into(&mut elements);
+        }
+        elements
+    }
+}
+
+impl Node {
+    // Flatten this node and its descendants into document order
+    pub fn to_elements(&self) -> Vec<DocumentElement> {
+        let mut elements = Vec::new();
+        self.flatten_into(&mut elements);
+        elements
+    }
+
+    fn flatten_into(&self, elements: &mut Vec<DocumentElement>) {
+        match self {
+            Node::Section { level, heading, children } => {
+                elements.push(DocumentElement::Heading { level: *level, text: heading.clone() });
+                flatten_nodes(children, elements);
+            },
+            Node::Paragraph { text, children } => {
+                elements.push(DocumentElement::Paragraph { text: text.clone() });
+                flatten_nodes(children, elements);
+            },
+            Node::Blockquote { text, children } => {
+                elements.push(DocumentElement::Blockquote { text: text.clone() });
+                flatten_nodes(children, elements);
+            },
+            Node::List { ordered, items } => {
+                // The flat List carries every item text, nested lists included, in document order;
+                // what else the items hold (code, links...) follows it
+                let mut texts = Vec::new();
+                collect_list_items(items, &mut texts);
+                elements.push(DocumentElement::List { items: texts, ordered: *ordered });
+                flatten_item_children(items, elements);
+            },
+            Node::Table { headers, rows } => {
+                let header_texts: Vec<String> = headers.iter().map(|cell| cell.text.clone()).collect();
+                let row_texts: Vec<Vec<String>> = rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.text.clone()).collect())
+                    .collect();
+                elements.push(DocumentElement::Table { headers: header_texts, rows: row_texts });
+                for cell in headers.iter().chain(rows.iter().flatten()) {
+                    flatten_nodes(&cell.children, elements);
+                }
+            },
+            Node::Element(element) => elements.push(element.clone()),
+        }
+    }
+}
+
+fn flatten_nodes(nodes: &[Node], elements: &mut Vec<DocumentElement>) {
+    for node in nodes {
+        node.flatten_into(elements);
+    }
+}
+
+// Item texts of a list and of the lists nested directly in its items
+fn collect_list_items(items: &[ListItem], texts: &mut Vec<String>) {
+    for item in items {
+        texts.push(item.text.clone());
+        for child in &item.children {
+            if let Node::List { items, .. } = child {
+                collect_list_items(items, texts);
+            }
+        }
+    }
+}
+
+// Everything the items hold except the nested lists, whose items are already in the flat List
+fn flatten_item_children(items: &[ListItem], elements: &mut Vec<DocumentElement>) {
+    for item in items {
+        for child in &item.children {
+            match child {
+                Node::List { items, .. } => flatten_item_children(items, elements),
+                child => child.flatten_into(elements),
+            }
+        }
+    }
+}
+
+// Nest sibling nodes under the preceding Section with a lower heading level.
+// Sections passed in may already hold children (e.g. links inside the heading); those stay first.
+pub fn group_sections(nodes: Vec<Node>) -> Vec<Node> {
+    let mut grouped = Vec::new();
+    let mut open_sections: Vec<Node> = Vec::new();
+
+    for node in nodes {
+        if let Node::Section { level, .. } = &node {
+            let level = *level;
+            // Close every open section at the same or a deeper level
+            while open_sections.last().is_some_and(|open| section_level(open) >= level) {
+                let closed = open_sections.pop().unwrap();
+                push_node(&mut open_sections, &mut grouped, closed);
+            }
+            open_sections.push(node);
+        } else {
+            push_node(&mut open_sections, &mut grouped, node);
+        }
+    }
+
+    while let Some(closed) = open_sections.pop() {
+        push_node(&mut open_sections, &mut grouped, closed);
+    }
+
+    grouped
+}
+
+fn section_level(node: &Node) -> u8 {
+    match node {
+        Node::Section { level, .. } => *level,
+        _ => 0,
+    }
+}
+
+// Append to the innermost open section, or to the top level when none is open
+fn push_node(open_sections: &mut [Node], grouped: &mut Vec<Node>, node: Node) {
+    match open_sections.last_mut() {
+        Some(Node::Section { children, .. }) => children.push(node),
+        _ => grouped.push(node),
+    }
+}
+
+// Fall back to the filename when the parsed document carries no title of its own
+pub fn fallback_title(elements: &mut Vec<DocumentElement>, path: &Path) {
+    if !elements.iter().any(|element| matches!(element, DocumentElement::Title { .. }))
+        && let Some(title_str) = path.file_stem().and_then(|filename| filename.to_str()) {
+        elements.insert(0, DocumentElement::Title {
+            text: title_str.to_string()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(text: &str, children: Vec<Node>) -> ListItem {
+        ListItem { text: text.to_string(), children }
+    }
+
+    #[test]
+    fn nested_list_items_are_flattened_once() {
+        let code = DocumentElement::Code { code: "x".to_string(), language: None, inline: true };
+        let nested = Node::List { ordered: false, items: vec![item("b", Vec::new()), item("c", Vec::new())] };
+        let document = Document {
+            metadata: Vec::new(),
+            nodes: vec![Node::List {
+                ordered: false,
+                items: vec![item("a", vec![nested, Node::Element(code.clone())]), item("d", Vec::new())],
+            }],
+        };
+
+        let elements = document.to_elements();
+        let list = DocumentElement::List { items: vec!["a".into(), "b".into(), "c".into(), "d".into()], ordered: false };
+        assert_eq!(elements, vec![list, code]);
+
+        // The flat elements rebuild a tree that flattens to the same elements
+        assert_eq!(Document::from_elements(elements.clone()).to_elements(), elements);
+    }
+
+    #[test]
+    fn nested_list_round_trips() {
+        let elements = vec![
+            DocumentElement::Title { text: "T".to_string() },
+            DocumentElement::Heading { level: 1, text: "H".to_string() },
+            DocumentElement::List { items: vec!["a".into(), "b".into(), "".into()], ordered: true },
+            DocumentElement::List { items: Vec::new(), ordered: false },
+            DocumentElement::Table { headers: Vec::new(), rows: Vec::new() },
+            DocumentElement::Paragraph { text: "p".to_string() },
+        ];
+
+        let document = Document::from_elements(elements.clone());
+        assert_eq!(document.to_elements(), elements);
+        assert_eq!(Document::from_elements(document.to_elements()), document);
+    }
+
+    #[test]
+    fn filename_is_the_title_only_when_none_was_found() {
+        let title = DocumentElement::Title { text: "Report".to_string() };
+        let mut titled = vec![title.clone()];
+        fallback_title(&mut titled, Path::new("notes/draft.pdf"));
+        assert_eq!(titled, vec![title]);
+
+        let mut untitled = vec![DocumentElement::Paragraph { text: "Text".to_string() }];
+        fallback_title(&mut untitled, Path::new("notes/draft.pdf"));
+        assert_eq!(untitled[0], DocumentElement::Title { text: "draft".to_string() });
+    }
+}
@@ -0,0 +1,526 @@
+// docx_parser.rs
+
+use roxmltree::Node;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::EventSink;
+use crate::parsers::documents_types::{fallback_title, DocumentElement};
+use crate::parsers::ooxml::{attribute, child, child_value, children, document_properties, is_named, open_package, parse_xml, read_part, relationship_id, relationships, Package, Relationship};
+use crate::parsers::parser::Parser;
+
+const DOCUMENT_PART: &str = "word/document.xml";
+// Styles are looked up through at most this many "based on" links
+const STYLE_MAX_DEPTH: usize = 10;
+
+// Main parsing function
+pub fn parse_docx_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    // Read the DOCX file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+
+    let mut elements = parse_docx_bytes(&bytes).map_err(|error| error.with_path(path))?;
+
+    fallback_title(&mut elements, path);
+
+    Ok(elements)
+}
+
+// Parse an in-memory DOCX: document properties first, then the body in reading order
+pub fn parse_docx_bytes(bytes: &[u8]) -> Result<Vec<DocumentElement>> {
+    let mut package = open_package(bytes)?;
+
+    let xml = read_part(&mut package, DOCUMENT_PART)?
+        .ok_or_else(|| OxidocError::malformed(format!("DOCX has no {}", DOCUMENT_PART)))?;
+    let document = parse_xml(&xml, DOCUMENT_PART)?;
+    let body = child(document.root_element(), "body")
+        .ok_or_else(|| OxidocError::malformed("DOCX document has no body"))?;
+
+    let context = DocxContext {
+        styles: load_styles(&mut package)?,
+        numbering: load_numbering(&mut package)?,
+        relationships: relationships(&mut package, DOCUMENT_PART)?,
+    };
+
+    let mut elements = document_properties(&mut package)?;
+    let mut body_elements = Vec::new();
+    extract_blocks(body, &context, &mut body_elements);
+
+    // A paragraph styled "Title" is the document title unless the properties already have one
+    let has_title = elements.iter().any(|element| matches!(element, DocumentElement::Title { .. }));
+    for element in body_elements {
+        match element {
+            DocumentElement::Title { text } if has_title => elements.push(DocumentElement::Heading { level: 1, text }),
+            DocumentElement::Title { text } => elements.insert(0, DocumentElement::Title { text }),
+            other => elements.push(other),
+        }
+    }
+
+    Ok(elements)
+}
+
+// DOCX implementation of the common Parser trait
+pub struct DocxParser;
+
+impl Parser for DocxParser {
+    fn name(&self) -> &str {
+        "docx"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["docx", "docm", "dotx"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/vnd.openxmlformats-officedocument.wordprocessingml.document"]
+    }
+
+    // A zip whose first entries name Word parts
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(b"PK\x03\x04") && bytes.windows(5).any(|window| window == b"word/")
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], _events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_docx_bytes(bytes)
+    }
+
+    fn parse_path(&self, path: &Path, _events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_docx_file(path)
+    }
+}
+
+// Package parts needed to interpret the body
+struct DocxContext {
+    styles: HashMap<String, Style>,
+    numbering: Numbering,
+    relationships: HashMap<String, Relationship>,
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to body content
+//-------------------------------------------------
+
+// What a paragraph style makes of its paragraphs
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ParagraphKind {
+    Title,
+    Heading(u8),
+    Quote,
+    Code,
+    Normal,
+}
+
+// Walk the block-level content of the body (or of a content control) in order
+fn extract_blocks(parent: Node, context: &DocxContext, elements: &mut Vec<DocumentElement>) {
+    for node in parent.children().filter(|node| node.is_element()) {
+        match node.tag_name().name() {
+            "p" => extract_paragraph(node, context, elements),
+            "tbl" => elements.push(extract_table(node)),
+            // Content controls wrap ordinary paragraphs and tables
+            "sdt" => {
+                if let Some(content) = child(node, "sdtContent") {
+                    extract_blocks(content, context, elements);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+fn extract_paragraph(paragraph: Node, context: &DocxContext, elements: &mut Vec<DocumentElement>) {
+    let text = paragraph_text(paragraph);
+    let properties = child(paragraph, "pPr");
+    let style_id = properties.and_then(|properties| child_value(properties, "pStyle"));
+    let kind = style_id.map_or(ParagraphKind::Normal, |style_id| paragraph_kind(style_id, &context.styles));
+    // Outline levels set on the paragraph itself also make headings
+    let kind = match properties.and_then(|properties| child_value(properties, "outlineLvl")) {
+        Some(level) if kind == ParagraphKind::Normal => outline_heading(level).unwrap_or(kind),
+        _ => kind,
+    };
+
+    if !text.trim().is_empty() {
+        // The paragraph's own numbering wins over the one its style ("List Bullet"...) carries
+        let own = properties.and_then(|properties| child(properties, "numPr"));
+        let inherited = style_id.and_then(|style_id| style_numbering(style_id, &context.styles));
+        let num_id = own.and_then(|own| child_value(own, "numId"))
+            .or(inherited.and_then(|(num_id, _)| num_id));
+        let level = own.and_then(|own| child_value(own, "ilvl"))
+            .or(inherited.and_then(|(_, level)| level))
+            .unwrap_or("0");
+        match num_id.and_then(|num_id| context.numbering.is_ordered(num_id, level)) {
+            Some(ordered) if !matches!(kind, ParagraphKind::Heading(_) | ParagraphKind::Title) => {
+                push_list_item(elements, text.trim().to_string(), ordered);
+            },
+            _ => push_paragraph(elements, text, kind),
+        }
+    }
+
+    // Hyperlinks and images follow the paragraph holding them
+    for node in paragraph.descendants() {
+        if is_named(node, "hyperlink") {
+            elements.extend(extract_link(node, &context.relationships));
+        } else if is_named(node, "drawing") {
+            elements.extend(extract_image(node, &context.relationships));
+        }
+    }
+}
+
+fn push_paragraph(elements: &mut Vec<DocumentElement>, text: String, kind: ParagraphKind) {
+    match kind {
+        ParagraphKind::Title => elements.push(DocumentElement::Title { text: text.trim().to_string() }),
+        ParagraphKind::Heading(level) => elements.push(DocumentElement::Heading { level, text: text.trim().to_string() }),
+        ParagraphKind::Quote => elements.push(DocumentElement::Blockquote { text: text.trim().to_string() }),
+        ParagraphKind::Code => {
+            // Code is written one paragraph per line: consecutive lines form one block
+            match elements.last_mut() {
+                Some(DocumentElement::Code { code, inline: false, .. }) => {
+                    code.push('\n');
+                    code.push_str(&text);
+                },
+                _ => elements.push(DocumentElement::Code { code: text, language: None, inline: false }),
+            }
+        },
+        ParagraphKind::Normal => elements.push(DocumentElement::Paragraph { text: text.trim().to_string() }),
+    }
+}
+
+// Consecutive numbered paragraphs of the same kind form one list
+fn push_list_item(elements: &mut Vec<DocumentElement>, text: String, ordered: bool) {
+    match elements.last_mut() {
+        Some(DocumentElement::List { items, ordered: list_ordered }) if *list_ordered == ordered => items.push(text),
+        _ => elements.push(DocumentElement::List { items: vec![text], ordered }),
+    }
+}
+
+// Text of the runs of a paragraph, including those inside hyperlinks, insertions and fields,
+// but not deleted text or field instructions
+fn paragraph_text(paragraph: Node) -> String {
+    let mut text = String::new();
+
+    for node in paragraph.descendants().filter(|node| node.is_element()) {
+        match node.tag_name().name() {
+            "t" => text.push_str(node.text().unwrap_or("")),
+            "tab" if is_named(node.parent().unwrap_or(node), "r") => text.push('\t'),
+            "br" | "cr" => text.push('\n'),
+            "noBreakHyphen" => text.push('-'),
+            _ => {},
+        }
+    }
+
+    text
+}
+
+// Rows marked as header rows become the headers, otherwise the first row does
+fn extract_table(table: Node) -> DocumentElement {
+    let mut headers = Vec::new();
+    let mut rows = Vec::new();
+
+    for row in children(table, "tr") {
+        let cells: Vec<String> = children(row, "tc")
+            .map(|cell| {
+                // Each paragraph of a cell is a line (nested tables included)
+                cell.descendants()
+                    .filter(|node| is_named(*node, "p"))
+                    .map(|paragraph| paragraph_text(paragraph).trim().to_string())
+                    .filter(|text| !text.is_empty())
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            })
+            .collect();
+
+        let is_header = child(row, "trPr").is_some_and(|properties| child(properties, "tblHeader").is_some());
+        if is_header && rows.is_empty() {
+            headers.extend(cells);
+        } else {
+            rows.push(cells);
+        }
+    }
+
+    if headers.is_empty() && !rows.is_empty() {
+        headers = rows.remove(0);
+    }
+
+    DocumentElement::Table { headers, rows }
+}
+
+// External links resolve through the relationships, internal ones point at a bookmark
+fn extract_link(hyperlink: Node, relationships: &HashMap<String, Relationship>) -> Option<DocumentElement> {
+    let url = match relationship_id(hyperlink, "id") {
+        Some(id) => relationships.get(id)?.target.clone(),
+        None => format!("#{}", attribute(hyperlink, "anchor")?),
+    };
+    let text = paragraph_text(hyperlink).trim().to_string();
+
+    if url.is_empty() || text.is_empty() {
+        return None;
+    }
+    Some(DocumentElement::Link { text, url })
+}
+
+// Pictures: the description (or name) is the alt text, the embedded media part the url
+fn extract_image(drawing: Node, relationships: &HashMap<String, Relationship>) -> Option<DocumentElement> {
+    let properties = drawing.descendants().find(|node| is_named(*node, "docPr"))?;
+    let alt = attribute(properties, "descr")
+        .or_else(|| attribute(properties, "title"))
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    let url = drawing.descendants()
+        .find(|node| is_named(*node, "blip"))
+        .and_then(|blip| relationship_id(blip, "embed").or_else(|| relationship_id(blip, "link")))
+        .and_then(|id| relationships.get(id))
+        .map(|relationship| relationship.target.clone());
+
+    if alt.is_empty() && url.is_none() {
+        return None;
+    }
+    Some(DocumentElement::Image { alt, url })
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to styles
+//-------------------------------------------------
+
+struct Style {
+    name: String,
+    based_on: Option<String>,
+    outline_level: Option<u8>,
+    // numId and ilvl of the numbering the style gives its paragraphs
+    num_id: Option<String>,
+    num_level: Option<String>,
+}
+
+fn load_styles(package: &mut Package) -> Result<HashMap<String, Style>> {
+    let mut styles = HashMap::new();
+    let Some(xml) = read_part(package, "word/styles.xml")? else {
+        return Ok(styles);
+    };
+    let document = parse_xml(&xml, "word/styles.xml")?;
+
+    for style in children(document.root_element(), "style") {
+        let Some(id) = attribute(style, "styleId") else {
+            continue;
+        };
+        let properties = child(style, "pPr");
+        let numbering = properties.and_then(|properties| child(properties, "numPr"));
+        styles.insert(id.to_string(), Style {
+            name: child_value(style, "name").unwrap_or(id).to_lowercase(),
+            based_on: child_value(style, "basedOn").map(|based_on| based_on.to_string()),
+            outline_level: properties
+                .and_then(|properties| child_value(properties, "outlineLvl"))
+                .and_then(|level| level.parse().ok()),
+            num_id: numbering.and_then(|numbering| child_value(numbering, "numId")).map(|id| id.to_string()),
+            num_level: numbering.and_then(|numbering| child_value(numbering, "ilvl")).map(|level| level.to_string()),
+        });
+    }
+
+    Ok(styles)
+}
+
+// Follow a style and the styles it is based on until one of them says what the paragraph is.
+// Built-in style names ("heading 1", "Title", "Quote") are the same in every language,
+// unlike style ids, which Word localizes.
+fn paragraph_kind(style_id: &str, styles: &HashMap<String, Style>) -> ParagraphKind {
+    let mut current = Some(style_id);
+
+    for _ in 0..STYLE_MAX_DEPTH {
+        let Some(id) = current else {
+            break;
+        };
+        let name = styles.get(id).map_or_else(|| id.to_lowercase(), |style| style.name.clone());
+
+        if let Some(kind) = style_name_kind(&name) {
+            return kind;
+        }
+        if let Some(level) = styles.get(id).and_then(|style| style.outline_level)
+            && level < 9 {
+            return ParagraphKind::Heading((level + 1).min(6));
+        }
+        current = styles.get(id).and_then(|style| style.based_on.as_deref());
+    }
+
+    ParagraphKind::Normal
+}
+
+// numId and ilvl a paragraph style gives, from the first style of its "based on" chain with numbering
+fn style_numbering<'a>(style_id: &str, styles: &'a HashMap<String, Style>) -> Option<(Option<&'a str>, Option<&'a str>)> {
+    let mut style = styles.get(style_id)?;
+
+    for _ in 0..STYLE_MAX_DEPTH {
+        if style.num_id.is_some() || style.num_level.is_some() {
+            return Some((style.num_id.as_deref(), style.num_level.as_deref()));
+        }
+        style = styles.get(style.based_on.as_deref()?)?;
+    }
+
+    None
+}
+
+fn style_name_kind(name: &str) -> Option<ParagraphKind> {
+    let compact: String = name.chars().filter(|c| !c.is_whitespace()).collect();
+
+    if compact == "title" {
+        return Some(ParagraphKind::Title);
+    }
+    if let Some(level) = compact.strip_prefix("heading").and_then(|level| level.parse::<u8>().ok()) {
+        return Some(ParagraphKind::Heading(level.clamp(1, 6)));
+    }
+    if compact == "quote" || compact == "intensequote" || compact == "blocktext" {
+        return Some(ParagraphKind::Quote);
+    }
+    // "Code", pandoc's "Source Code", Word's "HTML Preformatted" and "Macro Text"
+    if matches!(compact.as_str(), "code" | "codeblock" | "sourcecode" | "htmlpreformatted" | "macrotext") {
+        return Some(ParagraphKind::Code);
+    }
+    None
+}
+
+fn outline_heading(level: &str) -> Option<ParagraphKind> {
+    let level: u8 = level.parse().ok()?;
+    // Level 9 is body text
+    (level < 9).then(|| ParagraphKind::Heading((level + 1).min(6)))
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to numbering
+//-------------------------------------------------
+
+// numbering.xml: each numbering instance (numId) points at an abstract definition,
+// which gives the number format of every indentation level; an instance may override some levels
+struct Numbering {
+    instances: HashMap<String, String>,
+    // (abstractNumId, level) -> numFmt
+    formats: HashMap<(String, String), String>,
+    // (numId, level) -> numFmt, from <w:lvlOverride>
+    overrides: HashMap<(String, String), String>,
+}
+
+impl Numbering {
+    // Whether a paragraph numbered `id` at `level` is part of an ordered list; None when it is not in a list
+    fn is_ordered(&self, id: &str, level: &str) -> Option<bool> {
+        // numId 0 removes the numbering inherited from the style
+        if id == "0" {
+            return None;
+        }
+
+        let key = (id.to_string(), level.to_string());
+        let format = self.overrides.get(&key).or_else(|| {
+            self.instances.get(id)
+                .and_then(|abstract_id| self.formats.get(&(abstract_id.clone(), level.to_string())))
+        });
+        Some(format.is_some_and(|format| format != "bullet" && format != "none"))
+    }
+}
+
+fn load_numbering(package: &mut Package) -> Result<Numbering> {
+    let mut numbering = Numbering { instances: HashMap::new(), formats: HashMap::new(), overrides: HashMap::new() };
+    let Some(xml) = read_part(package, "word/numbering.xml")? else {
+        return Ok(numbering);
+    };
+    let document = parse_xml(&xml, "word/numbering.xml")?;
+    let root = document.root_element();
+
+    for definition in children(root, "abstractNum") {
+        let Some(abstract_id) = attribute(definition, "abstractNumId") else {
+            continue;
+        };
+        for level in children(definition, "lvl") {
+            if let (Some(level_id), Some(format)) = (attribute(level, "ilvl"), child_value(level, "numFmt")) {
+                numbering.formats.insert((abstract_id.to_string(), level_id.to_string()), format.to_string());
+            }
+        }
+    }
+
+    for instance in children(root, "num") {
+        let Some(id) = attribute(instance, "numId") else {
+            continue;
+        };
+        if let Some(abstract_id) = child_value(instance, "abstractNumId") {
+            numbering.instances.insert(id.to_string(), abstract_id.to_string());
+        }
+        // <w:lvlOverride w:ilvl="0"><w:lvl w:ilvl="0"><w:numFmt w:val="decimal"/></w:lvl></w:lvlOverride>
+        for level_override in children(instance, "lvlOverride") {
+            let format = child(level_override, "lvl").and_then(|level| child_value(level, "numFmt"));
+            if let (Some(level_id), Some(format)) = (attribute(level_override, "ilvl"), format) {
+                numbering.overrides.insert((id.to_string(), level_id.to_string()), format.to_string());
+            }
+        }
+    }
+
+    Ok(numbering)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    const W: &str = r#"xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main""#;
+
+    fn docx(body: &str, styles: &str, numbering: &str) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let parts = [
+            ("word/document.xml", format!("<w:document {}><w:body>{}</w:body></w:document>", W, body)),
+            ("word/styles.xml", format!("<w:styles {}>{}</w:styles>", W, styles)),
+            ("word/numbering.xml", format!("<w:numbering {}>{}</w:numbering>", W, numbering)),
+        ];
+        for (name, xml) in parts {
+            writer.start_file(name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(xml.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn paragraph(style: &str, text: &str) -> String {
+        format!(r#"<w:p><w:pPr><w:pStyle w:val="{}"/></w:pPr><w:r><w:t>{}</w:t></w:r></w:p>"#, style, text)
+    }
+
+    const NUMBERING: &str = r#"
+        <w:abstractNum w:abstractNumId="0"><w:lvl w:ilvl="0"><w:numFmt w:val="bullet"/></w:lvl></w:abstractNum>
+        <w:abstractNum w:abstractNumId="1"><w:lvl w:ilvl="0"><w:numFmt w:val="decimal"/></w:lvl></w:abstractNum>
+        <w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>
+        <w:num w:numId="2"><w:abstractNumId w:val="1"/></w:num>
+        <w:num w:numId="3"><w:abstractNumId w:val="0"/>
+            <w:lvlOverride w:ilvl="0"><w:lvl w:ilvl="0"><w:numFmt w:val="lowerLetter"/></w:lvl></w:lvlOverride>
+        </w:num>"#;
+
+    #[test]
+    fn list_styles_number_their_paragraphs_through_based_on() {
+        let styles = r#"
+            <w:style w:styleId="ListBullet"><w:name w:val="List Bullet"/><w:pPr><w:numPr><w:numId w:val="1"/></w:numPr></w:pPr></w:style>
+            <w:style w:styleId="MyBullets"><w:name w:val="My Bullets"/><w:basedOn w:val="ListBullet"/></w:style>
+            <w:style w:styleId="ListNumber"><w:name w:val="List Number"/><w:pPr><w:numPr><w:numId w:val="2"/></w:numPr></w:pPr></w:style>"#;
+        let body = [paragraph("MyBullets", "one"), paragraph("ListBullet", "two"), paragraph("ListNumber", "first")].concat();
+
+        let elements = parse_docx_bytes(&docx(&body, styles, NUMBERING)).unwrap();
+        assert_eq!(elements, vec![
+            DocumentElement::List { items: vec!["one".into(), "two".into()], ordered: false },
+            DocumentElement::List { items: vec!["first".into()], ordered: true },
+        ]);
+    }
+
+    #[test]
+    fn level_overrides_change_the_list_kind() {
+        let body = r#"<w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="3"/></w:numPr></w:pPr><w:r><w:t>a</w:t></w:r></w:p>"#;
+        let elements = parse_docx_bytes(&docx(body, "", NUMBERING)).unwrap();
+        assert_eq!(elements, vec![DocumentElement::List { items: vec!["a".into()], ordered: true }]);
+    }
+
+    #[test]
+    fn only_code_style_names_make_code() {
+        let styles = r#"
+            <w:style w:styleId="SourceCode"><w:name w:val="Source Code"/></w:style>
+            <w:style w:styleId="Barcode"><w:name w:val="Barcode Label"/></w:style>"#;
+        let body = [paragraph("SourceCode", "let x = 1;"), paragraph("Barcode", "Postcode")].concat();
+
+        let elements = parse_docx_bytes(&docx(&body, styles, "")).unwrap();
+        assert_eq!(elements, vec![
+            DocumentElement::Code { code: "let x = 1;".to_string(), language: None, inline: false },
+            DocumentElement::Paragraph { text: "Postcode".to_string() },
+        ]);
+    }
+}
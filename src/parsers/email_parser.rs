@@ -0,0 +1,656 @@
+// email_parser.rs
+
+// RFC 5322 messages (.eml) and mbox archives. Every message is introduced by a Message marker
+// carrying its Message-ID, date and thread references, followed by its Subject (Title),
+// From (Author) and Date (CreationDate) and its body. MIME bodies are walked part by part:
+// text/plain is preferred in multipart/alternative, HTML parts go through the HTML parser,
+// attachments are skipped. Quoted reply text ("> ..." lines) becomes QuotedText.
+
+use encoding_rs::Encoding;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::{EventSink, ParseEvent};
+use crate::parsers::documents_types::{fallback_title, Document, DocumentElement, ListItem, Node, TableCell};
+use crate::parsers::html_parser::{decode_html, parse_html_str};
+use crate::parsers::markup::elements_text;
+use crate::parsers::parser::Parser;
+use crate::parsers::txt_parser::{decode_text, parse_txt_str};
+
+// Multiparts nested deeper than this are skipped
+const MAX_MIME_DEPTH: usize = 16;
+
+// Header names a message commonly starts with, to recognize .eml files without an extension
+const LEADING_HEADERS: &[&str] = &[
+    "return-path", "received", "delivered-to", "from", "to", "subject", "date", "message-id",
+    "mime-version", "content-type", "x-mozilla-status", "x-original-to", "reply-to", "sender",
+    "envelope-to", "received-spf", "authentication-results", "dkim-signature", "arc-seal",
+];
+
+// Header names (lowercased) and unfolded values, in message order
+type Headers = Vec<(String, String)>;
+
+// Main parsing function
+pub fn parse_email_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    parse_email_path(path, &())
+}
+
+fn parse_email_path(path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    // Read the message or archive (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+
+    let mut elements = parse_email_bytes_with(&bytes, events)?;
+
+    fallback_title(&mut elements, path);
+
+    Ok(elements)
+}
+
+// Parse an in-memory message or mbox archive
+pub fn parse_email_bytes(bytes: &[u8]) -> Result<Vec<DocumentElement>> {
+    parse_email_bytes_with(bytes, &())
+}
+
+pub fn parse_email_bytes_with(bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    if is_mbox(bytes) {
+        Ok(mbox_messages(bytes)
+            .iter()
+            .enumerate()
+            .flat_map(|(index, message)| message_elements(message, index as u32, events))
+            .collect())
+    } else {
+        Ok(message_elements(bytes, 0, events))
+    }
+}
+
+// Email implementation of the common Parser trait
+pub struct EmailParser;
+
+impl Parser for EmailParser {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["eml", "mbox", "mbx"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["message/rfc822", "application/mbox"]
+    }
+
+    // An mbox "From " line, or a message starting with one of the usual headers
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        if is_mbox(bytes) {
+            return true;
+        }
+        let first_line = bytes.split(|byte| *byte == b'\n').next().unwrap_or_default();
+        let first_line = String::from_utf8_lossy(first_line);
+        first_line.split_once(':')
+            .is_some_and(|(name, _)| LEADING_HEADERS.contains(&name.to_lowercase().as_str()))
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_email_bytes_with(bytes, events)
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_email_path(path, events)
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to messages
+//-------------------------------------------------
+
+// mbox archives start every message with a "From sender date" line
+fn is_mbox(bytes: &[u8]) -> bool {
+    let mut lines = bytes.split(|byte| *byte == b'\n');
+    lines.next().is_some_and(|line| line.starts_with(b"From "))
+        && lines.next().is_some_and(|line| String::from_utf8_lossy(line).split_once(':').is_some_and(|(name, _)| is_header_name(name)))
+}
+
+// Messages of an mbox archive, without their "From " lines and with ">From " unescaped
+fn mbox_messages(bytes: &[u8]) -> Vec<Vec<u8>> {
+    let mut messages: Vec<Vec<u8>> = Vec::new();
+    let mut previous_blank = true;
+
+    for line in bytes.split_inclusive(|byte| *byte == b'\n') {
+        if previous_blank && line.starts_with(b"From ") {
+            messages.push(Vec::new());
+            previous_blank = false;
+            continue;
+        }
+        previous_blank = line.iter().all(|byte| byte.is_ascii_whitespace());
+        let Some(message) = messages.last_mut() else {
+            continue;
+        };
+        // mboxrd escapes body lines starting with "From " (after any ">") with one more ">"
+        let quotes = line.iter().take_while(|byte| **byte == b'>').count();
+        if quotes > 0 && line[quotes..].starts_with(b"From ") {
+            message.extend_from_slice(&line[1..]);
+        } else {
+            message.extend_from_slice(line);
+        }
+    }
+
+    messages
+}
+
+fn message_elements(bytes: &[u8], index: u32, events: &dyn EventSink) -> Vec<DocumentElement> {
+    let (headers, body) = split_entity(bytes);
+    let mut elements = Vec::new();
+
+    let date = header(&headers, "date").map(normalize_date);
+    let references = header(&headers, "references").map(message_ids).unwrap_or_default();
+    elements.push(DocumentElement::Message {
+        index,
+        id: header(&headers, "message-id").and_then(|value| message_ids(value).into_iter().next()),
+        date: date.clone(),
+        in_reply_to: header(&headers, "in-reply-to").and_then(|value| message_ids(value).into_iter().next()),
+        references,
+    });
+
+    if let Some(subject) = header(&headers, "subject").map(|subject| decode_words(subject, events)).filter(|subject| !subject.is_empty()) {
+        elements.push(DocumentElement::Title { text: subject });
+    }
+    if let Some(from) = header(&headers, "from").map(|from| decode_words(from, events)).filter(|from| !from.is_empty()) {
+        elements.push(DocumentElement::Author { text: from });
+    }
+    if let Some(date) = date {
+        elements.push(DocumentElement::CreationDate { text: date });
+    }
+
+    elements.extend(entity_elements(&headers, body, 0, events));
+    elements
+}
+
+// RFC 2822 dates ("Tue, 1 Jul 2003 10:52:37 +0200") as RFC 3339, anything else as found
+fn normalize_date(value: &str) -> String {
+    let value = value.trim();
+    // A trailing zone comment ("(PDT)") is not understood by every parser
+    let without_comment = value.split(" (").next().unwrap_or(value).trim();
+    chrono::DateTime::parse_from_rfc2822(value)
+        .or_else(|_| chrono::DateTime::parse_from_rfc2822(without_comment))
+        .map(|date| date.to_rfc3339())
+        .unwrap_or_else(|_| value.to_string())
+}
+
+// "<a@b> <c@d>" -> ["a@b", "c@d"]
+fn message_ids(value: &str) -> Vec<String> {
+    value.split('<')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('>'))
+        .map(|(id, _)| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to headers
+//-------------------------------------------------
+
+// Headers (lowercased names, unfolded values) and body of a message or MIME part
+fn split_entity(bytes: &[u8]) -> (Headers, &[u8]) {
+    let mut headers: Headers = Vec::new();
+    let mut offset = 0;
+
+    for line in bytes.split_inclusive(|byte| *byte == b'\n') {
+        offset += line.len();
+        let (text, _) = decode_text(line);
+        let text = text.trim_end_matches(['\r', '\n']);
+        if text.is_empty() {
+            return (headers, &bytes[offset..]);
+        }
+        // Folded header lines start with whitespace
+        if text.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(text.trim());
+            }
+            continue;
+        }
+        match text.split_once(':') {
+            Some((name, value)) if is_header_name(name) => headers.push((name.trim().to_lowercase(), value.trim().to_string())),
+            // No blank line before the body
+            _ if headers.is_empty() => return (headers, bytes),
+            _ => return (headers, &bytes[offset - line.len()..]),
+        }
+    }
+
+    (headers, &bytes[bytes.len()..])
+}
+
+fn is_header_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|byte| byte.is_ascii_graphic() && byte != b':')
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(header_name, _)| header_name == name).map(|(_, value)| value.as_str())
+}
+
+// "text/plain; charset="utf-8"" -> ("text/plain", {"charset": "utf-8"})
+fn parse_parameters(value: &str) -> (String, HashMap<String, String>) {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for character in value.chars() {
+        match character {
+            _ if escaped => {
+                field.push(character);
+                escaped = false;
+            },
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(character),
+        }
+    }
+    fields.push(field);
+
+    let mut fields = fields.into_iter();
+    let value = fields.next().unwrap_or_default().trim().to_lowercase();
+    let parameters = fields
+        .filter_map(|field| {
+            let (name, value) = field.split_once('=')?;
+            // RFC 2231 extended parameters: name*=utf-8''value
+            let name = name.trim().trim_end_matches('*').to_lowercase();
+            let value = value.trim();
+            let value = value.splitn(3, '\'').nth(2).filter(|_| field.contains("*=")).unwrap_or(value);
+            Some((name, value.to_string()))
+        })
+        .collect();
+
+    (value, parameters)
+}
+
+// RFC 2047 encoded words: "=?utf-8?Q?Caf=C3=A9?=" -> "Café"
+fn decode_words(value: &str, events: &dyn EventSink) -> String {
+    let mut decoded = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+
+    while let Some(position) = rest.find("=?") {
+        let (before, candidate) = rest.split_at(position);
+        match encoded_word(candidate) {
+            Some((charset, bytes, length)) => {
+                // Whitespace between two encoded words is not part of the text
+                if !(after_word && before.trim().is_empty()) {
+                    decoded.push_str(before);
+                }
+                decoded.push_str(&decode_charset(&bytes, Some(charset), events));
+                rest = &candidate[length..];
+                after_word = true;
+            },
+            None => {
+                decoded.push_str(before);
+                decoded.push_str("=?");
+                rest = &candidate[2..];
+                after_word = false;
+            },
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+// Charset, decoded bytes and length of the encoded word `text` starts with
+fn encoded_word(text: &str) -> Option<(&str, Vec<u8>, usize)> {
+    let mut fields = text[2..].splitn(3, '?');
+    let charset = fields.next()?;
+    let encoding = fields.next()?;
+    let rest = fields.next()?;
+    let end = rest.find("?=")?;
+    let encoded = &rest[..end];
+    if encoded.contains(char::is_whitespace) || charset.is_empty() {
+        return None;
+    }
+
+    let bytes = match encoding {
+        "B" | "b" => decode_base64(encoded.as_bytes()),
+        "Q" | "q" => decode_quoted_printable(encoded.replace('_', " ").as_bytes()),
+        _ => return None,
+    };
+    let length = 2 + charset.len() + 1 + encoding.len() + 1 + end + 2;
+    // "utf-8*en" carries a language after the charset
+    let charset = charset.split('*').next().unwrap_or(charset);
+    Some((charset, bytes, length))
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to MIME bodies
+//-------------------------------------------------
+
+fn entity_elements(headers: &[(String, String)], body: &[u8], depth: usize, events: &dyn EventSink) -> Vec<DocumentElement> {
+    let (content_type, parameters) = header(headers, "content-type")
+        .map(parse_parameters)
+        .unwrap_or_else(|| ("text/plain".to_string(), HashMap::new()));
+    let is_attachment = header(headers, "content-disposition")
+        .is_some_and(|disposition| disposition.trim().to_lowercase().starts_with("attachment"));
+    if is_attachment {
+        return Vec::new();
+    }
+
+    if content_type.starts_with("multipart/") {
+        if depth >= MAX_MIME_DEPTH {
+            events.emit(ParseEvent::Warning { path: None, message: "MIME parts nested too deep were skipped".to_string() });
+            return Vec::new();
+        }
+        let Some(boundary) = parameters.get("boundary") else {
+            events.emit(ParseEvent::Warning { path: None, message: format!("{} part has no boundary, skipped", content_type) });
+            return Vec::new();
+        };
+        let parts: Vec<(Headers, &[u8])> = split_multipart(body, boundary).into_iter().map(split_entity).collect();
+        return if content_type == "multipart/alternative" {
+            alternative_elements(&parts, depth, events)
+        } else {
+            parts.iter().flat_map(|(headers, body)| entity_elements(headers, body, depth + 1, events)).collect()
+        };
+    }
+
+    // Forwarded messages: their body is part of this one
+    if content_type == "message/rfc822" {
+        let body = decode_transfer(headers, body);
+        let (headers, body) = split_entity(&body);
+        return entity_elements(&headers, body, depth + 1, events);
+    }
+
+    if !content_type.starts_with("text/") {
+        return Vec::new();
+    }
+    let body = decode_transfer(headers, body);
+    if content_type == "text/html" {
+        // Without a charset parameter, the part's own <meta charset> applies
+        html_elements(&decode_html(&body, header(headers, "content-type"), None, events))
+    } else {
+        plain_text_elements(&decode_charset(&body, parameters.get("charset").map(|charset| charset.as_str()), events))
+    }
+}
+
+// The text/plain version when there is one, otherwise the last (richest) readable version
+fn alternative_elements(parts: &[(Headers, &[u8])], depth: usize, events: &dyn EventSink) -> Vec<DocumentElement> {
+    let is_plain = |headers: &[(String, String)]| {
+        header(headers, "content-type").is_none_or(|value| parse_parameters(value).0 == "text/plain")
+    };
+
+    if let Some((headers, body)) = parts.iter().find(|(headers, _)| is_plain(headers)) {
+        let elements = entity_elements(headers, body, depth + 1, events);
+        if !elements.is_empty() {
+            return elements;
+        }
+    }
+    parts.iter()
+        .rev()
+        .map(|(headers, body)| entity_elements(headers, body, depth + 1, events))
+        .find(|elements| !elements.is_empty())
+        .unwrap_or_default()
+}
+
+// Bodies of the parts between "--boundary" lines, up to "--boundary--"
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut offset = 0;
+
+    for line in body.split_inclusive(|byte| *byte == b'\n') {
+        let line_start = offset;
+        offset += line.len();
+        if !line.starts_with(delimiter.as_bytes()) {
+            continue;
+        }
+        // The line break before a delimiter belongs to the delimiter
+        if let Some(part_start) = start {
+            let mut part_end = line_start.max(part_start);
+            if part_end > part_start && body[part_end - 1] == b'\n' {
+                part_end -= 1;
+            }
+            if part_end > part_start && body[part_end - 1] == b'\r' {
+                part_end -= 1;
+            }
+            parts.push(&body[part_start..part_end]);
+        }
+        if line[delimiter.len()..].starts_with(b"--") {
+            return parts;
+        }
+        start = Some(offset);
+    }
+    // Truncated message: keep the last part
+    if let Some(part_start) = start {
+        parts.push(&body[part_start..]);
+    }
+
+    parts
+}
+
+fn decode_transfer(headers: &[(String, String)], body: &[u8]) -> Vec<u8> {
+    match header(headers, "content-transfer-encoding").map(|encoding| encoding.trim().to_lowercase()).as_deref() {
+        Some("base64") => decode_base64(body),
+        Some("quoted-printable") => decode_quoted_printable(body),
+        _ => body.to_vec(),
+    }
+}
+
+// Unknown or missing charsets fall back to UTF-8 (Latin-1 when it is not valid UTF-8)
+fn decode_charset(bytes: &[u8], charset: Option<&str>, events: &dyn EventSink) -> String {
+    match charset.map(|charset| (charset, Encoding::for_label(charset.trim().as_bytes()))) {
+        Some((_, Some(encoding))) => encoding.decode(bytes).0.into_owned(),
+        Some((charset, None)) => {
+            events.emit(ParseEvent::Warning { path: None, message: format!("unknown charset {:?}, decoded as UTF-8", charset) });
+            decode_text(bytes).0
+        },
+        None => decode_text(bytes).0,
+    }
+}
+
+fn decode_base64(encoded: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for byte in encoded {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            // Line breaks and anything else outside the alphabet
+            _ => continue,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+        }
+    }
+
+    decoded
+}
+
+fn decode_quoted_printable(encoded: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut index = 0;
+
+    while index < encoded.len() {
+        let byte = encoded[index];
+        if byte != b'=' {
+            decoded.push(byte);
+            index += 1;
+            continue;
+        }
+        // Soft line break "=\r\n"
+        let rest = &encoded[index + 1..];
+        if rest.starts_with(b"\r\n") {
+            index += 3;
+        } else if rest.starts_with(b"\n") {
+            index += 2;
+        } else if let Some(value) = rest.get(..2).and_then(|hex| std::str::from_utf8(hex).ok()).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            decoded.push(value);
+            index += 3;
+        } else {
+            decoded.push(byte);
+            index += 1;
+        }
+    }
+
+    decoded
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to body text
+//-------------------------------------------------
+
+// Blockquotes of HTML mail are the quoted messages: each one, with everything inside it,
+// becomes a single QuotedText
+fn html_elements(html: &str) -> Vec<DocumentElement> {
+    let document = parse_html_str(html);
+    let nodes = document.nodes.into_iter().map(mark_quotes).collect();
+    Document { metadata: Vec::new(), nodes }.to_elements()
+        .into_iter()
+        .filter(|element| !matches!(element, DocumentElement::QuotedText { text } if text.is_empty()))
+        .collect()
+}
+
+fn mark_quotes(node: Node) -> Node {
+    let mark_all = |nodes: Vec<Node>| nodes.into_iter().map(mark_quotes).collect();
+    let mark_cells = |cells: Vec<TableCell>| cells.into_iter()
+        .map(|cell| TableCell { text: cell.text, children: mark_all(cell.children) })
+        .collect();
+
+    match node {
+        Node::Blockquote { .. } => Node::Element(DocumentElement::QuotedText { text: elements_text(&node.to_elements()) }),
+        Node::Section { level, heading, children } => Node::Section { level, heading, children: mark_all(children) },
+        Node::Paragraph { text, children } => Node::Paragraph { text, children: mark_all(children) },
+        Node::List { ordered, items } => Node::List {
+            ordered,
+            items: items.into_iter().map(|item| ListItem { text: item.text, children: mark_all(item.children) }).collect(),
+        },
+        Node::Table { headers, rows } => Node::Table {
+            headers: mark_cells(headers),
+            rows: rows.into_iter().map(mark_cells).collect(),
+        },
+        Node::Element(element) => Node::Element(element),
+    }
+}
+
+// Runs of "> " lines (with the "On ... wrote:" line introducing them) become QuotedText,
+// as does everything after an Outlook "-----Original Message-----" line
+fn plain_text_elements(text: &str) -> Vec<DocumentElement> {
+    let text = text.replace("\r\n", "\n");
+    let mut elements = Vec::new();
+    let mut own: Vec<&str> = Vec::new();
+    let mut quoted: Vec<&str> = Vec::new();
+
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        if is_original_message_separator(line) {
+            flush_own(&mut own, &mut elements);
+            flush_quoted(&mut quoted, &mut elements);
+            let rest: Vec<&str> = std::iter::once(line).chain(lines.by_ref()).collect();
+            push_quoted(&rest.join("\n"), &mut elements);
+            break;
+        }
+
+        if let Some(unquoted) = line.trim_start().strip_prefix('>') {
+            if quoted.is_empty() {
+                // The attribution line belongs with the quote it introduces
+                let attribution = own.iter().rposition(|line| !line.trim().is_empty())
+                    .filter(|position| is_attribution(own[*position]));
+                let attribution = attribution.map(|position| own.split_off(position));
+                flush_own(&mut own, &mut elements);
+                quoted.extend(attribution.into_iter().flatten());
+            }
+            quoted.push(unquoted.strip_prefix(' ').unwrap_or(unquoted));
+        } else if !quoted.is_empty() && line.trim().is_empty() {
+            // A blank line may separate quoted paragraphs
+            quoted.push(line);
+        } else {
+            flush_quoted(&mut quoted, &mut elements);
+            own.push(line);
+        }
+    }
+    flush_own(&mut own, &mut elements);
+    flush_quoted(&mut quoted, &mut elements);
+
+    elements
+}
+
+fn flush_own(own: &mut Vec<&str>, elements: &mut Vec<DocumentElement>) {
+    if !own.is_empty() {
+        elements.extend(parse_txt_str(&own.join("\n")));
+        own.clear();
+    }
+}
+
+fn flush_quoted(quoted: &mut Vec<&str>, elements: &mut Vec<DocumentElement>) {
+    if !quoted.is_empty() {
+        push_quoted(&quoted.join("\n"), elements);
+        quoted.clear();
+    }
+}
+
+fn push_quoted(text: &str, elements: &mut Vec<DocumentElement>) {
+    let text = text.trim();
+    if !text.is_empty() {
+        elements.push(DocumentElement::QuotedText { text: text.to_string() });
+    }
+}
+
+// "On Tue, 1 Jul 2003, Jane Doe <jane@example.com> wrote:"
+fn is_attribution(line: &str) -> bool {
+    let line = line.trim().to_lowercase();
+    line.ends_with("wrote:") || line.ends_with("writes:")
+}
+
+fn is_original_message_separator(line: &str) -> bool {
+    let line = line.trim().to_lowercase();
+    line.starts_with("-----") && line.contains("original message")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(elements: Vec<DocumentElement>) -> Vec<DocumentElement> {
+        elements.into_iter()
+            .filter(|element| !element.is_metadata() && !matches!(element, DocumentElement::Message { .. }))
+            .collect()
+    }
+
+    #[test]
+    fn html_blockquote_is_one_quoted_text() {
+        let mail = b"Subject: Re\r\nContent-Type: text/html\r\n\r\n<p>Reply</p><blockquote><p>Original</p></blockquote>";
+        let elements = content(parse_email_bytes(mail).unwrap());
+        assert_eq!(elements, vec![
+            DocumentElement::Paragraph { text: "Reply".to_string() },
+            DocumentElement::QuotedText { text: "Original".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn alternative_prefers_plain_text_and_splits_quotes() {
+        let mail = b"Subject: Re\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/alternative; boundary=\"b\"\r\n\r\n\
+--b\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n\
+Thanks.\r\n\r\n> Can you come?\r\n> Tomorrow?\r\n\
+--b\r\nContent-Type: text/html\r\n\r\n<p>Thanks.</p>\r\n\
+--b--\r\n";
+        let elements = content(parse_email_bytes(mail).unwrap());
+        assert_eq!(elements, vec![
+            DocumentElement::Paragraph { text: "Thanks.".to_string() },
+            DocumentElement::QuotedText { text: "Can you come?\nTomorrow?".to_string() },
+        ]);
+    }
+}
@@ -0,0 +1,398 @@
+// epub_parser.rs
+
+use roxmltree::Node;
+use scraper::{Html, Selector};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::{EventSink, ParseEvent};
+use crate::parsers::documents_types::{fallback_title, Document, DocumentElement};
+use crate::parsers::html_parser::{decode_html, parse_html_str};
+use crate::parsers::ooxml::{attribute, child, children, is_named, open_package, parse_xml, read_part, read_part_bytes, resolve_part, Package};
+use crate::parsers::parser::Parser;
+
+const CONTAINER_PART: &str = "META-INF/container.xml";
+
+// One entry of the OPF manifest
+struct ManifestItem {
+    // Part name inside the package, resolved against the OPF directory
+    part: String,
+    media_type: String,
+    // Space-separated EPUB 3 properties ("nav", "cover-image"...)
+    properties: String,
+}
+
+// Main parsing function
+pub fn parse_epub_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    parse_epub_path(path, &())
+}
+
+fn parse_epub_path(path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    // Read the EPUB file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+
+    let mut elements = parse_epub_bytes_with(&bytes, events).map_err(|error| error.with_path(path))?;
+
+    fallback_title(&mut elements, path);
+
+    Ok(elements)
+}
+
+// Parse an in-memory EPUB
+pub fn parse_epub_bytes(bytes: &[u8]) -> Result<Vec<DocumentElement>> {
+    parse_epub_bytes_with(bytes, &())
+}
+
+// Book metadata, then every XHTML document of the spine in reading order, each introduced
+// by a Chapter marker carrying its index and its title from the table of contents
+pub fn parse_epub_bytes_with(bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    let mut package = open_package(bytes)?;
+
+    let opf_part = package_document_part(&mut package)?;
+    let opf_xml = read_part(&mut package, &opf_part)?
+        .ok_or_else(|| OxidocError::malformed(format!("EPUB has no package document {}", opf_part)))?;
+    let opf = parse_xml(&opf_xml, &opf_part)?;
+    let root = opf.root_element();
+    let opf_directory = opf_part.rsplit_once('/').map_or("", |(directory, _)| directory);
+
+    let mut elements = child(root, "metadata").map(book_metadata).unwrap_or_default();
+
+    let manifest: HashMap<&str, ManifestItem> = child(root, "manifest")
+        .into_iter()
+        .flat_map(|manifest| children(manifest, "item"))
+        .filter_map(|item| {
+            let id = attribute(item, "id")?;
+            let href = attribute(item, "href")?;
+            Some((id, ManifestItem {
+                part: resolve_part(opf_directory, &decode_href(href)),
+                media_type: attribute(item, "media-type").unwrap_or("").to_string(),
+                properties: attribute(item, "properties").unwrap_or("").to_string(),
+            }))
+        })
+        .collect();
+
+    let spine = child(root, "spine");
+    let toc = table_of_contents(&mut package, &manifest, spine, events)?;
+
+    let mut number = 0;
+    for itemref in spine.into_iter().flat_map(|spine| children(spine, "itemref")) {
+        let Some(item) = attribute(itemref, "idref").and_then(|idref| manifest.get(idref)) else {
+            events.emit(ParseEvent::Warning {
+                path: None,
+                message: format!("spine entry {:?} is not in the manifest", attribute(itemref, "idref").unwrap_or("")),
+            });
+            continue;
+        };
+        // The navigation document repeats the table of contents, other media are not text
+        if !is_xhtml(item) || has_property(item, "nav") {
+            continue;
+        }
+
+        let chapter = parse_chapter(&mut package, &item.part, events)?;
+        if chapter.is_empty() {
+            continue;
+        }
+
+        number += 1;
+        let title = toc.get(&item.part).cloned();
+        elements.push(DocumentElement::Chapter { number, title: title.clone() });
+        // The TOC title becomes the chapter heading unless the chapter already opens with it
+        if let Some(title) = title
+            && !matches!(chapter.first(), Some(DocumentElement::Heading { text, .. }) if same_title(text, &title)) {
+            elements.push(DocumentElement::Heading { level: 1, text: title });
+        }
+        elements.extend(chapter);
+    }
+
+    Ok(elements)
+}
+
+// EPUB implementation of the common Parser trait
+pub struct EpubParser;
+
+impl Parser for EpubParser {
+    fn name(&self) -> &str {
+        "epub"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["epub"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/epub+zip"]
+    }
+
+    // The first zip entry is the uncompressed "mimetype" file
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(b"PK\x03\x04") && bytes.get(30..58) == Some(b"mimetypeapplication/epub+zip".as_slice())
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_epub_bytes_with(bytes, events)
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_epub_path(path, events)
+    }
+}
+
+// META-INF/container.xml names the OPF package document
+fn package_document_part(package: &mut Package) -> Result<String> {
+    let xml = read_part(package, CONTAINER_PART)?
+        .ok_or_else(|| OxidocError::malformed(format!("EPUB has no {}", CONTAINER_PART)))?;
+    let container = parse_xml(&xml, CONTAINER_PART)?;
+
+    container.descendants()
+        .filter(|node| is_named(*node, "rootfile"))
+        .find(|rootfile| attribute(*rootfile, "media-type").is_none_or(|media_type| media_type == "application/oebps-package+xml"))
+        .and_then(|rootfile| attribute(rootfile, "full-path"))
+        .map(|full_path| resolve_part("", &decode_href(full_path)))
+        .ok_or_else(|| OxidocError::malformed(format!("{} names no package document", CONTAINER_PART)))
+}
+
+// Chapter content through the HTML extraction; the chapter's own <head> metadata is dropped
+fn parse_chapter(package: &mut Package, part: &str, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    let Some(bytes) = read_part_bytes(package, part)? else {
+        events.emit(ParseEvent::Warning {
+            path: None,
+            message: format!("spine document {} is missing from the EPUB", part),
+        });
+        return Ok(Vec::new());
+    };
+
+    let document = parse_html_str(&decode_html(&bytes, None, None, events));
+    Ok(Document { metadata: Vec::new(), nodes: document.nodes }.to_elements())
+}
+
+fn is_xhtml(item: &ManifestItem) -> bool {
+    matches!(item.media_type.as_str(), "application/xhtml+xml" | "text/html")
+}
+
+fn has_property(item: &ManifestItem, property: &str) -> bool {
+    item.properties.split_whitespace().any(|value| value == property)
+}
+
+fn same_title(heading: &str, title: &str) -> bool {
+    let normalize = |text: &str| text.split_whitespace().collect::<Vec<&str>>().join(" ").to_lowercase();
+    normalize(heading) == normalize(title)
+}
+
+// Hrefs are URLs: "chapter%201.xhtml" is the part "chapter 1.xhtml"
+fn decode_href(href: &str) -> String {
+    let bytes = href.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| href.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            },
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            },
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to Metadata elements
+//-------------------------------------------------
+
+// Dublin Core metadata of the OPF: dc:title, dc:creator, dc:language...
+fn book_metadata(metadata: Node) -> Vec<DocumentElement> {
+    let values = |name: &str| -> Vec<String> {
+        children(metadata, name)
+            .filter_map(|node| node.text())
+            .map(|text| text.split_whitespace().collect::<Vec<&str>>().join(" "))
+            .filter(|text| !text.is_empty())
+            .collect()
+    };
+
+    let mut elements = Vec::new();
+    if let Some(title) = values("title").into_iter().next() {
+        elements.push(DocumentElement::Title { text: title });
+    }
+    let creators = values("creator");
+    if !creators.is_empty() {
+        elements.push(DocumentElement::Author { text: creators.join(", ") });
+    }
+    if let Some(description) = values("description").into_iter().next() {
+        elements.push(DocumentElement::Description { text: description });
+    }
+    let subjects = values("subject");
+    if !subjects.is_empty() {
+        elements.push(DocumentElement::Keywords { text: subjects.join(", ") });
+    }
+    if let Some(language) = values("language").into_iter().next() {
+        elements.push(DocumentElement::Language { text: language });
+    }
+    if let Some(date) = values("date").into_iter().next() {
+        elements.push(DocumentElement::CreationDate { text: date });
+    }
+    // EPUB 3: <meta property="dcterms:modified">2024-01-31T10:00:00Z</meta>
+    if let Some(modified) = children(metadata, "meta")
+        .find(|meta| attribute(*meta, "property") == Some("dcterms:modified"))
+        .and_then(|meta| meta.text())
+        .map(str::trim)
+        .filter(|text| !text.is_empty()) {
+        elements.push(DocumentElement::ModificationDate { text: modified.to_string() });
+    }
+
+    elements
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to the table of contents
+//-------------------------------------------------
+
+// Title of every part listed in the table of contents: the EPUB 3 navigation document,
+// or the EPUB 2 NCX when there is none. A part listed several times keeps its first entry.
+fn table_of_contents(
+    package: &mut Package,
+    manifest: &HashMap<&str, ManifestItem>,
+    spine: Option<Node>,
+    events: &dyn EventSink,
+) -> Result<HashMap<String, String>> {
+    let mut entries = Vec::new();
+
+    if let Some(nav) = manifest.values().find(|item| has_property(item, "nav"))
+        && let Some(bytes) = read_part_bytes(package, &nav.part)? {
+        entries = navigation_entries(&decode_html(&bytes, None, None, events), &nav.part);
+    }
+
+    if entries.is_empty()
+        && let Some(ncx) = spine.and_then(|spine| attribute(spine, "toc")).and_then(|id| manifest.get(id))
+        && let Some(xml) = read_part(package, &ncx.part)? {
+        match parse_xml(&xml, &ncx.part) {
+            Ok(document) => entries = ncx_entries(&document, &ncx.part),
+            // A broken table of contents only costs the chapter titles
+            Err(error) => events.emit(ParseEvent::Warning { path: None, message: error.to_string() }),
+        }
+    }
+
+    let mut toc = HashMap::new();
+    for (part, title) in entries {
+        toc.entry(part).or_insert(title);
+    }
+    Ok(toc)
+}
+
+// <nav epub:type="toc"><ol><li><a href="chapter1.xhtml">Chapter 1</a>...
+fn navigation_entries(html_content: &str, nav_part: &str) -> Vec<(String, String)> {
+    let document = Html::parse_document(html_content);
+    let nav_selector = Selector::parse("nav").unwrap();
+    let link_selector = Selector::parse("a[href]").unwrap();
+
+    let navs: Vec<_> = document.select(&nav_selector).collect();
+    let Some(toc) = navs.iter()
+        .find(|nav| nav.value().attr("epub:type").is_some_and(|kind| kind.split_whitespace().any(|kind| kind == "toc")))
+        .or(navs.first()) else {
+        return Vec::new();
+    };
+
+    toc.select(&link_selector)
+        .filter_map(|link| {
+            let title = link.text().collect::<String>().split_whitespace().collect::<Vec<&str>>().join(" ");
+            let part = linked_part(nav_part, link.value().attr("href")?)?;
+            (!title.is_empty()).then_some((part, title))
+        })
+        .collect()
+}
+
+// <navPoint><navLabel><text>Chapter 1</text></navLabel><content src="chapter1.xhtml"/>
+fn ncx_entries(document: &roxmltree::Document, ncx_part: &str) -> Vec<(String, String)> {
+    document.descendants()
+        .filter(|node| is_named(*node, "navPoint"))
+        .filter_map(|point| {
+            let title = child(point, "navLabel")
+                .and_then(|label| child(label, "text"))
+                .and_then(|text| text.text())?
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" ");
+            let part = linked_part(ncx_part, attribute(child(point, "content")?, "src")?)?;
+            (!title.is_empty()).then_some((part, title))
+        })
+        .collect()
+}
+
+// Part a TOC link points at, without its fragment; external links are ignored
+fn linked_part(from_part: &str, href: &str) -> Option<String> {
+    let target = href.split('#').next().unwrap_or("");
+    if target.is_empty() || target.contains("://") {
+        return None;
+    }
+    let directory = from_part.rsplit_once('/').map_or("", |(directory, _)| directory);
+    Some(resolve_part(directory, &decode_href(target)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    fn epub(parts: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in parts {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    const CONTAINER: &str = r#"<container xmlns="urn:oasis:names:tc:opendocument:xmlns:container"><rootfiles>
+        <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles></container>"#;
+
+    const PACKAGE: &str = r#"<package xmlns="http://www.idpf.org/2007/opf" xmlns:dc="http://purl.org/dc/elements/1.1/">
+        <metadata><dc:title>The Book</dc:title><dc:creator>Ann</dc:creator><dc:creator>Bo</dc:creator></metadata>
+        <manifest>
+            <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+            <item id="one" href="text/chapter%201.xhtml" media-type="application/xhtml+xml"/>
+            <item id="two" href="text/two.xhtml" media-type="application/xhtml+xml"/>
+            <item id="cover" href="cover.jpg" media-type="image/jpeg"/>
+        </manifest>
+        <spine><itemref idref="nav"/><itemref idref="two"/><itemref idref="cover"/><itemref idref="one"/></spine>
+    </package>"#;
+
+    const NAV: &str = r#"<html><body><nav epub:type="toc"><ol>
+        <li><a href="text/chapter%201.xhtml">Beginnings</a></li>
+        <li><a href="text/two.xhtml#start">Endings</a></li>
+    </ol></nav></body></html>"#;
+
+    #[test]
+    fn chapters_follow_the_spine_with_toc_titles() {
+        let bytes = epub(&[
+            ("mimetype", "application/epub+zip"),
+            ("META-INF/container.xml", CONTAINER),
+            ("OEBPS/content.opf", PACKAGE),
+            ("OEBPS/nav.xhtml", NAV),
+            ("OEBPS/text/two.xhtml", "<html><body><h1>Endings</h1><p>The end.</p></body></html>"),
+            ("OEBPS/text/chapter 1.xhtml", "<html><head><title>ignored</title></head><body><p>It began.</p></body></html>"),
+        ]);
+
+        assert_eq!(parse_epub_bytes(&bytes).unwrap(), vec![
+            DocumentElement::Title { text: "The Book".to_string() },
+            DocumentElement::Author { text: "Ann, Bo".to_string() },
+            DocumentElement::Chapter { number: 1, title: Some("Endings".to_string()) },
+            DocumentElement::Heading { level: 1, text: "Endings".to_string() },
+            DocumentElement::Paragraph { text: "The end.".to_string() },
+            DocumentElement::Chapter { number: 2, title: Some("Beginnings".to_string()) },
+            DocumentElement::Heading { level: 1, text: "Beginnings".to_string() },
+            DocumentElement::Paragraph { text: "It began.".to_string() },
+        ]);
+    }
+}
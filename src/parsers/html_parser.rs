@@ -1,90 +1,120 @@
 // html.rs
 
-use scraper::{Html, Selector};
+use encoding_rs::Encoding;
+use scraper::{ElementRef, Html, Selector};
 use std::fs;
 use std::path::Path;
-use crate::parsers::documents_types::DocumentElement;
+use crate::parsers::documents_types::{group_sections, Document, DocumentElement, ListItem, Node, TableCell};
+use crate::error::{OxidocError, Result};
+use crate::events::{EventSink, ParseEvent};
+use crate::parsers::parser::Parser;
 
 
+// Bytes of an HTML document searched for a <meta charset>
+const CHARSET_SNIFF_LEN: usize = 1024;
 
 
 // Main parsing function
-pub fn parse_html_file(path: &Path) -> Result<Vec<DocumentElement>, Box<dyn std::error::Error>> {
-    println!("\nOxidoc HTML Parser");
-    println!("Parsing HTML file: {:?}", path);
-    
-    // Check if file exists
-    if !path.exists() {
-        eprintln!("❌ HTML file not found: {:?}", path);
-        return Ok(vec![]);
+pub fn parse_html_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    // Parse into the document tree, then flatten it in reading order
+    Ok(parse_html_document(path)?.to_elements())
+}
+
+// Parse an HTML file into the hierarchical document tree
+pub fn parse_html_document(path: &Path) -> Result<Document> {
+    parse_html_path(path, &())
+}
+
+fn parse_html_path(path: &Path, events: &dyn EventSink) -> Result<Document> {
+    // Read the HTML file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+    let html_content = decode_html(&bytes, None, Some(path), events);
+
+    Ok(parse_html_str(&html_content))
+}
+
+// Decode HTML with the charset of its Content-Type, else of its <meta> tag, else as UTF-8
+// (a byte order mark wins over both). Undeclared non-UTF-8 HTML is decoded as windows-1252,
+// the web's legacy default; every replacement decode is reported to `events`.
+pub fn decode_html(bytes: &[u8], content_type: Option<&str>, path: Option<&Path>, events: &dyn EventSink) -> String {
+    let warn = |message: String| events.emit(ParseEvent::Warning { path: path.map(|path| path.to_path_buf()), message });
+
+    let sniffed = String::from_utf8_lossy(&bytes[..bytes.len().min(CHARSET_SNIFF_LEN)]).to_lowercase();
+    let declared = content_type.and_then(charset_parameter).or_else(|| charset_parameter(&sniffed));
+    let encoding = match declared.as_deref().map(|charset| (charset, Encoding::for_label(charset.as_bytes()))) {
+        Some((_, Some(encoding))) => encoding,
+        Some((charset, None)) => {
+            warn(format!("HTML declares an unknown charset {:?}, decoded as UTF-8", charset));
+            encoding_rs::UTF_8
+        },
+        None if Encoding::for_bom(bytes).is_none() && std::str::from_utf8(bytes).is_err() => {
+            warn("HTML is not valid UTF-8 and declares no charset, decoded as windows-1252".to_string());
+            encoding_rs::WINDOWS_1252
+        },
+        None => encoding_rs::UTF_8,
+    };
+
+    let (text, used, had_errors) = encoding.decode(bytes);
+    if had_errors {
+        warn(format!("HTML is not valid {}, invalid bytes were replaced", used.name()));
     }
-    
-    // Read the HTML file
-    let html_content = fs::read_to_string(path)?;
-    
-    // Parse the HTML document
-    let document = Html::parse_document(&html_content);
-    
-    // Extract metadata first
-    let mut elements = extract_metadata(&document);
-    
-    // Extract all content elements
-    elements.extend(extract_elements(&document));
-    
-    // Display detailed results
-    display_parsing_results(&elements);
-    
-    Ok(elements)
+    text.into_owned()
 }
 
-// Display detailed parsing results
-fn display_parsing_results(elements: &[DocumentElement]) {
-    println!("\nParsing Results:");
-    
-    let mut metadata_count = 0;
-    let mut headings_count = 0;
-    let mut paragraphs_count = 0;
-    let mut lists_count = 0;
-    let mut tables_count = 0;
-    let mut code_count = 0;
-    let mut links_count = 0;
-    let mut images_count = 0;
-    let mut blockquotes_count = 0;
+// "text/html; charset=ISO-8859-1", and <meta charset="utf-8"> or content="...; charset=utf-8" in HTML
+fn charset_parameter(text: &str) -> Option<String> {
+    let lowercase = text.to_lowercase();
+    let start = lowercase.find("charset=")? + "charset=".len();
+    let charset: String = lowercase[start..]
+        .trim_start_matches(['"', '\''])
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+        .collect();
+    (!charset.is_empty()).then_some(charset)
+}
+
+// Parse HTML source into the hierarchical document tree
+pub fn parse_html_str(html_content: &str) -> Document {
+    // Parse the HTML document
+    let document = Html::parse_document(html_content);
     
-    for element in elements {
-        match element {
-            DocumentElement::Title { .. } |
-            DocumentElement::Description { .. } |
-            DocumentElement::Keywords { .. } |
-            DocumentElement::Author { .. } |
-            DocumentElement::Language { .. } => metadata_count += 1,
-            DocumentElement::Heading { .. } => headings_count += 1,
-            DocumentElement::Paragraph { .. } => paragraphs_count += 1,
-            DocumentElement::List { .. } => lists_count += 1,
-            DocumentElement::Table { .. } => tables_count += 1,
-            DocumentElement::Code { .. } => code_count += 1,
-            DocumentElement::Link { .. } => links_count += 1,
-            DocumentElement::Image { .. } => images_count += 1,
-            DocumentElement::Blockquote { .. } => blockquotes_count += 1,
-        }
+    // Extract metadata first, then the content tree
+    Document {
+        metadata: extract_metadata(&document),
+        nodes: extract_nodes(&document),
     }
-    
-    println!("Metadata: {}", metadata_count);
-    println!("Headings: {}", headings_count);
-    println!("Paragraphs: {}", paragraphs_count);
-    println!("Lists: {}", lists_count);
-    println!("Tables: {}", tables_count);
-    println!("Code blocks: {}", code_count);
-    println!("Links: {}", links_count);
-    println!("Images: {}", images_count);
-    println!("Blockquotes: {}", blockquotes_count);
-    println!("Total elements: {}", elements.len());
-    
 }
 
+// HTML implementation of the common Parser trait
+pub struct HtmlParser;
 
+impl Parser for HtmlParser {
+    fn name(&self) -> &str {
+        "html"
+    }
 
+    fn extensions(&self) -> &[&str] {
+        &["html", "htm", "xhtml"]
+    }
 
+    fn mime_types(&self) -> &[&str] {
+        &["text/html", "application/xhtml+xml"]
+    }
+
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        let prefix = String::from_utf8_lossy(&bytes[..bytes.len().min(256)]).to_lowercase();
+        let prefix = prefix.trim_start_matches('\u{feff}').trim_start();
+        prefix.starts_with("<!doctype html") || prefix.starts_with("<html")
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        Ok(parse_html_str(&decode_html(bytes, None, None, events)).to_elements())
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        Ok(parse_html_path(path, events)?.to_elements())
+    }
+}
 
 //-------------------------------------------------
 // Block of Functions related to Metadata elements
@@ -165,10 +195,9 @@ fn extract_meta_author(document: &Html) -> Option<String> {
 fn extract_document_language(document: &Html) -> Option<String> {
     // Try <html lang="..."> first
     let html_selector = Selector::parse("html").unwrap();
-    if let Some(html_element) = document.select(&html_selector).next() {
-        if let Some(lang) = html_element.value().attr("lang") {
-            return Some(lang.to_string());
-        }
+    if let Some(html_element) = document.select(&html_selector).next()
+        && let Some(lang) = html_element.value().attr("lang") {
+        return Some(lang.to_string());
     }
     
     // Fallback to meta http-equiv
@@ -186,269 +215,383 @@ fn extract_document_language(document: &Html) -> Option<String> {
 //---------------------------------------------
 
 // Main element extraction function
+// Flattens the document tree, so elements come out in reading order
 pub fn extract_elements(htmldocument: &Html) -> Vec<DocumentElement> {
     let mut elements = Vec::new();
 
-    // Extract different types of elements
-    elements.extend(extract_headings(htmldocument));
-    elements.extend(extract_paragraphs(htmldocument));
-    elements.extend(extract_blockquotes(htmldocument));
-    elements.extend(extract_lists(htmldocument));
-    elements.extend(extract_tables(htmldocument));
-    elements.extend(extract_code_blocks(htmldocument));
-    elements.extend(extract_inline_codes(htmldocument));
-    elements.extend(extract_links(htmldocument));
-    elements.extend(extract_image_descriptions(htmldocument));
+    for node in extract_nodes(htmldocument) {
+        elements.extend(node.to_elements());
+    }
 
     // Return the elements
     elements
 }
 
-// Extract headings (h1, h2, h3, h4, h5, h6)
-pub fn extract_headings(htmldocument: &Html) -> Vec<DocumentElement> {
-    // Vector to store the headings
-    let mut headings = Vec::new();
-    // Selector to find the headings
-    let heading_selector = Selector::parse("h1, h2, h3, h4, h5, h6").unwrap();
-
-    // Iterate over the headings
-    for heading in htmldocument.select(&heading_selector) {
-        // Extract heading level from tag name
-        let level = heading.value().name().chars().last().unwrap_or('1').to_digit(10).unwrap_or(1) as u8;
-        // Extract heading text
-        let text = heading.text().collect::<String>().trim().to_string();
-
-        // If the heading text is not empty, add it to the vector
-        if !text.is_empty() {
-            headings.push(DocumentElement::Heading { level, text });
-        }
-    }
-    // Return the headings
-    headings
+// Build the document tree with a single depth-first walk of the DOM
+pub fn extract_nodes(htmldocument: &Html) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    walk_element(htmldocument.root_element(), &mut nodes);
+    group_sections(nodes)
 }
 
-pub fn extract_paragraphs(htmldocument:&Html) -> Vec<DocumentElement> {
-    let mut paragraphs= Vec::new();
-    let paragraph_selector = Selector::parse("p").unwrap();
+// Visit one element and append the nodes it produces.
+// Containers without meaning of their own (div, article, section...) pass their children through.
+fn walk_element(element: ElementRef, nodes: &mut Vec<Node>) {
+    let tag = element.value().name();
+
+    match tag {
+        // Skip subtrees that never hold readable content (<title> and <meta> are handled by extract_metadata)
+        "head" | "script" | "style" | "template" | "noscript" => {},
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            // Extract heading level from tag name
+            let level = tag.chars().last().unwrap_or('1').to_digit(10).unwrap_or(1) as u8;
+            let heading = element_text(&element);
+            let children = child_nodes(&element);
+
+            // Only headings with text open a section
+            if heading.is_empty() {
+                nodes.extend(children);
+            } else {
+                nodes.push(Node::Section { level, heading, children });
+            }
+        },
+        "p" => {
+            let text = element_text(&element);
+            let children = child_nodes(&element);
 
-    for paragraph in htmldocument.select(&paragraph_selector) {
-        let text = paragraph.text().collect::<String>().trim().to_string();
-        if !text.is_empty() {
-            paragraphs.push(DocumentElement::Paragraph { text });
-        }
+            if text.is_empty() {
+                nodes.extend(children);
+            } else {
+                nodes.push(Node::Paragraph { text, children });
+            }
+        },
+        // A quote's paragraphs are its text; only what is not text (lists, code, links...) is walked
+        "blockquote" => {
+            let (text, children) = container_content(&element);
+
+            if text.is_empty() {
+                nodes.extend(children);
+            } else {
+                nodes.push(Node::Blockquote { text, children });
+            }
+        },
+        "ul" => nodes.push(extract_list(&element, false)),
+        "ol" => nodes.push(extract_list(&element, true)),
+        "table" => nodes.push(extract_table(&element)),
+        "img" => {
+            nodes.extend(extract_image_descriptions(&element).into_iter().map(Node::Element));
+        },
+        _ => {
+            let leaf = match tag {
+                "pre" => Some(extract_code_block(&element)),
+                "code" => extract_inline_code(&element),
+                "a" => extract_link(&element),
+                _ => None,
+            };
+            nodes.extend(leaf.map(Node::Element));
+
+            // Content nested in leaves (an image inside a link...) follows them
+            walk_children(&element, nodes);
+        },
     }
-    paragraphs
 }
 
-pub fn extract_blockquotes(htmldocument: &Html) -> Vec<DocumentElement> {
-    let mut blockquotes= Vec::new();
-    let blockquote_selector = Selector::parse("blockquote").unwrap();
-
-    for blockquote in htmldocument.select(&blockquote_selector) {
-        let text = blockquote.text().collect::<String>().trim().to_string();
-        if !text.is_empty() {
-            blockquotes.push(DocumentElement::Blockquote { text });
-        }
+fn walk_children(element: &ElementRef, nodes: &mut Vec<Node>) {
+    for child in element.children().filter_map(ElementRef::wrap) {
+        walk_element(child, nodes);
     }
-    blockquotes
 }
 
-pub fn extract_lists(htmldocument: &Html) -> Vec<DocumentElement> {
-    let mut lists = Vec::new();
+// Nested content of a block, grouped into sections of its own
+fn child_nodes(element: &ElementRef) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    walk_children(element, &mut nodes);
+    group_sections(nodes)
+}
 
-    // Extract unordered lists
-    let ul_selector = Selector::parse("ul").unwrap();
-    
-    for ul_element in htmldocument.select(&ul_selector) {
-        let li_selector = Selector::parse("li").unwrap();
-        let items: Vec<String> = ul_element.select(&li_selector)
-            .map(|li| li.text().collect::<String>().trim().to_string())
-            .filter(|item| !item.is_empty())
-            .collect();
-        
-        if !items.is_empty() {
-            lists.push(DocumentElement::List { items, ordered: false });
+fn element_text(element: &ElementRef) -> String {
+    element.text().collect::<String>().trim().to_string()
+}
+
+// Text and nested content of a list item, quote or cell. Paragraphs and inline content make the text
+// (one line per paragraph); nested lists, tables, code blocks and headings become child nodes and
+// stay out of the text, so nothing is emitted twice.
+fn container_content(element: &ElementRef) -> (String, Vec<Node>) {
+    let mut lines = vec![String::new()];
+    let mut children = Vec::new();
+    collect_content(element, &mut lines, &mut children);
+
+    let text = lines.iter()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    (text, group_sections(children))
+}
+
+fn collect_content(element: &ElementRef, lines: &mut Vec<String>, children: &mut Vec<Node>) {
+    for child in element.children() {
+        if let Some(text) = child.value().as_text() {
+            lines.last_mut().expect("lines start non-empty").push_str(text);
+            continue;
         }
-    }
-    
-    // Extract ordered lists
-    let ol_selector = Selector::parse("ol").unwrap();
-    
-    for ol_element in htmldocument.select(&ol_selector) {
-        let li_selector = Selector::parse("li").unwrap();
-        let items: Vec<String> = ol_element.select(&li_selector)
-            .map(|li| li.text().collect::<String>().trim().to_string())
-            .filter(|item| !item.is_empty())
-            .collect();
-        
-        if !items.is_empty() {
-            lists.push(DocumentElement::List { items, ordered: true });
+        let Some(child) = ElementRef::wrap(child) else {
+            continue;
+        };
+
+        match child.value().name() {
+            "ul" | "ol" | "table" | "pre" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+            | "head" | "script" | "style" | "template" | "noscript" => walk_element(child, children),
+            "p" | "blockquote" | "div" | "br" => {
+                lines.push(String::new());
+                collect_content(&child, lines, children);
+                lines.push(String::new());
+            },
+            tag => {
+                let leaf = match tag {
+                    "code" => extract_inline_code(&child),
+                    "a" => extract_link(&child),
+                    _ => None,
+                };
+                children.extend(leaf.map(Node::Element));
+                if tag == "img" {
+                    children.extend(extract_image_descriptions(&child).into_iter().map(Node::Element));
+                }
+                collect_content(&child, lines, children);
+            },
         }
     }
-    
-    lists
 }
 
-// Extract tables
-fn extract_tables(htmldocument: &Html) -> Vec<DocumentElement> {
-    let mut tables = Vec::new();
-    let table_selector = Selector::parse("table").unwrap();
-    
-    for table_element in htmldocument.select(&table_selector) {
-        // Extract headers
-        let th_selector = Selector::parse("th").unwrap();
-        let headers: Vec<String> = table_element.select(&th_selector)
-            .map(|th| th.text().collect::<String>().trim().to_string())
+// Extract an ordered or unordered list, keeping what each item holds (nested lists, code, links...)
+fn extract_list(element: &ElementRef, ordered: bool) -> Node {
+    let items = own_descendants(element, "li", &["ul", "ol"])
+        .into_iter()
+        .map(|li| {
+            let (text, children) = container_content(&li);
+            ListItem { text, children }
+        })
+        .collect();
+
+    Node::List { ordered, items }
+}
+
+// Extract a table (cells of nested tables belong to those tables)
+fn extract_table(element: &ElementRef) -> Node {
+    let to_cell = |cell: ElementRef| {
+        let (text, children) = container_content(&cell);
+        TableCell { text, children }
+    };
+
+    // Extract headers
+    let headers: Vec<TableCell> = own_descendants(element, "th", &["table"])
+        .into_iter()
+        .map(to_cell)
+        .collect();
+
+    // Extract rows
+    let mut rows = Vec::new();
+
+    for row in own_descendants(element, "tr", &["table"]) {
+        let cells: Vec<TableCell> = own_descendants(&row, "td", &["table"])
+            .into_iter()
+            .map(to_cell)
             .collect();
-        
-        // Extract rows
-        let tr_selector = Selector::parse("tr").unwrap();
-        let td_selector = Selector::parse("td").unwrap();
-        let mut rows = Vec::new();
-        
-        for row in table_element.select(&tr_selector) {
-            let cells: Vec<String> = row.select(&td_selector)
-                .map(|td| td.text().collect::<String>().trim().to_string())
-                .collect();
-            
-            if !cells.is_empty() {
-                rows.push(cells);
-            }
-        }
-        
-        if !headers.is_empty() || !rows.is_empty() {
-            tables.push(DocumentElement::Table { headers, rows });
+
+        if !cells.is_empty() {
+            rows.push(cells);
         }
     }
-    
-    tables
+
+    Node::Table { headers, rows }
 }
 
-// Extract code blocks
-fn extract_code_blocks(htmldocument: &Html) -> Vec<DocumentElement> {
-    let mut code_blocks = Vec::new();
-    let pre_selector = Selector::parse("pre").unwrap();
-    
-    for pre_element in htmldocument.select(&pre_selector) {
-        let code_selector = Selector::parse("code").unwrap();
-        let code_element = pre_element.select(&code_selector).next();
-        
-        if let Some(code_elem) = code_element {
-            let code = code_elem.text().collect::<String>();
-            let language = extract_code_language(&code_elem);
-            
-            code_blocks.push(DocumentElement::Code { 
-                code, 
-                language, 
-                inline: false 
-            });
-        } else {
-            // No <code> inside <pre>, just use the pre content
-            let code = pre_element.text().collect::<String>();
-            code_blocks.push(DocumentElement::Code { 
-                code, 
-                language: None, 
-                inline: false 
-            });
+// Descendants named `name`, without entering matches or any `boundaries` element (nested lists/tables)
+fn own_descendants<'a>(element: &ElementRef<'a>, name: &str, boundaries: &[&str]) -> Vec<ElementRef<'a>> {
+    let mut found = Vec::new();
+
+    for child in element.children().filter_map(ElementRef::wrap) {
+        let tag = child.value().name();
+        if tag == name {
+            found.push(child);
+        } else if !boundaries.contains(&tag) {
+            found.extend(own_descendants(&child, name, boundaries));
         }
     }
-    
-    code_blocks
+
+    found
 }
 
-// Extract inline code
-fn extract_inline_codes(htmldocument: &Html) -> Vec<DocumentElement> {
-    let mut inline_codes = Vec::new();
+// Extract a code block from <pre>
+fn extract_code_block(element: &ElementRef) -> DocumentElement {
     let code_selector = Selector::parse("code").unwrap();
-    
-    for element in htmldocument.select(&code_selector) {
-        // Skip if it's inside a <pre> (already handled by code blocks)
-        if element.parent().map_or(false, |parent| {
-            parent.value().is_element() && parent.value().as_element().unwrap().name() == "pre"
-        }) {
-            continue;
+
+    if let Some(code_elem) = element.select(&code_selector).next() {
+        let code = code_elem.text().collect::<String>();
+        let language = extract_code_language(&code_elem);
+
+        DocumentElement::Code {
+            code,
+            language,
+            inline: false
         }
-        
-        let code = element.text().collect::<String>().trim().to_string();
-        
-        if !code.is_empty() {
-            inline_codes.push(DocumentElement::Code { 
-                code, 
-                language: None, 
-                inline: true 
-            });
+    } else {
+        // No <code> inside <pre>, just use the pre content
+        let code = element.text().collect::<String>();
+        DocumentElement::Code {
+            code,
+            language: None,
+            inline: false
         }
     }
-    
-    inline_codes
 }
 
-// Extract links
-fn extract_links(htmldocument: &Html) -> Vec<DocumentElement> {
-    let mut links = Vec::new();
-    let link_selector = Selector::parse("a[href]").unwrap();
-    
-    for element in htmldocument.select(&link_selector) {
-        let url = element.value().attr("href").unwrap_or("").to_string();
-        let text = element.text().collect::<String>().trim().to_string();
-        
-        if !url.is_empty() && !text.is_empty() {
-            links.push(DocumentElement::Link { text, url });
-        }
+// Extract inline code
+fn extract_inline_code(element: &ElementRef) -> Option<DocumentElement> {
+    // Skip if it's inside a <pre> (already handled by code blocks)
+    if element.parent().is_some_and(|parent| {
+        parent.value().as_element().is_some_and(|parent| parent.name() == "pre")
+    }) {
+        return None;
     }
-    
-    links
+
+    let code = element.text().collect::<String>().trim().to_string();
+    if code.is_empty() {
+        return None;
+    }
+    Some(DocumentElement::Code {
+        code,
+        language: None,
+        inline: true
+    })
+}
+
+// Extract a link
+fn extract_link(element: &ElementRef) -> Option<DocumentElement> {
+    let url = element.value().attr("href")?.to_string();
+    let text = element.text().collect::<String>().trim().to_string();
+
+    if url.is_empty() || text.is_empty() {
+        return None;
+    }
+    Some(DocumentElement::Link { text, url })
 }
 
 // Extract image descriptions (alt text and captions)
-fn extract_image_descriptions(htmldocument: &Html) -> Vec<DocumentElement> {
+fn extract_image_descriptions(element: &ElementRef) -> Vec<DocumentElement> {
     let mut image_descriptions = Vec::new();
-    let img_selector = Selector::parse("img").unwrap();
-    
-    for element in htmldocument.select(&img_selector) {
-        // Extract alt text
-        if let Some(alt) = element.value().attr("alt") {
-            if !alt.trim().is_empty() && alt != "image" && alt != "photo" {
-                image_descriptions.push(DocumentElement::Image { 
-                    alt: alt.to_string(),
-                    url: element.value().attr("src").map(|s| s.to_string())
+
+    // Extract alt text
+    if let Some(alt) = element.value().attr("alt")
+        && !alt.trim().is_empty() && alt != "image" && alt != "photo" {
+        image_descriptions.push(DocumentElement::Image {
+            alt: alt.to_string(),
+            url: element.value().attr("src").map(|s| s.to_string())
+        });
+    }
+
+    // Try to find caption in parent figure
+    if let Some(parent) = element.parent().and_then(ElementRef::wrap) {
+        let figcaption = parent.children()
+            .filter_map(ElementRef::wrap)
+            .find(|sibling| sibling.value().name() == "figcaption");
+
+        if let Some(caption) = figcaption {
+            let caption_text = caption.text().collect::<String>().trim().to_string();
+            if !caption_text.is_empty() {
+                image_descriptions.push(DocumentElement::Image {
+                    alt: caption_text,
+                    url: None
                 });
             }
         }
-        
-        // Try to find caption in parent figure
-        if let Some(parent) = element.parent() {
-            if parent.value().is_element() {
-                // Find all figcaptions and check if any are children of this parent
-                let figcaption_selector = Selector::parse("figcaption").unwrap();
-                for caption in htmldocument.select(&figcaption_selector) {
-                    if caption.parent().map_or(false, |p| p == parent) {
-                        let caption_text = caption.text().collect::<String>().trim().to_string();
-                        if !caption_text.is_empty() {
-                            image_descriptions.push(DocumentElement::Image { 
-                                alt: caption_text,
-                                url: None
-                            });
-                        }
-                        break; // Found the caption for this image
-                    }
-                }
-            }
-        }
     }
-    
+
     image_descriptions
 }
 
 // Helper function to extract code language
-fn extract_code_language(element: &scraper::ElementRef) -> Option<String> {
+fn extract_code_language(element: &ElementRef) -> Option<String> {
     element.value().attr("class")
-        .and_then(|class| {
-            // Look for language-* classes
-            if class.starts_with("language-") {
-                Some(class.strip_prefix("language-").unwrap().to_string())
-            } else {
-                None
+        .and_then(|class| class.strip_prefix("language-"))
+        .map(|language| language.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body_elements(body: &str) -> Vec<DocumentElement> {
+        parse_html_str(&format!("<html><body>{}</body></html>", body)).to_elements()
+    }
+
+    fn list(items: &[&str]) -> DocumentElement {
+        DocumentElement::List { items: items.iter().map(|item| item.to_string()).collect(), ordered: false }
+    }
+
+    #[test]
+    fn nested_list_items_appear_once() {
+        let elements = body_elements("<ul><li>alpha<ul><li>beta</li></ul></li><li>gamma</li></ul>");
+        assert_eq!(elements, vec![list(&["alpha", "beta", "gamma"])]);
+    }
+
+    #[test]
+    fn paragraphs_in_items_and_cells_are_their_text() {
+        let elements = body_elements(
+            "<ul><li><p>one</p><p>two</p></li></ul>\
+             <table><tr><th>h</th></tr><tr><td><p>cell <a href=\"/x\">link</a></p></td></tr></table>"
+        );
+        assert_eq!(elements, vec![
+            list(&["one\ntwo"]),
+            DocumentElement::Table { headers: vec!["h".to_string()], rows: vec![vec!["cell link".to_string()]] },
+            DocumentElement::Link { text: "link".to_string(), url: "/x".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn quoted_paragraphs_are_not_repeated() {
+        let elements = body_elements("<blockquote><p>first</p><p>second</p></blockquote><p>after</p>");
+        assert_eq!(elements, vec![
+            DocumentElement::Blockquote { text: "first\nsecond".to_string() },
+            DocumentElement::Paragraph { text: "after".to_string() },
+        ]);
+    }
+
+    struct Warnings(std::cell::RefCell<Vec<String>>);
+
+    impl EventSink for Warnings {
+        fn emit(&self, event: ParseEvent) {
+            if let ParseEvent::Warning { message, .. } = event {
+                self.0.borrow_mut().push(message);
             }
-        })
+        }
+    }
+
+    #[test]
+    fn declared_charsets_are_used() {
+        let warnings = Warnings(Default::default());
+        let meta = b"<html><head><meta charset=\"iso-8859-1\"></head><body><p>caf\xe9</p></body></html>";
+        assert!(decode_html(meta, None, None, &warnings).contains("café"));
+        // The Content-Type header wins over the <meta> tag
+        let koi8 = b"<p>\xf0\xd2\xc9\xd7\xc5\xd4</p>";
+        assert_eq!(decode_html(koi8, Some("text/html; charset=KOI8-R"), None, &warnings), "<p>Привет</p>");
+        assert!(warnings.0.borrow().is_empty());
+    }
+
+    #[test]
+    fn undeclared_legacy_html_reads_the_same_from_path_and_bytes() {
+        let html = b"<html><body><p>\x93Quoted\x94 caf\xe9</p></body></html>";
+        let path = std::env::temp_dir().join(format!("oxidoc-legacy-{}.html", std::process::id()));
+        fs::write(&path, html).unwrap();
+
+        let from_path = Warnings(Default::default());
+        let path_elements = HtmlParser.parse_path(&path, &from_path);
+        fs::remove_file(&path).unwrap();
+        let from_bytes = Warnings(Default::default());
+        let byte_elements = HtmlParser.parse_bytes(html, &from_bytes).unwrap();
+
+        assert_eq!(byte_elements, vec![DocumentElement::Paragraph { text: "“Quoted” café".to_string() }]);
+        assert_eq!(path_elements.unwrap(), byte_elements);
+        assert_eq!((from_path.0.borrow().len(), from_bytes.0.borrow().len()), (1, 1));
+    }
 }
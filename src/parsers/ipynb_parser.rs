@@ -0,0 +1,343 @@
+// ipynb_parser.rs
+
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::EventSink;
+use crate::parsers::documents_types::{fallback_title, Document, DocumentElement};
+use crate::parsers::markdown_parser::parse_markdown_str;
+use crate::parsers::parser::Parser;
+
+// Appended to outputs cut at `max_output_len`
+const TRUNCATION_MARKER: &str = "\n[output truncated]";
+
+// What is kept of the outputs stored in a notebook
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputOptions {
+    // Drop every output when false
+    pub include: bool,
+    // Outputs longer than this many bytes are cut (at a character boundary)
+    pub max_len: Option<usize>,
+}
+
+impl Default for OutputOptions {
+    fn default() -> OutputOptions {
+        OutputOptions { include: true, max_len: None }
+    }
+}
+
+// Main parsing function
+pub fn parse_ipynb_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    parse_ipynb_file_with(path, OutputOptions::default())
+}
+
+pub fn parse_ipynb_file_with(path: &Path, options: OutputOptions) -> Result<Vec<DocumentElement>> {
+    // Read the notebook (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+
+    let mut elements = parse_ipynb_bytes_with(&bytes, options).map_err(|error| error.with_path(path))?;
+
+    fallback_title(&mut elements, path);
+
+    Ok(elements)
+}
+
+// Parse an in-memory notebook
+pub fn parse_ipynb_bytes(bytes: &[u8]) -> Result<Vec<DocumentElement>> {
+    parse_ipynb_bytes_with(bytes, OutputOptions::default())
+}
+
+// Every cell is introduced by a Cell marker with its index: markdown cells become structured
+// elements, code cells Code in the kernel's language followed by their outputs
+pub fn parse_ipynb_bytes_with(bytes: &[u8], options: OutputOptions) -> Result<Vec<DocumentElement>> {
+    let notebook: Value = serde_json::from_slice(bytes)
+        .map_err(|error| OxidocError::malformed(format!("notebook is not valid JSON: {}", error)))?;
+    if !notebook.is_object() {
+        return Err(OxidocError::malformed("notebook is not a JSON object"));
+    }
+
+    let metadata = &notebook["metadata"];
+    let mut elements = notebook_metadata(metadata);
+    let language = metadata["kernelspec"]["language"].as_str()
+        .or_else(|| metadata["language_info"]["name"].as_str())
+        .map(|language| language.to_lowercase());
+
+    for (index, cell) in notebook_cells(&notebook).into_iter().enumerate() {
+        elements.push(DocumentElement::Cell { index: index as u32 });
+        elements.extend(cell_elements(cell, language.as_deref(), options));
+    }
+
+    Ok(elements)
+}
+
+// Notebook implementation of the common Parser trait
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IpynbParser {
+    pub outputs: OutputOptions,
+}
+
+impl IpynbParser {
+    pub fn new() -> IpynbParser {
+        IpynbParser::default()
+    }
+
+    // Keep only the markdown and the code, not what running it printed
+    pub fn without_outputs(mut self) -> IpynbParser {
+        self.outputs.include = false;
+        self
+    }
+
+    // Cut outputs longer than `max_len` bytes
+    pub fn with_max_output_len(mut self, max_len: usize) -> IpynbParser {
+        self.outputs.max_len = Some(max_len);
+        self
+    }
+}
+
+impl Parser for IpynbParser {
+    fn name(&self) -> &str {
+        "ipynb"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ipynb"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/x-ipynb+json"]
+    }
+
+    // A JSON object whose first keys include the cell list or the format version
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        let prefix = String::from_utf8_lossy(&bytes[..bytes.len().min(256)]);
+        prefix.trim_start().starts_with('{') && (prefix.contains("\"cells\"") || prefix.contains("\"nbformat\""))
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], _events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_ipynb_bytes_with(bytes, self.outputs)
+    }
+
+    fn parse_path(&self, path: &Path, _events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_ipynb_file_with(path, self.outputs)
+    }
+}
+
+// nbformat 4 keeps the cells at the top level, nbformat 3 in worksheets
+fn notebook_cells(notebook: &Value) -> Vec<&Value> {
+    if let Some(cells) = notebook["cells"].as_array() {
+        return cells.iter().collect();
+    }
+    notebook["worksheets"].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|worksheet| worksheet["cells"].as_array())
+        .flatten()
+        .collect()
+}
+
+// Optional title and authors some tools (JupyterLab, Quarto) store in the notebook metadata
+fn notebook_metadata(metadata: &Value) -> Vec<DocumentElement> {
+    let mut elements = Vec::new();
+
+    if let Some(title) = metadata["title"].as_str().map(str::trim).filter(|title| !title.is_empty()) {
+        elements.push(DocumentElement::Title { text: title.to_string() });
+    }
+    let authors: Vec<&str> = metadata["authors"].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|author| author["name"].as_str().or_else(|| author.as_str()))
+        .map(str::trim)
+        .filter(|author| !author.is_empty())
+        .collect();
+    if !authors.is_empty() {
+        elements.push(DocumentElement::Author { text: authors.join(", ") });
+    }
+
+    elements
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to cells
+//-------------------------------------------------
+
+fn cell_elements(cell: &Value, language: Option<&str>, options: OutputOptions) -> Vec<DocumentElement> {
+    // nbformat 3 code cells keep their source in "input"
+    let source = multiline_text(&cell["source"]).or_else(|| multiline_text(&cell["input"])).unwrap_or_default();
+
+    match cell["cell_type"].as_str() {
+        Some("markdown") | Some("heading") => {
+            let document = parse_markdown_str(&source);
+            Document { metadata: Vec::new(), nodes: document.nodes }.to_elements()
+        },
+        Some("code") => {
+            let mut elements = Vec::new();
+            if !source.trim().is_empty() {
+                elements.push(DocumentElement::Code {
+                    code: source.trim_end().to_string(),
+                    language: language.map(|language| language.to_string()),
+                    inline: false,
+                });
+            }
+            if options.include {
+                let outputs = cell["outputs"].as_array().into_iter().flatten();
+                elements.extend(outputs.filter_map(|output| output_element(output, options.max_len)));
+            }
+            elements
+        },
+        // Raw cells hold text passed through untouched by nbconvert
+        _ if !source.trim().is_empty() => vec![DocumentElement::Paragraph { text: source.trim().to_string() }],
+        _ => Vec::new(),
+    }
+}
+
+// Text outputs: streams, plain-text results and errors. Images and HTML-only results are skipped.
+fn output_element(output: &Value, max_len: Option<usize>) -> Option<DocumentElement> {
+    let (kind, text) = match output["output_type"].as_str()? {
+        "stream" => {
+            let name = output["name"].as_str().or_else(|| output["stream"].as_str()).unwrap_or("stdout");
+            (name.to_string(), multiline_text(&output["text"])?)
+        },
+        "execute_result" | "display_data" | "pyout" => {
+            let text = multiline_text(&output["data"]["text/plain"]).or_else(|| multiline_text(&output["text"]))?;
+            ("result".to_string(), text)
+        },
+        "error" | "pyerr" => {
+            let traceback: Vec<String> = output["traceback"].as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|line| line.as_str())
+                .map(|line| line.to_string())
+                .collect();
+            let text = if traceback.is_empty() {
+                format!("{}: {}", output["ename"].as_str().unwrap_or("Error"), output["evalue"].as_str().unwrap_or(""))
+            } else {
+                traceback.join("\n")
+            };
+            ("error".to_string(), text)
+        },
+        _ => return None,
+    };
+
+    let text = strip_ansi(&text);
+    let text = text.trim_end();
+    if text.trim().is_empty() {
+        return None;
+    }
+    Some(DocumentElement::CodeOutput { text: truncate(text, max_len), kind })
+}
+
+// Sources and outputs are a string or a list of lines
+fn multiline_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(lines) => Some(lines.iter().filter_map(|line| line.as_str()).collect()),
+        _ => None,
+    }
+}
+
+fn truncate(text: &str, max_len: Option<usize>) -> String {
+    match max_len {
+        Some(max_len) if text.len() > max_len => {
+            let mut end = max_len;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            format!("{}{}", &text[..end], TRUNCATION_MARKER)
+        },
+        _ => text.to_string(),
+    }
+}
+
+// Terminal colour codes ("\x1b[31m") that IPython puts in tracebacks and progress bars
+fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut characters = text.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        if character == '\u{1b}' && characters.peek() == Some(&'[') {
+            characters.next();
+            // Parameters and intermediate bytes, then one final byte in @..~
+            for code in characters.by_ref() {
+                if ('@'..='~').contains(&code) {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(character);
+        }
+    }
+
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn notebook() -> Vec<u8> {
+        let red = format!("{}[0;31mZeroDivisionError{}[0m: division by zero", char::from(27), char::from(27));
+        json!({
+            "metadata": {
+                "kernelspec": { "name": "python3", "language": "python" },
+                "authors": [{ "name": "Ada" }]
+            },
+            "nbformat": 4,
+            "cells": [
+                { "cell_type": "markdown", "source": ["# Setup\n", "\n", "- load\n", "- clean\n"] },
+                { "cell_type": "code", "source": "print('hi')\n1 / 0\n", "outputs": [
+                    { "output_type": "stream", "name": "stdout", "text": ["hi\n"] },
+                    { "output_type": "error", "ename": "ZeroDivisionError", "evalue": "division by zero", "traceback": [red] },
+                    { "output_type": "display_data", "data": { "image/png": "iVBORw0KGgo=" } }
+                ] },
+                { "cell_type": "code", "source": "", "outputs": [] },
+                { "cell_type": "code", "source": ["'a' * 50"], "outputs": [
+                    { "output_type": "execute_result", "data": { "text/plain": "'aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa'" } }
+                ] }
+            ]
+        }).to_string().into_bytes()
+    }
+
+    fn code(code: &str) -> DocumentElement {
+        DocumentElement::Code { code: code.to_string(), language: Some("python".to_string()), inline: false }
+    }
+
+    fn output(text: &str, kind: &str) -> DocumentElement {
+        DocumentElement::CodeOutput { text: text.to_string(), kind: kind.to_string() }
+    }
+
+    #[test]
+    fn cells_keep_their_index_language_and_outputs() {
+        assert_eq!(parse_ipynb_bytes(&notebook()).unwrap(), vec![
+            DocumentElement::Author { text: "Ada".to_string() },
+            DocumentElement::Cell { index: 0 },
+            DocumentElement::Heading { level: 1, text: "Setup".to_string() },
+            DocumentElement::List { items: vec!["load".to_string(), "clean".to_string()], ordered: false },
+            DocumentElement::Cell { index: 1 },
+            code("print('hi')\n1 / 0"),
+            output("hi", "stdout"),
+            output("ZeroDivisionError: division by zero", "error"),
+            DocumentElement::Cell { index: 2 },
+            DocumentElement::Cell { index: 3 },
+            code("'a' * 50"),
+            output(&format!("'{}'", "a".repeat(50)), "result"),
+        ]);
+    }
+
+    #[test]
+    fn outputs_can_be_dropped_or_truncated() {
+        let without = parse_ipynb_bytes_with(&notebook(), OutputOptions { include: false, max_len: None }).unwrap();
+        assert!(!without.iter().any(|element| matches!(element, DocumentElement::CodeOutput { .. })));
+
+        let truncated = parse_ipynb_bytes_with(&notebook(), OutputOptions { include: true, max_len: Some(10) }).unwrap();
+        assert_eq!(truncated.last(), Some(&output(&format!("'aaaaaaaaa{}", TRUNCATION_MARKER), "result")));
+    }
+
+    #[test]
+    fn truncation_stops_at_a_character_boundary() {
+        assert_eq!(truncate("été", Some(2)), format!("é{}", TRUNCATION_MARKER));
+        assert_eq!(truncate("été", Some(5)), "été");
+    }
+}
@@ -0,0 +1,1284 @@
+// latex_parser.rs
+
+// LaTeX sources. \input, \include and \subfile are inlined first (relative to the main file),
+// comments are dropped, and only the document body is read: the preamble just gives the
+// title, author and date. Sectioning commands become headings, itemize/enumerate lists,
+// tabular tables, verbatim and listings code. Display math is kept as Math elements with its
+// TeX untouched, inline math stays in the text as "$...$". The bibliography comes from
+// thebibliography, or from the .bbl/.bib files named by \bibliography and \addbibresource.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::error::{OxidocError, Result};
+use crate::events::{EventSink, ParseEvent};
+use crate::parsers::documents_types::{fallback_title, DocumentElement};
+use crate::parsers::parser::Parser;
+use crate::parsers::txt_parser::{decode_text, warn_if_latin1};
+
+// Inputs nested deeper than this are not inlined
+const MAX_INPUT_DEPTH: usize = 16;
+
+// Sectioning commands by depth; the shallowest one used becomes heading level 1
+const SECTIONS: &[(&str, u8)] = &[
+    ("part", 0), ("chapter", 1), ("section", 2), ("subsection", 3), ("subsubsection", 4),
+    ("paragraph", 5), ("subparagraph", 6),
+];
+
+const MATH_ENVIRONMENTS: &[&str] = &[
+    "equation", "equation*", "align", "align*", "alignat", "alignat*", "flalign", "flalign*",
+    "gather", "gather*", "multline", "multline*", "eqnarray", "eqnarray*", "displaymath", "math",
+    "dmath", "dmath*",
+];
+const LIST_ENVIRONMENTS: &[&str] = &["itemize", "enumerate", "description", "compactitem", "compactenum", "inparaenum"];
+const TABLE_ENVIRONMENTS: &[&str] = &["tabular", "tabular*", "tabularx", "tabulary", "longtable", "longtable*", "supertabular"];
+const CODE_ENVIRONMENTS: &[&str] = &["verbatim", "verbatim*", "Verbatim", "BVerbatim", "lstlisting", "minted", "alltt", "comment"];
+const QUOTE_ENVIRONMENTS: &[&str] = &["quote", "quotation", "verse"];
+const FIGURE_ENVIRONMENTS: &[&str] = &["figure", "figure*", "wrapfigure", "subfigure"];
+// Drawings, not text
+const SKIPPED_ENVIRONMENTS: &[&str] = &["tikzpicture", "pgfpicture", "picture", "filecontents", "filecontents*"];
+
+// Commands dropped with this many arguments (optional [...] arguments are always dropped)
+const DROPPED_COMMANDS: &[(&str, usize)] = &[
+    ("label", 1), ("vspace", 1), ("hspace", 1), ("setlength", 2), ("addtolength", 2), ("setcounter", 2),
+    ("addtocounter", 2), ("usepackage", 1), ("documentclass", 1), ("bibliographystyle", 1),
+    ("pagestyle", 1), ("thispagestyle", 1), ("pagenumbering", 1), ("index", 1), ("nocite", 1),
+    ("addcontentsline", 3), ("hypersetup", 1), ("graphicspath", 1), ("footnote", 1), ("footnotetext", 1),
+    ("thanks", 1), ("title", 1), ("author", 1), ("date", 1), ("keywords", 1), ("color", 1),
+    ("textcolor", 1), ("colorbox", 1), ("newtheorem", 2), ("theoremstyle", 1), ("addbibresource", 1),
+    ("newcommand", 2), ("renewcommand", 2), ("providecommand", 2), ("newenvironment", 3),
+    ("renewenvironment", 3), ("DeclareMathOperator", 2), ("newlength", 1), ("captionsetup", 1),
+    ("lstset", 1), ("setminted", 1), ("usetikzlibrary", 1), ("affiliation", 1), ("address", 1),
+    ("email", 1), ("institute", 1), ("acknowledgments", 0), ("input", 1), ("include", 1), ("subfile", 1),
+    ("includeonly", 1), ("multicolumn", 2), ("multirow", 2), ("maketitle", 0), ("tableofcontents", 0),
+    ("listoffigures", 0), ("listoftables", 0),
+];
+
+// Commands written as a character
+const SYMBOLS: &[(&str, &str)] = &[
+    ("ss", "ß"), ("o", "ø"), ("O", "Ø"), ("ae", "æ"), ("AE", "Æ"), ("oe", "œ"), ("OE", "Œ"), ("aa", "å"),
+    ("AA", "Å"), ("l", "ł"), ("L", "Ł"), ("i", "i"), ("j", "j"), ("ldots", "…"), ("dots", "…"),
+    ("textellipsis", "…"), ("textendash", "–"), ("textemdash", "—"), ("LaTeX", "LaTeX"), ("TeX", "TeX"),
+    ("LaTeXe", "LaTeX2e"), ("textbackslash", "\\"), ("S", "§"), ("P", "¶"), ("copyright", "©"),
+    ("textcopyright", "©"), ("textregistered", "®"), ("texttrademark", "™"), ("euro", "€"), ("texteuro", "€"),
+    ("pounds", "£"), ("textdegree", "°"), ("textbullet", "•"), ("dag", "†"), ("ddag", "‡"),
+    ("textquoteleft", "‘"), ("textquoteright", "’"), ("textquotedblleft", "“"), ("textquotedblright", "”"),
+    ("guillemotleft", "«"), ("guillemotright", "»"), ("quad", " "), ("qquad", " "), ("newline", " "),
+    ("linebreak", " "), ("space", " "), ("enspace", " "), ("thinspace", " "), ("textasciitilde", "~"),
+    ("textasciicircum", "^"), ("textunderscore", "_"), ("textbar", "|"), ("textless", "<"), ("textgreater", ">"),
+];
+
+// Accent commands and the letters they combine with, as (plain, accented) pairs
+const ACCENTS: &[(char, &str)] = &[
+    ('\'', "aáeéiíoóuúyýAÁEÉIÍOÓUÚYÝcćnńsśzźCĆNŃSŚZŹ"),
+    ('`', "aàeèiìoòuùAÀEÈIÌOÒUÙ"),
+    ('^', "aâeêiîoôuûAÂEÊIÎOÔUÛ"),
+    ('"', "aäeëiïoöuüyÿAÄEËIÏOÖUÜ"),
+    ('~', "aãnñoõAÃNÑOÕ"),
+    ('=', "aāeēiīoōuūAĀEĒIĪOŌUŪ"),
+    ('.', "zżeėZŻEĖ"),
+    ('c', "cçCÇsşSŞ"),
+    ('v', "cčsšzžrřeěnňCČSŠZŽRŘEĚNŇ"),
+    ('u', "aăgğAĂGĞ"),
+    ('H', "oőuűOŐUŰ"),
+    ('k', "aąeęAĄEĘ"),
+];
+
+// Main parsing function
+pub fn parse_latex_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    parse_latex_path(path, &())
+}
+
+fn parse_latex_path(path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    // Read the main file (a missing file is an error, a missing input only a warning)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+    let (source, encoding) = decode_text(&bytes);
+    warn_if_latin1(encoding, Some(path), events);
+
+    let mut elements = parse_latex_str(&source, Some(path), events);
+
+    fallback_title(&mut elements, path);
+
+    Ok(elements)
+}
+
+// Parse an in-memory LaTeX document (its \input files cannot be resolved)
+pub fn parse_latex_bytes(bytes: &[u8]) -> Result<Vec<DocumentElement>> {
+    parse_latex_bytes_with(bytes, &())
+}
+
+pub fn parse_latex_bytes_with(bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    let (source, encoding) = decode_text(bytes);
+    warn_if_latin1(encoding, None, events);
+    Ok(parse_latex_str(&source, None, events))
+}
+
+// Inputs and bibliography files are looked up next to `main_file` when given
+pub fn parse_latex_str(source: &str, main_file: Option<&Path>, events: &dyn EventSink) -> Vec<DocumentElement> {
+    let directory = main_file.map(|path| path.parent().unwrap_or(Path::new("")).to_path_buf());
+    let mut stack = main_file.map(|path| vec![path.to_path_buf()]).unwrap_or_default();
+    let source = expand_inputs(&strip_comments(source), directory.as_deref(), &mut stack, events);
+
+    let (preamble, body) = match source.find("\\begin{document}") {
+        Some(start) => {
+            let body = &source[start + "\\begin{document}".len()..];
+            (&source[..start], body.split("\\end{document}").next().unwrap_or(body))
+        },
+        None => ("", source.as_str()),
+    };
+
+    let context = Context {
+        directory,
+        main_stem: main_file.and_then(|path| path.file_stem()).and_then(|stem| stem.to_str()).map(|stem| stem.to_string()),
+        bibliography_resources: command_arguments(&source, "addbibresource"),
+        top_depth: SECTIONS.iter()
+            .filter(|(name, _)| body.contains(&format!("\\{}{{", name)) || body.contains(&format!("\\{}*{{", name)))
+            .map(|(_, depth)| *depth)
+            .min()
+            .unwrap_or(2),
+        events,
+    };
+
+    let mut elements = document_metadata(preamble, body);
+    elements.extend(Reader::new(&context, body).read());
+    elements
+}
+
+// LaTeX implementation of the common Parser trait
+pub struct LatexParser;
+
+impl Parser for LatexParser {
+    fn name(&self) -> &str {
+        "latex"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tex", "latex", "ltx"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/x-tex", "text/x-tex", "application/x-latex"]
+    }
+
+    // A \documentclass line, after comments and blank lines
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        let prefix = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+        prefix.lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('%'))
+            .is_some_and(|line| line.starts_with("\\documentclass") || line.starts_with("\\documentstyle"))
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_latex_bytes_with(bytes, events)
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_latex_path(path, events)
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to sources
+//-------------------------------------------------
+
+// Drops "%" comments with their line break (as TeX does), except in verbatim environments
+fn strip_comments(source: &str) -> String {
+    let mut stripped = String::with_capacity(source.len());
+    let mut verbatim: Option<&str> = None;
+
+    for line in source.lines() {
+        if let Some(environment) = verbatim {
+            stripped.push_str(line);
+            stripped.push('\n');
+            if line.contains(&format!("\\end{{{}}}", environment)) {
+                verbatim = None;
+            }
+            continue;
+        }
+        if let Some(environment) = CODE_ENVIRONMENTS.iter().find(|environment| line.contains(&format!("\\begin{{{}}}", environment)))
+            && !line.contains(&format!("\\end{{{}}}", environment)) {
+            verbatim = Some(environment);
+        }
+
+        match comment_start(line) {
+            Some(position) => stripped.push_str(&line[..position]),
+            None => {
+                stripped.push_str(line);
+                stripped.push('\n');
+                // Keep the paragraph break of a blank line even after a comment ate the line break before it
+                if line.trim().is_empty() {
+                    stripped.push('\n');
+                }
+            },
+        }
+    }
+
+    stripped
+}
+
+// Byte offset of the first "%" not escaped as "\%"
+fn comment_start(line: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (index, character) in line.char_indices() {
+        match character {
+            '\\' => escaped = !escaped,
+            '%' if !escaped => return Some(index),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+// Inlines \input{file}, \include{file} and \subfile{file}; files that include themselves are skipped
+fn expand_inputs(source: &str, directory: Option<&Path>, stack: &mut Vec<PathBuf>, events: &dyn EventSink) -> String {
+    let mut expanded = String::with_capacity(source.len());
+    let mut rest = source;
+
+    while let Some(position) = next_input(rest) {
+        let (before, command) = rest.split_at(position);
+        expanded.push_str(before);
+        let name_length = command[1..].find(|character: char| !character.is_ascii_alphabetic()).map_or(command.len(), |length| length + 1);
+        let after = &command[name_length..];
+        // "\input{file}" or "\input file"
+        let (argument, remaining) = match after.trim_start().strip_prefix('{') {
+            Some(inner) => match inner.find('}') {
+                Some(end) => (inner[..end].trim(), &inner[end + 1..]),
+                None => ("", after),
+            },
+            None => {
+                let trimmed = after.trim_start_matches([' ', '\t']);
+                let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+                (&trimmed[..end], &trimmed[end..])
+            },
+        };
+        rest = remaining;
+        if argument.is_empty() {
+            continue;
+        }
+
+        match read_input(argument, directory, stack, events) {
+            Some(text) => {
+                // \include starts a new page, so a new paragraph
+                expanded.push_str("\n\n");
+                expanded.push_str(&text);
+                expanded.push_str("\n\n");
+            },
+            None => events.emit(ParseEvent::Warning { path: None, message: format!("LaTeX input {:?} was not found, skipped", argument) }),
+        }
+    }
+    expanded.push_str(rest);
+
+    expanded
+}
+
+fn next_input(text: &str) -> Option<usize> {
+    ["\\input", "\\include", "\\subfile"]
+        .iter()
+        .filter_map(|command| {
+            text.match_indices(command)
+                .find(|(position, _)| {
+                    let next = text[position + command.len()..].chars().next();
+                    // \include but not \includegraphics, \input but not \inputencoding
+                    next.is_none_or(|next| !next.is_ascii_alphabetic())
+                })
+                .map(|(position, _)| position)
+        })
+        .min()
+}
+
+fn read_input(argument: &str, directory: Option<&Path>, stack: &mut Vec<PathBuf>, events: &dyn EventSink) -> Option<String> {
+    let directory = directory?;
+    let candidates = [directory.join(argument), directory.join(format!("{}.tex", argument))];
+    let path = candidates.into_iter().find(|path| path.is_file())?;
+    if stack.len() >= MAX_INPUT_DEPTH || stack.contains(&path) {
+        return Some(String::new());
+    }
+
+    let bytes = fs::read(&path).ok()?;
+    let (text, encoding) = decode_text(&bytes);
+    warn_if_latin1(encoding, Some(&path), events);
+    let text = strip_comments(&text);
+    // A \subfile is a complete document of its own
+    let text = match text.find("\\begin{document}") {
+        Some(start) => {
+            let body = &text[start + "\\begin{document}".len()..];
+            body.split("\\end{document}").next().unwrap_or(body).to_string()
+        },
+        None => text,
+    };
+
+    stack.push(path);
+    let expanded = expand_inputs(&text, Some(directory), stack, events);
+    stack.pop();
+    Some(expanded)
+}
+
+// Arguments of every `\name{...}` in the source (after any [options])
+fn command_arguments(source: &str, name: &str) -> Vec<String> {
+    let command = format!("\\{}", name);
+    let mut arguments = Vec::new();
+
+    for (position, _) in source.match_indices(&command) {
+        let rest = &source[position + command.len()..];
+        if rest.starts_with(|character: char| character.is_ascii_alphabetic()) {
+            continue;
+        }
+        let mut reader = Scanner::new(rest);
+        reader.optional_argument();
+        if let Some(argument) = reader.group() {
+            arguments.push(argument);
+        }
+    }
+
+    arguments
+}
+
+// \title, \author and \date from the preamble or the body, \keywords, and the abstract
+fn document_metadata(preamble: &str, body: &str) -> Vec<DocumentElement> {
+    let mut elements = Vec::new();
+    let both = format!("{}\n{}", preamble, body);
+    let first = |name: &str| command_arguments(&both, name).into_iter().map(|argument| inline_text(&argument)).find(|text| !text.is_empty());
+
+    if let Some(title) = first("title") {
+        elements.push(DocumentElement::Title { text: title });
+    }
+    let authors: Vec<String> = command_arguments(&both, "author")
+        .iter()
+        .flat_map(|argument| argument.split("\\and").map(|author| author.to_string()).collect::<Vec<String>>())
+        .map(|author| inline_text(&author.replace("\\\\", ", ")))
+        .map(|author| author.replace(" ,", ",").trim_matches([',', ' ']).to_string())
+        .filter(|author| !author.is_empty())
+        .collect();
+    if !authors.is_empty() {
+        elements.push(DocumentElement::Author { text: authors.join(", ") });
+    }
+    if let Some(keywords) = first("keywords") {
+        elements.push(DocumentElement::Keywords { text: keywords });
+    }
+    if let Some(date) = command_arguments(&both, "date").first().filter(|date| !date.contains("\\today")).map(|date| inline_text(date)).filter(|date| !date.is_empty()) {
+        elements.push(DocumentElement::CreationDate { text: date });
+    }
+    if let Some(start) = body.find("\\begin{abstract}") {
+        let mut scanner = Scanner::new(&body[start + "\\begin{abstract}".len()..]);
+        let abstract_text = inline_text(&scanner.environment_body("abstract"));
+        if !abstract_text.is_empty() {
+            elements.push(DocumentElement::Description { text: abstract_text });
+        }
+    }
+
+    elements
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to scanning
+//-------------------------------------------------
+
+// Cursor over LaTeX source with brace-aware argument reading
+struct Scanner {
+    characters: Vec<char>,
+    position: usize,
+}
+
+impl Scanner {
+    fn new(source: &str) -> Scanner {
+        Scanner { characters: source.chars().collect(), position: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.characters.get(self.position).copied()
+    }
+
+    fn at(&self, text: &str) -> bool {
+        text.chars().enumerate().all(|(offset, character)| self.characters.get(self.position + offset) == Some(&character))
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(|character| character == ' ' || character == '\t') {
+            self.position += 1;
+        }
+    }
+
+    // Spaces and at most one line break (a blank line ends the paragraph and is kept)
+    fn skip_whitespace(&mut self) {
+        self.skip_spaces();
+        if self.peek() == Some('\n') {
+            let mut lookahead = self.position + 1;
+            while self.characters.get(lookahead).is_some_and(|character| *character == ' ' || *character == '\t') {
+                lookahead += 1;
+            }
+            if self.characters.get(lookahead) != Some(&'\n') {
+                self.position = lookahead;
+            }
+        }
+    }
+
+    // Name after a backslash: letters (and a trailing "*"), or a single other character
+    fn command_name(&mut self) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(|character| character.is_ascii_alphabetic() || character == '@') {
+            self.position += 1;
+        }
+        if self.position == start {
+            self.position += 1;
+            return self.characters.get(start).map(|character| character.to_string()).unwrap_or_default();
+        }
+        let name: String = self.characters[start..self.position].iter().collect();
+        if self.peek() == Some('*') {
+            self.position += 1;
+        }
+        name
+    }
+
+    // Content of a balanced "{...}" at the cursor (after whitespace); a single token otherwise
+    fn group(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        match self.peek()? {
+            '{' => Some(self.balanced('{', '}')),
+            '\\' => {
+                let start = self.position;
+                self.position += 1;
+                self.command_name();
+                Some(self.characters[start..self.position].iter().collect())
+            },
+            '}' | '[' => None,
+            character => {
+                self.position += 1;
+                Some(character.to_string())
+            },
+        }
+    }
+
+    // Content of "[...]" at the cursor, if any
+    fn optional_argument(&mut self) -> Option<String> {
+        let start = self.position;
+        self.skip_whitespace();
+        if self.peek() == Some('[') {
+            return Some(self.balanced('[', ']'));
+        }
+        self.position = start;
+        None
+    }
+
+    // Reads from an opening delimiter to its closing one (escaped braces and nested groups included)
+    fn balanced(&mut self, open: char, close: char) -> String {
+        let start = self.position + 1;
+        let mut depth = 0;
+
+        while let Some(character) = self.peek() {
+            self.position += 1;
+            match character {
+                '\\' => self.position += 1,
+                _ if character == open => depth += 1,
+                // Braces nest inside optional arguments too
+                '{' => depth += 1,
+                '}' if close != '}' => depth -= 1,
+                _ if character == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return self.characters[start..self.position - 1].iter().collect();
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        self.characters[start.min(self.characters.len())..].iter().collect()
+    }
+
+    // Source up to the matching \end{name}, skipping nested environments of the same name
+    fn environment_body(&mut self, name: &str) -> String {
+        let begin = format!("\\begin{{{}}}", name);
+        let end = format!("\\end{{{}}}", name);
+        let start = self.position;
+        let mut depth = 1;
+
+        while self.position < self.characters.len() {
+            if self.at(&begin) {
+                depth += 1;
+                self.position += begin.chars().count();
+            } else if self.at(&end) {
+                depth -= 1;
+                if depth == 0 {
+                    let body = self.characters[start..self.position].iter().collect();
+                    self.position += end.chars().count();
+                    return body;
+                }
+                self.position += end.chars().count();
+            } else {
+                self.position += if self.peek() == Some('\\') { 2 } else { 1 };
+            }
+        }
+
+        self.characters[start..].iter().collect()
+    }
+
+    // Raw text up to `delimiter` (math), leaving the cursor after it
+    fn until(&mut self, delimiter: &str) -> String {
+        let start = self.position;
+        while self.position < self.characters.len() && !self.at(delimiter) {
+            self.position += if self.peek() == Some('\\') && !self.at(delimiter) { 2 } else { 1 };
+        }
+        let end = self.position.min(self.characters.len());
+        self.position = (end + delimiter.chars().count()).min(self.characters.len());
+        self.characters[start..end].iter().collect()
+    }
+}
+
+// Source-level state shared by every reader of one document
+struct Context<'a> {
+    directory: Option<PathBuf>,
+    main_stem: Option<String>,
+    bibliography_resources: Vec<String>,
+    // Depth (see SECTIONS) of the shallowest sectioning command used
+    top_depth: u8,
+    events: &'a dyn EventSink,
+}
+
+impl Context<'_> {
+    fn heading_level(&self, depth: u8) -> u8 {
+        (depth.saturating_sub(self.top_depth) + 1).min(6)
+    }
+}
+
+// Text of an argument: commands resolved, blocks joined
+fn inline_text(source: &str) -> String {
+    let context = Context { directory: None, main_stem: None, bibliography_resources: Vec::new(), top_depth: 2, events: &() };
+    let elements = Reader::new(&context, source).read();
+    element_texts(&elements).join(" ")
+}
+
+fn element_texts(elements: &[DocumentElement]) -> Vec<String> {
+    elements.iter()
+        .filter_map(|element| match element {
+            DocumentElement::Paragraph { text } | DocumentElement::Heading { text, .. } | DocumentElement::Blockquote { text } => Some(text.clone()),
+            DocumentElement::List { items, .. } => Some(items.join("; ")),
+            DocumentElement::Math { tex, .. } => Some(tex.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to the body
+//-------------------------------------------------
+
+struct Reader<'a> {
+    context: &'a Context<'a>,
+    scanner: Scanner,
+    elements: Vec<DocumentElement>,
+    paragraph: String,
+    // \href and \url targets of the current paragraph
+    links: Vec<(String, String)>,
+}
+
+impl<'a> Reader<'a> {
+    fn new(context: &'a Context<'a>, source: &str) -> Reader<'a> {
+        Reader { context, scanner: Scanner::new(source), elements: Vec::new(), paragraph: String::new(), links: Vec::new() }
+    }
+
+    fn read(mut self) -> Vec<DocumentElement> {
+        while let Some(character) = self.scanner.peek() {
+            self.scanner.position += 1;
+            match character {
+                '\\' => self.command(),
+                '$' if self.scanner.peek() == Some('$') => {
+                    self.scanner.position += 1;
+                    let tex = self.scanner.until("$$");
+                    self.push_math(tex, None);
+                },
+                '$' => {
+                    let tex = self.scanner.until("$");
+                    self.paragraph.push_str(&format!("${}$", tex));
+                },
+                '\n' => {
+                    self.scanner.skip_spaces();
+                    if self.scanner.peek() == Some('\n') {
+                        self.flush();
+                    } else {
+                        self.paragraph.push(' ');
+                    }
+                },
+                '{' | '}' => {},
+                '~' => self.paragraph.push(' '),
+                '-' if self.scanner.at("--") => {
+                    self.scanner.position += 2;
+                    self.paragraph.push('—');
+                },
+                '-' if self.scanner.at("-") => {
+                    self.scanner.position += 1;
+                    self.paragraph.push('–');
+                },
+                '`' if self.scanner.at("`") => {
+                    self.scanner.position += 1;
+                    self.paragraph.push('“');
+                },
+                '\'' if self.scanner.at("'") => {
+                    self.scanner.position += 1;
+                    self.paragraph.push('”');
+                },
+                '&' => self.paragraph.push(' '),
+                _ => self.paragraph.push(character),
+            }
+        }
+        self.flush();
+        self.elements
+    }
+
+    fn flush(&mut self) {
+        let text = self.paragraph.split_whitespace().collect::<Vec<&str>>().join(" ");
+        if !text.is_empty() {
+            self.elements.push(DocumentElement::Paragraph { text });
+        }
+        self.paragraph.clear();
+        for (text, url) in self.links.drain(..) {
+            self.elements.push(DocumentElement::Link { text, url });
+        }
+    }
+
+    fn push_block(&mut self, element: DocumentElement) {
+        self.flush();
+        self.elements.push(element);
+    }
+
+    fn push_math(&mut self, tex: String, environment: Option<String>) {
+        let tex = tex.trim().to_string();
+        if !tex.is_empty() {
+            self.push_block(DocumentElement::Math { tex, environment });
+        }
+    }
+
+    fn command(&mut self) {
+        let name = self.scanner.command_name();
+        let context = self.context;
+
+        if let Some((_, depth)) = SECTIONS.iter().find(|(section, _)| *section == name) {
+            self.scanner.optional_argument();
+            let text = self.scanner.group().map(|title| inline_text(&title)).unwrap_or_default();
+            let level = context.heading_level(*depth);
+            self.push_block(DocumentElement::Heading { level, text });
+            return;
+        }
+        if let Some((_, count)) = DROPPED_COMMANDS.iter().find(|(dropped, _)| *dropped == name) {
+            self.scanner.optional_argument();
+            for _ in 0..*count {
+                self.scanner.optional_argument();
+                self.scanner.group();
+            }
+            self.scanner.skip_whitespace();
+            return;
+        }
+        if let Some((_, symbol)) = SYMBOLS.iter().find(|(command, _)| *command == name) {
+            self.paragraph.push_str(symbol);
+            // "\ss{}" or "\ldots\ "
+            if self.scanner.at("{}") {
+                self.scanner.position += 2;
+            } else {
+                self.scanner.skip_spaces();
+            }
+            return;
+        }
+        if let Some((_, letters)) = ACCENTS.iter().find(|(accent, _)| name.len() == 1 && name.starts_with(*accent)) {
+            let base = self.scanner.group().unwrap_or_default();
+            let base = base.trim_start_matches('\\').chars().next().unwrap_or(' ');
+            self.paragraph.push(accented(letters, base));
+            return;
+        }
+
+        match name.as_str() {
+            "begin" => {
+                let environment = self.scanner.group().unwrap_or_default();
+                self.environment(&environment);
+            },
+            // \end of an environment read in place (center, theorem...)
+            "end" => {
+                self.scanner.group();
+            },
+            "[" => {
+                let tex = self.scanner.until("\\]");
+                self.push_math(tex, None);
+            },
+            "(" => {
+                let tex = self.scanner.until("\\)");
+                self.paragraph.push_str(&format!("${}$", tex));
+            },
+            "\\" => {
+                // "\\[2pt]" and "\\*"
+                self.scanner.optional_argument();
+                self.paragraph.push(' ');
+            },
+            "par" => self.flush(),
+            "item" => {
+                // An \item outside of a list environment
+                self.flush();
+                if let Some(label) = self.scanner.optional_argument() {
+                    self.paragraph.push_str(&inline_text(&label));
+                    self.paragraph.push(' ');
+                }
+            },
+            "cite" | "citep" | "citet" | "citealp" | "citeauthor" | "citeyear" | "parencite" | "textcite" | "autocite" | "footcite" => {
+                self.scanner.optional_argument();
+                self.scanner.optional_argument();
+                let keys = self.scanner.group().unwrap_or_default();
+                let keys: Vec<&str> = keys.split(',').map(str::trim).collect();
+                self.paragraph.push_str(&format!("[{}]", keys.join(", ")));
+            },
+            "ref" | "eqref" | "autoref" | "cref" | "Cref" | "pageref" | "nameref" => {
+                let key = self.scanner.group().unwrap_or_default();
+                let reference = if name == "eqref" { format!("({})", key) } else { key };
+                self.paragraph.push_str(&reference);
+            },
+            "url" => {
+                let url = self.scanner.group().unwrap_or_default();
+                self.paragraph.push_str(&url);
+                self.links.push((url.clone(), url));
+            },
+            "href" => {
+                let url = self.scanner.group().unwrap_or_default();
+                let text = self.scanner.group().map(|text| inline_text(&text)).unwrap_or_default();
+                self.paragraph.push_str(&text);
+                self.links.push((text, url));
+            },
+            "verb" => {
+                // \verb|text| with any delimiter
+                if let Some(delimiter) = self.scanner.peek() {
+                    self.scanner.position += 1;
+                    let text = self.scanner.until(&delimiter.to_string());
+                    self.paragraph.push_str(&text);
+                }
+            },
+            "enquote" => {
+                let text = self.scanner.group().map(|text| inline_text(&text)).unwrap_or_default();
+                self.paragraph.push_str(&format!("“{}”", text));
+            },
+            "caption" => {
+                self.scanner.optional_argument();
+                let text = self.scanner.group().map(|text| inline_text(&text)).unwrap_or_default();
+                if !text.is_empty() {
+                    self.push_block(DocumentElement::Paragraph { text });
+                }
+            },
+            "includegraphics" => {
+                self.scanner.optional_argument();
+                let file = self.scanner.group().unwrap_or_default();
+                self.push_block(DocumentElement::Image { alt: String::new(), url: Some(file) });
+            },
+            "bibliography" | "printbibliography" => {
+                self.scanner.optional_argument();
+                let files = if name == "bibliography" {
+                    self.scanner.group().unwrap_or_default().split(',').map(|file| file.trim().to_string()).collect()
+                } else {
+                    context.bibliography_resources.clone()
+                };
+                let entries = bibliography_entries(context, &files);
+                self.push_references(entries);
+            },
+            "def" => {
+                // \def\name#1{body}
+                self.scanner.group();
+                while self.scanner.peek().is_some_and(|character| character != '{') {
+                    self.scanner.position += 1;
+                }
+                self.scanner.group();
+            },
+            // Escaped characters and spacing
+            "&" | "%" | "$" | "#" | "_" | "{" | "}" => self.paragraph.push_str(&name),
+            " " | "," | ";" | ":" | "!" | "/" | "@" => self.paragraph.push(' '),
+            "-" => {},
+            // Formatting (\textbf, \emph...) and unknown commands: their arguments are read as text
+            _ => {
+                if !name.is_empty() && name.chars().all(|character| character.is_ascii_alphabetic()) {
+                    self.scanner.skip_spaces();
+                }
+            },
+        }
+    }
+
+    fn environment(&mut self, name: &str) {
+        let context = self.context;
+
+        if MATH_ENVIRONMENTS.contains(&name) {
+            let tex = self.scanner.environment_body(name);
+            self.push_math(tex, Some(name.to_string()));
+        } else if LIST_ENVIRONMENTS.contains(&name) {
+            self.scanner.optional_argument();
+            let body = self.scanner.environment_body(name);
+            let items = list_items(context, &body, name == "description");
+            if !items.is_empty() {
+                let ordered = name.contains("enum");
+                self.push_block(DocumentElement::List { items, ordered });
+            }
+        } else if TABLE_ENVIRONMENTS.contains(&name) {
+            // tabular*{width}{spec}, tabularx{width}{spec}, tabular[pos]{spec}
+            if matches!(name, "tabular*" | "tabularx" | "tabulary") {
+                self.scanner.group();
+            }
+            self.scanner.optional_argument();
+            self.scanner.group();
+            let body = self.scanner.environment_body(name);
+            if let Some(table) = table(&body) {
+                self.push_block(table);
+            }
+        } else if CODE_ENVIRONMENTS.contains(&name) {
+            let language = match name {
+                "lstlisting" => self.scanner.optional_argument().and_then(|options| listing_language(&options)),
+                "minted" => {
+                    self.scanner.optional_argument();
+                    self.scanner.group()
+                },
+                _ => None,
+            };
+            let code = self.scanner.environment_body(name);
+            let code = code.trim_start_matches([' ', '\t']).trim_start_matches('\n').trim_end().to_string();
+            // "comment" blocks are commented out
+            if name != "comment" && !code.is_empty() {
+                self.push_block(DocumentElement::Code { code, language, inline: false });
+            }
+        } else if QUOTE_ENVIRONMENTS.contains(&name) {
+            let body = self.scanner.environment_body(name);
+            let text = inline_text(&body);
+            if !text.is_empty() {
+                self.push_block(DocumentElement::Blockquote { text });
+            }
+        } else if FIGURE_ENVIRONMENTS.contains(&name) {
+            self.scanner.optional_argument();
+            if name == "wrapfigure" || name == "subfigure" {
+                self.scanner.group();
+                self.scanner.optional_argument();
+                self.scanner.group();
+            }
+            let body = self.scanner.environment_body(name);
+            self.flush();
+            self.elements.extend(figure(context, &body));
+        } else if name == "thebibliography" {
+            self.scanner.group();
+            let body = self.scanner.environment_body(name);
+            self.push_references(bibliography_items(&body));
+        } else if name == "abstract" || SKIPPED_ENVIRONMENTS.contains(&name) {
+            // The abstract is the document description
+            self.scanner.environment_body(name);
+        } else {
+            // Containers (center, minipage, theorem, proof...) are read in place
+            self.scanner.optional_argument();
+            if matches!(name, "minipage" | "multicols" | "adjustbox" | "resizebox") {
+                self.scanner.group();
+            }
+        }
+    }
+
+    fn push_references(&mut self, entries: Vec<String>) {
+        if entries.is_empty() {
+            return;
+        }
+        let level = self.context.heading_level(2);
+        self.push_block(DocumentElement::Heading { level, text: "References".to_string() });
+        self.push_block(DocumentElement::List { items: entries, ordered: true });
+    }
+}
+
+fn accented(letters: &str, base: char) -> char {
+    let letters: Vec<char> = letters.chars().collect();
+    letters.chunks(2)
+        .find(|pair| pair[0] == base)
+        .map_or(base, |pair| pair[1])
+}
+
+// Items of a list body split at its own \item commands; nested lists become following items
+fn list_items(context: &Context, body: &str, description: bool) -> Vec<String> {
+    let mut items = Vec::new();
+
+    for item in split_top_level(body, "\\item") {
+        let mut scanner = Scanner::new(&item);
+        let label = scanner.optional_argument().map(|label| inline_text(&label));
+        let rest: String = scanner.characters[scanner.position..].iter().collect();
+        let elements = Reader::new(context, &rest).read();
+
+        let (nested, own): (Vec<&DocumentElement>, Vec<&DocumentElement>) = elements.iter()
+            .partition(|element| matches!(element, DocumentElement::List { .. }));
+        let own: Vec<DocumentElement> = own.into_iter().cloned().collect();
+        let mut text = element_texts(&own).join(" ");
+        if let Some(label) = label.filter(|label| !label.is_empty()) {
+            text = if description { format!("{}: {}", label, text) } else { format!("{} {}", label, text) };
+        }
+        if !text.trim().is_empty() {
+            items.push(text.trim().to_string());
+        }
+        for list in nested {
+            if let DocumentElement::List { items: nested_items, .. } = list {
+                items.extend(nested_items.iter().cloned());
+            }
+        }
+    }
+
+    items
+}
+
+// Splits at `separator` outside braces and nested environments; text before the first one is dropped
+fn split_top_level(body: &str, separator: &str) -> Vec<String> {
+    let mut scanner = Scanner::new(body);
+    let mut parts: Vec<String> = Vec::new();
+    let mut start: Option<usize> = None;
+
+    while scanner.position < scanner.characters.len() {
+        if scanner.at(separator)
+            && !scanner.characters.get(scanner.position + separator.chars().count()).is_some_and(|character| character.is_ascii_alphabetic()) {
+            if let Some(first) = start {
+                parts.push(scanner.characters[first..scanner.position].iter().collect());
+            }
+            scanner.position += separator.chars().count();
+            start = Some(scanner.position);
+        } else if scanner.at("\\begin{") {
+            scanner.position += "\\begin".len();
+            let environment = scanner.group().unwrap_or_default();
+            scanner.environment_body(&environment);
+        } else if scanner.peek() == Some('{') {
+            scanner.balanced('{', '}');
+        } else {
+            scanner.position += if scanner.peek() == Some('\\') { 2 } else { 1 };
+        }
+    }
+    if let Some(first) = start {
+        parts.push(scanner.characters[first..].iter().collect());
+    }
+
+    parts
+}
+
+// Rows end at "\\", cells at "&"; rules (\hline, \toprule...) are dropped and the first row is the header
+fn table(body: &str) -> Option<DocumentElement> {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+
+    for row in split_cells(body, "\\\\") {
+        let row = remove_rules(&row);
+        if row.trim().is_empty() {
+            continue;
+        }
+        let cells: Vec<String> = split_cells(&row, "&").iter().map(|cell| inline_text(cell)).collect();
+        if cells.iter().any(|cell| !cell.is_empty()) {
+            rows.push(cells);
+        }
+    }
+
+    if rows.is_empty() {
+        return None;
+    }
+    let headers = rows.remove(0);
+    Some(DocumentElement::Table { headers, rows })
+}
+
+// Splits at `separator` outside braces ("\&" is not a separator)
+fn split_cells(text: &str, separator: &str) -> Vec<String> {
+    let mut scanner = Scanner::new(text);
+    let mut parts = Vec::new();
+    let mut start = 0;
+
+    while scanner.position < scanner.characters.len() {
+        if scanner.at(separator) {
+            parts.push(scanner.characters[start..scanner.position].iter().collect());
+            scanner.position += separator.chars().count();
+            // "\\[2pt]" sets the row spacing
+            if separator == "\\\\" {
+                scanner.optional_argument();
+            }
+            start = scanner.position;
+        } else if scanner.peek() == Some('{') {
+            scanner.balanced('{', '}');
+        } else {
+            scanner.position += if scanner.peek() == Some('\\') { 2 } else { 1 };
+        }
+    }
+    parts.push(scanner.characters[start..].iter().collect());
+
+    parts
+}
+
+fn remove_rules(row: &str) -> String {
+    let mut row = row.to_string();
+    for rule in ["\\hline", "\\toprule", "\\midrule", "\\bottomrule", "\\endhead", "\\endfirsthead", "\\endfoot", "\\endlastfoot"] {
+        row = row.replace(rule, " ");
+    }
+    // \cline{2-3} and \cmidrule(lr){2-3}
+    for rule in ["\\cline", "\\cmidrule"] {
+        while let Some(position) = row.find(rule) {
+            let mut scanner = Scanner::new(&row[position + rule.len()..]);
+            if scanner.peek() == Some('(') {
+                scanner.until(")");
+            }
+            scanner.group();
+            let rest: String = scanner.characters[scanner.position..].iter().collect();
+            row = format!("{} {}", &row[..position], rest);
+        }
+    }
+    row
+}
+
+// "language=Python, caption=..." -> "python"
+fn listing_language(options: &str) -> Option<String> {
+    options.split(',')
+        .filter_map(|option| option.split_once('='))
+        .find(|(key, _)| key.trim() == "language")
+        .map(|(_, value)| value.trim().trim_matches(['{', '}']).to_lowercase())
+}
+
+// Graphics of a figure with its caption as their description; a figure without graphics
+// (a drawing, a table) keeps its content and caption as text
+fn figure(context: &Context, body: &str) -> Vec<DocumentElement> {
+    let elements = Reader::new(context, body).read();
+    let caption = command_arguments(body, "caption").first().map(|caption| inline_text(caption));
+    let has_images = elements.iter().any(|element| matches!(element, DocumentElement::Image { .. }));
+    if !has_images {
+        return elements;
+    }
+
+    elements.into_iter()
+        .filter_map(|element| match element {
+            DocumentElement::Image { url, .. } => {
+                let alt = caption.clone().unwrap_or_else(|| url.clone().unwrap_or_default());
+                Some(DocumentElement::Image { alt, url })
+            },
+            // The caption is the description of the images
+            DocumentElement::Paragraph { text } if caption.as_ref() == Some(&text) => None,
+            element => Some(element),
+        })
+        .collect()
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to bibliography
+//-------------------------------------------------
+
+// "\bibitem[label]{key} text" entries of thebibliography (also what BibTeX writes in .bbl files)
+fn bibliography_items(body: &str) -> Vec<String> {
+    split_top_level(body, "\\bibitem")
+        .iter()
+        .map(|item| {
+            let mut scanner = Scanner::new(item);
+            scanner.optional_argument();
+            scanner.group();
+            let rest: String = scanner.characters[scanner.position..].iter().collect();
+            // BibTeX styles separate fields with \newblock
+            inline_text(&rest.replace("\\newblock", " "))
+        })
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+// The compiled .bbl next to the main file lists the cited entries in order; otherwise every
+// entry of the named .bib files is listed
+fn bibliography_entries(context: &Context, files: &[String]) -> Vec<String> {
+    let Some(directory) = &context.directory else {
+        return Vec::new();
+    };
+
+    if let Some(stem) = &context.main_stem
+        && let bbl_path = directory.join(format!("{}.bbl", stem))
+        && let Ok(bytes) = fs::read(&bbl_path) {
+        let (bbl, encoding) = decode_text(&bytes);
+        warn_if_latin1(encoding, Some(&bbl_path), context.events);
+        if let Some(start) = bbl.find("\\begin{thebibliography}") {
+            let mut scanner = Scanner::new(&bbl[start + "\\begin{thebibliography}".len()..]);
+            scanner.group();
+            let items = bibliography_items(&scanner.environment_body("thebibliography"));
+            if !items.is_empty() {
+                return items;
+            }
+        }
+    }
+
+    let mut entries = Vec::new();
+    for file in files.iter().filter(|file| !file.is_empty()) {
+        let name = if file.ends_with(".bib") { file.clone() } else { format!("{}.bib", file) };
+        let bib_path = directory.join(&name);
+        match fs::read(&bib_path) {
+            Ok(bytes) => {
+                let (bib, encoding) = decode_text(&bytes);
+                warn_if_latin1(encoding, Some(&bib_path), context.events);
+                entries.extend(bibtex_entries(&bib));
+            },
+            Err(_) => context.events.emit(ParseEvent::Warning { path: None, message: format!("bibliography {:?} was not found, skipped", name) }),
+        }
+    }
+    entries
+}
+
+// "@article{key, author = {A and B}, title = {T}, year = 2020, journal = {J}}" -> "A and B (2020). T. J."
+fn bibtex_entries(bib: &str) -> Vec<String> {
+    let mut entries = Vec::new();
+
+    for chunk in bib.split('@').skip(1) {
+        let Some((kind, rest)) = chunk.split_once(['{', '(']) else {
+            continue;
+        };
+        if matches!(kind.trim().to_lowercase().as_str(), "string" | "comment" | "preamble") {
+            continue;
+        }
+        let Some((_, fields)) = rest.split_once(',') else {
+            continue;
+        };
+
+        let fields = bibtex_fields(fields);
+        let field = |name: &str| fields.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str()).filter(|value| !value.is_empty());
+        let mut entry = String::new();
+        if let Some(author) = field("author").or_else(|| field("editor")) {
+            entry.push_str(&author.replace(" and ", ", "));
+        }
+        if let Some(year) = field("year").or_else(|| field("date")) {
+            entry.push_str(&format!(" ({})", year));
+        }
+        for name in ["title", "journal", "booktitle", "publisher", "howpublished", "url"] {
+            if let Some(value) = field(name) {
+                if !entry.is_empty() {
+                    entry.push_str(". ");
+                }
+                entry.push_str(value);
+            }
+        }
+        let entry = entry.trim().trim_start_matches(". ").to_string();
+        if !entry.is_empty() {
+            entries.push(format!("{}.", entry.trim_end_matches('.')));
+        }
+    }
+
+    entries
+}
+
+// "key = {value}" / "key = "value"" / "key = 2020" pairs, values as text
+fn bibtex_fields(fields: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut scanner = Scanner::new(fields);
+
+    loop {
+        let start = scanner.position;
+        while scanner.peek().is_some_and(|character| character != '=') {
+            scanner.position += 1;
+        }
+        if scanner.peek().is_none() {
+            break;
+        }
+        let key: String = scanner.characters[start..scanner.position].iter().collect();
+        let key = key.trim().trim_start_matches(',').trim().to_lowercase();
+        scanner.position += 1;
+        scanner.skip_whitespace();
+
+        let value = match scanner.peek() {
+            Some('{') => scanner.balanced('{', '}'),
+            Some('"') => {
+                scanner.position += 1;
+                scanner.until("\"")
+            },
+            _ => {
+                let start = scanner.position;
+                while scanner.peek().is_some_and(|character| character != ',' && character != '}' && character != '\n') {
+                    scanner.position += 1;
+                }
+                scanner.characters[start..scanner.position].iter().collect()
+            },
+        };
+        pairs.push((key, inline_text(&value)));
+
+        while scanner.peek().is_some_and(|character| character != ',') {
+            scanner.position += 1;
+        }
+        if scanner.peek().is_none() {
+            break;
+        }
+        scanner.position += 1;
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r"\documentclass{article}
+\usepackage{amsmath}
+\newcommand{\R}{\mathbb{R}}
+\title{On Sums}
+\author{Ann Lee \and Bo Chen}
+\begin{document}
+\maketitle
+\section{Introduction}
+We study sums % of what?
+of $n$ terms.
+\subsection{Setup}
+\begin{enumerate}
+\item First point
+\item Second point
+\end{enumerate}
+\begin{equation}
+\sum_{i=1}^{n} i = \frac{n(n+1)}{2}
+\end{equation}
+\begin{tabular}{ll}
+\hline
+Name & Value \\
+\hline
+a & 1 \\
+\end{tabular}
+\input{results}
+\end{document}
+";
+
+    fn heading(level: u8, text: &str) -> DocumentElement {
+        DocumentElement::Heading { level, text: text.to_string() }
+    }
+
+    #[test]
+    fn sections_lists_math_and_tables() {
+        assert_eq!(parse_latex_bytes(SOURCE.as_bytes()).unwrap(), vec![
+            DocumentElement::Title { text: "On Sums".to_string() },
+            DocumentElement::Author { text: "Ann Lee, Bo Chen".to_string() },
+            heading(1, "Introduction"),
+            DocumentElement::Paragraph { text: "We study sums of $n$ terms.".to_string() },
+            heading(2, "Setup"),
+            DocumentElement::List { items: vec!["First point".to_string(), "Second point".to_string()], ordered: true },
+            DocumentElement::Math { tex: r"\sum_{i=1}^{n} i = \frac{n(n+1)}{2}".to_string(), environment: Some("equation".to_string()) },
+            DocumentElement::Table {
+                headers: vec!["Name".to_string(), "Value".to_string()],
+                rows: vec![vec!["a".to_string(), "1".to_string()]],
+            },
+        ]);
+    }
+
+    #[test]
+    fn inputs_resolve_next_to_the_main_file() {
+        let directory = std::env::temp_dir().join(format!("oxidoc-latex-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let main = directory.join("paper.tex");
+        fs::write(&main, SOURCE).unwrap();
+        fs::write(directory.join("results.tex"), "\\section{Results}\nIt works.\n").unwrap();
+
+        let elements = parse_latex_file(&main).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(elements[elements.len() - 2..], [
+            heading(1, "Results"),
+            DocumentElement::Paragraph { text: "It works.".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn latin1_inputs_are_reported() {
+        let directory = std::env::temp_dir().join(format!("oxidoc-latex-latin1-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let main = directory.join("paper.tex");
+        fs::write(&main, SOURCE).unwrap();
+        let results = directory.join("results.tex");
+        fs::write(&results, b"\\section{R\xe9sultats}\nIt works.\n").unwrap();
+
+        let warnings = std::cell::RefCell::new(Vec::new());
+        let sink = |event: ParseEvent| if let ParseEvent::Warning { path, .. } = event {
+            warnings.borrow_mut().push(path);
+        };
+        let elements = LatexParser.parse_path(&main, &sink).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        LatexParser.parse_bytes(b"\\section{Caf\xe9}\n", &sink).unwrap();
+
+        assert!(elements.contains(&heading(1, "Résultats")));
+        assert_eq!(*warnings.borrow(), vec![Some(results), None]);
+    }
+}
@@ -0,0 +1,464 @@
+// markdown_parser.rs
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, MetadataBlockKind, Options, Tag, TagEnd};
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::{EventSink, ParseEvent};
+use crate::parsers::documents_types::{fallback_title, group_sections, Document, DocumentElement, ListItem, Node, TableCell};
+use crate::parsers::html_parser::parse_html_str;
+use crate::parsers::parser::Parser;
+
+
+// Main parsing function
+pub fn parse_markdown_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    // Parse into the document tree, then flatten it in reading order
+    Ok(parse_markdown_document(path)?.to_elements())
+}
+
+// Parse a Markdown file into the hierarchical document tree
+pub fn parse_markdown_document(path: &Path) -> Result<Document> {
+    parse_markdown_path(path, &())
+}
+
+fn parse_markdown_path(path: &Path, events: &dyn EventSink) -> Result<Document> {
+    // Read the Markdown file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+    let markdown = decode_markdown(&bytes, Some(path), events);
+
+    let mut document = parse_markdown_str(&markdown);
+
+    fallback_title(&mut document.metadata, path);
+
+    Ok(document)
+}
+
+// Invalid UTF-8 is replaced rather than rejected, with a warning
+fn decode_markdown<'a>(bytes: &'a [u8], path: Option<&Path>, events: &dyn EventSink) -> Cow<'a, str> {
+    let markdown = String::from_utf8_lossy(bytes);
+    if let Cow::Owned(_) = markdown {
+        events.emit(ParseEvent::Warning {
+            path: path.map(|path| path.to_path_buf()),
+            message: "Markdown is not valid UTF-8, invalid bytes were replaced".to_string(),
+        });
+    }
+    markdown
+}
+
+// Parse CommonMark with the GitHub extensions (tables, task lists, strikethrough, footnotes)
+// and YAML ("---") or TOML ("+++") front matter into the hierarchical document tree
+pub fn parse_markdown_str(markdown: &str) -> Document {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_GFM
+        | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+        | Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS;
+
+    let mut builder = TreeBuilder::new();
+    for event in pulldown_cmark::Parser::new_ext(markdown, options) {
+        builder.push_event(event);
+    }
+    builder.finish()
+}
+
+// Markdown implementation of the common Parser trait
+pub struct MarkdownParser;
+
+impl Parser for MarkdownParser {
+    fn name(&self) -> &str {
+        "markdown"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["md", "markdown", "mdown", "mkd"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["text/markdown", "text/x-markdown"]
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        Ok(parse_markdown_str(&decode_markdown(bytes, None, events)).to_elements())
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        Ok(parse_markdown_path(path, events)?.to_elements())
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to the document tree
+//-------------------------------------------------
+
+// What an open Markdown container will become once it is closed
+enum FrameKind {
+    Root,
+    Heading(u8),
+    Paragraph,
+    Blockquote,
+    List { ordered: bool, items: Vec<ListItem> },
+    Item,
+    Table { headers: Vec<TableCell>, rows: Vec<Vec<TableCell>>, row: Vec<TableCell> },
+    Cell,
+    CodeBlock(Option<String>),
+    HtmlBlock,
+    Link(String),
+    Image(String),
+    // Footnotes and definition lists: their content joins the enclosing block
+    Container,
+    FrontMatter(MetadataBlockKind),
+}
+
+// An open container with the text and nodes collected so far
+struct Frame {
+    kind: FrameKind,
+    text: String,
+    children: Vec<Node>,
+}
+
+// Builds the Node tree from the flat pulldown-cmark event stream
+struct TreeBuilder {
+    stack: Vec<Frame>,
+    metadata: Vec<DocumentElement>,
+}
+
+impl TreeBuilder {
+    fn new() -> TreeBuilder {
+        TreeBuilder {
+            stack: vec![Frame { kind: FrameKind::Root, text: String::new(), children: Vec::new() }],
+            metadata: Vec::new(),
+        }
+    }
+
+    fn finish(mut self) -> Document {
+        // Close anything left open, then the root
+        while self.stack.len() > 1 {
+            self.close();
+        }
+        let root = self.stack.pop().unwrap();
+
+        Document {
+            metadata: self.metadata,
+            nodes: group_sections(root.children),
+        }
+    }
+
+    fn open(&mut self, kind: FrameKind) {
+        self.stack.push(Frame { kind, text: String::new(), children: Vec::new() });
+    }
+
+    fn top(&mut self) -> &mut Frame {
+        self.stack.last_mut().unwrap()
+    }
+
+    fn push_event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(TagEnd::TableHead) => {
+                if let FrameKind::Table { headers, row, .. } = &mut self.top().kind {
+                    *headers = std::mem::take(row);
+                }
+            },
+            Event::End(TagEnd::TableRow) => {
+                if let FrameKind::Table { rows, row, .. } = &mut self.top().kind {
+                    rows.push(std::mem::take(row));
+                }
+            },
+            Event::End(TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Superscript | TagEnd::Subscript) => {},
+            Event::End(_) => self.close(),
+            Event::Text(text) => self.top().text.push_str(&text),
+            Event::Code(code) => {
+                let frame = self.top();
+                frame.text.push_str(&code);
+                frame.children.push(Node::Element(DocumentElement::Code {
+                    code: code.to_string(),
+                    language: None,
+                    inline: true,
+                }));
+            },
+            Event::InlineMath(math) | Event::DisplayMath(math) => self.top().text.push_str(&math),
+            Event::Html(html) => self.top().text.push_str(&html),
+            Event::SoftBreak => self.top().text.push(' '),
+            Event::HardBreak => self.top().text.push('\n'),
+            Event::TaskListMarker(checked) => {
+                self.top().text.push_str(if checked { "[x] " } else { "[ ] " });
+            },
+            // Inline HTML tags, rules and footnote references carry no text of their own
+            Event::InlineHtml(_) | Event::Rule | Event::FootnoteReference(_) => {},
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        let kind = match tag {
+            Tag::Heading { level, .. } => FrameKind::Heading(heading_level(level)),
+            Tag::Paragraph => FrameKind::Paragraph,
+            Tag::BlockQuote(_) => FrameKind::Blockquote,
+            Tag::List(start) => FrameKind::List { ordered: start.is_some(), items: Vec::new() },
+            Tag::Item => FrameKind::Item,
+            Tag::Table(_) => FrameKind::Table { headers: Vec::new(), rows: Vec::new(), row: Vec::new() },
+            Tag::TableCell => FrameKind::Cell,
+            Tag::CodeBlock(kind) => FrameKind::CodeBlock(code_language(kind)),
+            Tag::HtmlBlock => FrameKind::HtmlBlock,
+            Tag::Link { dest_url, .. } => FrameKind::Link(dest_url.to_string()),
+            Tag::Image { dest_url, .. } => FrameKind::Image(dest_url.to_string()),
+            Tag::FootnoteDefinition(_)
+            | Tag::DefinitionList
+            | Tag::DefinitionListTitle
+            | Tag::DefinitionListDefinition => FrameKind::Container,
+            Tag::MetadataBlock(kind) => FrameKind::FrontMatter(kind),
+            // Rows and heads are tracked by the table itself; inline styling only wraps text
+            Tag::TableHead | Tag::TableRow
+            | Tag::Emphasis | Tag::Strong | Tag::Strikethrough | Tag::Superscript | Tag::Subscript => return,
+        };
+        self.open(kind);
+    }
+
+    // Close the innermost frame and hand its result to the frame below
+    fn close(&mut self) {
+        let Frame { kind, text, children } = self.stack.pop().unwrap();
+        if let FrameKind::FrontMatter(kind) = kind {
+            self.metadata.extend(front_matter_metadata(&text, kind));
+            return;
+        }
+        let parent = self.top();
+
+        match kind {
+            FrameKind::Root => unreachable!("the root frame is only closed by finish"),
+            FrameKind::Heading(level) => {
+                let heading = text.trim().to_string();
+                // Only headings with text open a section
+                if heading.is_empty() {
+                    parent.children.extend(children);
+                } else {
+                    parent.children.push(Node::Section { level, heading, children });
+                }
+            },
+            FrameKind::Paragraph | FrameKind::Blockquote | FrameKind::Container => {
+                let text = text.trim().to_string();
+                // Paragraphs of loose list items, quotes and cells are the text of the item, quote or cell
+                let absorbs_text = matches!(parent.kind,
+                    FrameKind::Item | FrameKind::Blockquote | FrameKind::Container | FrameKind::Cell);
+
+                if absorbs_text || text.is_empty() {
+                    if !text.is_empty() {
+                        if !parent.text.trim().is_empty() {
+                            parent.text.push('\n');
+                        }
+                        parent.text.push_str(&text);
+                    }
+                    parent.children.extend(children);
+                } else if let FrameKind::Blockquote = kind {
+                    parent.children.push(Node::Blockquote { text, children });
+                } else {
+                    parent.children.push(Node::Paragraph { text, children });
+                }
+            },
+            FrameKind::List { ordered, items } => {
+                parent.children.push(Node::List { ordered, items });
+            },
+            FrameKind::Item => {
+                if let FrameKind::List { items, .. } = &mut parent.kind {
+                    items.push(ListItem { text: text.trim().to_string(), children: group_sections(children) });
+                }
+            },
+            FrameKind::Table { headers, rows, .. } => {
+                parent.children.push(Node::Table { headers, rows });
+            },
+            FrameKind::Cell => {
+                if let FrameKind::Table { row, .. } = &mut parent.kind {
+                    row.push(TableCell { text: text.trim().to_string(), children });
+                }
+            },
+            FrameKind::CodeBlock(language) => {
+                parent.children.push(Node::Element(DocumentElement::Code { code: text, language, inline: false }));
+            },
+            FrameKind::HtmlBlock => {
+                // Raw HTML blocks go through the HTML parser
+                parent.children.extend(parse_html_str(&text).nodes);
+            },
+            FrameKind::Link(url) => {
+                parent.text.push_str(&text);
+                let link_text = text.trim().to_string();
+                if !url.is_empty() && !link_text.is_empty() {
+                    parent.children.push(Node::Element(DocumentElement::Link { text: link_text, url }));
+                }
+                // Content nested in the link (an image...) follows it
+                parent.children.extend(children);
+            },
+            FrameKind::Image(url) => {
+                // The alt text describes the image, it is not part of the surrounding text
+                parent.children.push(Node::Element(DocumentElement::Image {
+                    alt: text.trim().to_string(),
+                    url: Some(url).filter(|url| !url.is_empty()),
+                }));
+            },
+            FrameKind::FrontMatter(_) => unreachable!("front matter is closed above"),
+        }
+    }
+}
+
+fn heading_level(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+// The language is the first word of a fence's info string ("```rust,ignore" -> "rust")
+fn code_language(kind: CodeBlockKind) -> Option<String> {
+    match kind {
+        CodeBlockKind::Fenced(info) => info.split(|c: char| c.is_whitespace() || c == ',' || c == '{')
+            .next()
+            .filter(|language| !language.is_empty())
+            .map(|language| language.to_string()),
+        CodeBlockKind::Indented => None,
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to Metadata elements
+//-------------------------------------------------
+
+// Map the usual front matter keys to metadata elements. Only flat "key: value" (YAML) or
+// "key = value" (TOML) entries are read, with values given inline ("[a, b]") or as a YAML "- item" list.
+fn front_matter_metadata(front_matter: &str, kind: MetadataBlockKind) -> Vec<DocumentElement> {
+    let separator = match kind {
+        MetadataBlockKind::YamlStyle => ':',
+        MetadataBlockKind::PlusesStyle => '=',
+    };
+
+    let mut entries: Vec<(String, Vec<String>)> = Vec::new();
+    for line in front_matter.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        // "- item" continues the list of the key above
+        if let Some(item) = trimmed.strip_prefix("- ") {
+            if let Some((_, values)) = entries.last_mut() {
+                values.push(unquote(item));
+            }
+            continue;
+        }
+
+        // Nested keys belong to a mapping we do not read
+        if line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        if let Some((key, value)) = trimmed.split_once(separator) {
+            entries.push((key.trim().to_lowercase(), split_values(value.trim())));
+        }
+    }
+
+    let value = |keys: &[&str]| -> Option<String> {
+        entries.iter()
+            .find(|(key, values)| keys.contains(&key.as_str()) && !values.is_empty())
+            .map(|(_, values)| values.join(", "))
+    };
+
+    let mut metadata = Vec::new();
+    if let Some(title) = value(&["title"]) {
+        metadata.push(DocumentElement::Title { text: title });
+    }
+    if let Some(description) = value(&["description", "summary"]) {
+        metadata.push(DocumentElement::Description { text: description });
+    }
+    if let Some(keywords) = value(&["keywords", "tags"]) {
+        metadata.push(DocumentElement::Keywords { text: keywords });
+    }
+    if let Some(author) = value(&["author", "authors"]) {
+        metadata.push(DocumentElement::Author { text: author });
+    }
+    if let Some(language) = value(&["lang", "language"]) {
+        metadata.push(DocumentElement::Language { text: language });
+    }
+    metadata
+}
+
+// "a" -> [a]; "[a, 'b']" -> [a, b]
+fn split_values(value: &str) -> Vec<String> {
+    match value.strip_prefix('[').and_then(|inner| inner.strip_suffix(']')) {
+        Some(inner) => inner.split(',')
+            .map(|item| unquote(item.trim()))
+            .filter(|item| !item.is_empty())
+            .collect(),
+        None if value.is_empty() => Vec::new(),
+        None => vec![unquote(value)],
+    }
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    value.strip_prefix('"').and_then(|inner| inner.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|inner| inner.strip_suffix('\'')))
+        .unwrap_or(value)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARKDOWN: &str = "---\ntitle: Guide\nauthor: Ann\n---\n\n# Intro\n\nSome *text* with a [link](http://x.y).\n\n\
+- [x] done\n- open\n  - nested\n\n```rust\nfn main() {}\n```\n\n| A | B |\n|---|---|\n| 1 | 2 |\n\n> quoted\n";
+
+    #[test]
+    fn front_matter_and_blocks_flatten_in_order() {
+        assert_eq!(parse_markdown_str(MARKDOWN).to_elements(), vec![
+            DocumentElement::Title { text: "Guide".to_string() },
+            DocumentElement::Author { text: "Ann".to_string() },
+            DocumentElement::Heading { level: 1, text: "Intro".to_string() },
+            DocumentElement::Paragraph { text: "Some text with a link.".to_string() },
+            DocumentElement::Link { text: "link".to_string(), url: "http://x.y".to_string() },
+            DocumentElement::List { items: vec!["[x] done".into(), "open".into(), "nested".into()], ordered: false },
+            DocumentElement::Code { code: "fn main() {}\n".to_string(), language: Some("rust".to_string()), inline: false },
+            DocumentElement::Table { headers: vec!["A".into(), "B".into()], rows: vec![vec!["1".into(), "2".into()]] },
+            DocumentElement::Blockquote { text: "quoted".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn nested_lists_stay_inside_their_item() {
+        let document = parse_markdown_str(MARKDOWN);
+        let [Node::Section { heading, children, .. }] = document.nodes.as_slice() else {
+            panic!("expected one section, got {:?}", document.nodes);
+        };
+        assert_eq!(heading, "Intro");
+
+        let items = children.iter().find_map(|node| match node {
+            Node::List { items, .. } => Some(items),
+            _ => None,
+        }).unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(matches!(items[1].children.as_slice(), [Node::List { items, .. }] if items[0].text == "nested"));
+    }
+
+    #[test]
+    fn latin1_files_decode_like_latin1_bytes() {
+        let markdown = b"# Caf\xe9\n\nCr\xe8me br\xfbl\xe9e.\n";
+        let path = std::env::temp_dir().join(format!("oxidoc-latin1-{}.md", std::process::id()));
+        fs::write(&path, markdown).unwrap();
+
+        let warnings = std::cell::RefCell::new(0);
+        let sink = |event: ParseEvent| if let ParseEvent::Warning { .. } = event {
+            *warnings.borrow_mut() += 1;
+        };
+        let from_path = MarkdownParser.parse_path(&path, &sink);
+        fs::remove_file(&path).unwrap();
+        let from_bytes = MarkdownParser.parse_bytes(markdown, &sink).unwrap();
+
+        // Only the filename title differs
+        assert_eq!(from_path.unwrap()[1..], from_bytes[..]);
+        assert_eq!(*warnings.borrow(), 2);
+    }
+}
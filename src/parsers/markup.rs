@@ -0,0 +1,237 @@
+// markup.rs
+
+// Pieces shared by the lightweight markup parsers (reStructuredText, AsciiDoc, Org):
+// indented blocks, inline emphasis, tables, quotes and admonitions, list items.
+
+use crate::parsers::documents_types::DocumentElement;
+
+// Admonition kinds the three languages have in common, lowercase
+pub const ADMONITIONS: &[&str] = &[
+    "note", "tip", "hint", "important", "warning", "caution", "danger", "attention", "error", "seealso", "todo",
+];
+
+// Characters after which an emphasis marker may open, and before which it may close
+const OPEN_BEFORE: &str = " \t-({['\"<";
+const CLOSE_AFTER: &str = " \t-.,;:!?')]}\"\\>";
+
+// Replace tabs with spaces up to the next multiple of 8 columns, so indentation can be compared
+pub fn expand_tabs(text: &str) -> String {
+    if !text.contains('\t') {
+        return text.to_string();
+    }
+
+    let mut expanded = String::with_capacity(text.len());
+    let mut column = 0;
+    for c in text.chars() {
+        match c {
+            '\t' => {
+                let width = 8 - column % 8;
+                expanded.extend(std::iter::repeat_n(' ', width));
+                column += width;
+            },
+            '\n' => {
+                expanded.push(c);
+                column = 0;
+            },
+            _ => {
+                expanded.push(c);
+                column += 1;
+            },
+        }
+    }
+    expanded
+}
+
+pub fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+// Number of leading spaces (tabs are expanded beforehand)
+pub fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+// Remove the indentation shared by every non-blank line, and the blank lines around the block
+pub fn dedent<'a>(lines: &[&'a str]) -> Vec<&'a str> {
+    let indent = lines.iter()
+        .filter(|line| !is_blank(line))
+        .map(|line| indentation(line))
+        .min()
+        .unwrap_or(0);
+
+    let start = lines.iter().position(|line| !is_blank(line)).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|line| !is_blank(line)).map_or(start, |last| last + 1);
+    lines[start..end].iter()
+        .map(|line| line.get(indent..).unwrap_or(""))
+        .collect()
+}
+
+// Join wrapped lines into one line of text
+pub fn join_lines<S: AsRef<str>>(lines: &[S]) -> String {
+    lines.iter()
+        .map(|line| line.as_ref().trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Remove emphasis markers around a word or phrase, such as "*bold*" or "=code=" (longest markers first).
+// A marker only opens at the start or after a space or opening punctuation, before a non-space,
+// and only closes after a non-space, at the end or before a space or punctuation.
+pub fn strip_emphasis(text: &str, markers: &[&str]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+
+    'scan: while i < chars.len() {
+        if i == 0 || OPEN_BEFORE.contains(chars[i - 1]) {
+            for marker in markers {
+                let marker: Vec<char> = marker.chars().collect();
+                let start = i + marker.len();
+                if !chars[i..].starts_with(&marker) || chars.get(start).is_none_or(|c| c.is_whitespace()) {
+                    continue;
+                }
+
+                for end in start + 1..=chars.len().saturating_sub(marker.len()) {
+                    if chars[end..].starts_with(&marker)
+                        && !chars[end - 1].is_whitespace()
+                        && chars.get(end + marker.len()).is_none_or(|c| CLOSE_AFTER.contains(*c)) {
+                        let inner: String = chars[start..end].iter().collect();
+                        result.push_str(&strip_emphasis(&inner, markers));
+                        i = end + marker.len();
+                        continue 'scan;
+                    }
+                }
+            }
+        }
+        result.push(chars[i]);
+        i += 1;
+    }
+    result
+}
+
+// Build a table from rows of cell texts; the first `header_rows` rows are merged into the headers
+pub fn table(mut rows: Vec<Vec<String>>, header_rows: usize) -> Option<DocumentElement> {
+    if rows.is_empty() {
+        return None;
+    }
+
+    let body = rows.split_off(header_rows.min(rows.len()));
+    let mut headers: Vec<String> = Vec::new();
+    for row in rows {
+        for (column, cell) in row.into_iter().enumerate() {
+            match headers.get_mut(column) {
+                Some(header) if !cell.is_empty() => {
+                    if !header.is_empty() {
+                        header.push(' ');
+                    }
+                    header.push_str(&cell);
+                },
+                Some(_) => {},
+                None => headers.push(cell),
+            }
+        }
+    }
+
+    Some(DocumentElement::Table { headers, rows: body })
+}
+
+// How an admonition kind is written in front of its text: "note" -> "Note", "seealso" -> "See also"
+pub fn admonition_label(kind: &str) -> String {
+    let kind = kind.to_lowercase();
+    if kind == "seealso" {
+        return "See also".to_string();
+    }
+    let mut chars = kind.chars();
+    chars.next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+// Turn the blocks of a quote or an admonition into one Blockquote ("Note: ..." when labelled).
+// Links and images found inside follow the quote.
+pub fn push_quote(label: Option<&str>, elements: Vec<DocumentElement>, out: &mut Vec<DocumentElement>) {
+    let text = elements_text(&elements);
+    let text = match label {
+        Some(label) if text.is_empty() => label.to_string(),
+        Some(label) => format!("{}: {}", label, text),
+        None => text,
+    };
+    if !text.is_empty() {
+        out.push(DocumentElement::Blockquote { text });
+    }
+    out.extend(elements.into_iter()
+        .filter(|element| matches!(element, DocumentElement::Link { .. } | DocumentElement::Image { .. })));
+}
+
+// Text of the blocks parsed from a nested block, one block per line
+pub fn elements_text(elements: &[DocumentElement]) -> String {
+    elements.iter()
+        .filter_map(|element| match element {
+            DocumentElement::Heading { text, .. }
+            | DocumentElement::Paragraph { text }
+            | DocumentElement::Blockquote { text } => Some(text.clone()),
+            DocumentElement::List { items, .. } => Some(items.join("\n")),
+            DocumentElement::Code { code, .. } => Some(code.clone()),
+            DocumentElement::Math { tex, .. } => Some(tex.clone()),
+            DocumentElement::Table { headers, rows } => Some(
+                std::iter::once(headers)
+                    .chain(rows)
+                    .filter(|row| !row.is_empty())
+                    .map(|row| row.join(" | "))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            _ => None,
+        })
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Fold the blocks parsed from one list item into the list: its paragraphs make the item text,
+// nested lists add their items after it, anything else (code, tables...) follows the list
+pub fn push_list_item(elements: Vec<DocumentElement>, items: &mut Vec<String>, trailing: &mut Vec<DocumentElement>) {
+    let mut text = String::new();
+    let mut nested = Vec::new();
+    for element in elements {
+        match element {
+            DocumentElement::Paragraph { text: paragraph } => {
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(&paragraph);
+            },
+            DocumentElement::List { items: nested_items, .. } => nested.extend(nested_items),
+            element => trailing.push(element),
+        }
+    }
+
+    if !text.is_empty() {
+        items.push(text);
+    }
+    items.extend(nested);
+}
+
+// Split a line of comma-separated values, with "..." quoting ("" inside quotes is a quote).
+// A quote may also open a value after "=", as in attribute lists: cols="1,2".
+pub fn split_csv(line: &str, separator: char) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            },
+            '"' if quoted || cell.trim().is_empty() || cell.ends_with('=') => quoted = !quoted,
+            c if c == separator && !quoted => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
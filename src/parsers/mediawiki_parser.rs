@@ -0,0 +1,1173 @@
+// mediawiki_parser.rs
+
+// MediaWiki XML dumps (pages-articles.xml...) and bare wikitext (.wiki). Dumps are read as a stream,
+// one <page> at a time, so files of many gigabytes never sit in memory: every page is introduced by
+// a WikiPage marker carrying its title, namespace and revision ID, followed by its title (Title) and
+// revision timestamp (ModificationDate) and its text. Redirects and pages that are not wikitext
+// (CSS, JavaScript, Lua modules...) are skipped; full-history dumps keep the last revision only.
+// In the wikitext, "== headings ==" become headings, "*" and "#" lists Lists, "{| ... |}" tables
+// Tables, <pre>, <syntaxhighlight> and space-indented lines Code, <math> on its own line Math and
+// <blockquote> Blockquotes. Templates, references, comments and category links are dropped,
+// internal links are replaced by their text, external links and [[File:...]] images follow their
+// block as Links and Images.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read};
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::{EventSink, ParseEvent};
+use crate::parsers::documents_types::{fallback_title, Document, DocumentElement};
+use crate::parsers::markup::{elements_text, is_blank, join_lines, push_quote, table};
+use crate::parsers::ooxml::{attribute, child, children, is_named};
+use crate::parsers::parser::Parser;
+use crate::parsers::txt_parser::decode_text;
+
+// Number of leading bytes searched for the <mediawiki> root element
+const MAGIC_LEN: u64 = 1024;
+
+// Tags whose content is not wikitext, or is removed altogether
+const PROTECTED_TAGS: &[&str] = &[
+    "nowiki", "pre", "syntaxhighlight", "source", "math", "blockquote", "gallery", "ref", "references",
+    "includeonly",
+];
+
+// [[File:...]] options that are not the caption
+const IMAGE_OPTIONS: &[&str] = &[
+    "thumb", "thumbnail", "frame", "framed", "frameless", "border", "left", "right", "center", "centre",
+    "none", "upright", "baseline", "middle", "sub", "super", "top", "text-top", "bottom", "text-bottom",
+];
+
+// Wikipedia language editions: [[de:Artikel]] with one of these prefixes is an interlanguage link
+const LANGUAGE_CODES: &[&str] = &[
+    "aa", "ab", "ace", "ady", "af", "ak", "als", "alt", "am", "ami", "an", "ang", "anp", "ar", "arc", "ary",
+    "arz", "as", "ast", "atj", "av", "avk", "awa", "ay", "az", "azb", "ba", "ban", "bar", "bat-smg", "bbc",
+    "bcl", "be", "be-tarask", "be-x-old", "bew", "bg", "bh", "bi", "bjn", "blk", "bm", "bn", "bo", "bpy",
+    "br", "bs", "btm", "bug", "bxr", "ca", "cbk-zam", "cdo", "ce", "ceb", "ch", "cho", "chr", "chy", "ckb",
+    "co", "cr", "crh", "cs", "csb", "cu", "cv", "cy", "da", "dag", "de", "dga", "din", "diq", "dsb", "dtp",
+    "dty", "dv", "dz", "ee", "el", "eml", "en", "eo", "es", "et", "eu", "ext", "fa", "fat", "ff", "fi",
+    "fiu-vro", "fj", "fo", "fon", "fr", "frp", "frr", "fur", "fy", "ga", "gag", "gan", "gcr", "gd", "gl",
+    "glk", "gn", "gom", "gor", "got", "gpe", "gu", "guc", "gur", "guw", "gv", "ha", "hak", "haw", "he", "hi",
+    "hif", "ho", "hr", "hsb", "ht", "hu", "hy", "hyw", "hz", "ia", "iba", "id", "ie", "ig", "igl", "ii", "ik",
+    "ilo", "inh", "io", "is", "it", "iu", "ja", "jam", "jbo", "jv", "ka", "kaa", "kab", "kbd", "kbp", "kcg",
+    "kg", "kge", "ki", "kj", "kk", "kl", "km", "kn", "knc", "ko", "koi", "kr", "krc", "ks", "ksh", "ku",
+    "kus", "kv", "kw", "ky", "la", "lad", "lb", "lbe", "lez", "lfn", "lg", "li", "lij", "lld", "lmo", "ln",
+    "lo", "lrc", "lt", "ltg", "lv", "lzh", "mad", "mai", "map-bms", "mdf", "mg", "mh", "mhr", "mi", "min",
+    "mk", "ml", "mn", "mni", "mnw", "mo", "mos", "mr", "mrj", "ms", "mt", "mus", "mwl", "my", "myv", "mzn",
+    "na", "nah", "nan", "nap", "nds", "nds-nl", "ne", "new", "ng", "nia", "nl", "nn", "no", "nov", "nqo",
+    "nr", "nrm", "nso", "nup", "nv", "ny", "oc", "olo", "om", "or", "os", "pa", "pag", "pam", "pap", "pcd",
+    "pcm", "pdc", "pfl", "pi", "pih", "pl", "pms", "pnb", "pnt", "ps", "pt", "pwn", "qu", "rm", "rmy", "rn",
+    "ro", "roa-rup", "roa-tara", "rsk", "ru", "rue", "rup", "rw", "sa", "sah", "sat", "sc", "scn", "sco",
+    "sd", "se", "sg", "sgs", "sh", "shi", "shn", "shy", "si", "simple", "sk", "skr", "sl", "sm", "smn", "sn",
+    "so", "sq", "sr", "srn", "ss", "st", "stq", "su", "sv", "sw", "syl", "szl", "szy", "ta", "tay", "tcy",
+    "tdd", "te", "tet", "tg", "th", "ti", "tig", "tk", "tl", "tly", "tn", "to", "tpi", "tr", "trv", "ts",
+    "tt", "tum", "tw", "ty", "tyv", "udm", "ug", "uk", "ur", "uz", "ve", "vec", "vep", "vi", "vls", "vo",
+    "vro", "wa", "war", "wo", "wuu", "xal", "xh", "xmf", "yi", "yo", "yue", "za", "zea", "zgh", "zh",
+    "zh-classical", "zh-min-nan", "zh-yue", "zu",
+];
+
+// Main parsing function
+pub fn parse_mediawiki_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    parse_mediawiki_path(path, &())
+}
+
+fn parse_mediawiki_path(path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    // Open the dump or wikitext file (a missing file is an error)
+    let file = File::open(path).map_err(|source| OxidocError::io(path, source))?;
+
+    let mut elements = Vec::new();
+    MediaWikiParser.parse_records(&mut BufReader::new(file), events, &mut |page| {
+        elements.extend(page);
+        Ok(())
+    }).map_err(|error| error.with_path(path))?;
+
+    fallback_title(&mut elements, path);
+
+    Ok(elements)
+}
+
+// Stream the pages of an XML dump, handing the elements of each page to `page` as soon as it is read
+pub fn parse_mediawiki_dump(
+    reader: &mut dyn Read,
+    events: &dyn EventSink,
+    page: &mut dyn FnMut(Vec<DocumentElement>) -> Result<()>,
+) -> Result<()> {
+    let mut dump = DumpReader { reader: BufReader::new(reader), buffer: Vec::new() };
+    let mut namespaces = Namespaces::default();
+
+    while let Some(element) = dump.next_element()? {
+        let xml = String::from_utf8_lossy(&element);
+        let document = match roxmltree::Document::parse(&xml) {
+            Ok(document) => document,
+            Err(error) => {
+                events.emit(ParseEvent::Warning { path: None, message: format!("malformed dump page skipped: {}", error) });
+                continue;
+            },
+        };
+
+        let root = document.root_element();
+        if is_named(root, "siteinfo") {
+            namespaces = Namespaces::from_siteinfo(root);
+        } else if let Some(elements) = page_elements(root, &namespaces) {
+            page(elements)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Parse bare wikitext into the hierarchical document tree
+pub fn parse_wikitext_str(source: &str) -> Document {
+    Document::from_elements(wikitext_elements(source, &Namespaces::default()))
+}
+
+// MediaWiki implementation of the common Parser trait
+pub struct MediaWikiParser;
+
+impl Parser for MediaWikiParser {
+    fn name(&self) -> &str {
+        "mediawiki"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["wiki", "mediawiki", "wikitext"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["text/x-wiki"]
+    }
+
+    // The <mediawiki> root element of a dump, after the XML declaration
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        is_dump(bytes)
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        if is_dump(bytes) {
+            let mut elements = Vec::new();
+            parse_mediawiki_dump(&mut Cursor::new(bytes), events, &mut |page| {
+                elements.extend(page);
+                Ok(())
+            })?;
+            Ok(elements)
+        } else {
+            let (source, _) = decode_text(bytes);
+            Ok(parse_wikitext_str(&source).to_elements())
+        }
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_mediawiki_path(path, events)
+    }
+
+    // Dumps are streamed page by page; bare wikitext is a single record
+    fn parse_records(
+        &self,
+        reader: &mut dyn Read,
+        events: &dyn EventSink,
+        record: &mut dyn FnMut(Vec<DocumentElement>) -> Result<()>,
+    ) -> Result<()> {
+        let mut prefix = Vec::new();
+        reader.take(MAGIC_LEN).read_to_end(&mut prefix)?;
+
+        if is_dump(&prefix) {
+            parse_mediawiki_dump(&mut Cursor::new(prefix).chain(reader), events, record)
+        } else {
+            let mut bytes = prefix;
+            reader.read_to_end(&mut bytes)?;
+            record(self.parse_bytes(&bytes, events)?)
+        }
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to the XML dump
+//-------------------------------------------------
+
+fn is_dump(bytes: &[u8]) -> bool {
+    let prefix = &bytes[..bytes.len().min(MAGIC_LEN as usize)];
+    find(prefix, b"<mediawiki").is_some()
+}
+
+// Cuts the <siteinfo> and <page> elements out of a dump as they stream by. Text inside a page is
+// escaped, so the closing tags cannot appear in it and the elements can be found without parsing
+// the whole document.
+struct DumpReader<R> {
+    reader: R,
+    // Bytes read but not handed out yet
+    buffer: Vec<u8>,
+}
+
+impl<R: BufRead> DumpReader<R> {
+    // Next <siteinfo> or <page> element, whole; None at the end of the dump
+    fn next_element(&mut self) -> Result<Option<Vec<u8>>> {
+        let closing: &[u8] = loop {
+            let page = find(&self.buffer, b"<page>");
+            let siteinfo = find(&self.buffer, b"<siteinfo>");
+            let (start, closing): (usize, &[u8]) = match (page, siteinfo) {
+                (Some(page), Some(siteinfo)) if siteinfo < page => (siteinfo, b"</siteinfo>"),
+                (Some(page), _) => (page, b"</page>"),
+                (None, Some(siteinfo)) => (siteinfo, b"</siteinfo>"),
+                (None, None) => {
+                    // Keep the end of the buffer, it may hold the beginning of a tag
+                    let keep = self.buffer.len().saturating_sub(b"<siteinfo>".len());
+                    self.buffer.drain(..keep);
+                    if self.fill()? == 0 {
+                        return Ok(None);
+                    }
+                    continue;
+                },
+            };
+            self.buffer.drain(..start);
+            break closing;
+        };
+
+        let mut searched = 0;
+        // Start of the last <revision> seen, whose predecessors can be dropped
+        let mut revision: Option<usize> = None;
+        loop {
+            let rest = &self.buffer[searched..];
+            let end = find(rest, closing).map(|offset| searched + offset);
+            let next_revision = find(rest, b"<revision>").map(|offset| searched + offset);
+
+            match (end, next_revision) {
+                (Some(end), next_revision) if next_revision.is_none_or(|next_revision| end < next_revision) => {
+                    let end = end + closing.len();
+                    let element = self.buffer[..end].to_vec();
+                    self.buffer.drain(..end);
+                    return Ok(Some(element));
+                },
+                (_, Some(next_revision)) => {
+                    // Full-history dumps hold every revision of a page; only the last one is kept
+                    let start = match revision {
+                        Some(previous) => {
+                            self.buffer.drain(previous..next_revision);
+                            previous
+                        },
+                        None => next_revision,
+                    };
+                    revision = Some(start);
+                    searched = start + b"<revision>".len();
+                },
+                _ => {
+                    searched = self.buffer.len().saturating_sub(closing.len().max(b"<revision>".len()));
+                    if self.fill()? == 0 {
+                        return Err(OxidocError::malformed("MediaWiki dump ends inside a page"));
+                    }
+                },
+            }
+        }
+    }
+
+    // Append the next chunk of the stream to the buffer, returning its length (0 at the end)
+    fn fill(&mut self) -> Result<usize> {
+        let chunk = self.reader.fill_buf()?;
+        let length = chunk.len();
+        self.buffer.extend_from_slice(chunk);
+        self.reader.consume(length);
+        Ok(length)
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+// Localized names of the namespaces links are told apart by, lowercase
+struct Namespaces {
+    files: Vec<String>,
+    categories: Vec<String>,
+}
+
+impl Default for Namespaces {
+    fn default() -> Namespaces {
+        Namespaces {
+            files: vec!["file".to_string(), "image".to_string()],
+            categories: vec!["category".to_string()],
+        }
+    }
+}
+
+impl Namespaces {
+    // <namespace key="14" case="first-letter">Kategorie</namespace>, besides the English names
+    fn from_siteinfo(siteinfo: roxmltree::Node) -> Namespaces {
+        let mut namespaces = Namespaces::default();
+        let declared = child(siteinfo, "namespaces").into_iter().flat_map(|node| children(node, "namespace"));
+        for namespace in declared {
+            let name = namespace.text().unwrap_or_default().trim().to_lowercase();
+            match attribute(namespace, "key") {
+                Some("6") if !name.is_empty() => namespaces.files.push(name),
+                Some("14") if !name.is_empty() => namespaces.categories.push(name),
+                _ => {},
+            }
+        }
+        namespaces
+    }
+}
+
+// Marker, metadata and body of a <page>; None for redirects and pages that are not wikitext
+fn page_elements(page: roxmltree::Node, namespaces: &Namespaces) -> Option<Vec<DocumentElement>> {
+    if child(page, "redirect").is_some() {
+        return None;
+    }
+
+    let title = child_text(page, "title").unwrap_or_default().to_string();
+    let namespace = child_text(page, "ns").and_then(|ns| ns.parse().ok()).unwrap_or(0);
+    let revision = children(page, "revision").last()?;
+    if child_text(revision, "model").is_some_and(|model| model != "wikitext") {
+        return None;
+    }
+
+    let mut elements = vec![
+        DocumentElement::WikiPage {
+            title: title.clone(),
+            namespace,
+            revision_id: child_text(revision, "id").and_then(|id| id.parse().ok()),
+        },
+        DocumentElement::Title { text: title },
+    ];
+    if let Some(timestamp) = child_text(revision, "timestamp") {
+        elements.push(DocumentElement::ModificationDate { text: timestamp.to_string() });
+    }
+    // Deleted revisions have an empty <text deleted="deleted"/>
+    if let Some(text) = child(revision, "text").and_then(|node| node.text()) {
+        elements.extend(wikitext_elements(text, namespaces));
+    }
+    Some(elements)
+}
+
+fn child_text<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name).and_then(|node| node.text()).map(str::trim)
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to wikitext blocks
+//-------------------------------------------------
+
+// Content kept out of the wikitext while templates and markup are removed
+enum Protected {
+    // <nowiki>: shown as written
+    Text(String),
+    // <pre>, <syntaxhighlight lang="...">, <source>
+    Code { code: String, language: Option<String> },
+    // <math>, and whether it is displayed as a block
+    Math { tex: String, block: bool },
+    // <blockquote>: wikitext of its own
+    Quote(String),
+    // <gallery>: "File:Name.jpg|caption" lines
+    Gallery(String),
+}
+
+fn wikitext_elements(source: &str, namespaces: &Namespaces) -> Vec<DocumentElement> {
+    let source = source.replace("\r\n", "\n").replace([PLACEHOLDER_START, PLACEHOLDER_END], "");
+    let source = remove_comments(&source);
+    let (source, protected) = protect_tags(&source);
+    let source = strip_templates(&source);
+    let source = remove_magic_words(&source);
+
+    let reader = Wikitext { namespaces, protected };
+    let lines: Vec<&str> = source.lines().collect();
+    reader.blocks(&lines)
+}
+
+struct Wikitext<'a> {
+    namespaces: &'a Namespaces,
+    protected: Vec<Protected>,
+}
+
+impl Wikitext<'_> {
+    fn blocks(&self, lines: &[&str]) -> Vec<DocumentElement> {
+        let mut elements = Vec::new();
+        let mut paragraph: Vec<&str> = Vec::new();
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i];
+            let trimmed = line.trim();
+
+            if is_blank(line) || is_rule(trimmed) {
+                self.push_paragraph(&mut paragraph, &mut elements);
+                i += 1;
+            } else if let Some((level, text)) = heading(trimmed) {
+                self.push_paragraph(&mut paragraph, &mut elements);
+                let mut links = Vec::new();
+                let text = self.text(text, &mut links);
+                if !text.is_empty() {
+                    elements.push(DocumentElement::Heading { level, text });
+                }
+                elements.extend(links);
+                i += 1;
+            } else if trimmed.starts_with("{|") {
+                self.push_paragraph(&mut paragraph, &mut elements);
+                let end = table_end(lines, i);
+                self.table(&lines[i..end], &mut elements);
+                i = end;
+            } else if let Some(index) = self.block_placeholder(trimmed.trim_start_matches(':')) {
+                self.push_paragraph(&mut paragraph, &mut elements);
+                self.protected_block(index, &mut elements);
+                i += 1;
+            } else if line.starts_with(['*', '#', ';', ':']) {
+                self.push_paragraph(&mut paragraph, &mut elements);
+                let end = list_end(lines, i);
+                self.list(&lines[i..end], &mut elements);
+                i = end;
+            } else if line.starts_with(' ') {
+                // Lines indented by a space are preformatted
+                self.push_paragraph(&mut paragraph, &mut elements);
+                let end = lines[i..].iter()
+                    .position(|line| !line.starts_with(' ') || is_blank(line))
+                    .map_or(lines.len(), |offset| i + offset);
+                let mut links = Vec::new();
+                let code: Vec<String> = lines[i..end].iter().map(|line| self.inline(&line[1..], &mut links)).collect();
+                elements.push(DocumentElement::Code { code: code.join("\n"), language: None, inline: false });
+                elements.extend(links);
+                i = end;
+            } else {
+                paragraph.push(line);
+                i += 1;
+            }
+        }
+
+        self.push_paragraph(&mut paragraph, &mut elements);
+        elements
+    }
+
+    fn push_paragraph(&self, lines: &mut Vec<&str>, elements: &mut Vec<DocumentElement>) {
+        if lines.is_empty() {
+            return;
+        }
+        let mut links = Vec::new();
+        let text = self.text(&join_lines(lines), &mut links);
+        if !text.is_empty() {
+            elements.push(DocumentElement::Paragraph { text });
+        }
+        elements.extend(links);
+        lines.clear();
+    }
+
+    // Index of the <pre>, <syntaxhighlight>, <blockquote>, <gallery> or display <math> a line is made of
+    fn block_placeholder(&self, line: &str) -> Option<usize> {
+        let index = line.trim().strip_prefix(PLACEHOLDER_START)?.strip_suffix(PLACEHOLDER_END)?.parse().ok()?;
+        match self.protected.get(index)? {
+            Protected::Text(_) => None,
+            Protected::Math { .. } | Protected::Code { .. } | Protected::Quote(_) | Protected::Gallery(_) => Some(index),
+        }
+    }
+
+    fn protected_block(&self, index: usize, elements: &mut Vec<DocumentElement>) {
+        match &self.protected[index] {
+            Protected::Text(_) => {},
+            Protected::Code { code, language } => {
+                let code = code.trim_matches('\n').to_string();
+                elements.push(DocumentElement::Code { code, language: language.clone(), inline: false });
+            },
+            Protected::Math { tex, .. } => {
+                elements.push(DocumentElement::Math { tex: tex.trim().to_string(), environment: None });
+            },
+            Protected::Quote(wikitext) => push_quote(None, wikitext_elements(wikitext, self.namespaces), elements),
+            Protected::Gallery(gallery) => elements.extend(self.gallery(gallery)),
+        }
+    }
+
+    fn gallery(&self, gallery: &str) -> Vec<DocumentElement> {
+        gallery.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (file, caption) = line.split_once('|').unwrap_or((line, ""));
+                let file = file.split_once(':').map_or(file, |(_, name)| name).trim();
+                let mut links = Vec::new();
+                let caption = self.text(caption, &mut links);
+                let alt = if caption.is_empty() { file.to_string() } else { caption };
+                DocumentElement::Image { alt, url: Some(file.to_string()) }
+            })
+            .collect()
+    }
+
+    // "*" and "#" items (nested ones flattened into the list) and "; term : definition" items
+    // (as "term: definition"); lines indented with ":" alone are paragraphs
+    fn list(&self, lines: &[&str], elements: &mut Vec<DocumentElement>) {
+        let ordered = lines[0].starts_with('#');
+        let mut items: Vec<String> = Vec::new();
+        let mut links = Vec::new();
+        // Whether the last term still waits for its definition
+        let mut open_term = false;
+
+        for line in lines {
+            let markers = line.len() - line.trim_start_matches(['*', '#', ';', ':']).len();
+            let (prefix, content) = line.split_at(markers);
+
+            if prefix.starts_with(';') {
+                let (term, definition) = split_top_level(content, ':').unwrap_or((content, ""));
+                let term = self.text(term, &mut links);
+                let definition = self.text(definition, &mut links);
+                open_term = definition.is_empty();
+                items.push(match open_term {
+                    true => term,
+                    false => format!("{}: {}", term, definition),
+                });
+                continue;
+            }
+
+            let text = self.text(content, &mut links);
+            if text.is_empty() {
+                continue;
+            }
+            match items.last_mut() {
+                // The definition of a term, or an item continued on an indented line ("#:")
+                Some(item) if prefix.ends_with(':') => {
+                    item.push_str(if open_term { ": " } else { " " });
+                    item.push_str(&text);
+                    open_term = false;
+                },
+                None if prefix.ends_with(':') => elements.push(DocumentElement::Paragraph { text }),
+                _ => items.push(text),
+            }
+        }
+
+        if !items.is_empty() {
+            elements.push(DocumentElement::List { items, ordered });
+        }
+        elements.extend(links);
+    }
+
+    // "{|" ... "|}": "|+" caption, "|-" rows, "!" header cells and "|" data cells ("!!" and "||"
+    // separate cells on one line, "attributes | content" gives the content). A first row of header
+    // cells becomes the headers; nested tables follow the table.
+    fn table(&self, lines: &[&str], elements: &mut Vec<DocumentElement>) {
+        let mut rows: Vec<Vec<(bool, String)>> = vec![Vec::new()];
+        let mut nested = Vec::new();
+        let mut links = Vec::new();
+        let mut i = 1;
+
+        while i < lines.len() {
+            let line = lines[i].trim();
+            if line.starts_with("{|") {
+                let end = table_end(lines, i);
+                self.table(&lines[i..end], &mut nested);
+                i = end;
+                continue;
+            }
+            i += 1;
+
+            if line.starts_with("|}") {
+                break;
+            } else if let Some(caption) = line.strip_prefix("|+") {
+                let caption = self.text(cell_content(caption), &mut links);
+                if !caption.is_empty() {
+                    elements.push(DocumentElement::Paragraph { text: caption });
+                }
+            } else if line.starts_with("|-") {
+                rows.push(Vec::new());
+            } else if let Some(cells) = line.strip_prefix('!') {
+                let row = rows.last_mut().expect("rows start non-empty");
+                row.extend(split_cells(cells, true).into_iter().map(|cell| (true, cell.to_string())));
+            } else if let Some(cells) = line.strip_prefix('|') {
+                let row = rows.last_mut().expect("rows start non-empty");
+                row.extend(split_cells(cells, false).into_iter().map(|cell| (false, cell.to_string())));
+            } else if let Some((_, cell)) = rows.last_mut().and_then(|row| row.last_mut()) {
+                // A cell continued on the next lines
+                cell.push('\n');
+                cell.push_str(line);
+            }
+        }
+
+        rows.retain(|row| !row.is_empty());
+        let header_rows = usize::from(rows.first().is_some_and(|row| row.iter().all(|(header, _)| *header)));
+        let rows: Vec<Vec<String>> = rows.into_iter()
+            .map(|row| row.into_iter().map(|(_, cell)| self.text(cell_content(&cell), &mut links)).collect())
+            .collect();
+
+        elements.extend(table(rows, header_rows));
+        elements.extend(nested);
+        elements.extend(links);
+    }
+}
+
+// "== Heading ==" (level 2); unbalanced markers give the smaller level
+fn heading(line: &str) -> Option<(u8, &str)> {
+    let opening = line.len() - line.trim_start_matches('=').len();
+    let closing = line.len() - line.trim_end_matches('=').len();
+    if opening == 0 || closing == 0 || opening + closing >= line.len() {
+        return None;
+    }
+    let level = opening.min(closing).min(6);
+    let text = line[level..line.len() - level].trim();
+    (!text.is_empty()).then_some((level as u8, text))
+}
+
+fn is_rule(line: &str) -> bool {
+    line.len() >= 4 && line.chars().all(|c| c == '-')
+}
+
+// Index of the line after the "|}" closing the table opened at `start`
+fn table_end(lines: &[&str], start: usize) -> usize {
+    let mut depth = 0;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        let line = line.trim_start();
+        if line.starts_with("{|") {
+            depth += 1;
+        } else if line.starts_with("|}") {
+            depth -= 1;
+            if depth == 0 {
+                return i + 1;
+            }
+        }
+    }
+    lines.len()
+}
+
+// Index of the line after a list: "*" lists, "#" lists and ";" / ":" lists end where another kind starts
+fn list_end(lines: &[&str], start: usize) -> usize {
+    let kind = |line: &str| match line.chars().next() {
+        Some('*') => Some('*'),
+        Some('#') => Some('#'),
+        Some(';') | Some(':') => Some(';'),
+        _ => None,
+    };
+    let first = kind(lines[start]);
+    lines[start..].iter()
+        .position(|line| kind(line) != first)
+        .map_or(lines.len(), |offset| start + offset)
+}
+
+// Cells of a table line, split on "||" (and "!!" on header lines) outside links
+fn split_cells(line: &str, header: bool) -> Vec<&str> {
+    let mut cells = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let bytes = line.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match &bytes[i..] {
+            [b'[', b'[', ..] => { depth += 1; i += 2; },
+            [b']', b']', ..] => { depth -= 1; i += 2; },
+            [b'|', b'|', ..] | [b'!', b'!', ..] if depth <= 0 && (bytes[i] == b'|' || header) => {
+                cells.push(&line[start..i]);
+                i += 2;
+                start = i;
+            },
+            _ => i += 1,
+        }
+    }
+    cells.push(&line[start..]);
+    cells
+}
+
+// Content of a cell without its attributes: "style="..." | content" -> "content"
+fn cell_content(cell: &str) -> &str {
+    match split_top_level(cell, '|') {
+        Some((attributes, content)) if !attributes.contains("[[") => content,
+        _ => cell,
+    }
+}
+
+// Split at the first `separator` outside [[links]] and [external links]
+fn split_top_level(text: &str, separator: char) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            c if c == separator && depth <= 0 => return Some((&text[..i], &text[i + c.len_utf8()..])),
+            _ => {},
+        }
+    }
+    None
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to removed markup
+//-------------------------------------------------
+
+// Protected content is replaced by its index between these private-use characters
+const PLACEHOLDER_START: char = '\u{E000}';
+const PLACEHOLDER_END: char = '\u{E001}';
+
+fn remove_comments(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("<!--") {
+        result.push_str(&rest[..start]);
+        rest = match rest[start..].find("-->") {
+            Some(end) => &rest[start + end + 3..],
+            None => "",
+        };
+    }
+    result.push_str(rest);
+    result
+}
+
+// Replace <nowiki>, <pre>, <math>... by placeholders so templates and markup inside them survive,
+// and drop <ref>, <references> and <includeonly> with their content
+fn protect_tags(text: &str) -> (String, Vec<Protected>) {
+    let lowercase = text.to_ascii_lowercase();
+    let mut result = String::with_capacity(text.len());
+    let mut protected = Vec::new();
+    let mut i = 0;
+
+    while let Some(offset) = lowercase[i..].find('<') {
+        let start = i + offset;
+        result.push_str(&text[i..start]);
+        i = start + 1;
+
+        let name: String = lowercase[start + 1..].chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+        let after_name = start + 1 + name.len();
+        if !PROTECTED_TAGS.contains(&name.as_str())
+            || !lowercase[after_name..].starts_with(['>', '/', ' ', '\t', '\n']) {
+            result.push('<');
+            continue;
+        }
+        let Some(open_end) = lowercase[after_name..].find('>').map(|offset| after_name + offset) else {
+            result.push('<');
+            continue;
+        };
+
+        let attributes = &text[after_name..open_end];
+        let (content, end) = if attributes.trim_end().ends_with('/') {
+            ("", open_end + 1)
+        } else {
+            let closing = format!("</{}", name);
+            match lowercase[open_end..].find(&closing) {
+                Some(offset) => {
+                    let close_start = open_end + offset;
+                    let close_end = lowercase[close_start..].find('>').map_or(lowercase.len(), |offset| close_start + offset + 1);
+                    (&text[open_end + 1..close_start], close_end)
+                },
+                // Unclosed <ref> swallow the rest of the page, anything else is shown as written
+                None if name == "ref" => (&text[open_end + 1..], text.len()),
+                None => {
+                    result.push('<');
+                    continue;
+                },
+            }
+        };
+        i = end;
+
+        let content = content.to_string();
+        let kept = match name.as_str() {
+            "nowiki" => Protected::Text(content),
+            "pre" => Protected::Code { code: content, language: None },
+            "syntaxhighlight" | "source" => Protected::Code { code: content, language: attribute_value(attributes, "lang") },
+            "math" => Protected::Math { tex: content, block: attribute_value(attributes, "display").as_deref() == Some("block") },
+            "blockquote" => Protected::Quote(content),
+            "gallery" => Protected::Gallery(content),
+            _ => continue,
+        };
+        // Block content on a line of its own stays on a line of its own
+        let block = matches!(kept, Protected::Code { .. } | Protected::Quote(_) | Protected::Gallery(_) | Protected::Math { block: true, .. });
+        if block && !result.ends_with('\n') && !result.is_empty() {
+            result.push('\n');
+        }
+        result.push(PLACEHOLDER_START);
+        result.push_str(&protected.len().to_string());
+        result.push(PLACEHOLDER_END);
+        if block {
+            result.push('\n');
+        }
+        protected.push(kept);
+    }
+
+    result.push_str(&text[i..]);
+    (result, protected)
+}
+
+// lang="python" or lang=python
+fn attribute_value(attributes: &str, name: &str) -> Option<String> {
+    let lowercase = attributes.to_ascii_lowercase();
+    let start = lowercase.find(&format!("{}=", name))? + name.len() + 1;
+    let value = attributes[start..].trim_start_matches(['"', '\'']);
+    let value: String = value.chars().take_while(|c| !matches!(c, '"' | '\'' | ' ' | '/' | '>')).collect();
+    (!value.is_empty()).then(|| value.to_lowercase())
+}
+
+// Remove {{templates}}, {{#parser functions:...}} and {{{parameters}}}, however deeply nested.
+// An unclosed template is kept as written.
+fn strip_templates(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut depth = 0;
+    let mut outermost = 0;
+    let mut i = 0;
+
+    while i < text.len() {
+        let rest = &text[i..];
+        if rest.starts_with("{{") {
+            if depth == 0 {
+                outermost = i;
+            }
+            depth += 1;
+            i += 2;
+        } else if depth > 0 && rest.starts_with("}}") {
+            depth -= 1;
+            i += 2;
+            // The last brace of a {{{parameter}}}
+            if depth == 0 && text[i..].starts_with('}') && text[outermost..].starts_with("{{{") {
+                i += 1;
+            }
+        } else {
+            let c = rest.chars().next().expect("not at the end");
+            if depth == 0 {
+                result.push(c);
+            }
+            i += c.len_utf8();
+        }
+    }
+
+    if depth > 0 {
+        result.push_str(&text[outermost..]);
+    }
+    result
+}
+
+// __TOC__, __NOTOC__ and the other behavior switches
+fn remove_magic_words(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("__") {
+        let word = &rest[start + 2..];
+        let length = word.chars().take_while(|c| c.is_ascii_uppercase()).count();
+        if length > 0 && word[length..].starts_with("__") {
+            result.push_str(&rest[..start]);
+            rest = &word[length + 2..];
+        } else {
+            result.push_str(&rest[..start + 2]);
+            rest = word;
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to inline markup
+//-------------------------------------------------
+
+impl Wikitext<'_> {
+    // Plain text of a line, on one line
+    fn text(&self, text: &str, links: &mut Vec<DocumentElement>) -> String {
+        self.inline(text, links).split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    // Links replaced by their text, bold and italics, HTML tags and entities removed;
+    // external links and images are added to `links`
+    fn inline(&self, text: &str, links: &mut Vec<DocumentElement>) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut i = 0;
+
+        while i < text.len() {
+            let rest = &text[i..];
+
+            // [[internal links]], [[File:...]] images and [[Category:...]] links
+            if let Some(inner) = rest.strip_prefix("[[")
+                && let Some(end) = link_end(inner) {
+                result.push_str(&self.internal_link(&inner[..end], links));
+                i += end + 4;
+                continue;
+            }
+
+            // [https://example.com text]
+            if let Some(inner) = rest.strip_prefix('[')
+                && is_url(inner)
+                && let Some(end) = inner.find(']') {
+                let (url, shown) = inner[..end].split_once(' ').unwrap_or((&inner[..end], ""));
+                let shown = self.text(shown, &mut Vec::new());
+                links.push(DocumentElement::Link {
+                    text: if shown.is_empty() { url.to_string() } else { shown.clone() },
+                    url: url.to_string(),
+                });
+                result.push_str(&shown);
+                i += end + 2;
+                continue;
+            }
+
+            let c = rest.chars().next().expect("not at the end");
+            result.push(c);
+            i += c.len_utf8();
+        }
+
+        let result = strip_quotes(&result);
+        let result = strip_html_tags(&result);
+        let result = decode_entities(&result);
+        self.restore(&result, links)
+    }
+
+    // Text shown for [[target|text]]; images and categories show nothing
+    fn internal_link(&self, inner: &str, links: &mut Vec<DocumentElement>) -> String {
+        let (target, shown) = match split_top_level(inner, '|') {
+            Some((target, shown)) => (target.trim(), Some(shown)),
+            None => (inner.trim(), None),
+        };
+        let visible = target.starts_with(':');
+        let target = target.trim_start_matches(':').trim();
+        let (prefix, name) = target.split_once(':').unwrap_or(("", target));
+        let prefix = prefix.trim().replace('_', " ").to_lowercase();
+
+        if !visible && self.namespaces.categories.contains(&prefix) {
+            return String::new();
+        }
+        if !visible && self.namespaces.files.contains(&prefix) {
+            links.push(self.image(name.trim(), shown.unwrap_or("")));
+            return String::new();
+        }
+        // Interlanguage links such as [[de:Artikel]] at the end of a page
+        if !visible && shown.is_none() && is_language_code(&prefix) {
+            return String::new();
+        }
+
+        match shown {
+            // Pipe trick: [[Paris (France)|]] shows "Paris"
+            Some(shown) if shown.trim().is_empty() => {
+                let name = if prefix.is_empty() { target } else { name };
+                name.split(" (").next().unwrap_or(name).split(',').next().unwrap_or(name).trim().to_string()
+            },
+            Some(shown) => self.inline(shown, links),
+            None => target.trim_start_matches('#').to_string(),
+        }
+    }
+
+    // [[File:Name.jpg|thumb|200px|alt=...|Caption]]: the caption (or the alt text) describes the image
+    fn image(&self, file: &str, options: &str) -> DocumentElement {
+        let mut caption = String::new();
+        let mut alt = String::new();
+        let mut rest = options;
+        while !rest.is_empty() {
+            let (option, next) = split_top_level(rest, '|').unwrap_or((rest, ""));
+            rest = next;
+            let option = option.trim();
+            if let Some(text) = option.strip_prefix("alt=") {
+                alt = text.to_string();
+            } else if !is_image_option(option) {
+                caption = option.to_string();
+            }
+        }
+
+        let description = if caption.is_empty() { alt } else { caption };
+        let alt = self.text(&description, &mut Vec::new());
+        DocumentElement::Image {
+            alt: if alt.is_empty() { file.to_string() } else { alt },
+            url: Some(file.to_string()),
+        }
+    }
+
+    // Put protected content back: <nowiki> as written, <math> as TeX, block content as its text
+    fn restore(&self, text: &str, links: &mut Vec<DocumentElement>) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find(PLACEHOLDER_START) {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + PLACEHOLDER_START.len_utf8()..];
+            let Some(end) = after.find(PLACEHOLDER_END) else {
+                rest = after;
+                continue;
+            };
+            match after[..end].parse().ok().and_then(|index: usize| self.protected.get(index)) {
+                Some(Protected::Text(text)) => result.push_str(&decode_entities(text)),
+                Some(Protected::Code { code, .. }) => result.push_str(code),
+                Some(Protected::Math { tex, .. }) => result.push_str(tex.trim()),
+                Some(Protected::Quote(wikitext)) => result.push_str(&elements_text(&wikitext_elements(wikitext, self.namespaces))),
+                Some(Protected::Gallery(gallery)) => links.extend(self.gallery(gallery)),
+                None => {},
+            }
+            rest = &after[end + PLACEHOLDER_END.len_utf8()..];
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+// Length of a link's content up to its closing "]]", links inside image captions included
+fn link_end(inner: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = 0;
+    while i < inner.len() {
+        let rest = &inner[i..];
+        if rest.starts_with("[[") {
+            depth += 1;
+            i += 2;
+        } else if rest.starts_with("]]") {
+            if depth == 0 {
+                return Some(i);
+            }
+            depth -= 1;
+            i += 2;
+        } else if rest.starts_with('\n') && rest[1..].starts_with('\n') {
+            // Links do not span paragraphs
+            return None;
+        } else {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    None
+}
+
+fn is_url(text: &str) -> bool {
+    ["http://", "https://", "ftp://", "//", "mailto:"].iter().any(|scheme| text.starts_with(scheme))
+}
+
+// "de", "fr", "zh-yue", "simple"; "help" or "portal" are namespaces, not languages
+fn is_language_code(prefix: &str) -> bool {
+    LANGUAGE_CODES.contains(&prefix)
+}
+
+fn is_image_option(option: &str) -> bool {
+    let size = option.strip_suffix("px")
+        .is_some_and(|size| !size.is_empty() && size.chars().all(|c| c.is_ascii_digit() || c == 'x'));
+    let setting = ["link=", "page=", "class=", "lang=", "upright=", "border"].iter().any(|name| option.starts_with(name));
+    size || setting || IMAGE_OPTIONS.contains(&option)
+}
+
+// Remove ''italic'', '''bold''' and '''''both''''' markers ('''' is an apostrophe then bold)
+fn strip_quotes(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\'' {
+            result.push(c);
+            continue;
+        }
+        let mut count = 1;
+        while chars.next_if_eq(&'\'').is_some() {
+            count += 1;
+        }
+        let apostrophes = match count {
+            1 => 1,
+            2 | 3 | 5 => 0,
+            4 => 1,
+            _ => count - 5,
+        };
+        result.extend(std::iter::repeat_n('\'', apostrophes));
+    }
+    result
+}
+
+// Drop HTML tags (<span ...>, </div>, <br />) but keep their content; a lone "<" stays
+fn strip_html_tags(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        result.push_str(&rest[..start]);
+        let tag = &rest[start + 1..];
+        let is_tag = tag.trim_start_matches('/').starts_with(|c: char| c.is_ascii_alphabetic());
+        match tag.find(['>', '<']) {
+            Some(end) if is_tag && tag[end..].starts_with('>') => {
+                if tag[..end].to_ascii_lowercase().starts_with("br") {
+                    result.push(' ');
+                }
+                rest = &tag[end + 1..];
+            },
+            _ => {
+                result.push('<');
+                rest = tag;
+            },
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+// Character references left in the wikitext (the dump's own XML escaping is already undone)
+fn decode_entities(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let entity = &rest[start + 1..];
+        let decoded = entity.find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| decode_entity(&entity[..end]).map(|decoded| (decoded, end)));
+        match decoded {
+            Some((decoded, end)) => {
+                result.push_str(&decoded);
+                rest = &entity[end + 1..];
+            },
+            None => {
+                result.push('&');
+                rest = entity;
+            },
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+// Decoded entity; invisible direction and joiner marks decode to nothing
+fn decode_entity(name: &str) -> Option<String> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code).map(String::from);
+    }
+    let decoded = match name {
+        "amp" => "&",
+        "lt" => "<",
+        "gt" => ">",
+        "quot" => "\"",
+        "apos" => "'",
+        "nbsp" | "thinsp" | "ensp" | "emsp" => " ",
+        "ndash" => "–",
+        "mdash" => "—",
+        "minus" => "−",
+        "hellip" => "…",
+        "times" => "×",
+        "deg" => "°",
+        "middot" => "·",
+        "laquo" => "«",
+        "raquo" => "»",
+        "zwj" | "zwnj" | "lrm" | "rlm" | "shy" => "",
+        _ => return None,
+    };
+    Some(decoded.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paragraphs(source: &str) -> Vec<String> {
+        parse_wikitext_str(source).to_elements().into_iter()
+            .filter_map(|element| match element {
+                DocumentElement::Paragraph { text } => Some(text),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn namespace_links_keep_their_target() {
+        assert_eq!(paragraphs("See [[Help:Editing]] and [[Portal:Science]]."), vec!["See Help:Editing and Portal:Science."]);
+    }
+
+    #[test]
+    fn interlanguage_and_category_links_are_dropped() {
+        let source = "Text [[Paris (France)|]] [[de:Paris]] [[zh-yue:巴黎]] [[Category:Cities]]";
+        assert_eq!(paragraphs(source), vec!["Text Paris"]);
+    }
+
+    #[test]
+    fn dump_pages_stream_with_their_markers() {
+        let dump = r#"<mediawiki>
+<siteinfo><namespaces><namespace key="14">Kategorie</namespace></namespaces></siteinfo>
+<page><title>Erste</title><ns>0</ns><revision><id>1</id><text>Alt</text></revision>
+<revision><id>2</id><text>Neu [[Kategorie:Test]]</text></revision></page>
+<page><title>Umleitung</title><ns>0</ns><redirect title="Erste" /><revision><id>3</id><text>#REDIRECT [[Erste]]</text></revision></page>
+<page><title>Hilfe:Zweite</title><ns>12</ns><revision><id>4</id><text>Zwei</text></revision></page>
+</mediawiki>"#;
+
+        let mut pages = Vec::new();
+        parse_mediawiki_dump(&mut dump.as_bytes(), &(), &mut |page| {
+            pages.push(page);
+            Ok(())
+        }).unwrap();
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0][0], DocumentElement::WikiPage { title: "Erste".to_string(), namespace: 0, revision_id: Some(2) });
+        assert!(pages[0].contains(&DocumentElement::Paragraph { text: "Neu".to_string() }));
+        assert!(matches!(&pages[1][0], DocumentElement::WikiPage { namespace: 12, revision_id: Some(4), .. }));
+    }
+}
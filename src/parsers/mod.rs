@@ -1,3 +1,26 @@
+pub mod asciidoc_parser;
+pub mod code_parser;
 pub mod documents_types;
+pub mod docx_parser;
+pub mod email_parser;
+pub mod epub_parser;
 pub mod html_parser;
-pub mod pdf_parser;
\ No newline at end of file
+pub mod ipynb_parser;
+pub mod latex_parser;
+pub mod markdown_parser;
+pub mod markup;
+pub mod mediawiki_parser;
+pub mod odt_parser;
+pub mod ooxml;
+pub mod org_parser;
+pub mod parser;
+pub mod pdf_layout;
+pub mod pdf_parser;
+pub mod pptx_parser;
+pub mod registry;
+pub mod rst_parser;
+pub mod rtf_parser;
+pub mod subtitle_parser;
+pub mod txt_parser;
+pub mod warc_parser;
+pub mod xlsx_parser;
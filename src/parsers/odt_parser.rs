@@ -0,0 +1,542 @@
+// odt_parser.rs
+
+use roxmltree::Node;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::EventSink;
+use crate::parsers::documents_types::{fallback_title, DocumentElement};
+use crate::parsers::ooxml::{attribute, child, children, is_named, normalize_date, open_package, parse_xml, read_part, Package};
+use crate::parsers::parser::Parser;
+
+const CONTENT_PART: &str = "content.xml";
+// Styles are looked up through at most this many parent styles
+const STYLE_MAX_DEPTH: usize = 10;
+
+// Main parsing function
+pub fn parse_odt_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    // Read the ODT file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+
+    let mut elements = parse_odt_bytes(&bytes).map_err(|error| error.with_path(path))?;
+
+    fallback_title(&mut elements, path);
+
+    Ok(elements)
+}
+
+// Parse an in-memory ODT: meta.xml properties first, then the text body in reading order
+pub fn parse_odt_bytes(bytes: &[u8]) -> Result<Vec<DocumentElement>> {
+    let mut package = open_package(bytes)?;
+
+    let xml = read_part(&mut package, CONTENT_PART)?
+        .ok_or_else(|| OxidocError::malformed(format!("ODT has no {}", CONTENT_PART)))?;
+    let content = parse_xml(&xml, CONTENT_PART)?;
+    let body = child(content.root_element(), "body")
+        .and_then(|body| child(body, "text"))
+        .ok_or_else(|| OxidocError::malformed("ODT document has no text body"))?;
+
+    let mut context = OdtContext { styles: HashMap::new(), list_styles: HashMap::new() };
+    // Named styles live in styles.xml, automatic (per-document) styles in both parts
+    if let Some(styles_xml) = read_part(&mut package, "styles.xml")? {
+        context.load_styles(parse_xml(&styles_xml, "styles.xml")?.root_element());
+    }
+    context.load_styles(content.root_element());
+
+    let mut elements = odf_metadata(&mut package)?;
+    let mut body_elements = Vec::new();
+    extract_blocks(body, &context, &mut body_elements);
+
+    // A paragraph styled "Title" is the document title unless meta.xml already has one
+    let has_title = elements.iter().any(|element| matches!(element, DocumentElement::Title { .. }));
+    for element in body_elements {
+        match element {
+            DocumentElement::Title { text } if has_title => elements.push(DocumentElement::Heading { level: 1, text }),
+            DocumentElement::Title { text } => elements.insert(0, DocumentElement::Title { text }),
+            other => elements.push(other),
+        }
+    }
+
+    Ok(elements)
+}
+
+// Title, author, dates... from the "meta.xml" part every OpenDocument package carries
+// (text documents, spreadsheets and presentations alike)
+pub fn odf_metadata(package: &mut Package) -> Result<Vec<DocumentElement>> {
+    let mut metadata = Vec::new();
+    let Some(xml) = read_part(package, "meta.xml")? else {
+        return Ok(metadata);
+    };
+    let document = parse_xml(&xml, "meta.xml")?;
+    let Some(meta) = child(document.root_element(), "meta") else {
+        return Ok(metadata);
+    };
+
+    let values = |name: &str| -> Vec<String> {
+        children(meta, name)
+            .filter_map(|node| node.text())
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect()
+    };
+    let property = |name: &str| values(name).into_iter().next();
+
+    if let Some(title) = property("title") {
+        metadata.push(DocumentElement::Title { text: title });
+    }
+    if let Some(author) = property("initial-creator").or_else(|| property("creator")) {
+        metadata.push(DocumentElement::Author { text: author });
+    }
+    if let Some(description) = property("description").or_else(|| property("subject")) {
+        metadata.push(DocumentElement::Description { text: description });
+    }
+    let keywords = values("keyword");
+    if !keywords.is_empty() {
+        metadata.push(DocumentElement::Keywords { text: keywords.join(", ") });
+    }
+    if let Some(language) = property("language") {
+        metadata.push(DocumentElement::Language { text: language });
+    }
+    if let Some(generator) = property("generator") {
+        metadata.push(DocumentElement::Creator { text: generator });
+    }
+    if let Some(created) = property("creation-date") {
+        metadata.push(DocumentElement::CreationDate { text: normalize_date(&created) });
+    }
+    // dc:date is the date of the last modification
+    if let Some(modified) = property("date") {
+        metadata.push(DocumentElement::ModificationDate { text: normalize_date(&modified) });
+    }
+
+    Ok(metadata)
+}
+
+// ODT implementation of the common Parser trait
+pub struct OdtParser;
+
+impl Parser for OdtParser {
+    fn name(&self) -> &str {
+        "odt"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["odt", "ott"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/vnd.oasis.opendocument.text"]
+    }
+
+    // The first zip entry is the uncompressed "mimetype" file
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(b"PK\x03\x04")
+            && bytes.get(30..38) == Some(b"mimetype".as_slice())
+            && bytes[38..].starts_with(b"application/vnd.oasis.opendocument.text")
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], _events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_odt_bytes(bytes)
+    }
+
+    fn parse_path(&self, path: &Path, _events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_odt_file(path)
+    }
+}
+
+// Styles needed to interpret the body
+struct OdtContext {
+    styles: HashMap<String, Style>,
+    // List style name -> whether each level (from 1) is numbered
+    list_styles: HashMap<String, Vec<bool>>,
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to body content
+//-------------------------------------------------
+
+// What a paragraph style makes of its paragraphs
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ParagraphKind {
+    Title,
+    Quote,
+    Code,
+    Normal,
+}
+
+// Walk the block-level content of the body (or of a section or text box) in order
+fn extract_blocks(parent: Node, context: &OdtContext, elements: &mut Vec<DocumentElement>) {
+    for node in parent.children().filter(|node| node.is_element()) {
+        match node.tag_name().name() {
+            "h" => {
+                let mut extras = Vec::new();
+                let text = inline_text(node, context, &mut extras);
+                if !text.trim().is_empty() {
+                    let level = attribute(node, "outline-level").and_then(|level| level.parse::<u8>().ok()).unwrap_or(1);
+                    elements.push(DocumentElement::Heading { level: level.clamp(1, 6), text: text.trim().to_string() });
+                }
+                elements.extend(extras);
+            },
+            "p" => extract_paragraph(node, context, elements),
+            "list" => {
+                let mut extras = Vec::new();
+                extract_list(node, attribute(node, "style-name"), 1, context, elements, &mut extras);
+                elements.extend(extras);
+            },
+            "table" => elements.push(extract_table(node, context)),
+            "section" => extract_blocks(node, context, elements),
+            "frame" => elements.extend(extract_frame(node, context)),
+            // Generated indexes (table-of-content, alphabetical-index...) repeat the headings
+            _ => {},
+        }
+    }
+}
+
+fn extract_paragraph(paragraph: Node, context: &OdtContext, elements: &mut Vec<DocumentElement>) {
+    let mut extras = Vec::new();
+    let text = inline_text(paragraph, context, &mut extras);
+    let kind = attribute(paragraph, "style-name").map_or(ParagraphKind::Normal, |style| context.paragraph_kind(style));
+
+    if !text.trim().is_empty() {
+        match kind {
+            ParagraphKind::Title => elements.push(DocumentElement::Title { text: text.trim().to_string() }),
+            ParagraphKind::Quote => elements.push(DocumentElement::Blockquote { text: text.trim().to_string() }),
+            ParagraphKind::Code => {
+                // Code is written one paragraph per line: consecutive lines form one block
+                match elements.last_mut() {
+                    Some(DocumentElement::Code { code, inline: false, .. }) => {
+                        code.push('\n');
+                        code.push_str(&text);
+                    },
+                    _ => elements.push(DocumentElement::Code { code: text, language: None, inline: false }),
+                }
+            },
+            ParagraphKind::Normal => elements.push(DocumentElement::Paragraph { text: text.trim().to_string() }),
+        }
+    }
+
+    // Links, images and text boxes follow the paragraph holding them
+    elements.extend(extras);
+}
+
+// Text of a paragraph. Links, images and text boxes met on the way are added to `extras`.
+// Whitespace in the XML collapses; <text:s/>, <text:tab/> and <text:line-break/> are the real ones.
+fn inline_text(node: Node, context: &OdtContext, extras: &mut Vec<DocumentElement>) -> String {
+    let mut text = String::new();
+
+    for child in node.children() {
+        if child.is_text() {
+            let content = child.text().unwrap_or("");
+            let mut previous_space = text.ends_with(' ');
+            for character in content.chars() {
+                if character.is_whitespace() {
+                    if !previous_space {
+                        text.push(' ');
+                    }
+                    previous_space = true;
+                } else {
+                    text.push(character);
+                    previous_space = false;
+                }
+            }
+            continue;
+        }
+        if !child.is_element() {
+            continue;
+        }
+
+        match child.tag_name().name() {
+            "s" => {
+                let count = attribute(child, "c").and_then(|count| count.parse::<usize>().ok()).unwrap_or(1);
+                text.push_str(&" ".repeat(count));
+            },
+            "tab" => text.push('\t'),
+            "line-break" => text.push('\n'),
+            "a" => {
+                let link_text = inline_text(child, context, extras);
+                if let Some(url) = attribute(child, "href").filter(|url| !url.is_empty())
+                    && !link_text.trim().is_empty() {
+                    extras.push(DocumentElement::Link { text: link_text.trim().to_string(), url: url.to_string() });
+                }
+                text.push_str(&link_text);
+            },
+            "frame" => extras.extend(extract_frame(child, context)),
+            // Footnotes, comments and tracked deletions are not part of the running text
+            "note" | "annotation" | "annotation-end" | "tracked-changes" => {},
+            _ => text.push_str(&inline_text(child, context, extras)),
+        }
+    }
+
+    text
+}
+
+// Items of a list and of the lists nested in it, in document order. Headings inside lists
+// (outline numbering) stay headings.
+fn extract_list(
+    list: Node,
+    style: Option<&str>,
+    level: usize,
+    context: &OdtContext,
+    elements: &mut Vec<DocumentElement>,
+    extras: &mut Vec<DocumentElement>,
+) {
+    let ordered = context.list_is_ordered(style, level);
+
+    for item in list.children().filter(|node| is_named(*node, "list-item") || is_named(*node, "list-header")) {
+        let mut item_text = Vec::new();
+        for block in item.children().filter(|node| node.is_element()) {
+            match block.tag_name().name() {
+                "p" => {
+                    let text = inline_text(block, context, extras);
+                    if !text.trim().is_empty() {
+                        item_text.push(text.trim().to_string());
+                    }
+                },
+                "h" => {
+                    flush_list_item(elements, &mut item_text, ordered);
+                    let text = inline_text(block, context, extras);
+                    if !text.trim().is_empty() {
+                        let level = attribute(block, "outline-level").and_then(|level| level.parse::<u8>().ok()).unwrap_or(1);
+                        elements.push(DocumentElement::Heading { level: level.clamp(1, 6), text: text.trim().to_string() });
+                    }
+                },
+                "list" => {
+                    flush_list_item(elements, &mut item_text, ordered);
+                    let nested_style = attribute(block, "style-name").or(style);
+                    extract_list(block, nested_style, level + 1, context, elements, extras);
+                },
+                "table" => {
+                    flush_list_item(elements, &mut item_text, ordered);
+                    elements.push(extract_table(block, context));
+                },
+                _ => {},
+            }
+        }
+        flush_list_item(elements, &mut item_text, ordered);
+    }
+}
+
+// Consecutive items of the same kind form one list, whatever their level
+fn flush_list_item(elements: &mut Vec<DocumentElement>, item_text: &mut Vec<String>, ordered: bool) {
+    if item_text.is_empty() {
+        return;
+    }
+    let text = item_text.join(" ");
+    item_text.clear();
+
+    match elements.last_mut() {
+        Some(DocumentElement::List { items, ordered: list_ordered }) if *list_ordered == ordered => items.push(text),
+        _ => elements.push(DocumentElement::List { items: vec![text], ordered }),
+    }
+}
+
+// Header rows become the headers, otherwise the first row does
+fn extract_table(table: Node, context: &OdtContext) -> DocumentElement {
+    let mut headers = Vec::new();
+    let mut rows = Vec::new();
+    collect_rows(table, context, false, &mut headers, &mut rows);
+
+    if headers.is_empty() && !rows.is_empty() {
+        headers = rows.remove(0);
+    }
+
+    DocumentElement::Table { headers, rows }
+}
+
+// Rows can be grouped in header, body and row groups
+fn collect_rows(parent: Node, context: &OdtContext, header: bool, headers: &mut Vec<String>, rows: &mut Vec<Vec<String>>) {
+    for node in parent.children().filter(|node| node.is_element()) {
+        match node.tag_name().name() {
+            "table-header-rows" => collect_rows(node, context, true, headers, rows),
+            "table-rows" | "table-row-group" => collect_rows(node, context, header, headers, rows),
+            "table-row" => {
+                // Cells merged into their neighbour (covered-table-cell) are skipped
+                let cells: Vec<String> = children(node, "table-cell")
+                    .map(|cell| {
+                        let mut extras = Vec::new();
+                        cell.descendants()
+                            .filter(|node| is_named(*node, "p") || is_named(*node, "h"))
+                            .map(|paragraph| inline_text(paragraph, context, &mut extras).trim().to_string())
+                            .filter(|text| !text.is_empty())
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    })
+                    .collect();
+                if header && headers.is_empty() {
+                    headers.extend(cells);
+                } else {
+                    rows.push(cells);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+// Frames hold an image (its description is the alt text) or a text box with its own paragraphs
+fn extract_frame(frame: Node, context: &OdtContext) -> Vec<DocumentElement> {
+    let mut elements = Vec::new();
+
+    if let Some(image) = child(frame, "image") {
+        let alt = child(frame, "desc")
+            .or_else(|| child(frame, "title"))
+            .and_then(|node| node.text())
+            .or_else(|| attribute(frame, "name"))
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        let url = attribute(image, "href").filter(|url| !url.is_empty()).map(|url| url.to_string());
+        if !alt.is_empty() || url.is_some() {
+            elements.push(DocumentElement::Image { alt, url });
+        }
+    }
+    if let Some(text_box) = child(frame, "text-box") {
+        extract_blocks(text_box, context, &mut elements);
+    }
+
+    elements
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to styles
+//-------------------------------------------------
+
+struct Style {
+    // Display name, falling back to the internal name, lowercased ("heading 1", "quotations")
+    name: String,
+    parent: Option<String>,
+}
+
+impl OdtContext {
+    fn load_styles(&mut self, root: Node) {
+        for node in root.descendants() {
+            if is_named(node, "style") {
+                let Some(name) = attribute(node, "name") else {
+                    continue;
+                };
+                let display_name = attribute(node, "display-name").unwrap_or(name);
+                self.styles.insert(name.to_string(), Style {
+                    name: display_name.replace("_20_", " ").to_lowercase(),
+                    parent: attribute(node, "parent-style-name").map(|parent| parent.to_string()),
+                });
+            } else if is_named(node, "list-style")
+                && let Some(name) = attribute(node, "name") {
+                // One level style per nesting level, numbered or bulleted
+                let mut levels = Vec::new();
+                for level_style in node.children().filter(|child| child.is_element()) {
+                    let level = attribute(level_style, "level").and_then(|level| level.parse::<usize>().ok()).unwrap_or(levels.len() + 1);
+                    if level > levels.len() {
+                        levels.resize(level, false);
+                    }
+                    levels[level - 1] = level_style.tag_name().name() == "list-level-style-number";
+                }
+                self.list_styles.insert(name.to_string(), levels);
+            }
+        }
+    }
+
+    // Follow a style and its parents until one of them says what the paragraph is
+    fn paragraph_kind(&self, style_name: &str) -> ParagraphKind {
+        let mut current = Some(style_name);
+
+        for _ in 0..STYLE_MAX_DEPTH {
+            let Some(name) = current else {
+                break;
+            };
+            let display_name = self.styles.get(name).map_or_else(|| name.replace("_20_", " ").to_lowercase(), |style| style.name.clone());
+
+            if let Some(kind) = style_name_kind(&display_name) {
+                return kind;
+            }
+            current = self.styles.get(name).and_then(|style| style.parent.as_deref());
+        }
+
+        ParagraphKind::Normal
+    }
+
+    fn list_is_ordered(&self, style: Option<&str>, level: usize) -> bool {
+        style.and_then(|style| self.list_styles.get(style))
+            .and_then(|levels| levels.get(level - 1))
+            .copied()
+            .unwrap_or(false)
+    }
+}
+
+fn style_name_kind(name: &str) -> Option<ParagraphKind> {
+    let compact: String = name.chars().filter(|c| !c.is_whitespace()).collect();
+
+    if compact == "title" {
+        return Some(ParagraphKind::Title);
+    }
+    if compact == "quotations" || compact == "quote" || compact == "blocktext" {
+        return Some(ParagraphKind::Quote);
+    }
+    if compact == "preformattedtext" || compact == "sourcetext" || compact.contains("code") {
+        return Some(ParagraphKind::Code);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    fn odt(content: &str, meta: &str) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, part) in [("mimetype", "application/vnd.oasis.opendocument.text"), ("content.xml", content), ("meta.xml", meta)] {
+            writer.start_file(name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(part.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    const META: &str = r#"<office:document-meta xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+        xmlns:meta="urn:oasis:names:tc:opendocument:xmlns:meta:1.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+        <office:meta><dc:title>Minutes</dc:title><meta:initial-creator>Clerk</meta:initial-creator></office:meta>
+    </office:document-meta>"#;
+
+    const CONTENT: &str = r#"<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+        xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0"
+        xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0">
+        <office:automatic-styles>
+            <style:style style:name="P1" style:family="paragraph" style:parent-style-name="Quotations"/>
+            <text:list-style style:name="L1"><text:list-level-style-number text:level="1"/><text:list-level-style-bullet text:level="2"/></text:list-style>
+        </office:automatic-styles>
+        <office:body><office:text>
+            <text:h text:outline-level="2">Attendance</text:h>
+            <text:p>Present:<text:s text:c="2"/>all   members.</text:p>
+            <text:p text:style-name="P1">Order, order.</text:p>
+            <text:list text:style-name="L1">
+                <text:list-item><text:p>Budget</text:p>
+                    <text:list><text:list-item><text:p>Roads</text:p></text:list-item></text:list>
+                </text:list-item>
+                <text:list-item><text:p>Schools</text:p></text:list-item>
+            </text:list>
+            <table:table>
+                <table:table-header-rows><table:table-row><table:table-cell><text:p>Item</text:p></table:table-cell><table:table-cell><text:p>Vote</text:p></table:table-cell></table:table-row></table:table-header-rows>
+                <table:table-row><table:table-cell><text:p>Budget</text:p></table:table-cell><table:table-cell><text:p>Yes</text:p></table:table-cell></table:table-row>
+            </table:table>
+        </office:text></office:body>
+    </office:document-content>"#;
+
+    #[test]
+    fn body_blocks_follow_the_metadata() {
+        let items = |items: &[&str]| items.iter().map(|item| item.to_string()).collect::<Vec<String>>();
+
+        assert_eq!(parse_odt_bytes(&odt(CONTENT, META)).unwrap(), vec![
+            DocumentElement::Title { text: "Minutes".to_string() },
+            DocumentElement::Author { text: "Clerk".to_string() },
+            DocumentElement::Heading { level: 2, text: "Attendance".to_string() },
+            DocumentElement::Paragraph { text: "Present:  all members.".to_string() },
+            DocumentElement::Blockquote { text: "Order, order.".to_string() },
+            DocumentElement::List { items: items(&["Budget"]), ordered: true },
+            DocumentElement::List { items: items(&["Roads"]), ordered: false },
+            DocumentElement::List { items: items(&["Schools"]), ordered: true },
+            DocumentElement::Table { headers: items(&["Item", "Vote"]), rows: vec![items(&["Budget", "Yes"])] },
+        ]);
+    }
+}
@@ -0,0 +1,204 @@
+// ooxml.rs
+
+// Pieces shared by the Office Open XML parsers: the zip package, XML parts,
+// relationships between parts, and the document properties. The zip and XML
+// helpers also serve the other zip-based formats (EPUB).
+
+use chrono::DateTime;
+use roxmltree::{Node, ParsingOptions};
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+use zip::result::ZipError;
+use crate::error::{OxidocError, Result};
+use crate::parsers::documents_types::DocumentElement;
+
+pub type Package<'a> = ZipArchive<Cursor<&'a [u8]>>;
+
+// A link from one part to another part, or to an external URL
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relationship {
+    // Part name inside the package ("word/media/image1.png"), or the URL when external
+    pub target: String,
+    pub external: bool,
+    // Type without its namespace URI: "slide", "notesSlide", "hyperlink", "image"...
+    pub kind: String,
+}
+
+pub fn open_package(bytes: &[u8]) -> Result<Package<'_>> {
+    Ok(ZipArchive::new(Cursor::new(bytes))?)
+}
+
+// Read a part as text, None when the package does not contain it
+pub fn read_part(package: &mut Package, name: &str) -> Result<Option<String>> {
+    let Some(bytes) = read_part_bytes(package, name)? else {
+        return Ok(None);
+    };
+    let text = String::from_utf8(bytes)
+        .map_err(|error| OxidocError::encoding(format!("{} is not valid UTF-8: {}", name, error)))?;
+    Ok(Some(text))
+}
+
+// Read a part as raw bytes, None when the package does not contain it
+pub fn read_part_bytes(package: &mut Package, name: &str) -> Result<Option<Vec<u8>>> {
+    let mut file = match package.by_name(name) {
+        Ok(file) => file,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(error) => return Err(error.into()),
+    };
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(Some(bytes))
+}
+
+pub fn parse_xml<'a>(text: &'a str, part: &str) -> Result<roxmltree::Document<'a>> {
+    let options = ParsingOptions { allow_dtd: true, ..ParsingOptions::default() };
+    roxmltree::Document::parse_with_options(text, options)
+        .map_err(|error| OxidocError::malformed(format!("{}: {}", part, error)))
+}
+
+// Relationships of a part, by id ("rId4"), from the part's "_rels/<name>.rels"
+pub fn relationships(package: &mut Package, part: &str) -> Result<HashMap<String, Relationship>> {
+    let (directory, file_name) = part.rsplit_once('/').unwrap_or(("", part));
+    let rels_part = if directory.is_empty() {
+        format!("_rels/{}.rels", file_name)
+    } else {
+        format!("{}/_rels/{}.rels", directory, file_name)
+    };
+
+    let Some(xml) = read_part(package, &rels_part)? else {
+        return Ok(HashMap::new());
+    };
+    let document = parse_xml(&xml, &rels_part)?;
+
+    let mut relationships = HashMap::new();
+    for node in document.descendants().filter(|node| is_named(*node, "Relationship")) {
+        let (Some(id), Some(target)) = (attribute(node, "Id"), attribute(node, "Target")) else {
+            continue;
+        };
+        let external = attribute(node, "TargetMode") == Some("External");
+        let target = if external { target.to_string() } else { resolve_part(directory, target) };
+        let kind = attribute(node, "Type").and_then(|kind| kind.rsplit('/').next()).unwrap_or("").to_string();
+        relationships.insert(id.to_string(), Relationship { target, external, kind });
+    }
+
+    Ok(relationships)
+}
+
+// Resolve a target relative to the directory of the part that references it
+pub fn resolve_part(directory: &str, target: &str) -> String {
+    let mut segments: Vec<&str> = match target.strip_prefix('/') {
+        Some(_) => Vec::new(),
+        None => directory.split('/').filter(|segment| !segment.is_empty()).collect(),
+    };
+
+    for segment in target.split('/') {
+        match segment {
+            "" | "." => {},
+            ".." => {
+                segments.pop();
+            },
+            _ => segments.push(segment),
+        }
+    }
+
+    segments.join("/")
+}
+
+// Title, author, dates... from "docProps/core.xml", and the authoring application from "docProps/app.xml"
+pub fn document_properties(package: &mut Package) -> Result<Vec<DocumentElement>> {
+    let mut metadata = Vec::new();
+
+    if let Some(xml) = read_part(package, "docProps/core.xml")? {
+        let document = parse_xml(&xml, "docProps/core.xml")?;
+        let root = document.root_element();
+        let property = |name: &str| -> Option<String> {
+            child(root, name)
+                .and_then(|node| node.text())
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty())
+        };
+
+        if let Some(title) = property("title") {
+            metadata.push(DocumentElement::Title { text: title });
+        }
+        if let Some(author) = property("creator") {
+            metadata.push(DocumentElement::Author { text: author });
+        }
+        if let Some(description) = property("description").or_else(|| property("subject")) {
+            metadata.push(DocumentElement::Description { text: description });
+        }
+        if let Some(keywords) = property("keywords") {
+            metadata.push(DocumentElement::Keywords { text: keywords });
+        }
+        if let Some(language) = property("language") {
+            metadata.push(DocumentElement::Language { text: language });
+        }
+        if let Some(created) = property("created") {
+            metadata.push(DocumentElement::CreationDate { text: normalize_date(&created) });
+        }
+        if let Some(modified) = property("modified") {
+            metadata.push(DocumentElement::ModificationDate { text: normalize_date(&modified) });
+        }
+    }
+
+    if let Some(xml) = read_part(package, "docProps/app.xml")? {
+        let document = parse_xml(&xml, "docProps/app.xml")?;
+        if let Some(application) = child(document.root_element(), "Application").and_then(|node| node.text())
+            && !application.trim().is_empty() {
+            metadata.push(DocumentElement::Creator { text: application.trim().to_string() });
+        }
+    }
+
+    Ok(metadata)
+}
+
+// W3C dates ("2024-01-31T10:00:00Z") as RFC 3339, like the other parsers; anything else is kept as is
+pub fn normalize_date(date: &str) -> String {
+    DateTime::parse_from_rfc3339(date)
+        .map(|date| date.to_rfc3339())
+        .unwrap_or_else(|_| date.to_string())
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to XML navigation
+//-------------------------------------------------
+
+// Element and attribute names are matched on their local part: every part uses
+// its own prefixes, and the namespaces themselves have several versions.
+
+pub fn is_named(node: Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+pub fn attribute<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|attribute| attribute.name() == name)
+        .map(|attribute| attribute.value())
+}
+
+// Relationship id attribute such as r:id="rId4" or r:embed="rId5". Other attributes
+// share the local name (<p:sldId id="256" r:id="rId2"/>), so the namespace decides.
+pub fn relationship_id<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|attribute| {
+            attribute.name() == name
+                && attribute.namespace().is_some_and(|namespace| namespace.ends_with("relationships"))
+        })
+        .map(|attribute| attribute.value())
+}
+
+pub fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| is_named(*child, name))
+}
+
+pub fn children<'a, 'input>(node: Node<'a, 'input>, name: &str) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(move |child| is_named(*child, name))
+}
+
+// Value of the "val" attribute of a property child, e.g. <w:pStyle w:val="Heading1"/>
+pub fn child_value<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name).and_then(|child| attribute(child, "val"))
+}
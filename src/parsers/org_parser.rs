@@ -0,0 +1,704 @@
+// org_parser.rs
+
+// Org mode. "#+TITLE:", "#+AUTHOR:", "#+DATE:" and the other export keywords give the metadata;
+// "*" headlines become headings without their TODO keyword, priority, statistics cookies and tags
+// (COMMENT subtrees are skipped). Source and example blocks and ":" fixed-width lines become Code,
+// "|" tables Tables, plain lists Lists (description items as "term: description"), quote blocks and
+// special blocks such as #+BEGIN_NOTE Blockquotes, LaTeX math environments Math, and Babel results
+// CodeOutput. Drawers, planning lines and comments are dropped; inline markup is removed and links
+// follow their block as Links.
+
+use std::fs;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::EventSink;
+use crate::parsers::documents_types::{fallback_title, Document, DocumentElement};
+use crate::parsers::html_parser::parse_html_str;
+use crate::parsers::markup::{
+    admonition_label, dedent, expand_tabs, indentation, is_blank, join_lines, push_list_item,
+    push_quote, strip_emphasis, table, ADMONITIONS,
+};
+use crate::parsers::parser::Parser;
+use crate::parsers::txt_parser::{decode_text, warn_if_latin1};
+
+// TODO keywords recognized in headlines, besides those declared by #+TODO lines
+const TODO_KEYWORDS: &[&str] = &["TODO", "DONE", "NEXT", "WAITING", "HOLD", "STARTED", "SOMEDAY", "CANCELLED", "CANCELED"];
+
+const MATH_ENVIRONMENTS: &[&str] = &[
+    "equation", "equation*", "align", "align*", "gather", "gather*", "multline", "multline*", "eqnarray",
+    "eqnarray*", "displaymath", "math",
+];
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "svg", "webp", "bmp", "tif", "tiff"];
+
+// Main parsing function
+pub fn parse_org_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    // Parse into the document tree, then flatten it in reading order
+    Ok(parse_org_document(path)?.to_elements())
+}
+
+// Parse an Org file into the hierarchical document tree
+pub fn parse_org_document(path: &Path) -> Result<Document> {
+    parse_org_path(path, &())
+}
+
+fn parse_org_path(path: &Path, events: &dyn EventSink) -> Result<Document> {
+    // Read the file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+    let (source, encoding) = decode_text(&bytes);
+    warn_if_latin1(encoding, Some(path), events);
+
+    let mut document = parse_org_str(&source);
+    fallback_title(&mut document.metadata, path);
+    Ok(document)
+}
+
+pub fn parse_org_str(source: &str) -> Document {
+    let source = expand_tabs(source);
+    let lines: Vec<&str> = source.lines().collect();
+
+    // "#+TODO: TODO(t) WAIT | DONE CANCELED" declares more keywords
+    let mut todo_keywords: Vec<String> = TODO_KEYWORDS.iter().map(|keyword| keyword.to_string()).collect();
+    for (key, value) in lines.iter().filter_map(|line| keyword(line.trim())) {
+        if matches!(key.to_lowercase().as_str(), "todo" | "seq_todo" | "typ_todo") {
+            todo_keywords.extend(value.split_whitespace()
+                .filter(|word| *word != "|")
+                .map(|word| word.split('(').next().unwrap_or(word).to_string()));
+        }
+    }
+
+    let mut reader = Reader { todo_keywords, metadata: Vec::new(), depth: 0 };
+    let elements = reader.blocks(&lines);
+
+    Document::from_elements(reader.metadata.into_iter().chain(elements).collect())
+}
+
+// Org implementation of the common Parser trait
+pub struct OrgParser;
+
+impl Parser for OrgParser {
+    fn name(&self) -> &str {
+        "org"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["org"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["text/org", "text/x-org"]
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        let (source, encoding) = decode_text(bytes);
+        warn_if_latin1(encoding, None, events);
+        Ok(parse_org_str(&source).to_elements())
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        Ok(parse_org_path(path, events)?.to_elements())
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to elements
+//-------------------------------------------------
+
+struct Reader {
+    todo_keywords: Vec<String>,
+    metadata: Vec<DocumentElement>,
+    // Nesting of the blocks being read (1 for the document itself): headlines only exist at the top
+    depth: usize,
+}
+
+impl Reader {
+    fn blocks(&mut self, lines: &[&str]) -> Vec<DocumentElement> {
+        self.depth += 1;
+        let mut out = Vec::new();
+        let mut caption: Option<String> = None;
+        // Whether the next block holds the results of a source block ("#+RESULTS:")
+        let mut results = false;
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i].trim_end();
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                i += 1;
+                continue;
+            }
+
+            if let Some(level) = self.headline_level(line) {
+                let (text, commented) = self.headline(&line[level..]);
+                if commented {
+                    // The whole subtree is commented out
+                    i = (i + 1..lines.len())
+                        .find(|&next| self.headline_level(lines[next]).is_some_and(|next_level| next_level <= level))
+                        .unwrap_or(lines.len());
+                    continue;
+                }
+                let mut links = Vec::new();
+                out.push(DocumentElement::Heading { level: level.min(6) as u8, text: inline(&text, &mut links) });
+                out.extend(links);
+                i += 1;
+                continue;
+            }
+
+            let lowercase = trimmed.to_lowercase();
+
+            // Greater blocks: #+BEGIN_SRC ... #+END_SRC
+            if let Some(rest) = lowercase.strip_prefix("#+begin_") {
+                let name = rest.split_whitespace().next().unwrap_or_default().to_string();
+                let arguments = trimmed.get("#+begin_".len() + name.len()..).unwrap_or_default().trim();
+                let closing = format!("#+end_{}", name);
+                let end = (i + 1..lines.len())
+                    .find(|&next| lines[next].trim().to_lowercase().starts_with(&closing))
+                    .unwrap_or(lines.len());
+                let content = dedent(&lines[(i + 1).min(end)..end]);
+                self.greater_block(&name, arguments, &content, results, &mut out);
+                results = false;
+                i = end + 1;
+                continue;
+            }
+
+            // Dynamic blocks (clock tables, column views) are generated
+            if lowercase.starts_with("#+begin:") {
+                i = (i + 1..lines.len())
+                    .find(|&next| lines[next].trim().to_lowercase().starts_with("#+end:"))
+                    .map_or(lines.len(), |end| end + 1);
+                continue;
+            }
+
+            if let Some((key, value)) = keyword(trimmed) {
+                match key.to_lowercase().as_str() {
+                    "caption" => caption = Some(value.to_string()),
+                    "results" => results = true,
+                    key => self.metadata.extend(keyword_element(key, value)),
+                }
+                i += 1;
+                continue;
+            }
+
+            // Comment lines
+            if trimmed == "#" || trimmed.starts_with("# ") {
+                i += 1;
+                continue;
+            }
+
+            // Drawers (:PROPERTIES:, :LOGBOOK:...) up to :END:
+            if is_drawer(trimmed) {
+                i = (i + 1..lines.len())
+                    .find(|&next| lines[next].trim().eq_ignore_ascii_case(":end:"))
+                    .map_or(lines.len(), |end| end + 1);
+                continue;
+            }
+
+            // Planning lines under a headline
+            if ["SCHEDULED:", "DEADLINE:", "CLOSED:"].iter().any(|planning| trimmed.starts_with(planning)) {
+                i += 1;
+                continue;
+            }
+
+            if trimmed.starts_with('|') || (trimmed.starts_with("+-") && trimmed.ends_with('+')) {
+                let end = run_end(lines, i, |line| line.starts_with('|') || line.starts_with('+'));
+                if let Some(caption) = caption.take() {
+                    out.push(DocumentElement::Paragraph { text: inline(&caption, &mut Vec::new()) });
+                }
+                out.extend(org_table(&lines[i..end]));
+                results = false;
+                i = end;
+                continue;
+            }
+
+            // Fixed-width lines (": text")
+            if trimmed == ":" || trimmed.starts_with(": ") {
+                let end = run_end(lines, i, |line| line == ":" || line.starts_with(": "));
+                let text = lines[i..end].iter()
+                    .map(|line| line.trim().strip_prefix(": ").unwrap_or_default())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                out.push(match results {
+                    true => DocumentElement::CodeOutput { text, kind: "result".to_string() },
+                    false => DocumentElement::Code { code: text, language: None, inline: false },
+                });
+                results = false;
+                i = end;
+                continue;
+            }
+
+            // Horizontal rule
+            if trimmed.len() >= 5 && trimmed.chars().all(|c| c == '-') {
+                i += 1;
+                continue;
+            }
+
+            // LaTeX environments and display math
+            if let Some(rest) = trimmed.strip_prefix("\\begin{")
+                && let Some((environment, _)) = rest.split_once('}') {
+                let closing = format!("\\end{{{}}}", environment);
+                let end = (i..lines.len()).find(|&next| lines[next].contains(&closing)).unwrap_or(lines.len() - 1);
+                let source = lines[i..=end].join("\n");
+                out.push(match MATH_ENVIRONMENTS.contains(&environment) {
+                    true => {
+                        let body = source.trim()
+                            .trim_start_matches(&format!("\\begin{{{}}}", environment))
+                            .trim_end_matches(&closing);
+                        DocumentElement::Math { tex: body.trim().to_string(), environment: Some(environment.to_string()) }
+                    },
+                    false => DocumentElement::Code { code: source, language: Some("latex".to_string()), inline: false },
+                });
+                i = end + 1;
+                continue;
+            }
+            if let Some(rest) = trimmed.strip_prefix("\\[").or_else(|| trimmed.strip_prefix("$$")) {
+                let closing = if trimmed.starts_with("\\[") { "\\]" } else { "$$" };
+                let end = match rest.contains(closing) {
+                    true => i,
+                    false => (i + 1..lines.len()).find(|&next| lines[next].contains(closing)).unwrap_or(lines.len() - 1),
+                };
+                let source = lines[i..=end].join("\n");
+                let tex = source.trim()[2..].trim_end().trim_end_matches(closing).trim().to_string();
+                out.push(DocumentElement::Math { tex, environment: None });
+                i = end + 1;
+                continue;
+            }
+
+            if self.list_item(line).is_some() {
+                i = self.list(lines, i, &mut out);
+                continue;
+            }
+
+            let end = (i + 1..lines.len())
+                .find(|&next| self.ends_paragraph(lines[next]))
+                .unwrap_or(lines.len());
+            let text = join_lines(&lines[i..end]);
+
+            // A paragraph made of a link to an image file is the image
+            if let Some((target, description)) = text.strip_prefix("[[").and_then(|rest| rest.strip_suffix("]]")).map(split_link)
+                && !target.contains("]]")
+                && is_image(target) {
+                let alt = caption.take().or(description.map(|description| description.to_string())).unwrap_or_default();
+                let url = target.strip_prefix("file:").unwrap_or(target);
+                out.push(DocumentElement::Image { alt: inline(&alt, &mut Vec::new()), url: Some(url.to_string()) });
+            } else {
+                let mut links = Vec::new();
+                out.push(DocumentElement::Paragraph { text: inline(&text, &mut links) });
+                out.extend(links);
+            }
+            i = end;
+        }
+
+        self.depth -= 1;
+        out
+    }
+
+    fn greater_block(&mut self, name: &str, arguments: &str, content: &[&str], results: bool, out: &mut Vec<DocumentElement>) {
+        // Lines starting with "*" or "#+" are escaped with a comma inside blocks
+        let unescaped = || content.iter()
+            .map(|line| match line.trim_start().starts_with(",*") || line.trim_start().starts_with(",#+") {
+                true => line.replacen(',', "", 1),
+                false => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        match name {
+            "src" => {
+                let language = arguments.split_whitespace().next().map(|language| language.to_string());
+                out.push(DocumentElement::Code { code: unescaped(), language, inline: false });
+            },
+            "example" if results => out.push(DocumentElement::CodeOutput { text: unescaped(), kind: "result".to_string() }),
+            "example" => out.push(DocumentElement::Code { code: unescaped(), language: None, inline: false }),
+            "quote" => {
+                let nested = self.blocks(content);
+                push_quote(None, nested, out);
+            },
+            "verse" => {
+                let mut links = Vec::new();
+                let text = content.iter().map(|line| inline(line, &mut links)).collect::<Vec<_>>().join("\n");
+                out.push(DocumentElement::Blockquote { text });
+                out.extend(links);
+            },
+            "comment" => {},
+            "export" => {
+                if arguments.eq_ignore_ascii_case("html") {
+                    let document = parse_html_str(&content.join("\n"));
+                    out.extend(Document { metadata: Vec::new(), nodes: document.nodes }.to_elements());
+                }
+            },
+            name if ADMONITIONS.contains(&name) => {
+                let nested = self.blocks(content);
+                push_quote(Some(&admonition_label(name)), nested, out);
+            },
+            // CENTER and other special blocks: their content
+            _ => {
+                let nested = self.blocks(content);
+                out.extend(nested);
+            },
+        }
+    }
+
+    // Items of one plain list, nested items included; returns the line after the list
+    fn list(&mut self, lines: &[&str], mut i: usize, out: &mut Vec<DocumentElement>) -> usize {
+        let Some((ordered, base, _)) = self.list_item(lines[i]) else { return i + 1 };
+        let mut items = Vec::new();
+        let mut trailing = Vec::new();
+
+        while i < lines.len() {
+            // Two blank lines end a list
+            if is_blank(lines[i]) {
+                if lines.get(i + 1).is_none_or(|next| is_blank(next)) {
+                    break;
+                }
+                i += 1;
+                continue;
+            }
+            // An item of the other kind starts another list
+            let Some((_, _, text_start)) = self.list_item(lines[i])
+                .filter(|(item_ordered, indent, _)| *indent == base && *item_ordered == ordered) else { break };
+
+            // The item runs until the next line indented no deeper than its bullet
+            let end = (i + 1..lines.len())
+                .find(|&next| !is_blank(lines[next]) && indentation(lines[next]) <= base)
+                .unwrap_or(lines.len());
+            let mut body = vec![item_text(&lines[i][text_start..])];
+            body.extend(lines[i + 1..end].iter().map(|line| line[indentation(line).min(text_start)..].to_string()));
+            let nested = self.blocks(&body.iter().map(String::as_str).collect::<Vec<_>>());
+            push_list_item(nested, &mut items, &mut trailing);
+            i = end;
+        }
+
+        if !items.is_empty() {
+            out.push(DocumentElement::List { items, ordered });
+        }
+        out.extend(trailing);
+        i
+    }
+
+    // Bullet ("-", "+", or "*" when indented) or counter ("1.", "1)", "a.") at the start of a line.
+    // Returns whether the list is ordered, the bullet indentation and where the item text starts.
+    fn list_item(&self, line: &str) -> Option<(bool, usize, usize)> {
+        let indent = indentation(line);
+        let rest = line[indent..].trim_end();
+        let (marker, text) = rest.split_once(' ').unwrap_or((rest, ""));
+        let text_start = line.len() - line[indent + marker.len()..].trim_start().len();
+
+        if marker == "-" || marker == "+" || (marker == "*" && (indent > 0 || self.depth > 1)) {
+            return Some((false, indent, text_start.min(line.len())));
+        }
+        let counter = marker.strip_suffix('.').or_else(|| marker.strip_suffix(')'))?;
+        let is_counter = (!counter.is_empty() && counter.len() <= 3 && counter.chars().all(|c| c.is_ascii_digit()))
+            || (counter.len() == 1 && counter.chars().all(|c| c.is_ascii_alphabetic()));
+        (is_counter && !text.trim().is_empty()).then_some((true, indent, text_start))
+    }
+
+    // Number of stars of a headline ("** Title"), only outside nested blocks
+    fn headline_level(&self, line: &str) -> Option<usize> {
+        if self.depth > 1 {
+            return None;
+        }
+        let level = line.len() - line.trim_start_matches('*').len();
+        (level > 0 && line[level..].starts_with(' ')).then_some(level)
+    }
+
+    // Headline text without its TODO keyword, priority, statistics cookies and tags,
+    // and whether it is commented out ("* COMMENT Title")
+    fn headline(&self, text: &str) -> (String, bool) {
+        let mut text = text.trim();
+        if let Some((word, rest)) = text.split_once(' ')
+            && self.todo_keywords.iter().any(|keyword| keyword == word) {
+            text = rest.trim_start();
+        }
+        if text.starts_with("[#") && text.get(3..4) == Some("]") {
+            text = text[4..].trim_start();
+        }
+        let commented = text == "COMMENT" || text.starts_with("COMMENT ");
+
+        // Tags: ":work:urgent:" at the end
+        if let Some((title, tags)) = text.rsplit_once(' ')
+            && tags.len() > 2
+            && tags.starts_with(':')
+            && tags.ends_with(':')
+            && tags.chars().all(|c| c.is_alphanumeric() || "_@#%:".contains(c)) {
+            text = title.trim_end();
+        }
+
+        let words: Vec<&str> = text.split_whitespace().filter(|word| !is_statistics_cookie(word)).collect();
+        (words.join(" "), commented)
+    }
+
+    fn ends_paragraph(&self, line: &str) -> bool {
+        let trimmed = line.trim();
+        trimmed.is_empty()
+            || self.headline_level(line).is_some()
+            || trimmed.starts_with("#+")
+            || trimmed.starts_with('|')
+            || trimmed == ":"
+            || trimmed.starts_with(": ")
+            || trimmed.starts_with("\\begin{")
+            || is_drawer(trimmed)
+            || self.list_item(line).is_some()
+    }
+}
+
+// "#+KEY: value"
+fn keyword(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.strip_prefix("#+")?.split_once(':')?;
+    (!key.is_empty() && !key.contains(char::is_whitespace)).then(|| (key, value.trim()))
+}
+
+// The metadata an export keyword gives, None for the others (options, startup, setup files...)
+fn keyword_element(key: &str, value: &str) -> Option<DocumentElement> {
+    let text = inline(value, &mut Vec::new());
+    if text.is_empty() {
+        return None;
+    }
+    match key {
+        "title" => Some(DocumentElement::Title { text }),
+        "author" => Some(DocumentElement::Author { text }),
+        "date" => Some(DocumentElement::CreationDate { text }),
+        "description" | "subtitle" => Some(DocumentElement::Description { text }),
+        "keywords" => Some(DocumentElement::Keywords { text }),
+        "language" => Some(DocumentElement::Language { text }),
+        "creator" => Some(DocumentElement::Creator { text }),
+        _ => None,
+    }
+}
+
+// ":PROPERTIES:", ":LOGBOOK:"... (":END:" closes them)
+fn is_drawer(line: &str) -> bool {
+    line.len() > 2
+        && line.starts_with(':')
+        && line.ends_with(':')
+        && !line.eq_ignore_ascii_case(":end:")
+        && line[1..line.len() - 1].chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+// "[2/5]", "[40%]", and their empty forms "[/]" and "[%]"
+fn is_statistics_cookie(word: &str) -> bool {
+    word.strip_prefix('[').and_then(|word| word.strip_suffix(']'))
+        .is_some_and(|inner| {
+            let digits = inner.replace(['/', '%'], "");
+            (inner.contains('/') || inner.ends_with('%')) && digits.chars().all(|c| c.is_ascii_digit())
+        })
+}
+
+// End of the run of lines from `start` whose trimmed text satisfies `belongs`
+fn run_end(lines: &[&str], start: usize, belongs: impl Fn(&str) -> bool) -> usize {
+    (start + 1..lines.len())
+        .find(|&next| !belongs(lines[next].trim()))
+        .unwrap_or(lines.len())
+}
+
+// The text of an item without its counter cookie ("[@3]"), its checkbox written "[x] " or "[ ] "
+// like Markdown task items ("[-] " when partly done), and a description item's
+// "term :: description" written "term: description"
+fn item_text(text: &str) -> String {
+    let mut text = text;
+    if text.starts_with("[@") && let Some(end) = text.find(']') {
+        text = text[end + 1..].trim_start();
+    }
+    let mut checkbox = "";
+    for (marker, written) in [("[ ]", "[ ] "), ("[X]", "[x] "), ("[x]", "[x] "), ("[-]", "[-] ")] {
+        if let Some(rest) = text.strip_prefix(marker) {
+            text = rest.trim_start();
+            checkbox = written;
+        }
+    }
+    match text.split_once(" :: ") {
+        Some((term, description)) => format!("{}{}: {}", checkbox, term.trim(), description.trim()),
+        None => format!("{}{}", checkbox, text.strip_suffix(" ::").unwrap_or(text)),
+    }
+}
+
+// Rows of "|" lines; "|---+---|" rules separate the header from the body
+fn org_table(lines: &[&str]) -> Option<DocumentElement> {
+    let mut rows = Vec::new();
+    let mut header_rows = 0;
+    let mut links = Vec::new();
+
+    for line in lines {
+        let line = line.trim();
+        if line.starts_with("|-") || line.starts_with('+') {
+            if header_rows == 0 && !rows.is_empty() {
+                header_rows = rows.len();
+            }
+            continue;
+        }
+        let inner = line.strip_prefix('|').unwrap_or(line);
+        let inner = inner.strip_suffix('|').unwrap_or(inner);
+        rows.push(inner.split('|').map(|cell| inline(cell.trim(), &mut links)).collect::<Vec<String>>());
+    }
+
+    // A rule after the last row is not a header separator
+    if header_rows >= rows.len() {
+        header_rows = 0;
+    }
+    table(rows, header_rows)
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to inline markup
+//-------------------------------------------------
+
+// "target][description" of a bracket link
+fn split_link(link: &str) -> (&str, Option<&str>) {
+    match link.split_once("][") {
+        Some((target, description)) => (target, Some(description)),
+        None => (link, None),
+    }
+}
+
+fn is_image(target: &str) -> bool {
+    target.rsplit_once('.')
+        .is_some_and(|(_, extension)| IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+fn is_url(target: &str) -> bool {
+    target.contains("://") || target.starts_with("mailto:")
+}
+
+// Remove inline markup: links, targets, footnote references, macros, export snippets and emphasis.
+// Links to URLs (bracketed or plain) are added to `links`.
+fn inline(text: &str, links: &mut Vec<DocumentElement>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+
+    'scan: while i < text.len() {
+        let rest = &text[i..];
+
+        // [[target][description]] and [[target]]
+        if let Some(inner) = rest.strip_prefix("[[")
+            && let Some(end) = inner.find("]]") {
+            let (target, description) = split_link(&inner[..end]);
+            let shown = description.unwrap_or_else(|| {
+                let target = target.strip_prefix("file:").unwrap_or(target);
+                target.trim_start_matches(['#', '*'])
+            });
+            if is_url(target) {
+                links.push(DocumentElement::Link { text: shown.to_string(), url: target.to_string() });
+            }
+            result.push_str(shown);
+            i += end + 4;
+            continue;
+        }
+
+        // Radio targets <<<text>>> show their text, targets <<id>> nothing
+        if let Some(inner) = rest.strip_prefix("<<<")
+            && let Some(end) = inner.find(">>>") {
+            result.push_str(&inner[..end]);
+            i += end + 6;
+            continue;
+        }
+        if let Some(inner) = rest.strip_prefix("<<")
+            && let Some(end) = inner.find(">>") {
+            i += end + 4;
+            continue;
+        }
+
+        // Footnote references [fn:1], [fn::inline definition]; macros {{{name}}}; snippets @@html:...@@
+        for (open, close) in [("[fn:", "]"), ("{{{", "}}}"), ("@@", "@@")] {
+            if let Some(inner) = rest.strip_prefix(open)
+                && let Some(end) = inner.find(close) {
+                i += open.len() + end + close.len();
+                continue 'scan;
+            }
+        }
+
+        // Plain links
+        let at_boundary = i == 0 || text[..i].ends_with(|c: char| c.is_whitespace() || "(<\"'".contains(c));
+        if at_boundary && ["https://", "http://", "ftp://", "mailto:"].iter().any(|scheme| rest.starts_with(scheme)) {
+            let url = rest.split(|c: char| c.is_whitespace() || c == '>').next().unwrap_or_default()
+                .trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '\'', '"']);
+            links.push(DocumentElement::Link { text: url.to_string(), url: url.to_string() });
+            result.push_str(url);
+            i += url.len();
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap_or_default();
+        result.push(c);
+        i += c.len_utf8();
+    }
+
+    let result = strip_emphasis(&result, &["*", "/", "_", "=", "~", "+"]);
+    // "\\" at the end of a line is a line break
+    result.split_whitespace().filter(|word| *word != "\\\\").collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::ParseEvent;
+
+    const SOURCE: &str = "#+TITLE: Guide
+#+AUTHOR: Ann
+
+* Install
+** Steps
+- one
+- two
+1. first
+2. second
+#+BEGIN_SRC shell
+make
+#+END_SRC
+#+BEGIN_NOTE
+Needs make.
+#+END_NOTE
+| Name | Size |
+|------+------|
+| a    | 1    |
+";
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn headlines_blocks_lists_and_tables() {
+        assert_eq!(parse_org_str(SOURCE).to_elements(), vec![
+            DocumentElement::Title { text: "Guide".to_string() },
+            DocumentElement::Author { text: "Ann".to_string() },
+            DocumentElement::Heading { level: 1, text: "Install".to_string() },
+            DocumentElement::Heading { level: 2, text: "Steps".to_string() },
+            DocumentElement::List { items: strings(&["one", "two"]), ordered: false },
+            DocumentElement::List { items: strings(&["first", "second"]), ordered: true },
+            DocumentElement::Code { code: "make".to_string(), language: Some("shell".to_string()), inline: false },
+            DocumentElement::Blockquote { text: "Note: Needs make.".to_string() },
+            DocumentElement::Table { headers: strings(&["Name", "Size"]), rows: vec![strings(&["a", "1"])] },
+        ]);
+    }
+
+    #[test]
+    fn latin1_files_are_reported() {
+        let source = b"Caf\xe9 cr\xe8me.\n";
+        let path = std::env::temp_dir().join(format!("oxidoc-latin1-{}.org", std::process::id()));
+        fs::write(&path, source).unwrap();
+
+        let warnings = std::cell::RefCell::new(Vec::new());
+        let sink = |event: ParseEvent| if let ParseEvent::Warning { path, .. } = event {
+            warnings.borrow_mut().push(path);
+        };
+        let from_path = OrgParser.parse_path(&path, &sink);
+        fs::remove_file(&path).unwrap();
+        let from_bytes = OrgParser.parse_bytes(source, &sink).unwrap();
+
+        // Only the filename title differs
+        assert_eq!(from_path.unwrap()[1..], from_bytes[..]);
+        assert_eq!(from_bytes, vec![DocumentElement::Paragraph { text: "Café crème.".to_string() }]);
+        assert_eq!(*warnings.borrow(), vec![Some(path), None]);
+    }
+
+    #[test]
+    fn checklist_items_keep_their_checkbox() {
+        assert_eq!(parse_org_str("- [X] Buy milk\n- [ ] Call Ann\n- [-] Pack [1/2]\n").to_elements(), vec![
+            DocumentElement::List {
+                items: vec!["[x] Buy milk".to_string(), "[ ] Call Ann".to_string(), "[-] Pack [1/2]".to_string()],
+                ordered: false,
+            },
+        ]);
+    }
+}
@@ -0,0 +1,56 @@
+// parser.rs
+
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::EventSink;
+use crate::parsers::documents_types::DocumentElement;
+
+// Common interface for every input format.
+// Implement it (and register it in a ParserRegistry) to add a format from outside this crate.
+pub trait Parser: Send + Sync {
+    // Short format name ("html", "pdf"...), used as the document type of exported records
+    fn name(&self) -> &str;
+
+    // File extensions handled by this parser, lowercase and without the dot
+    fn extensions(&self) -> &[&str];
+
+    // MIME types handled by this parser
+    fn mime_types(&self) -> &[&str] {
+        &[]
+    }
+
+    // Whether the first bytes of a file look like this format
+    fn matches_magic(&self, _bytes: &[u8]) -> bool {
+        false
+    }
+
+    // Parse an in-memory document, reporting warnings to `events`
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>>;
+
+    // Parse everything a reader yields
+    fn parse_reader(&self, reader: &mut dyn Read, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        self.parse_bytes(&bytes, events)
+    }
+
+    // Parse a file on disk
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+        self.parse_bytes(&bytes, events).map_err(|error| error.with_path(path))
+    }
+
+    // Parse a document made of independent records (wiki dump pages, web archive captures...) one
+    // record at a time, handing each record's elements to `record` as soon as it is parsed, so files
+    // far larger than memory can be processed. Formats without records are handed over as a single record.
+    fn parse_records(
+        &self,
+        reader: &mut dyn Read,
+        events: &dyn EventSink,
+        record: &mut dyn FnMut(Vec<DocumentElement>) -> Result<()>,
+    ) -> Result<()> {
+        record(self.parse_reader(reader, events)?)
+    }
+}
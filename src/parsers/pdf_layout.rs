@@ -0,0 +1,160 @@
+// pdf_layout.rs
+
+use pdf_extract::{Document, MediaBox, OutputDev, OutputError, Transform};
+use crate::error::Result;
+
+// Horizontal gap (in ems) that splits a line into separate chunks (table cells, columns)
+const CHUNK_GAP_EMS: f64 = 1.0;
+// Horizontal gap (in ems) that becomes a space between words
+const WORD_GAP_EMS: f64 = 0.1;
+
+// Positioned text of one page. Coordinates are in points from the top-left corner;
+// `y` is the baseline, so it grows down the page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageLayout {
+    pub number: u32,
+    pub width: f64,
+    pub height: f64,
+    pub lines: Vec<TextLine>,
+    // x of the gaps between text columns, empty when the page has a single column
+    pub gutters: Vec<f64>,
+}
+
+// Characters sharing a baseline, split into chunks wherever a wide gap separates them
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLine {
+    pub y: f64,
+    pub font_size: f64,
+    pub chunks: Vec<TextChunk>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextChunk {
+    pub text: String,
+    pub x: f64,
+    pub x_end: f64,
+}
+
+impl TextLine {
+    pub fn text(&self) -> String {
+        self.chunks.iter()
+            .map(|chunk| chunk.text.trim())
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    pub fn x(&self) -> f64 {
+        self.chunks.first().map_or(0.0, |chunk| chunk.x)
+    }
+
+    pub fn x_end(&self) -> f64 {
+        self.chunks.last().map_or(0.0, |chunk| chunk.x_end)
+    }
+}
+
+// Lay out one page (1-based) of a loaded PDF
+pub fn extract_page_layout(document: &Document, page_number: u32) -> Result<PageLayout> {
+    let mut collector = LayoutCollector::new(page_number);
+    pdf_extract::output_doc_page(document, &mut collector, page_number)?;
+    Ok(collector.finish())
+}
+
+// OutputDev that records where every character is drawn
+struct LayoutCollector {
+    page: PageLayout,
+    // Sum of character sizes on the current line, to average the font size
+    size_total: f64,
+    size_count: usize,
+    last_end: f64,
+}
+
+impl LayoutCollector {
+    fn new(page_number: u32) -> LayoutCollector {
+        LayoutCollector {
+            page: PageLayout { number: page_number, width: 0.0, height: 0.0, lines: Vec::new(), gutters: Vec::new() },
+            size_total: 0.0,
+            size_count: 0,
+            last_end: 0.0,
+        }
+    }
+
+    fn finish(mut self) -> PageLayout {
+        self.close_line();
+        self.page.lines.retain(|line| !line.text().is_empty());
+        self.page
+    }
+
+    fn close_line(&mut self) {
+        if let Some(line) = self.page.lines.last_mut()
+            && self.size_count > 0 {
+            line.font_size = self.size_total / self.size_count as f64;
+        }
+        self.size_total = 0.0;
+        self.size_count = 0;
+    }
+}
+
+impl OutputDev for LayoutCollector {
+    fn begin_page(&mut self, _page_num: u32, media_box: &MediaBox, _art_box: Option<(f64, f64, f64, f64)>) -> std::result::Result<(), OutputError> {
+        self.page.width = media_box.urx - media_box.llx;
+        self.page.height = media_box.ury - media_box.lly;
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> std::result::Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn output_character(&mut self, trm: &Transform, width: f64, _spacing: f64, font_size: f64, char: &str) -> std::result::Result<(), OutputError> {
+        // Flip to top-down coordinates
+        let x = trm.m31;
+        let y = self.page.height - trm.m32;
+        // Size of the glyph once the text matrix is applied
+        let scaled_x = font_size * trm.m11 + font_size * trm.m21;
+        let scaled_y = font_size * trm.m12 + font_size * trm.m22;
+        let size = (scaled_x * scaled_y).abs().sqrt().max(1.0);
+        let end = x + width * size;
+
+        let same_line = self.page.lines.last().is_some_and(|line| {
+            (y - line.y).abs() <= size * 0.5 && x >= self.last_end - size
+        });
+
+        if !same_line {
+            self.close_line();
+            self.page.lines.push(TextLine { y, font_size: size, chunks: Vec::new() });
+        }
+        let line = self.page.lines.last_mut().unwrap();
+
+        let gap = x - self.last_end;
+        match line.chunks.last_mut() {
+            Some(chunk) if gap <= size * CHUNK_GAP_EMS => {
+                if gap > size * WORD_GAP_EMS && !chunk.text.ends_with(' ') {
+                    chunk.text.push(' ');
+                }
+                chunk.text.push_str(char);
+                chunk.x_end = end;
+            },
+            _ => line.chunks.push(TextChunk { text: char.to_string(), x, x_end: end }),
+        }
+
+        // Spaces drawn as glyphs do not count towards the font size
+        if !char.trim().is_empty() {
+            self.size_total += size;
+            self.size_count += 1;
+        }
+        self.last_end = end;
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> std::result::Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> std::result::Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> std::result::Result<(), OutputError> {
+        Ok(())
+    }
+}
@@ -1,53 +1,1186 @@
-use pdf_extract;
+use chrono::{FixedOffset, TimeZone};
+use pdf_extract::{Dictionary, Document, Object};
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::path::Path;
-use crate::parsers::documents_types::DocumentElement;
+use std::str::FromStr;
+use crate::error::{OxidocError, Result};
+use crate::events::{EventSink, ParseEvent};
+use crate::parsers::documents_types::{fallback_title, DocumentElement};
+use crate::parsers::parser::Parser;
+use crate::parsers::pdf_layout::{extract_page_layout, PageLayout, TextChunk, TextLine};
+use crate::parsers::txt_parser::list_marker;
 
-pub fn parse_pdf_file(path: &Path) -> Result<Vec<DocumentElement>, Box<dyn std::error::Error>> {
-    println!("\nOxidoc PDF Parser");
-    println!("Parsing PDF file: {:?}", path);
+pub fn parse_pdf_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    parse_pdf_file_pages(path, None)
+}
 
-    // Check if file exists
-    if !path.exists() {
-       println!("❌ PDF file not found: {:?}", path);
-       return Ok(vec![]);
-    }
+// Parse a PDF file, keeping only the pages in `pages` (every page when None)
+pub fn parse_pdf_file_pages(path: &Path, pages: Option<PageRange>) -> Result<Vec<DocumentElement>> {
+    // Read the PDF file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
     
-    // Extract metadata (simple approach for now)
-    let mut elements = extract_pdf_metadata(path);
+    let mut elements = parse_pdf_bytes_pages(&bytes, pages).map_err(|error| error.with_path(path))?;
+
+    fallback_title(&mut elements, path);
+
+    Ok(elements)
+}
+
+// Parse an in-memory PDF
+pub fn parse_pdf_bytes(bytes: &[u8]) -> Result<Vec<DocumentElement>> {
+    parse_pdf_bytes_pages(bytes, None)
+}
+
+// Parse an in-memory PDF page by page; every page's elements follow a Page marker
+pub fn parse_pdf_bytes_pages(bytes: &[u8], pages: Option<PageRange>) -> Result<Vec<DocumentElement>> {
+    let document = load_pdf(bytes)?;
+
+    // Extract metadata from the Info dictionary and XMP packet
+    let mut elements = extract_pdf_metadata(&document);
     
-    // Extract text from PDF using pdf-extract
-    let pdf_content = pdf_extract::extract_text(path)?;
+    // Lay out each selected page using pdf-extract
+    let mut layouts = Vec::new();
+    for &page_number in document.get_pages().keys() {
+        if pages.is_some_and(|pages| !pages.contains(page_number)) {
+            continue;
+        }
+        layouts.push(extract_page_layout(&document, page_number)?);
+    }
 
-    // Split paragraphs
-    let paragraphs: Vec<&str> = pdf_content.split("\n\n")
-        .map(|p| p.trim())
-        .filter(|p| !p.is_empty() && p.len() > 20)
-        .collect();
+    // Drop running headers, footers and page numbers before they get classified as text
+    strip_headers_and_footers(&mut layouts);
 
-    for paragraph in paragraphs {
-        elements.push(DocumentElement::Paragraph {
-            text: paragraph.to_string(),
-        });
+    // Put multi-column pages back in reading order: column by column, top to bottom
+    for layout in &mut layouts {
+        order_columns(layout);
     }
 
-    // Debug: Uncomment the next line to see PDF content in terminal
-    // println!("Extracted text from PDF: {}", pdf_content);
+    // Classify the laid out text into headings, lists, tables and paragraphs
+    elements.extend(layout_elements(&layouts));
 
     Ok(elements)
 }
 
-pub fn extract_pdf_metadata(path: &Path) -> Vec<DocumentElement> {
-    let mut metadata = Vec::new();
+// Inclusive, 1-based page selection: "3-10", "5", or "3-" for page 3 to the end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRange {
+    pub first: u32,
+    pub last: Option<u32>,
+}
+
+impl PageRange {
+    pub fn new(first: u32, last: Option<u32>) -> PageRange {
+        PageRange { first, last }
+    }
+
+    pub fn contains(&self, page: u32) -> bool {
+        page >= self.first && self.last.is_none_or(|last| page <= last)
+    }
+}
+
+impl FromStr for PageRange {
+    type Err = OxidocError;
+
+    fn from_str(range: &str) -> Result<PageRange> {
+        let invalid = || OxidocError::malformed(format!("invalid page range {:?}, expected e.g. \"3-10\"", range));
+        let parse_page = |page: &str| page.trim().parse::<u32>().ok().filter(|page| *page > 0);
+
+        let (first, last) = match range.split_once('-') {
+            Some((first, "")) => (parse_page(first).ok_or_else(invalid)?, None),
+            Some((first, last)) => (parse_page(first).ok_or_else(invalid)?, Some(parse_page(last).ok_or_else(invalid)?)),
+            None => {
+                let page = parse_page(range).ok_or_else(invalid)?;
+                (page, Some(page))
+            },
+        };
+
+        if last.is_some_and(|last| last < first) {
+            return Err(invalid());
+        }
+        Ok(PageRange { first, last })
+    }
+}
+
+// Load a PDF, decrypting it when it only has an owner password
+fn load_pdf(bytes: &[u8]) -> Result<Document> {
+    let mut document = Document::load_mem(bytes).map_err(pdf_error)?;
+    if document.is_encrypted() {
+        document.decrypt("").map_err(pdf_error)?;
+    }
+    Ok(document)
+}
+
+fn pdf_error(error: pdf_extract::Error) -> OxidocError {
+    OxidocError::from(pdf_extract::OutputError::PdfError(error))
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to layout analysis
+//-------------------------------------------------
+
+// Font weight is not exposed by pdf-extract, so structure is inferred from size and position only.
+
+// Lines at least this much larger than body text can be headings
+const HEADING_SIZE_RATIO: f64 = 1.15;
+// Headings are short blocks
+const HEADING_MAX_LINES: usize = 3;
+const HEADING_MAX_CHARS: usize = 200;
+// A heading size must be used on this many pages to rank as a heading level,
+// and at least this share of its headings must have distinct text
+const HEADING_MIN_PAGES: usize = 2;
+const HEADING_MIN_DISTINCT_SHARE: f64 = 0.5;
+// A new block starts when lines are this much further apart than the usual line spacing
+const BLOCK_GAP_RATIO: f64 = 1.25;
+// Text columns wider than this share of the page are prose, not table cells
+const TABLE_MAX_CELL_WIDTH: f64 = 0.4;
+
+// A classified run of lines, before list items are merged
+enum LayoutBlock {
+    Heading { font_size: f64, text: String },
+    Paragraph { text: String },
+    ListItem { ordered: bool, text: String },
+    Table { headers: Vec<String>, rows: Vec<Vec<String>> },
+}
+
+// Where and how often headings of one font size appear
+struct HeadingSizeUse {
+    font_size: f64,
+    pages: Vec<u32>,
+    texts: Vec<String>,
+    count: usize,
+}
+
+// Turn laid out pages into document elements, each page introduced by its Page marker
+fn layout_elements(pages: &[PageLayout]) -> Vec<DocumentElement> {
+    let body_size = body_font_size(pages);
+    let block_gap = typical_line_gap(pages) * BLOCK_GAP_RATIO;
+
+    let page_blocks: Vec<(u32, Vec<LayoutBlock>)> = pages.iter()
+        .map(|page| (page.number, classify_page(page, body_size, block_gap)))
+        .collect();
+
+    // Larger heading sizes get lower levels. Only sizes that recur as headings rank: a size seen
+    // on a single page is a cover line or a figure label, and so is one whose blocks keep
+    // repeating the same few words ("Cons", "i32" in every diagram of a series).
+    let mut size_uses: Vec<HeadingSizeUse> = Vec::new();
+    for (page_number, blocks) in &page_blocks {
+        for block in blocks {
+            if let LayoutBlock::Heading { font_size, text } = block {
+                let index = match size_uses.iter().position(|size_use| size_use.font_size == *font_size) {
+                    Some(index) => index,
+                    None => {
+                        size_uses.push(HeadingSizeUse { font_size: *font_size, pages: Vec::new(), texts: Vec::new(), count: 0 });
+                        size_uses.len() - 1
+                    },
+                };
+                let size_use = &mut size_uses[index];
+                if !size_use.pages.contains(page_number) {
+                    size_use.pages.push(*page_number);
+                }
+                if !size_use.texts.contains(text) {
+                    size_use.texts.push(text.clone());
+                }
+                size_use.count += 1;
+            }
+        }
+    }
+    let min_pages = HEADING_MIN_PAGES.min(pages.len());
+    let mut heading_sizes: Vec<f64> = size_uses.into_iter()
+        .filter(|size_use| size_use.pages.len() >= min_pages)
+        .filter(|size_use| size_use.texts.len() as f64 >= size_use.count as f64 * HEADING_MIN_DISTINCT_SHARE)
+        .map(|size_use| size_use.font_size)
+        .collect();
+    heading_sizes.sort_by(|a, b| b.total_cmp(a));
+
+    let mut elements = Vec::new();
+    for (page_number, blocks) in page_blocks {
+        elements.push(DocumentElement::Page { number: page_number });
+
+        for block in blocks {
+            match block {
+                LayoutBlock::Heading { font_size, text } => {
+                    // Blocks of a one-off size, or without a single letter ("5 10"), are text
+                    match heading_sizes.iter().position(|size| *size == font_size) {
+                        Some(rank) if text.chars().any(char::is_alphabetic) => {
+                            elements.push(DocumentElement::Heading { level: (rank + 1).min(6) as u8, text });
+                        },
+                        _ => elements.push(DocumentElement::Paragraph { text }),
+                    }
+                },
+                LayoutBlock::Paragraph { text } => elements.push(DocumentElement::Paragraph { text }),
+                LayoutBlock::ListItem { ordered, text } => {
+                    // Consecutive items of the same kind form one list
+                    match elements.last_mut() {
+                        Some(DocumentElement::List { items, ordered: list_ordered }) if *list_ordered == ordered => {
+                            items.push(text);
+                        },
+                        _ => elements.push(DocumentElement::List { items: vec![text], ordered }),
+                    }
+                },
+                LayoutBlock::Table { headers, rows } => elements.push(DocumentElement::Table { headers, rows }),
+            }
+        }
+    }
+
+    elements
+}
+
+// Most common font size across the document, weighted by amount of text
+fn body_font_size(pages: &[PageLayout]) -> f64 {
+    let mut histogram: Vec<(f64, usize)> = Vec::new();
+
+    for line in pages.iter().flat_map(|page| &page.lines) {
+        let size = round_font_size(line.font_size);
+        let weight = line.text().chars().count();
+        match histogram.iter_mut().find(|(known, _)| *known == size) {
+            Some((_, total)) => *total += weight,
+            None => histogram.push((size, weight)),
+        }
+    }
+
+    histogram.into_iter()
+        .max_by_key(|(_, weight)| *weight)
+        .map_or(0.0, |(size, _)| size)
+}
+
+// Most common distance between consecutive baselines, in ems
+fn typical_line_gap(pages: &[PageLayout]) -> f64 {
+    let mut histogram: Vec<(i64, usize)> = Vec::new();
+
+    for page in pages {
+        for pair in page.lines.windows(2) {
+            let size = pair[0].font_size.max(pair[1].font_size);
+            let gap_ems = (pair[1].y - pair[0].y) / size;
+            // Ignore jumps back up the page and gaps between blocks
+            if gap_ems <= 0.0 || gap_ems > 3.0 {
+                continue;
+            }
+            let bucket = (gap_ems * 20.0).round() as i64;
+            match histogram.iter_mut().find(|(known, _)| *known == bucket) {
+                Some((_, count)) => *count += 1,
+                None => histogram.push((bucket, 1)),
+            }
+        }
+    }
+
+    histogram.into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map_or(1.2, |(bucket, _)| bucket as f64 / 20.0)
+}
+
+// Half-point buckets, so sizes from the same font compare equal
+fn round_font_size(size: f64) -> f64 {
+    (size * 2.0).round() / 2.0
+}
+
+fn classify_page(page: &PageLayout, body_size: f64, block_gap: f64) -> Vec<LayoutBlock> {
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    let mut text_start = 0;
+
+    while start < page.lines.len() {
+        match table_end(page, start) {
+            Some(end) => {
+                blocks.extend(classify_lines(&page.lines[text_start..start], &page.gutters, body_size, block_gap));
+                blocks.push(table_block(&page.lines[start..end]));
+                start = end;
+                text_start = end;
+            },
+            None => start += 1,
+        }
+    }
+    blocks.extend(classify_lines(&page.lines[text_start..], &page.gutters, body_size, block_gap));
+
+    blocks
+}
+
+// If a table starts at `start`, the index just past its last row
+fn table_end(page: &PageLayout, start: usize) -> Option<usize> {
+    let first = &page.lines[start];
+    let is_grid_row = |line: &TextLine| {
+        line.chunks.len() >= 2
+            && line.chunks.iter().all(|chunk| chunk.x_end - chunk.x < page.width * TABLE_MAX_CELL_WIDTH)
+    };
+    if !is_grid_row(first) {
+        return None;
+    }
+
+    let mut end = start + 1;
+    while end < page.lines.len() {
+        let line = &page.lines[end];
+        if !is_grid_row(line) || line.chunks.len() != first.chunks.len() || !columns_align(first, line) {
+            break;
+        }
+        end += 1;
+    }
+
+    // A single row with wide gaps is just spaced-out text
+    (end - start >= 2).then_some(end)
+}
+
+// Every cell lines up with the cell above it on the left edge, right edge or center
+fn columns_align(first: &TextLine, line: &TextLine) -> bool {
+    let tolerance = first.font_size.max(line.font_size) * 2.0;
+    first.chunks.iter().zip(&line.chunks).all(|(top, cell)| {
+        (top.x - cell.x).abs() <= tolerance
+            || (top.x_end - cell.x_end).abs() <= tolerance
+            || ((top.x + top.x_end) - (cell.x + cell.x_end)).abs() / 2.0 <= tolerance
+    })
+}
+
+fn table_block(lines: &[TextLine]) -> LayoutBlock {
+    let cells = |line: &TextLine| -> Vec<String> {
+        line.chunks.iter().map(|chunk| normalize_text(&chunk.text)).collect()
+    };
+
+    // The first row is taken as the header row
+    LayoutBlock::Table {
+        headers: cells(&lines[0]),
+        rows: lines[1..].iter().map(cells).collect(),
+    }
+}
+
+// Group lines into blocks by spacing (`block_gap` in ems), size and list markers, then classify each block
+fn classify_lines(lines: &[TextLine], gutters: &[f64], body_size: f64, block_gap: f64) -> Vec<LayoutBlock> {
+    // Right edge of the text, or of the column, to tell wrapped lines from short ones
+    let text_right = lines.iter().map(|line| line.x_end()).fold(0.0, f64::max);
+    let right_edge = |group: &[&TextLine]| {
+        gutters.iter().copied().find(|gutter| *gutter > group[0].x()).unwrap_or(text_right)
+    };
+    let mut groups: Vec<Vec<&TextLine>> = Vec::new();
+
+    for line in lines {
+        let starts_block = match groups.last().and_then(|group| group.last()) {
+            None => true,
+            Some(previous) => {
+                let gap = line.y - previous.y;
+                let size = previous.font_size.max(line.font_size);
+                // Only heading-sized text breaks on a size change; inline code shifts the average size of body lines
+                let size_changed = (previous.font_size - line.font_size).abs() > size * 0.1
+                    && size >= body_size * HEADING_SIZE_RATIO;
+                let first_of_group = groups.last().unwrap()[0];
+                // A list item continues only while its lines stay indented past the marker
+                let leaves_item = list_marker(&first_of_group.text()).is_some()
+                    && line.x() <= first_of_group.x() + size * 0.5;
+
+                gap <= 0.0
+                    || gap > size * block_gap
+                    || size_changed
+                    || leaves_item
+                    || list_marker(&line.text()).is_some()
+            },
+        };
+
+        if starts_block {
+            groups.push(vec![line]);
+        } else {
+            groups.last_mut().unwrap().push(line);
+        }
+    }
+
+    groups.into_iter()
+        .map(|group| classify_block(&group, body_size, right_edge(&group)))
+        .filter(|block| match block {
+            LayoutBlock::Heading { text, .. }
+            | LayoutBlock::Paragraph { text }
+            | LayoutBlock::ListItem { text, .. } => !text.is_empty(),
+            LayoutBlock::Table { .. } => true,
+        })
+        .collect()
+}
+
+fn classify_block(lines: &[&TextLine], body_size: f64, right_edge: f64) -> LayoutBlock {
+    let mut texts: Vec<String> = lines.iter().map(|line| normalize_text(&line.text())).collect();
+
+    if let Some((ordered, rest)) = list_marker(&texts[0]) {
+        texts[0] = rest.to_string();
+        return LayoutBlock::ListItem { ordered, text: join_lines(&texts, lines, right_edge) };
+    }
+
+    let text = join_lines(&texts, lines, right_edge);
+    let font_size = lines.iter().map(|line| line.font_size).sum::<f64>() / lines.len() as f64;
+
+    if body_size > 0.0
+        && font_size >= body_size * HEADING_SIZE_RATIO
+        && lines.len() <= HEADING_MAX_LINES
+        && text.chars().count() <= HEADING_MAX_CHARS {
+        return LayoutBlock::Heading {
+            font_size: round_font_size(font_size),
+            text: texts.join(" ").trim().to_string(),
+        };
+    }
+
+    LayoutBlock::Paragraph { text }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to reading order
+//-------------------------------------------------
+
+// Gutters are looked for away from the edges of the text, as a share of its width
+const GUTTER_SEARCH_MARGIN: f64 = 0.2;
+// Narrowest gap (in ems) between two columns
+const GUTTER_MIN_EMS: f64 = 1.0;
+// Share of the text chunks allowed to cross a gutter (titles, captions, wide figures)
+const GUTTER_MAX_CROSSING: f64 = 0.1;
+// A column holds at least this many chunks, typically as wide as this share of the page
+const COLUMN_MIN_CHUNKS: usize = 5;
+const COLUMN_MIN_WIDTH: f64 = 0.2;
+
+// Reorder the lines of a page laid out in columns. Lines are split at the gutters; text
+// spanning the columns (titles, abstracts, figures, tables) stays where it is and the
+// columns above it are read before it.
+fn order_columns(page: &mut PageLayout) {
+    let font_size = median(page.lines.iter().map(|line| line.font_size).collect());
+    let chunks: Vec<&TextChunk> = page.lines.iter().flat_map(|line| &line.chunks).collect();
+    let right = chunks.iter().map(|chunk| chunk.x_end).fold(0.0, f64::max);
+    let left = chunks.iter().map(|chunk| chunk.x).fold(right, f64::min);
+
+    let mut gutters = Vec::new();
+    find_gutters(&chunks, left, right, page.width, font_size, &mut gutters);
+    if gutters.is_empty() {
+        return;
+    }
+    gutters.sort_by(f64::total_cmp);
+    page.gutters = gutters.clone();
+
+    // Split every line into one piece per column, unless it spans them
+    let column_of = |x: f64| gutters.iter().filter(|gutter| **gutter < x).count();
+    let mut spanning: Vec<TextLine> = Vec::new();
+    let mut pieces: Vec<(usize, TextLine)> = Vec::new();
+    for line in std::mem::take(&mut page.lines) {
+        if spans_columns(&line, &gutters, page.width) {
+            spanning.push(line);
+            continue;
+        }
+        for column in 0..=gutters.len() {
+            let chunks: Vec<TextChunk> = line.chunks.iter()
+                .filter(|chunk| column_of(chunk.x) == column)
+                .cloned()
+                .collect();
+            if !chunks.is_empty() {
+                pieces.push((column, TextLine { y: line.y, font_size: line.font_size, chunks }));
+            }
+        }
+    }
+    spanning.sort_by(|a, b| a.y.total_cmp(&b.y));
+
+    // Spanning lines cut the page into bands; each band is read column by column
+    let mut bands: Vec<Vec<(usize, TextLine)>> = vec![Vec::new(); spanning.len() + 1];
+    for (column, piece) in pieces {
+        let band = spanning.partition_point(|line| line.y < piece.y);
+        bands[band].push((column, piece));
+    }
+
+    let mut spanning = spanning.into_iter();
+    for mut band in bands {
+        band.sort_by(|(a_column, a), (b_column, b)| a_column.cmp(b_column).then(a.y.total_cmp(&b.y)));
+        page.lines.extend(band.into_iter().map(|(_, piece)| piece));
+        page.lines.extend(spanning.next());
+    }
+}
+
+// Find the widest vertical gap between `left` and `right` that separates two columns of text,
+// then look for more columns on either side of it
+fn find_gutters(chunks: &[&TextChunk], left: f64, right: f64, page_width: f64, font_size: f64, gutters: &mut Vec<f64>) {
+    let region: Vec<&TextChunk> = chunks.iter()
+        .filter(|chunk| chunk.x >= left && chunk.x_end <= right)
+        .copied()
+        .collect();
+    let margin = (right - left) * GUTTER_SEARCH_MARGIN;
+    let max_crossing = (region.len() as f64 * GUTTER_MAX_CROSSING) as usize;
+
+    // Scan the region point by point for the widest run crossed by few chunks
+    let mut best: Option<(f64, f64)> = None;
+    let mut run_start: Option<f64> = None;
+    let mut x = left + margin;
+    while x <= right - margin + 1.0 {
+        let crossing = region.iter().filter(|chunk| chunk.x < x && x < chunk.x_end).count();
+        let open = crossing <= max_crossing && x <= right - margin;
+        match (open, run_start) {
+            (true, None) => run_start = Some(x),
+            (false, Some(start)) => {
+                if best.is_none_or(|(best_start, best_end)| x - start > best_end - best_start) {
+                    best = Some((start, x));
+                }
+                run_start = None;
+            },
+            _ => {},
+        }
+        x += 1.0;
+    }
+
+    let Some((start, end)) = best else {
+        return;
+    };
+    if end - start < font_size * GUTTER_MIN_EMS {
+        return;
+    }
+
+    // Both sides must read like columns of prose, not like table cells
+    let gutter = (start + end) / 2.0;
+    let is_column = |chunks: Vec<&TextChunk>| {
+        chunks.len() >= COLUMN_MIN_CHUNKS
+            && median(chunks.iter().map(|chunk| chunk.x_end - chunk.x).collect()) >= page_width * COLUMN_MIN_WIDTH
+    };
+    let left_chunks = region.iter().filter(|chunk| chunk.x_end <= gutter).copied().collect();
+    let right_chunks = region.iter().filter(|chunk| chunk.x >= gutter).copied().collect();
+    if !is_column(left_chunks) || !is_column(right_chunks) {
+        return;
+    }
+
+    gutters.push(gutter);
+    find_gutters(chunks, left, gutter, page_width, font_size, gutters);
+    find_gutters(chunks, gutter, right, page_width, font_size, gutters);
+}
+
+// A line spans the columns when text crosses a gutter, or when it is a row of short
+// pieces spread over several columns, as in a full-width table or a figure's labels
+fn spans_columns(line: &TextLine, gutters: &[f64], page_width: f64) -> bool {
+    let crosses = line.chunks.iter().any(|chunk| gutters.iter().any(|gutter| chunk.x < *gutter && *gutter < chunk.x_end));
+    let columns_used = line.chunks.iter()
+        .map(|chunk| gutters.iter().filter(|gutter| **gutter < chunk.x).count())
+        .collect::<HashSet<usize>>()
+        .len();
+    let column_width = page_width / (gutters.len() + 1) as f64;
+    let is_grid_row = line.chunks.len() > gutters.len() + 1
+        && columns_used >= 2
+        && line.chunks.iter().all(|chunk| chunk.x_end - chunk.x < column_width / 2.0);
+
+    crosses || is_grid_row
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    values[values.len() / 2]
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to text cleanup
+//-------------------------------------------------
+
+// Share of the page height, at the top and at the bottom, where headers and footers sit
+const PAGE_MARGIN_RATIO: f64 = 0.1;
+// A margin line repeated on at least this many pages, and this share of pages, is a running header/footer
+const REPEATED_MIN_PAGES: usize = 3;
+const REPEATED_MIN_RATIO: f64 = 0.1;
+// Page numbers are stripped once this many pages number themselves the same way
+const SEQUENCE_MIN_PAGES: usize = 2;
+// A line ending this close (in ems) to the right edge of the text was wrapped
+const WRAP_MARGIN_EMS: f64 = 6.0;
+
+// Remove running headers and footers: margin lines repeated across pages, and page numbers
+// counting up with the pages
+fn strip_headers_and_footers(pages: &mut [PageLayout]) {
+    // Count on how many pages each margin line appears (digits ignored, so "Page 3" matches "Page 4"),
+    // and on how many pages each page number sits at the same distance from the page's own number
+    let mut pages_per_line: HashMap<String, usize> = HashMap::new();
+    let mut pages_per_offset: HashMap<(bool, i64), usize> = HashMap::new();
+    for page in pages.iter() {
+        let margin: Vec<String> = page.lines.iter()
+            .filter(|line| in_page_margin(page.height, line))
+            .map(TextLine::text)
+            .collect();
+        let signatures: HashSet<String> = margin.iter().map(|text| margin_signature(text)).collect();
+        for signature in signatures {
+            *pages_per_line.entry(signature).or_default() += 1;
+        }
+        let offsets: HashSet<(bool, i64)> = margin.iter()
+            .filter_map(|text| page_number_offset(text, page.number))
+            .collect();
+        for offset in offsets {
+            *pages_per_offset.entry(offset).or_default() += 1;
+        }
+    }
+
+    let min_pages = REPEATED_MIN_PAGES.max((pages.len() as f64 * REPEATED_MIN_RATIO).ceil() as usize);
+    for page in pages.iter_mut() {
+        let (number, height, lines) = (page.number, page.height, std::mem::take(&mut page.lines));
+        page.lines = lines.into_iter()
+            .filter(|line| {
+                if !in_page_margin(height, line) {
+                    return true;
+                }
+                let text = line.text();
+                if let Some(offset) = page_number_offset(&text, number) {
+                    return pages_per_offset.get(&offset).is_none_or(|count| *count < SEQUENCE_MIN_PAGES);
+                }
+                pages_per_line.get(&margin_signature(&text)).is_none_or(|count| *count < min_pages)
+            })
+            .collect();
+    }
+}
+
+fn in_page_margin(page_height: f64, line: &TextLine) -> bool {
+    line.y < page_height * PAGE_MARGIN_RATIO || line.y > page_height * (1.0 - PAGE_MARGIN_RATIO)
+}
+
+fn margin_signature(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_digit() { '#' } else { c })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+// Whether a margin line is a roman page number, and how far its value is from the page's own number
+fn page_number_offset(text: &str, page: u32) -> Option<(bool, i64)> {
+    let (roman, value) = page_number(text)?;
+    Some((roman, value as i64 - page as i64))
+}
+
+// "12", "- 12 -", "xii", "Page 12", "12 of 40", "12 / 40": whether the number is roman, and its value
+fn page_number(text: &str) -> Option<(bool, u32)> {
+    let text = text.trim().trim_matches(|c: char| c == '-' || c == '–' || c.is_whitespace()).to_lowercase();
+    let text = text.strip_prefix("page").unwrap_or(&text).trim();
+    let number = text.split_once(" of ")
+        .or_else(|| text.split_once('/'))
+        .map_or(text, |(page, _)| page.trim());
+
+    if !number.is_empty() && number.len() <= 4 && number.chars().all(|c| c.is_ascii_digit()) {
+        return number.parse().ok().map(|value| (false, value));
+    }
+    roman_value(number).map(|value| (true, value))
+}
+
+// Value of a well-formed lowercase roman numeral ("xiv" -> 14); "ill" or "civil" are words
+fn roman_value(text: &str) -> Option<u32> {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "m"), (900, "cm"), (500, "d"), (400, "cd"), (100, "c"), (90, "xc"), (50, "l"),
+        (40, "xl"), (10, "x"), (9, "ix"), (5, "v"), (4, "iv"), (1, "i"),
+    ];
+
+    // Read the numeral greedily, then accept it only when writing the value back gives the same text
+    let mut value = 0;
+    let mut rest = text;
+    for (numeral_value, numeral) in NUMERALS {
+        while let Some(after) = rest.strip_prefix(numeral) {
+            value += numeral_value;
+            rest = after;
+        }
+    }
+    if !rest.is_empty() || value == 0 {
+        return None;
+    }
+
+    let mut written = String::new();
+    let mut remaining = value;
+    for (numeral_value, numeral) in NUMERALS {
+        while remaining >= numeral_value {
+            written.push_str(numeral);
+            remaining -= numeral_value;
+        }
+    }
+    (written == text).then_some(value)
+}
+
+// Join the lines of a block: wrapped lines become running text (words hyphenated across
+// the break are rejoined), while short lines such as code or addresses keep their breaks
+fn join_lines(texts: &[String], lines: &[&TextLine], right_edge: f64) -> String {
+    let mut joined = String::new();
+
+    for (index, text) in texts.iter().enumerate() {
+        if index > 0 {
+            let previous = lines[index - 1];
+            let wrapped = previous.x_end() >= right_edge - previous.font_size * WRAP_MARGIN_EMS;
+            let hyphenated = joined.ends_with('-')
+                && joined.chars().rev().nth(1).is_some_and(|c| c.is_alphabetic())
+                && text.chars().next().is_some_and(|c| c.is_lowercase());
+
+            if !wrapped {
+                joined.push('\n');
+            } else if hyphenated {
+                joined.pop();
+            } else if !joined.ends_with('-') {
+                joined.push(' ');
+            }
+        }
+        joined.push_str(text);
+    }
+
+    joined.trim().to_string()
+}
+
+// Expand typographic ligatures, drop soft hyphens and collapse runs of spaces
+fn normalize_text(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
 
-    // Simple metadata extraction using filename for now
-    // TODO: Add proper PDF metadata extraction later
-    if let Some(filename) = path.file_stem() {
-        if let Some(title_str) = filename.to_str() {
-            metadata.push(DocumentElement::Title { 
-                text: title_str.to_string() 
-            });
+    for c in text.chars() {
+        match c {
+            'ﬀ' => normalized.push_str("ff"),
+            'ﬁ' => normalized.push_str("fi"),
+            'ﬂ' => normalized.push_str("fl"),
+            'ﬃ' => normalized.push_str("ffi"),
+            'ﬄ' => normalized.push_str("ffl"),
+            'ﬅ' | 'ﬆ' => normalized.push_str("st"),
+            '\u{AD}' => {},
+            '\u{A0}' | '\t' => normalized.push(' '),
+            _ => normalized.push(c),
         }
     }
 
+    normalized.split(' ')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to Metadata elements
+//-------------------------------------------------
+
+// Read document metadata from the Info dictionary, filling gaps from the XMP packet
+pub fn extract_pdf_metadata(document: &Document) -> Vec<DocumentElement> {
+    let info = info_dictionary(document);
+    let xmp = xmp_packet(document);
+
+    let field = |info_key: &[u8], xmp_names: &[&str]| -> Option<String> {
+        info.and_then(|info| info_string(document, info, info_key))
+            .or_else(|| {
+                let xmp = xmp.as_deref()?;
+                xmp_names.iter().find_map(|name| xmp_value(xmp, name))
+            })
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    let mut metadata = Vec::new();
+
+    if let Some(text) = field(b"Title", &["dc:title"]) {
+        metadata.push(DocumentElement::Title { text });
+    }
+    if let Some(text) = field(b"Author", &["dc:creator"]) {
+        metadata.push(DocumentElement::Author { text });
+    }
+    if let Some(text) = field(b"Subject", &["dc:description"]) {
+        metadata.push(DocumentElement::Description { text });
+    }
+    if let Some(text) = field(b"Keywords", &["pdf:Keywords", "dc:subject"]) {
+        metadata.push(DocumentElement::Keywords { text });
+    }
+    if let Some(text) = field(b"Creator", &["xmp:CreatorTool"]) {
+        metadata.push(DocumentElement::Creator { text });
+    }
+    if let Some(text) = field(b"Producer", &["pdf:Producer"]) {
+        metadata.push(DocumentElement::Producer { text });
+    }
+    if let Some(date) = field(b"CreationDate", &["xmp:CreateDate"]) {
+        metadata.push(DocumentElement::CreationDate { text: normalize_pdf_date(&date) });
+    }
+    if let Some(date) = field(b"ModDate", &["xmp:ModifyDate"]) {
+        metadata.push(DocumentElement::ModificationDate { text: normalize_pdf_date(&date) });
+    }
+    // Language lives in the catalog (/Lang) or in XMP
+    let language = document.catalog().ok()
+        .and_then(|catalog| info_string(document, catalog, b"Lang"))
+        .or_else(|| xmp_value(xmp.as_deref()?, "dc:language"))
+        .filter(|language| !language.trim().is_empty());
+    if let Some(text) = language {
+        metadata.push(DocumentElement::Language { text: text.trim().to_string() });
+    }
+
     metadata
 }
+
+fn info_dictionary(document: &Document) -> Option<&Dictionary> {
+    let info = document.trailer.get(b"Info").ok()?;
+    document.dereference(info).ok()?.1.as_dict().ok()
+}
+
+// Read a text string entry, following references
+fn info_string(document: &Document, dictionary: &Dictionary, key: &[u8]) -> Option<String> {
+    match dictionary.get_deref(key, document).ok()? {
+        Object::String(bytes, _) => Some(decode_pdf_text(bytes)),
+        _ => None,
+    }
+}
+
+// PDF text strings are UTF-16BE or UTF-8 with a byte order mark, otherwise PDFDocEncoding
+fn decode_pdf_text(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = utf16.chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+    if let Some(utf8) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return String::from_utf8_lossy(utf8).to_string();
+    }
+    // PDFDocEncoding matches Latin-1 for printable text
+    bytes.iter().map(|&byte| byte as char).collect()
+}
+
+// The XMP metadata stream referenced from the catalog
+fn xmp_packet(document: &Document) -> Option<String> {
+    let stream = document.catalog().ok()?
+        .get_deref(b"Metadata", document).ok()?
+        .as_stream().ok()?;
+    let content = stream.decompressed_content().unwrap_or_else(|_| stream.content.clone());
+    Some(String::from_utf8_lossy(&content).to_string())
+}
+
+// First value of an XMP property, written either as an element (possibly an rdf:Alt/Bag/Seq) or an attribute
+fn xmp_value(xmp: &str, name: &str) -> Option<String> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+
+    if let Some(start) = xmp.find(&open) {
+        let after_open = &xmp[start + open.len()..];
+        let body_start = after_open.find('>')? + 1;
+        let body = &after_open[body_start..after_open.find(&close)?];
+
+        // Arrays keep their values in rdf:li elements
+        let value = match body.find("<rdf:li") {
+            Some(li_start) => {
+                let li = &body[li_start..];
+                let li_body = &li[li.find('>')? + 1..];
+                &li_body[..li_body.find("</rdf:li>")?]
+            },
+            None => body,
+        };
+        return Some(decode_xml_entities(value.trim()));
+    }
+
+    let attribute = format!("{}=\"", name);
+    let start = xmp.find(&attribute)? + attribute.len();
+    let end = xmp[start..].find('"')?;
+    Some(decode_xml_entities(&xmp[start..start + end]))
+}
+
+fn decode_xml_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// Turn a PDF date (D:YYYYMMDDHHmmSSOHH'mm') into RFC 3339; XMP dates and unknown formats pass through
+fn normalize_pdf_date(date: &str) -> String {
+    let Some(raw) = date.strip_prefix("D:") else {
+        return date.to_string();
+    };
+
+    let digits: String = raw.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let number = |start: usize, len: usize, default: u32| -> u32 {
+        digits.get(start..start + len).and_then(|part| part.parse().ok()).unwrap_or(default)
+    };
+    let Some(year) = digits.get(0..4).and_then(|part| part.parse::<i32>().ok()) else {
+        return date.to_string();
+    };
+
+    // Timezone: Z, or +HH'mm' / -HH'mm'
+    let zone = &raw[digits.len()..];
+    let offset_seconds = match zone.chars().next() {
+        Some(sign @ ('+' | '-')) => {
+            let zone_digits: String = zone.chars().filter(|c| c.is_ascii_digit()).collect();
+            let hours: i32 = zone_digits.get(0..2).and_then(|part| part.parse().ok()).unwrap_or(0);
+            let minutes: i32 = zone_digits.get(2..4).and_then(|part| part.parse().ok()).unwrap_or(0);
+            let seconds = hours * 3600 + minutes * 60;
+            if sign == '-' { -seconds } else { seconds }
+        },
+        _ => 0,
+    };
+
+    FixedOffset::east_opt(offset_seconds)
+        .and_then(|offset| {
+            offset.with_ymd_and_hms(
+                year,
+                number(4, 2, 1),
+                number(6, 2, 1),
+                number(8, 2, 0),
+                number(10, 2, 0),
+                number(12, 2, 0),
+            ).single()
+        })
+        .map(|datetime| datetime.to_rfc3339())
+        .unwrap_or_else(|| date.to_string())
+}
+
+// PDF implementation of the common Parser trait
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PdfParser {
+    // Only parse these pages (every page when None)
+    pub pages: Option<PageRange>,
+}
+
+impl PdfParser {
+    pub fn new() -> PdfParser {
+        PdfParser::default()
+    }
+
+    pub fn with_pages(mut self, pages: PageRange) -> PdfParser {
+        self.pages = Some(pages);
+        self
+    }
+}
+
+impl Parser for PdfParser {
+    fn name(&self) -> &str {
+        "pdf"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pdf"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/pdf"]
+    }
+
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(b"%PDF-")
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        let elements = parse_pdf_bytes_pages(bytes, self.pages)?;
+        warn_if_no_text(&elements, None, events);
+        Ok(elements)
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        let elements = parse_pdf_file_pages(path, self.pages)?;
+        warn_if_no_text(&elements, Some(path), events);
+        Ok(elements)
+    }
+}
+
+// Scanned PDFs have no text layer, which otherwise looks like an empty success
+fn warn_if_no_text(elements: &[DocumentElement], path: Option<&Path>, events: &dyn EventSink) {
+    // Any content counts (a PDF of headings, lists or tables has text); metadata and page markers do not
+    if elements.iter().all(|element| element.is_metadata() || matches!(element, DocumentElement::Page { .. })) {
+        events.emit(ParseEvent::Warning {
+            path: path.map(|path| path.to_path_buf()),
+            message: "no extractable text found (scanned PDF?)".to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A line of chunks given as (x, x_end, text)
+    fn text_line(y: f64, font_size: f64, chunks: &[(f64, f64, &str)]) -> TextLine {
+        let chunks = chunks.iter()
+            .map(|(x, x_end, text)| TextChunk { text: text.to_string(), x: *x, x_end: *x_end })
+            .collect();
+        TextLine { y, font_size, chunks }
+    }
+
+    fn layout(lines: Vec<TextLine>) -> PageLayout {
+        PageLayout { number: 1, width: 612.0, height: 792.0, lines, gutters: Vec::new() }
+    }
+
+    // A page with one line in the body and one in the footer
+    fn page(number: u32, footer: &str) -> PageLayout {
+        let lines = vec![text_line(400.0, 10.0, &[(72.0, 200.0, "Body text.")]), text_line(760.0, 10.0, &[(72.0, 200.0, footer)])];
+        PageLayout { number, ..layout(lines) }
+    }
+
+    fn footers(pages: &[PageLayout]) -> Vec<Option<String>> {
+        pages.iter().map(|page| page.lines.get(1).map(TextLine::text)).collect()
+    }
+
+    // Records the warnings a parse emits
+    struct Warnings(std::cell::RefCell<Vec<String>>);
+
+    impl EventSink for Warnings {
+        fn emit(&self, event: ParseEvent) {
+            if let ParseEvent::Warning { message, .. } = event {
+                self.0.borrow_mut().push(message);
+            }
+        }
+    }
+
+    #[test]
+    fn any_content_counts_as_text() {
+        let warnings = Warnings(Default::default());
+        let title = DocumentElement::Title { text: "Scan".to_string() };
+        warn_if_no_text(&[title.clone(), DocumentElement::Page { number: 1 }], None, &warnings);
+        assert_eq!(warnings.0.borrow().len(), 1);
+
+        let table = DocumentElement::Table { headers: vec!["a".to_string()], rows: Vec::new() };
+        warn_if_no_text(&[title, DocumentElement::Page { number: 1 }, table], None, &warnings);
+        assert_eq!(warnings.0.borrow().len(), 1);
+    }
+
+    #[test]
+    fn pdf_dates_become_rfc_3339() {
+        assert_eq!(normalize_pdf_date("D:20240102030405+02'00'"), "2024-01-02T03:04:05+02:00");
+        assert_eq!(normalize_pdf_date("D:2023"), "2023-01-01T00:00:00+00:00");
+        assert_eq!(normalize_pdf_date("2024-05-06T07:08:09Z"), "2024-05-06T07:08:09Z");
+    }
+
+    #[test]
+    fn text_strings_and_xmp_values_are_decoded() {
+        assert_eq!(decode_pdf_text(&[0xFE, 0xFF, 0x00, 0x52, 0x00, 0xE9]), "Ré");
+        assert_eq!(decode_pdf_text(b"Caf\xe9"), "Café");
+
+        let xmp = r#"<rdf:Description pdf:Producer="Tex &amp; Co">
+            <dc:title><rdf:Alt><rdf:li xml:lang="x-default">On &lt;Growth&gt;</rdf:li></rdf:Alt></dc:title>
+        </rdf:Description>"#;
+        assert_eq!(xmp_value(xmp, "dc:title").as_deref(), Some("On <Growth>"));
+        assert_eq!(xmp_value(xmp, "pdf:Producer").as_deref(), Some("Tex & Co"));
+        assert_eq!(xmp_value(xmp, "dc:creator"), None);
+    }
+
+    #[test]
+    fn page_ranges_parse_and_select_pages() {
+        let range: PageRange = "3-".parse().unwrap();
+        assert!(range.contains(3) && range.contains(400) && !range.contains(2));
+        assert_eq!("5".parse::<PageRange>().unwrap(), PageRange::new(5, Some(5)));
+        assert!("10-3".parse::<PageRange>().is_err());
+        assert!("0-2".parse::<PageRange>().is_err());
+    }
+
+    #[test]
+    fn selected_pages_are_marked_in_order() {
+        let elements = parse_pdf_file_pages(Path::new("test_files/pdf_test.pdf"), Some(PageRange::new(2, Some(3)))).unwrap();
+        let pages: Vec<u32> = elements.iter()
+            .filter_map(|element| match element {
+                DocumentElement::Page { number } => Some(*number),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(pages, vec![2, 3]);
+        // Nothing but metadata comes before the first page
+        let first_page = elements.iter().position(|element| matches!(element, DocumentElement::Page { .. })).unwrap();
+        assert!(elements[..first_page].iter().all(DocumentElement::is_metadata));
+    }
+
+    #[test]
+    fn headings_lists_and_tables_come_from_the_layout() {
+        let page = layout(vec![
+            text_line(100.0, 18.0, &[(72.0, 300.0, "Results")]),
+            text_line(130.0, 10.0, &[(72.0, 540.0, "The experiment ran for three weeks and every sample was")]),
+            text_line(142.0, 10.0, &[(72.0, 400.0, "measured twice.")]),
+            text_line(166.0, 10.0, &[(72.0, 300.0, "1. First finding")]),
+            text_line(178.0, 10.0, &[(72.0, 300.0, "2. Second finding")]),
+            text_line(202.0, 10.0, &[(72.0, 120.0, "Sample"), (200.0, 240.0, "Mass")]),
+            text_line(214.0, 10.0, &[(72.0, 100.0, "A"), (200.0, 230.0, "1.5")]),
+            text_line(226.0, 10.0, &[(72.0, 100.0, "B"), (200.0, 230.0, "2.0")]),
+        ]);
+
+        assert_eq!(layout_elements(&[page]), vec![
+            DocumentElement::Page { number: 1 },
+            DocumentElement::Heading { level: 1, text: "Results".to_string() },
+            DocumentElement::Paragraph { text: "The experiment ran for three weeks and every sample was measured twice.".to_string() },
+            DocumentElement::List { items: vec!["First finding".to_string(), "Second finding".to_string()], ordered: true },
+            DocumentElement::Table {
+                headers: vec!["Sample".to_string(), "Mass".to_string()],
+                rows: vec![vec!["A".to_string(), "1.5".to_string()], vec!["B".to_string(), "2.0".to_string()]],
+            },
+        ]);
+    }
+
+    #[test]
+    fn only_recurring_heading_sizes_rank() {
+        let body = |y: f64| text_line(y, 10.0, &[(72.0, 540.0, "Every sample was measured twice and the results agree.")]);
+        let first = layout(vec![
+            text_line(80.0, 30.0, &[(300.0, 360.0, "Cons")]),
+            text_line(140.0, 16.0, &[(72.0, 300.0, "Chapter One")]),
+            body(170.0),
+            text_line(200.0, 13.0, &[(72.0, 300.0, "Method")]),
+            body(230.0),
+        ]);
+        let second = PageLayout { number: 2, ..layout(vec![
+            text_line(140.0, 16.0, &[(72.0, 300.0, "Chapter Two")]),
+            body(170.0),
+            text_line(200.0, 13.0, &[(72.0, 300.0, "Results")]),
+            body(230.0),
+            text_line(260.0, 16.0, &[(72.0, 120.0, "5 10")]),
+        ]) };
+
+        let headings: Vec<DocumentElement> = layout_elements(&[first, second]).into_iter()
+            .filter(|element| !matches!(element, DocumentElement::Page { .. }))
+            .filter(|element| !matches!(element, DocumentElement::Paragraph { text } if text.starts_with("Every")))
+            .collect();
+        assert_eq!(headings, vec![
+            DocumentElement::Paragraph { text: "Cons".to_string() },
+            DocumentElement::Heading { level: 1, text: "Chapter One".to_string() },
+            DocumentElement::Heading { level: 2, text: "Method".to_string() },
+            DocumentElement::Heading { level: 1, text: "Chapter Two".to_string() },
+            DocumentElement::Heading { level: 2, text: "Results".to_string() },
+            DocumentElement::Paragraph { text: "5 10".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn ligatures_and_hyphenated_line_breaks_are_repaired() {
+        assert_eq!(normalize_text("e\u{FB03}cient \u{FB01}nal\u{AD}ly\u{A0} done"), "efficient finally done");
+
+        let lines = [
+            text_line(100.0, 10.0, &[(72.0, 540.0, "A wrapped line ends with a hyphen-")]),
+            text_line(112.0, 10.0, &[(72.0, 300.0, "ated word.")]),
+            text_line(124.0, 10.0, &[(72.0, 200.0, "Short line")]),
+        ];
+        let texts: Vec<String> = lines.iter().map(TextLine::text).collect();
+        let lines: Vec<&TextLine> = lines.iter().collect();
+        assert_eq!(join_lines(&texts, &lines, 540.0), "A wrapped line ends with a hyphenated word.\nShort line");
+    }
+
+    #[test]
+    fn two_columns_are_read_one_after_the_other() {
+        let mut lines = vec![text_line(80.0, 16.0, &[(150.0, 460.0, "A title across both columns")])];
+        for row in 0..6 {
+            let y = 120.0 + row as f64 * 12.0;
+            lines.push(text_line(y, 10.0, &[(72.0, 290.0, &format!("left {}", row)), (320.0, 540.0, &format!("right {}", row))]));
+        }
+        let mut page = layout(lines);
+        order_columns(&mut page);
+
+        let texts: Vec<String> = page.lines.iter().map(TextLine::text).collect();
+        let expected: Vec<String> = std::iter::once("A title across both columns".to_string())
+            .chain((0..6).map(|row| format!("left {}", row)))
+            .chain((0..6).map(|row| format!("right {}", row)))
+            .collect();
+        assert_eq!(texts, expected);
+        assert_eq!(page.gutters.len(), 1);
+    }
+
+    #[test]
+    fn only_well_formed_roman_numerals_are_page_numbers() {
+        assert_eq!(page_number("xiv"), Some((true, 14)));
+        assert_eq!(page_number("- vii -"), Some((true, 7)));
+        assert_eq!(page_number("Page 12 of 40"), Some((false, 12)));
+        assert_eq!(page_number("civil"), None);
+        assert_eq!(page_number("ill"), None);
+        assert_eq!(page_number("iiii"), None);
+    }
+
+    #[test]
+    fn page_numbers_counting_with_the_pages_are_stripped() {
+        let mut pages = vec![page(1, "i"), page(2, "ii"), page(3, "1"), page(4, "2")];
+        strip_headers_and_footers(&mut pages);
+        assert_eq!(footers(&pages), vec![None, None, None, None]);
+    }
+
+    #[test]
+    fn lone_numbers_and_words_in_the_margin_are_kept() {
+        let mut pages = vec![page(1, "vii"), page(2, "1990"), page(3, "Civil")];
+        strip_headers_and_footers(&mut pages);
+        assert_eq!(footers(&pages), vec![Some("vii".to_string()), Some("1990".to_string()), Some("Civil".to_string())]);
+
+        let mut single = vec![page(1, "42")];
+        strip_headers_and_footers(&mut single);
+        assert_eq!(footers(&single), vec![Some("42".to_string())]);
+    }
+
+    #[test]
+    fn repeated_running_headers_are_stripped() {
+        let mut pages: Vec<PageLayout> = (1..=4).map(|number| page(number, "Annual Report")).collect();
+        strip_headers_and_footers(&mut pages);
+        assert!(footers(&pages).iter().all(Option::is_none));
+    }
+}
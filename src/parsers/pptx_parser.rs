@@ -0,0 +1,363 @@
+// pptx_parser.rs
+
+use roxmltree::Node;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::EventSink;
+use crate::parsers::documents_types::{fallback_title, DocumentElement};
+use crate::parsers::ooxml::{attribute, child, children, document_properties, is_named, open_package, parse_xml, read_part, relationship_id, relationships, Package, Relationship};
+use crate::parsers::parser::Parser;
+
+const PRESENTATION_PART: &str = "ppt/presentation.xml";
+
+// Main parsing function
+pub fn parse_pptx_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    // Read the PPTX file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+
+    let mut elements = parse_pptx_bytes(&bytes).map_err(|error| error.with_path(path))?;
+
+    fallback_title(&mut elements, path);
+
+    Ok(elements)
+}
+
+// Parse an in-memory PPTX: document properties, then every slide in presentation order,
+// each introduced by its Slide marker and followed by its speaker notes
+pub fn parse_pptx_bytes(bytes: &[u8]) -> Result<Vec<DocumentElement>> {
+    let mut package = open_package(bytes)?;
+    let mut elements = document_properties(&mut package)?;
+
+    for (index, slide_part) in slide_parts(&mut package)?.into_iter().enumerate() {
+        elements.push(DocumentElement::Slide { number: index as u32 + 1 });
+        elements.extend(parse_slide(&mut package, &slide_part)?);
+    }
+
+    Ok(elements)
+}
+
+// PPTX implementation of the common Parser trait
+pub struct PptxParser;
+
+impl Parser for PptxParser {
+    fn name(&self) -> &str {
+        "pptx"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pptx", "pptm", "potx"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/vnd.openxmlformats-officedocument.presentationml.presentation"]
+    }
+
+    // A zip whose first entries name PowerPoint parts
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(b"PK\x03\x04") && bytes.windows(4).any(|window| window == b"ppt/")
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], _events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_pptx_bytes(bytes)
+    }
+
+    fn parse_path(&self, path: &Path, _events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_pptx_file(path)
+    }
+}
+
+// Slide parts in the order of the presentation's slide list (not the order of the file names)
+fn slide_parts(package: &mut Package) -> Result<Vec<String>> {
+    let xml = read_part(package, PRESENTATION_PART)?
+        .ok_or_else(|| OxidocError::malformed(format!("PPTX has no {}", PRESENTATION_PART)))?;
+    let presentation = parse_xml(&xml, PRESENTATION_PART)?;
+    let relationships = relationships(package, PRESENTATION_PART)?;
+
+    let Some(slide_list) = child(presentation.root_element(), "sldIdLst") else {
+        return Ok(Vec::new());
+    };
+    Ok(children(slide_list, "sldId")
+        .filter_map(|slide| relationship_id(slide, "id").and_then(|id| relationships.get(id)))
+        .map(|relationship| relationship.target.clone())
+        .collect())
+}
+
+fn parse_slide(package: &mut Package, slide_part: &str) -> Result<Vec<DocumentElement>> {
+    let Some(xml) = read_part(package, slide_part)? else {
+        return Ok(Vec::new());
+    };
+    let slide = parse_xml(&xml, slide_part)?;
+    let relationships = relationships(package, slide_part)?;
+
+    let mut elements = Vec::new();
+    if let Some(shapes) = slide.descendants().find(|node| is_named(*node, "spTree")) {
+        extract_shapes(shapes, &relationships, &mut elements);
+    }
+
+    // Speaker notes live in the notes slide linked from the slide
+    let notes_part = relationships.values()
+        .find(|relationship| relationship.kind == "notesSlide")
+        .map(|relationship| relationship.target.clone());
+    if let Some(notes_part) = notes_part
+        && let Some(notes) = parse_notes(package, &notes_part)? {
+        elements.push(DocumentElement::SpeakerNotes { text: notes });
+    }
+
+    Ok(elements)
+}
+
+// Text of the notes placeholder; the slide image and slide number placeholders are skipped
+fn parse_notes(package: &mut Package, notes_part: &str) -> Result<Option<String>> {
+    let Some(xml) = read_part(package, notes_part)? else {
+        return Ok(None);
+    };
+    let notes = parse_xml(&xml, notes_part)?;
+
+    let text = notes.descendants()
+        .filter(|node| is_named(*node, "sp") && placeholder_type(*node) == Some("body"))
+        .filter_map(|shape| child(shape, "txBody"))
+        .flat_map(|body| children(body, "p"))
+        .map(paragraph_text)
+        .filter(|text| !text.trim().is_empty())
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    Ok((!text.trim().is_empty()).then(|| text.trim().to_string()))
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to slide content
+//-------------------------------------------------
+
+// Walk the shapes of a slide (or of a group) in z-order, which is also the usual reading order
+fn extract_shapes(shapes: Node, relationships: &HashMap<String, Relationship>, elements: &mut Vec<DocumentElement>) {
+    for shape in shapes.children().filter(|node| node.is_element()) {
+        match shape.tag_name().name() {
+            "sp" => extract_text_shape(shape, relationships, elements),
+            "grpSp" => extract_shapes(shape, relationships, elements),
+            "graphicFrame" => {
+                if let Some(table) = shape.descendants().find(|node| is_named(*node, "tbl")) {
+                    elements.push(extract_table(table));
+                }
+            },
+            "pic" => elements.extend(extract_picture(shape, relationships)),
+            _ => {},
+        }
+    }
+}
+
+fn extract_text_shape(shape: Node, relationships: &HashMap<String, Relationship>, elements: &mut Vec<DocumentElement>) {
+    let placeholder = placeholder_type(shape);
+    // Footer, date and slide number placeholders repeat on every slide
+    if matches!(placeholder, Some("dt" | "ftr" | "sldNum")) {
+        return;
+    }
+    let Some(body) = child(shape, "txBody") else {
+        return;
+    };
+
+    if matches!(placeholder, Some("title" | "ctrTitle")) {
+        let text = children(body, "p")
+            .map(paragraph_text)
+            .filter(|text| !text.trim().is_empty())
+            .collect::<Vec<String>>()
+            .join(" ");
+        if !text.trim().is_empty() {
+            elements.push(DocumentElement::Heading { level: 1, text: text.trim().to_string() });
+        }
+        elements.extend(extract_links(body, relationships));
+        return;
+    }
+
+    // Body placeholders are bulleted by the slide master unless a paragraph says otherwise;
+    // plain text boxes only have bullets that are set explicitly
+    let bulleted_by_default = matches!(placeholder, Some("body" | "obj"));
+
+    for paragraph in children(body, "p") {
+        let text = paragraph_text(paragraph).trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+        match bullet(paragraph, bulleted_by_default) {
+            Some(ordered) => match elements.last_mut() {
+                // Consecutive bullets of the same kind form one list, whatever their level
+                Some(DocumentElement::List { items, ordered: list_ordered }) if *list_ordered == ordered => items.push(text),
+                _ => elements.push(DocumentElement::List { items: vec![text], ordered }),
+            },
+            None => elements.push(DocumentElement::Paragraph { text }),
+        }
+    }
+
+    elements.extend(extract_links(body, relationships));
+}
+
+// Some(ordered) when the paragraph is a list item
+fn bullet(paragraph: Node, bulleted_by_default: bool) -> Option<bool> {
+    let properties = child(paragraph, "pPr");
+    let has = |name: &str| properties.is_some_and(|properties| child(properties, name).is_some());
+
+    if has("buNone") {
+        None
+    } else if has("buAutoNum") {
+        Some(true)
+    } else if has("buChar") || has("buBlip") || bulleted_by_default {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+// Runs, fields (dates, slide numbers) and line breaks of a text paragraph
+fn paragraph_text(paragraph: Node) -> String {
+    let mut text = String::new();
+
+    for node in paragraph.children().filter(|node| node.is_element()) {
+        match node.tag_name().name() {
+            "r" | "fld" => {
+                if let Some(run_text) = child(node, "t").and_then(|t| t.text()) {
+                    text.push_str(run_text);
+                }
+            },
+            "br" => text.push('\n'),
+            _ => {},
+        }
+    }
+
+    text
+}
+
+// Cells keep one line per paragraph; the first row is taken as the header row
+fn extract_table(table: Node) -> DocumentElement {
+    let mut rows: Vec<Vec<String>> = children(table, "tr")
+        .map(|row| {
+            children(row, "tc")
+                .map(|cell| {
+                    cell.descendants()
+                        .filter(|node| is_named(*node, "p"))
+                        .map(paragraph_text)
+                        .map(|text| text.trim().to_string())
+                        .filter(|text| !text.is_empty())
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                })
+                .collect()
+        })
+        .collect();
+
+    let headers = if rows.is_empty() { Vec::new() } else { rows.remove(0) };
+    DocumentElement::Table { headers, rows }
+}
+
+// Click actions of runs that point at a URL
+fn extract_links(body: Node, relationships: &HashMap<String, Relationship>) -> Vec<DocumentElement> {
+    body.descendants()
+        .filter(|node| is_named(*node, "r"))
+        .filter_map(|run| {
+            let id = child(run, "rPr")
+                .and_then(|properties| child(properties, "hlinkClick"))
+                .and_then(|click| relationship_id(click, "id"))?;
+            let relationship = relationships.get(id).filter(|relationship| relationship.external)?;
+            let text = child(run, "t").and_then(|t| t.text())?.trim().to_string();
+            (!text.is_empty()).then(|| DocumentElement::Link { text, url: relationship.target.clone() })
+        })
+        .collect()
+}
+
+// Pictures: the description (or name) is the alt text, the embedded media part the url
+fn extract_picture(picture: Node, relationships: &HashMap<String, Relationship>) -> Option<DocumentElement> {
+    let properties = picture.descendants().find(|node| is_named(*node, "cNvPr"))?;
+    let alt = attribute(properties, "descr")
+        .or_else(|| attribute(properties, "title"))
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    let url = picture.descendants()
+        .find(|node| is_named(*node, "blip"))
+        .and_then(|blip| relationship_id(blip, "embed").or_else(|| relationship_id(blip, "link")))
+        .and_then(|id| relationships.get(id))
+        .map(|relationship| relationship.target.clone());
+
+    if alt.is_empty() && url.is_none() {
+        return None;
+    }
+    Some(DocumentElement::Image { alt, url })
+}
+
+// <p:nvSpPr><p:nvPr><p:ph type="title"/>; placeholders without a type are body placeholders
+fn placeholder_type<'a>(shape: Node<'a, '_>) -> Option<&'a str> {
+    let placeholder = child(shape, "nvSpPr")
+        .and_then(|properties| child(properties, "nvPr"))
+        .and_then(|properties| child(properties, "ph"))?;
+    Some(attribute(placeholder, "type").unwrap_or("body"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    const NAMESPACES: &str = r#"xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships""#;
+
+    fn pptx(parts: &[(&str, String)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, xml) in parts {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(xml.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn relationships_xml(targets: &[(&str, &str, &str)]) -> String {
+        let relationships: String = targets.iter()
+            .map(|(id, kind, target)| format!(
+                r#"<Relationship Id="{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/{}" Target="{}"/>"#,
+                id, kind, target,
+            ))
+            .collect();
+        format!(r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{}</Relationships>"#, relationships)
+    }
+
+    // A shape holding one paragraph per text, in the given placeholder
+    fn shape(placeholder: &str, texts: &[&str]) -> String {
+        let paragraphs: String = texts.iter().map(|text| format!("<a:p><a:r><a:t>{}</a:t></a:r></a:p>", text)).collect();
+        format!(
+            r#"<p:sp><p:nvSpPr><p:nvPr><p:ph type="{}"/></p:nvPr></p:nvSpPr><p:txBody>{}</p:txBody></p:sp>"#,
+            placeholder, paragraphs,
+        )
+    }
+
+    fn slide_xml(shapes: &[String]) -> String {
+        format!("<p:sld {}><p:cSld><p:spTree>{}</p:spTree></p:cSld></p:sld>", NAMESPACES, shapes.concat())
+    }
+
+    #[test]
+    fn slides_follow_the_slide_list_with_their_notes() {
+        let bytes = pptx(&[
+            ("ppt/presentation.xml", format!(
+                r#"<p:presentation {}><p:sldIdLst><p:sldId id="256" r:id="rId2"/><p:sldId id="257" r:id="rId1"/></p:sldIdLst></p:presentation>"#,
+                NAMESPACES,
+            )),
+            ("ppt/_rels/presentation.xml.rels", relationships_xml(&[("rId1", "slide", "slides/slide1.xml"), ("rId2", "slide", "slides/slide2.xml")])),
+            ("ppt/slides/slide2.xml", slide_xml(&[shape("title", &["Agenda"]), shape("body", &["One", "Two"]), shape("ftr", &["Confidential"])])),
+            ("ppt/slides/_rels/slide2.xml.rels", relationships_xml(&[("rId1", "notesSlide", "../notesSlides/notesSlide1.xml")])),
+            ("ppt/notesSlides/notesSlide1.xml", format!(
+                "<p:notes {}><p:cSld><p:spTree>{}{}</p:spTree></p:cSld></p:notes>",
+                NAMESPACES, shape("sldNum", &["1"]), shape("body", &["Say hello"]),
+            )),
+            ("ppt/slides/slide1.xml", slide_xml(&[shape("title", &["Thanks"])])),
+        ]);
+
+        assert_eq!(parse_pptx_bytes(&bytes).unwrap(), vec![
+            DocumentElement::Slide { number: 1 },
+            DocumentElement::Heading { level: 1, text: "Agenda".to_string() },
+            DocumentElement::List { items: vec!["One".to_string(), "Two".to_string()], ordered: false },
+            DocumentElement::SpeakerNotes { text: "Say hello".to_string() },
+            DocumentElement::Slide { number: 2 },
+            DocumentElement::Heading { level: 1, text: "Thanks".to_string() },
+        ]);
+    }
+}
@@ -0,0 +1,263 @@
+// registry.rs
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::{ElementCounts, EventSink, ParseEvent};
+use crate::parsers::asciidoc_parser::AsciidocParser;
+use crate::parsers::code_parser::CodeParser;
+use crate::parsers::documents_types::DocumentElement;
+use crate::parsers::docx_parser::DocxParser;
+use crate::parsers::email_parser::EmailParser;
+use crate::parsers::epub_parser::EpubParser;
+use crate::parsers::html_parser::HtmlParser;
+use crate::parsers::ipynb_parser::IpynbParser;
+use crate::parsers::latex_parser::LatexParser;
+use crate::parsers::markdown_parser::MarkdownParser;
+use crate::parsers::mediawiki_parser::MediaWikiParser;
+use crate::parsers::odt_parser::OdtParser;
+use crate::parsers::org_parser::OrgParser;
+use crate::parsers::parser::Parser;
+use crate::parsers::pdf_parser::PdfParser;
+use crate::parsers::pptx_parser::PptxParser;
+use crate::parsers::rst_parser::RstParser;
+use crate::parsers::rtf_parser::RtfParser;
+use crate::parsers::subtitle_parser::SubtitleParser;
+use crate::parsers::txt_parser::TxtParser;
+use crate::parsers::warc_parser::WarcParser;
+use crate::parsers::xlsx_parser::XlsxParser;
+
+// Number of leading bytes read from a file to sniff its format
+const MAGIC_PREFIX_LEN: u64 = 1024;
+
+// Picks a Parser by extension, MIME type or magic bytes.
+// Parsers registered later win, so callers can override the built-in ones.
+pub struct ParserRegistry {
+    parsers: Vec<Box<dyn Parser>>,
+}
+
+impl ParserRegistry {
+    // Empty registry
+    pub fn new() -> ParserRegistry {
+        ParserRegistry { parsers: Vec::new() }
+    }
+
+    // Registry with every parser shipped by oxidoc
+    pub fn with_defaults() -> ParserRegistry {
+        let mut registry = ParserRegistry::new();
+        registry.register(HtmlParser);
+        registry.register(PdfParser::new());
+        registry.register(TxtParser);
+        registry.register(MarkdownParser);
+        registry.register(DocxParser);
+        registry.register(PptxParser);
+        registry.register(XlsxParser::new());
+        registry.register(EpubParser);
+        registry.register(OdtParser);
+        registry.register(RtfParser);
+        registry.register(IpynbParser::new());
+        registry.register(CodeParser);
+        registry.register(EmailParser);
+        registry.register(SubtitleParser);
+        registry.register(LatexParser);
+        registry.register(RstParser);
+        registry.register(AsciidocParser);
+        registry.register(OrgParser);
+        registry.register(MediaWikiParser);
+        registry.register(WarcParser);
+        registry
+    }
+
+    pub fn register<P: Parser + 'static>(&mut self, parser: P) {
+        self.parsers.push(Box::new(parser));
+    }
+
+    pub fn parsers(&self) -> impl Iterator<Item = &dyn Parser> {
+        self.parsers.iter().rev().map(|parser| parser.as_ref())
+    }
+
+    // Find a parser by file extension (with or without the leading dot)
+    pub fn for_extension(&self, extension: &str) -> Option<&dyn Parser> {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+        self.parsers().find(|parser| parser.extensions().contains(&extension.as_str()))
+    }
+
+    // Find a parser by MIME type, ignoring parameters such as "; charset=utf-8"
+    pub fn for_mime_type(&self, mime_type: &str) -> Option<&dyn Parser> {
+        let mime_type = mime_type.split(';').next().unwrap_or("").trim().to_lowercase();
+        self.parsers().find(|parser| parser.mime_types().contains(&mime_type.as_str()))
+    }
+
+    // Find a parser from the leading bytes of a document
+    pub fn for_magic(&self, bytes: &[u8]) -> Option<&dyn Parser> {
+        self.parsers().find(|parser| parser.matches_magic(bytes))
+    }
+
+    // Find a parser for a file: extension first, then magic bytes
+    pub fn detect_path(&self, path: &Path) -> Result<Option<&dyn Parser>> {
+        if let Some(parser) = path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| self.for_extension(extension)) {
+            return Ok(Some(parser));
+        }
+
+        let mut prefix = Vec::new();
+        File::open(path)
+            .and_then(|file| file.take(MAGIC_PREFIX_LEN).read_to_end(&mut prefix))
+            .map_err(|source| OxidocError::io(path, source))?;
+        Ok(self.for_magic(&prefix))
+    }
+
+    // Detect the format of a file and parse it, reporting start and completion to `events`
+    pub fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        match self.detect_path(path)? {
+            Some(parser) => {
+                let source = Some(path.to_path_buf());
+                events.emit(ParseEvent::Started { path: source.clone(), format: parser.name().to_string() });
+                let elements = parser.parse_path(path, events)?;
+                events.emit(ParseEvent::Finished {
+                    path: source,
+                    format: parser.name().to_string(),
+                    counts: ElementCounts::from_elements(&elements),
+                });
+                Ok(elements)
+            },
+            None => {
+                let format = path.extension()
+                    .map(|extension| extension.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+                Err(OxidocError::unsupported_format(format).with_path(path))
+            },
+        }
+    }
+
+    // Detect the format of a file and parse it record by record (see Parser::parse_records),
+    // reporting start and completion to `events`
+    pub fn parse_path_records(
+        &self,
+        path: &Path,
+        events: &dyn EventSink,
+        record: &mut dyn FnMut(Vec<DocumentElement>) -> Result<()>,
+    ) -> Result<()> {
+        match self.detect_path(path)? {
+            Some(parser) => {
+                let source = Some(path.to_path_buf());
+                events.emit(ParseEvent::Started { path: source.clone(), format: parser.name().to_string() });
+                let file = File::open(path).map_err(|source| OxidocError::io(path, source))?;
+                let mut counts = ElementCounts::default();
+                parser.parse_records(&mut BufReader::new(file), events, &mut |elements| {
+                    counts += ElementCounts::from_elements(&elements);
+                    record(elements)
+                }).map_err(|error| error.with_path(path))?;
+                events.emit(ParseEvent::Finished {
+                    path: source,
+                    format: parser.name().to_string(),
+                    counts,
+                });
+                Ok(())
+            },
+            None => {
+                let format = path.extension()
+                    .map(|extension| extension.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+                Err(OxidocError::unsupported_format(format).with_path(path))
+            },
+        }
+    }
+
+    // Detect the format of an in-memory document and parse it, reporting start and completion to `events`
+    pub fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        match self.for_magic(bytes) {
+            Some(parser) => {
+                events.emit(ParseEvent::Started { path: None, format: parser.name().to_string() });
+                let elements = parser.parse_bytes(bytes, events)?;
+                events.emit(ParseEvent::Finished {
+                    path: None,
+                    format: parser.name().to_string(),
+                    counts: ElementCounts::from_elements(&elements),
+                });
+                Ok(elements)
+            },
+            None => Err(OxidocError::unsupported_format("unrecognized document bytes")),
+        }
+    }
+}
+
+impl Default for ParserRegistry {
+    fn default() -> ParserRegistry {
+        ParserRegistry::with_defaults()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::fs;
+
+    // Claims ".txt" to check that later registrations win
+    struct ShoutingParser;
+
+    impl Parser for ShoutingParser {
+        fn name(&self) -> &str {
+            "shouting"
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["txt"]
+        }
+
+        fn parse_bytes(&self, bytes: &[u8], _events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+            Ok(vec![DocumentElement::Paragraph { text: String::from_utf8_lossy(bytes).to_uppercase() }])
+        }
+    }
+
+    #[test]
+    fn parsers_are_found_by_extension_mime_type_and_magic() {
+        let registry = ParserRegistry::with_defaults();
+        assert_eq!(registry.for_extension(".MD").map(|parser| parser.name()), Some("markdown"));
+        assert_eq!(registry.for_mime_type("text/html; charset=utf-8").map(|parser| parser.name()), Some("html"));
+        assert_eq!(registry.for_magic(b"%PDF-1.7\n").map(|parser| parser.name()), Some("pdf"));
+        assert!(registry.for_extension("unknown-extension").is_none());
+    }
+
+    #[test]
+    fn later_registrations_override_earlier_ones() {
+        let mut registry = ParserRegistry::with_defaults();
+        registry.register(ShoutingParser);
+        assert_eq!(registry.for_extension("txt").map(|parser| parser.name()), Some("shouting"));
+    }
+
+    #[test]
+    fn unrecognized_bytes_are_an_unsupported_format() {
+        let registry = ParserRegistry::with_defaults();
+        let error = registry.parse_bytes(&[0, 159, 146, 150], &()).unwrap_err();
+        assert!(matches!(error, OxidocError::UnsupportedFormat { .. }));
+    }
+
+    #[test]
+    fn dumps_are_streamed_one_record_per_page() {
+        let path = std::env::temp_dir().join(format!("oxidoc-dump-{}.xml", std::process::id()));
+        fs::write(&path, "<mediawiki>\
+            <page><title>One</title><ns>0</ns><revision><id>1</id><text>== Intro ==\nFirst.</text></revision></page>\
+            <page><title>Two</title><ns>0</ns><revision><id>2</id><text>Second.</text></revision></page>\
+            </mediawiki>").unwrap();
+
+        let finished = RefCell::new(None);
+        let sink = |event: ParseEvent| if let ParseEvent::Finished { counts, .. } = event {
+            *finished.borrow_mut() = Some(counts);
+        };
+        let mut records = Vec::new();
+        let result = ParserRegistry::with_defaults().parse_path_records(&path, &sink, &mut |elements| {
+            records.push(elements);
+            Ok(())
+        });
+        fs::remove_file(&path).unwrap();
+        result.unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert!(matches!(&records[1][0], DocumentElement::WikiPage { title, .. } if title == "Two"));
+        assert_eq!(finished.into_inner(), Some(ElementCounts::from_elements(&records.concat())));
+    }
+}
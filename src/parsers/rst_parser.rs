@@ -0,0 +1,946 @@
+// rst_parser.rs
+
+// reStructuredText, as written for docutils and Sphinx. Section titles take their level from the
+// order in which their adornment styles first appear; a lone top-level title is the document title,
+// and the docinfo field list after it (and the meta directive) give the other metadata. Literal
+// blocks ("::"), code directives and doctests become Code, bullet/enumerated/definition/field lists
+// become Lists, grid/simple tables and list-table/csv-table become Tables, admonitions become
+// Blockquotes ("Note: ..."). Inline markup is removed; external hyperlinks follow their block as Links.
+
+use std::fs;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::EventSink;
+use crate::parsers::documents_types::{fallback_title, Document, DocumentElement};
+use crate::parsers::markup::{
+    admonition_label, dedent, elements_text, expand_tabs, indentation, is_blank, join_lines,
+    push_list_item, push_quote, split_csv, strip_emphasis, table, ADMONITIONS,
+};
+use crate::parsers::parser::Parser;
+use crate::parsers::txt_parser::{decode_text, warn_if_latin1};
+
+// Characters section titles may be underlined (and overlined) with
+const ADORNMENT_CHARS: &str = "=-`:'\"~^_*+#<>.";
+
+const BULLETS: &[&str] = &["-", "*", "+", "•", "‣", "⁃"];
+
+// Directives whose content is source code (the argument, when given, is its language)
+const CODE_DIRECTIVES: &[&str] = &["code-block", "code", "sourcecode", "parsed-literal", "productionlist"];
+// Directives whose argument is not text: only their content is read
+const CONTAINER_DIRECTIVES: &[&str] = &[
+    "container", "only", "ifconfig", "compound", "hlist", "glossary", "rst-class", "class", "cssclass", "tabs",
+    "line-block",
+];
+// Directives that show nothing of the document text (tables of contents, includes, raw output...)
+const SKIPPED_DIRECTIVES: &[&str] = &[
+    "toctree", "contents", "index", "include", "literalinclude", "raw", "sectnum", "target-notes", "header",
+    "footer", "tabularcolumns", "autosummary", "default-role", "role", "title", "replace", "unicode", "date",
+    "currentmodule", "module", "moduleauthor", "sectionauthor", "codeauthor", "default-domain",
+];
+
+// Main parsing function
+pub fn parse_rst_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    // Parse into the document tree, then flatten it in reading order
+    Ok(parse_rst_document(path)?.to_elements())
+}
+
+// Parse a reStructuredText file into the hierarchical document tree
+pub fn parse_rst_document(path: &Path) -> Result<Document> {
+    parse_rst_path(path, &())
+}
+
+fn parse_rst_path(path: &Path, events: &dyn EventSink) -> Result<Document> {
+    // Read the file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+    let (source, encoding) = decode_text(&bytes);
+    warn_if_latin1(encoding, Some(path), events);
+
+    let mut document = parse_rst_str(&source);
+    fallback_title(&mut document.metadata, path);
+    Ok(document)
+}
+
+pub fn parse_rst_str(source: &str) -> Document {
+    let source = expand_tabs(source);
+    let lines: Vec<&str> = source.lines().collect();
+
+    let mut reader = Reader { styles: Vec::new(), highlight: None, metadata: Vec::new(), depth: 0 };
+    let elements = reader.blocks(&lines);
+
+    // A section title opening the document, with no other title of its level, is the document title
+    let mut metadata = Vec::new();
+    if let Some(DocumentElement::Heading { level: 1, text }) = elements.first()
+        && elements.iter().filter(|element| matches!(element, DocumentElement::Heading { level: 1, .. })).count() == 1
+        && !reader.metadata.iter().any(|element| matches!(element, DocumentElement::Title { .. })) {
+        metadata.push(DocumentElement::Title { text: text.clone() });
+    }
+    metadata.extend(reader.metadata);
+
+    Document::from_elements(metadata.into_iter().chain(elements).collect())
+}
+
+// reStructuredText implementation of the common Parser trait
+pub struct RstParser;
+
+impl Parser for RstParser {
+    fn name(&self) -> &str {
+        "rst"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["rst", "rest"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["text/x-rst", "text/prs.fallenstein.rst"]
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        let (source, encoding) = decode_text(bytes);
+        warn_if_latin1(encoding, None, events);
+        Ok(parse_rst_str(&source).to_elements())
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        Ok(parse_rst_path(path, events)?.to_elements())
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to body elements
+//-------------------------------------------------
+
+struct Reader {
+    // Adornment styles (character, overlined) in order of first appearance: their rank is the heading level
+    styles: Vec<(char, bool)>,
+    // Language of "::" literal blocks, set by the highlight directive
+    highlight: Option<String>,
+    metadata: Vec<DocumentElement>,
+    // Nesting of the blocks being read (1 for the document itself)
+    depth: usize,
+}
+
+impl Reader {
+    // Read a run of body elements; `lines` are dedented so that the blocks start at column 0
+    fn blocks(&mut self, lines: &[&str]) -> Vec<DocumentElement> {
+        self.depth += 1;
+        let mut out = Vec::new();
+        let mut literal_next = false;
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i].trim_end();
+            if line.is_empty() {
+                i += 1;
+                continue;
+            }
+
+            // An indented block is the literal block announced by "::", or else a block quote
+            if indentation(line) > 0 {
+                let end = block_end(lines, i, 1);
+                let block = dedent(&lines[i..end]);
+                if literal_next {
+                    out.push(DocumentElement::Code { code: block.join("\n"), language: self.highlight.clone(), inline: false });
+                } else {
+                    let nested = self.blocks(&block);
+                    push_quote(None, nested, &mut out);
+                }
+                literal_next = false;
+                i = end;
+                continue;
+            }
+            literal_next = false;
+            let next = lines.get(i + 1).map_or("", |next| next.trim_end());
+
+            // Section title between an overline and an underline
+            if let Some(c) = adornment(line)
+                && !next.is_empty()
+                && lines.get(i + 2).and_then(|under| adornment(under.trim_end())) == Some(c) {
+                self.heading(next.trim(), (c, true), &mut out);
+                i += 3;
+                continue;
+            }
+
+            // Transition
+            if adornment(line).is_some() && line.len() >= 4 && next.is_empty() {
+                i += 1;
+                continue;
+            }
+
+            // Section title with an underline only
+            if let Some(c) = adornment(next)
+                && !line.starts_with("..")
+                && (next.chars().count() >= line.chars().count() || next.len() >= 4) {
+                self.heading(line, (c, false), &mut out);
+                i += 2;
+                continue;
+            }
+
+            // Explicit markup: directives, comments, targets, footnotes
+            if line == ".." || line.starts_with(".. ") {
+                let end = block_end(lines, i + 1, 1);
+                self.explicit(&lines[i..end], &mut out);
+                i = end;
+                continue;
+            }
+
+            if line.starts_with("+-") || line.starts_with("+=") {
+                let end = lines[i..].iter()
+                    .position(|line| !line.starts_with('+') && !line.starts_with('|'))
+                    .map_or(lines.len(), |offset| i + offset);
+                out.extend(grid_table(&lines[i..end]));
+                i = end;
+                continue;
+            }
+
+            if let Some(columns) = simple_table_columns(line) {
+                let (element, end) = simple_table(lines, i, &columns);
+                out.extend(element);
+                i = end;
+                continue;
+            }
+
+            if let Some((ordered, _)) = list_item(line) {
+                i = self.list(lines, i, ordered, &mut out);
+                continue;
+            }
+
+            if field(line).is_some() {
+                i = self.field_list(lines, i, &mut out);
+                continue;
+            }
+
+            // Line block: every "| " line is kept on its own line
+            if line == "|" || line.starts_with("| ") {
+                let end = block_end_at(lines, i, |line| line.starts_with('|') || indentation(line) > 0);
+                let mut links = Vec::new();
+                let text = lines[i..end].iter()
+                    .map(|line| inline(line.trim_start_matches('|'), &mut links))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                out.push(DocumentElement::Paragraph { text: text.trim().to_string() });
+                out.extend(links);
+                i = end;
+                continue;
+            }
+
+            // Doctest block
+            if line.starts_with(">>>") {
+                let end = block_end_at(lines, i, |_| true);
+                out.push(DocumentElement::Code {
+                    code: lines[i..end].join("\n"),
+                    language: Some("python".to_string()),
+                    inline: false,
+                });
+                i = end;
+                continue;
+            }
+
+            // Definition list: a one-line term directly followed by its indented definition
+            if lines.get(i + 1).is_some_and(|next| !is_blank(next) && indentation(next) > 0) {
+                i = self.definition_list(lines, i, &mut out);
+                continue;
+            }
+
+            // Paragraph, which announces a literal block when it ends with "::"
+            let end = block_end_at(lines, i, |line| indentation(line) == 0);
+            let mut text = join_lines(&lines[i..end]);
+            if let Some(stripped) = text.strip_suffix("::") {
+                literal_next = true;
+                text = if stripped.is_empty() || stripped.ends_with(' ') {
+                    stripped.trim_end().to_string()
+                } else {
+                    format!("{}:", stripped)
+                };
+            }
+            if !text.is_empty() {
+                let mut links = Vec::new();
+                out.push(DocumentElement::Paragraph { text: inline(&text, &mut links) });
+                out.extend(links);
+            }
+            i = end;
+        }
+
+        self.depth -= 1;
+        out
+    }
+
+    fn heading(&mut self, title: &str, style: (char, bool), out: &mut Vec<DocumentElement>) {
+        let rank = match self.styles.iter().position(|known| *known == style) {
+            Some(rank) => rank,
+            None => {
+                self.styles.push(style);
+                self.styles.len() - 1
+            },
+        };
+        let mut links = Vec::new();
+        out.push(DocumentElement::Heading { level: (rank + 1).min(6) as u8, text: inline(title, &mut links) });
+        out.extend(links);
+    }
+
+    // Consecutive items of one list kind; returns the line after the list
+    fn list(&mut self, lines: &[&str], mut i: usize, ordered: bool, out: &mut Vec<DocumentElement>) -> usize {
+        let mut items = Vec::new();
+        let mut trailing = Vec::new();
+
+        while let Some(line) = lines.get(i) {
+            if is_blank(line) {
+                i += 1;
+                continue;
+            }
+            let Some((item_ordered, width)) = list_item(line) else { break };
+            if item_ordered != ordered {
+                break;
+            }
+            let end = block_end(lines, i + 1, 1);
+            let body = item_body(&lines[i..end], width);
+            let nested = self.blocks(&body.iter().map(String::as_str).collect::<Vec<_>>());
+            push_list_item(nested, &mut items, &mut trailing);
+            i = end;
+        }
+
+        if !items.is_empty() {
+            out.push(DocumentElement::List { items, ordered });
+        }
+        out.extend(trailing);
+        i
+    }
+
+    // Terms and their definitions, as "term: definition" items
+    fn definition_list(&mut self, lines: &[&str], mut i: usize, out: &mut Vec<DocumentElement>) -> usize {
+        let mut items = Vec::new();
+        let mut trailing = Vec::new();
+
+        while i < lines.len() && !is_blank(lines[i]) && indentation(lines[i]) == 0
+            && lines.get(i + 1).is_some_and(|next| !is_blank(next) && indentation(next) > 0) {
+            // Classifiers ("term : classifier") are dropped
+            let term = lines[i].split(" : ").next().unwrap_or_default();
+            let term = inline(term, &mut trailing);
+            let end = block_end(lines, i + 1, 1);
+            let definition = self.blocks(&dedent(&lines[i + 1..end]));
+            let mut parts = Vec::new();
+            push_list_item(definition, &mut parts, &mut trailing);
+            items.push(match parts.is_empty() {
+                true => term,
+                false => format!("{}: {}", term, parts.join(" ")),
+            });
+
+            i = end;
+            while lines.get(i).is_some_and(|line| is_blank(line)) {
+                i += 1;
+            }
+        }
+
+        out.push(DocumentElement::List { items, ordered: false });
+        out.extend(trailing);
+        i
+    }
+
+    // A field list is the docinfo when only section titles precede it, and "name: body" items otherwise
+    fn field_list(&mut self, lines: &[&str], mut i: usize, out: &mut Vec<DocumentElement>) -> usize {
+        let docinfo = self.depth == 1 && out.iter().all(|element| matches!(element, DocumentElement::Heading { .. }));
+        let mut items = Vec::new();
+        let mut links = Vec::new();
+
+        while let Some((name, body)) = lines.get(i).and_then(|line| field(line)) {
+            let end = block_end(lines, i + 1, 1);
+            let mut body_lines = vec![body];
+            body_lines.extend(&lines[i + 1..end]);
+            let value = inline(&join_lines(&body_lines), &mut links);
+
+            if docinfo {
+                self.metadata.extend(docinfo_element(name, value));
+            } else {
+                items.push(format!("{}: {}", inline(name, &mut links), value));
+            }
+            i = end;
+        }
+
+        if !items.is_empty() {
+            out.push(DocumentElement::List { items, ordered: false });
+        }
+        out.extend(links);
+        i
+    }
+
+    // Directives, footnotes and citations; comments, hyperlink targets and substitution definitions are skipped
+    fn explicit(&mut self, block: &[&str], out: &mut Vec<DocumentElement>) {
+        let first = block[0].get(2..).unwrap_or_default().trim();
+        if first.starts_with('_') || first.starts_with('|') {
+            return;
+        }
+
+        if let Some(rest) = first.strip_prefix('[')
+            && let Some((label, text)) = rest.split_once(']') {
+            let mut text_lines = vec![text];
+            text_lines.extend(&block[1..]);
+            let mut links = Vec::new();
+            let text = inline(&join_lines(&text_lines), &mut links);
+            out.push(DocumentElement::Paragraph { text: format!("[{}] {}", label, text) });
+            out.extend(links);
+            return;
+        }
+
+        let Some((name, argument)) = first.split_once("::") else { return };
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return;
+        }
+        self.directive(&name.to_lowercase(), argument.trim(), &dedent(&block[1..]), out);
+    }
+
+    fn directive(&mut self, name: &str, argument: &str, body: &[&str], out: &mut Vec<DocumentElement>) {
+        // Options are the field list opening the body, the content follows
+        let options_end = body.iter().position(|line| field(line).is_none()).unwrap_or(body.len());
+        let options: Vec<(String, String)> = body[..options_end].iter()
+            .filter_map(|line| field(line))
+            .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+            .collect();
+        let option = |key: &str| options.iter().find(|(name, _)| name == key).map(|(_, value)| value.clone());
+        let content = dedent(&body[options_end..]);
+        let mut links = Vec::new();
+
+        match name {
+            "highlight" => self.highlight = (!argument.is_empty()).then(|| argument.to_string()),
+            "meta" => {
+                for (key, value) in options {
+                    self.metadata.extend(docinfo_element(&key, value));
+                }
+            },
+            "math" => {
+                let tex = match content.is_empty() {
+                    true => argument.to_string(),
+                    false => content.join("\n"),
+                };
+                out.push(DocumentElement::Math { tex, environment: None });
+            },
+            name if CODE_DIRECTIVES.contains(&name) => {
+                let language = match argument.is_empty() {
+                    true if name == "parsed-literal" => None,
+                    true => self.highlight.clone(),
+                    false => Some(argument.to_string()),
+                };
+                out.push(DocumentElement::Code { code: content.join("\n"), language, inline: false });
+            },
+            name if ADMONITIONS.contains(&name) => {
+                let mut lines = vec![argument];
+                lines.extend(content);
+                let nested = self.blocks(&lines);
+                push_quote(Some(&admonition_label(name)), nested, out);
+            },
+            "admonition" => {
+                let nested = self.blocks(&content);
+                push_quote(Some(&inline(argument, &mut links)), nested, out);
+            },
+            "versionadded" | "versionchanged" | "versionremoved" | "deprecated" => {
+                let (version, text) = argument.split_once(' ').unwrap_or((argument, ""));
+                let label = match name {
+                    "versionadded" => format!("New in version {}", version),
+                    "versionchanged" => format!("Changed in version {}", version),
+                    "versionremoved" => format!("Removed in version {}", version),
+                    _ => format!("Deprecated since version {}", version),
+                };
+                let mut lines = vec![text];
+                lines.extend(content);
+                let nested = self.blocks(&lines);
+                push_quote(Some(&label), nested, out);
+            },
+            "image" | "figure" => {
+                // The caption of a figure (its first paragraph) stands for the image when it has no alt text
+                let caption = self.blocks(&content);
+                let alt = option("alt")
+                    .or_else(|| caption.iter().find_map(|element| match element {
+                        DocumentElement::Paragraph { text } => Some(text.clone()),
+                        _ => None,
+                    }))
+                    .unwrap_or_default();
+                out.push(DocumentElement::Image { alt, url: Some(argument.to_string()) });
+            },
+            "list-table" => {
+                let header_rows = option("header-rows").and_then(|rows| rows.parse().ok()).unwrap_or(0);
+                let rows = item_bodies(&content).into_iter()
+                    .map(|row| {
+                        let row: Vec<&str> = row.iter().map(String::as_str).collect();
+                        item_bodies(&row).into_iter()
+                            .map(|cell| elements_text(&self.blocks(&cell.iter().map(String::as_str).collect::<Vec<_>>())).replace('\n', " "))
+                            .collect()
+                    })
+                    .collect();
+                out.extend(table(rows, header_rows));
+            },
+            "csv-table" => {
+                let delimiter = option("delim").and_then(|delim| delim.chars().next()).unwrap_or(',');
+                let mut rows = Vec::new();
+                let mut header_rows = option("header-rows").and_then(|rows| rows.parse().ok()).unwrap_or(0);
+                if let Some(header) = option("header") {
+                    rows.push(split_csv(&header, ','));
+                    header_rows = 1;
+                }
+                rows.extend(content.iter().filter(|line| !is_blank(line)).map(|line| split_csv(line, delimiter)));
+                let rows = rows.into_iter()
+                    .map(|row| row.iter().map(|cell| inline(cell, &mut links)).collect())
+                    .collect();
+                out.extend(table(rows, header_rows));
+            },
+            "epigraph" | "pull-quote" | "highlights" => {
+                let nested = self.blocks(&content);
+                push_quote(None, nested, out);
+            },
+            "rubric" | "centered" => out.push(DocumentElement::Paragraph { text: inline(argument, &mut links) }),
+            name if SKIPPED_DIRECTIVES.contains(&name) => {},
+            name => {
+                // Tables, topics, sidebars, Sphinx object descriptions...: the argument (title or
+                // signature) introduces the content; directives without content are only markup
+                if content.is_empty() {
+                    return;
+                }
+                if !argument.is_empty() && !CONTAINER_DIRECTIVES.contains(&name) {
+                    out.push(DocumentElement::Paragraph { text: inline(argument, &mut links) });
+                }
+                let nested = self.blocks(&content);
+                out.extend(nested);
+            },
+        }
+        out.extend(links);
+    }
+}
+
+// The metadata a docinfo field (or meta directive option) gives, None for the others (version, orphan...)
+fn docinfo_element(name: &str, text: String) -> Option<DocumentElement> {
+    match name.to_lowercase().as_str() {
+        "title" => Some(DocumentElement::Title { text }),
+        "author" | "authors" => Some(DocumentElement::Author { text }),
+        "date" => Some(DocumentElement::CreationDate { text }),
+        "description" | "abstract" | "summary" => Some(DocumentElement::Description { text }),
+        "keywords" | "tags" => Some(DocumentElement::Keywords { text }),
+        "language" | "lang" => Some(DocumentElement::Language { text }),
+        _ => None,
+    }
+}
+
+// The adornment character when the line is a section underline or overline
+fn adornment(line: &str) -> Option<char> {
+    let mut chars = line.chars();
+    let first = chars.next()?;
+    (ADORNMENT_CHARS.contains(first) && line.len() >= 2 && chars.all(|c| c == first)).then_some(first)
+}
+
+// End of the block starting at `start`: the first non-blank line indented less than `indent`
+fn block_end(lines: &[&str], start: usize, indent: usize) -> usize {
+    (start..lines.len())
+        .find(|&i| !is_blank(lines[i]) && indentation(lines[i]) < indent)
+        .unwrap_or(lines.len())
+}
+
+// End of the run of non-blank lines from `start` on that satisfy `belongs`
+fn block_end_at(lines: &[&str], start: usize, belongs: impl Fn(&str) -> bool) -> usize {
+    (start + 1..lines.len())
+        .find(|&i| is_blank(lines[i]) || !belongs(lines[i]))
+        .unwrap_or(lines.len())
+}
+
+// A bullet or enumerator ("1.", "#.", "(a)", "iv)") at the start of the line.
+// Returns whether the list is ordered and where the item text starts.
+fn list_item(line: &str) -> Option<(bool, usize)> {
+    if indentation(line) > 0 {
+        return None;
+    }
+    let line = line.trim_end();
+    let (marker, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let width = line.len() - rest.trim_start().len();
+    if BULLETS.contains(&marker) {
+        return Some((false, width));
+    }
+    if rest.trim().is_empty() {
+        return None;
+    }
+
+    let numbering = marker.strip_prefix('(').and_then(|inner| inner.strip_suffix(')'))
+        .or_else(|| marker.strip_suffix('.'))
+        .or_else(|| marker.strip_suffix(')'))?;
+    let is_number = !numbering.is_empty() && numbering.len() <= 3 && numbering.chars().all(|c| c.is_ascii_digit());
+    let is_letter = numbering.len() == 1 && numbering.chars().all(|c| c.is_ascii_alphabetic());
+    let is_roman = !numbering.is_empty() && numbering.len() <= 4
+        && (numbering.chars().all(|c| "ivxlc".contains(c)) || numbering.chars().all(|c| "IVXLC".contains(c)));
+
+    (numbering == "#" || is_number || is_letter || is_roman).then_some((true, width))
+}
+
+// The lines of a list item with its marker removed and its indentation undone
+fn item_body(lines: &[&str], width: usize) -> Vec<String> {
+    let mut body = vec![lines[0].get(width..).unwrap_or_default().to_string()];
+    body.extend(lines[1..].iter().map(|line| line[indentation(line).min(width)..].to_string()));
+    body
+}
+
+// The bodies of every item of a bullet list (list-table rows and cells)
+fn item_bodies(lines: &[&str]) -> Vec<Vec<String>> {
+    let mut bodies = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let Some((_, width)) = list_item(lines[i]) else {
+            i += 1;
+            continue;
+        };
+        let end = block_end(lines, i + 1, 1);
+        bodies.push(item_body(&lines[i..end], width));
+        i = end;
+    }
+    bodies
+}
+
+// A ":name: body" field, whose name holds no interpreted text (":ref:`x`" starts a paragraph)
+fn field(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix(':')?;
+    let (name, body) = rest.split_once(": ").or_else(|| rest.strip_suffix(':').map(|name| (name, "")))?;
+    (!name.is_empty() && !name.starts_with(' ') && !name.contains('`')).then_some((name, body))
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to tables
+//-------------------------------------------------
+
+// Grid table: "+---+" borders give the columns, "+===+" closes the header
+fn grid_table(lines: &[&str]) -> Option<DocumentElement> {
+    let columns: Vec<usize> = lines[0].char_indices()
+        .filter(|(_, c)| *c == '+')
+        .map(|(index, _)| index)
+        .collect();
+    let mut rows = Vec::new();
+    let mut row: Option<Vec<Vec<String>>> = None;
+    let mut header_rows = 0;
+    let mut links = Vec::new();
+
+    for line in &lines[1..] {
+        if line.starts_with('+') {
+            rows.extend(row.take());
+            if line.contains('=') {
+                header_rows = rows.len();
+            }
+            continue;
+        }
+        let chars: Vec<char> = line.chars().collect();
+        let cells = row.get_or_insert_with(|| vec![Vec::new(); columns.len().saturating_sub(1)]);
+        for (cell, bounds) in cells.iter_mut().zip(columns.windows(2)) {
+            let end = bounds[1].min(chars.len());
+            if bounds[0] + 1 < end {
+                cell.push(chars[bounds[0] + 1..end].iter().collect());
+            }
+        }
+    }
+    rows.extend(row);
+
+    let rows = rows.into_iter()
+        .map(|cells| cells.iter().map(|lines| inline(&join_lines(lines), &mut links)).collect())
+        .collect();
+    table(rows, header_rows)
+}
+
+// Start columns of a simple table border ("=====  ======"), which has at least two columns
+fn simple_table_columns(line: &str) -> Option<Vec<usize>> {
+    if !line.starts_with('=') || !line.chars().all(|c| c == '=' || c == ' ') {
+        return None;
+    }
+    let columns: Vec<usize> = line.char_indices()
+        .filter(|&(index, c)| c == '=' && (index == 0 || line.as_bytes()[index - 1] == b' '))
+        .map(|(index, _)| index)
+        .collect();
+    (columns.len() >= 2).then_some(columns)
+}
+
+// Simple table from its top border at `start`; a third border means the rows before the second are the header.
+// Returns the table and the line after it.
+fn simple_table(lines: &[&str], start: usize, columns: &[usize]) -> (Option<DocumentElement>, usize) {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut header_rows = 0;
+    let mut borders = 1;
+    let mut i = start + 1;
+
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+        i += 1;
+        if simple_table_columns(line).is_some() {
+            borders += 1;
+            if borders == 3 || lines.get(i).is_none_or(|next| is_blank(next)) {
+                break;
+            }
+            header_rows = rows.len();
+            continue;
+        }
+        // Blank lines, and the dashes underlining column spans
+        if line.is_empty() || line.chars().all(|c| c == '-' || c == ' ') {
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let cells: Vec<String> = columns.iter().enumerate()
+            .map(|(column, &begin)| {
+                let end = columns.get(column + 1).map_or(chars.len(), |&next| next.min(chars.len()));
+                match begin < end {
+                    true => chars[begin..end].iter().collect::<String>().trim().to_string(),
+                    false => String::new(),
+                }
+            })
+            .collect();
+
+        // A row with an empty first column continues the previous one
+        match rows.last_mut() {
+            Some(previous) if cells[0].is_empty() => {
+                for (cell, text) in previous.iter_mut().zip(cells) {
+                    if !text.is_empty() {
+                        cell.push(' ');
+                        cell.push_str(&text);
+                    }
+                }
+            },
+            _ => rows.push(cells),
+        }
+    }
+
+    let mut links = Vec::new();
+    let rows = rows.into_iter()
+        .map(|cells| cells.iter().map(|cell| inline(cell.trim(), &mut links)).collect())
+        .collect();
+    (table(rows, header_rows), i)
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to inline markup
+//-------------------------------------------------
+
+// Remove inline markup: emphasis, literals, interpreted text and roles, references, footnote
+// references and escapes. External hyperlinks are added to `links`.
+fn inline(text: &str, links: &mut Vec<DocumentElement>) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::new();
+    // Text whose emphasis markers are still to be removed
+    let mut plain = String::new();
+    let flush = |plain: &mut String, result: &mut String| {
+        result.push_str(&strip_emphasis(plain, &["**", "*"]));
+        plain.clear();
+    };
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        // Escaped character ("\ " is removed)
+        if c == '\\' {
+            if let Some(next) = chars.get(i + 1).filter(|next| !next.is_whitespace()) {
+                plain.push(*next);
+            }
+            i += 2;
+            continue;
+        }
+
+        // ``inline literal``
+        if chars[i..].starts_with(&['`', '`'])
+            && let Some(end) = find(&chars, i + 2, "``") {
+            flush(&mut plain, &mut result);
+            result.extend(&chars[i + 2..end]);
+            i = end + 2;
+            continue;
+        }
+
+        // :role:`text`
+        if c == ':'
+            && (i == 0 || !chars[i - 1].is_alphanumeric())
+            && let Some((role, after)) = role_at(&chars, i)
+            && chars.get(after) == Some(&'`')
+            && let Some(end) = find(&chars, after + 1, "`") {
+            flush(&mut plain, &mut result);
+            let content: String = chars[after + 1..end].iter().collect();
+            result.push_str(&interpreted_text(&role, &content, links));
+            i = end + 1;
+            continue;
+        }
+
+        // `text`, `text`:role:, `text`_ and `text <url>`_
+        if c == '`' && let Some(end) = find(&chars, i + 1, "`") {
+            let content: String = chars[i + 1..end].iter().collect();
+            let mut role = String::new();
+            let mut next = end + 1;
+            if chars.get(next) == Some(&'_') {
+                next += if chars.get(next + 1) == Some(&'_') { 2 } else { 1 };
+            } else if chars.get(next) == Some(&':') && let Some((suffix, after)) = role_at(&chars, next) {
+                role = suffix;
+                next = after;
+            }
+            flush(&mut plain, &mut result);
+            result.push_str(&interpreted_text(&role, &content, links));
+            i = next;
+            continue;
+        }
+
+        // Footnote and citation references: [1]_, [#]_, [#note]_, [*]_, [CIT2002]_
+        if c == '['
+            && let Some(end) = find(&chars, i + 1, "]_")
+            && end > i + 1
+            && !chars[i + 1..end].iter().any(|c| c.is_whitespace()) {
+            if plain.ends_with(' ') {
+                plain.pop();
+            }
+            i = end + 2;
+            continue;
+        }
+
+        // Substitution references: |name| and |name|_
+        if c == '|'
+            && let Some(end) = find(&chars, i + 1, "|")
+            && end > i + 1
+            && !chars[i + 1].is_whitespace()
+            && !chars[end - 1].is_whitespace() {
+            plain.extend(&chars[i + 1..end]);
+            i = end + 1;
+            while chars.get(i) == Some(&'_') {
+                i += 1;
+            }
+            continue;
+        }
+
+        // Reference names: word_ and word__ (but not the underscores of "__init__")
+        if c == '_'
+            && i > 0
+            && chars[i - 1].is_alphanumeric()
+            && !plain.rsplit(' ').next().unwrap_or_default().starts_with('_') {
+            let end = if chars.get(i + 1) == Some(&'_') { i + 2 } else { i + 1 };
+            if chars.get(end).is_none_or(|next| !next.is_alphanumeric() && *next != '_') {
+                i = end;
+                continue;
+            }
+        }
+
+        plain.push(c);
+        i += 1;
+    }
+    flush(&mut plain, &mut result);
+
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Position of `pattern` in `chars` from `start` on
+fn find(chars: &[char], start: usize, pattern: &str) -> Option<usize> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    (start..chars.len()).find(|&i| chars[i..].starts_with(&pattern))
+}
+
+// A ":role:" (the role name may hold a domain, ":py:func:") starting at `start`.
+// Returns the role name and the position after its closing colon.
+fn role_at(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let end = (start + 1..chars.len())
+        .find(|&i| !(chars[i].is_alphanumeric() || "-_+.:".contains(chars[i])))
+        .unwrap_or(chars.len());
+    (end >= start + 3 && chars[end - 1] == ':')
+        .then(|| (chars[start + 1..end - 1].iter().collect(), end))
+}
+
+// The text shown for interpreted text: "Title <target>" shows its title, ":math:" stays as "$...$",
+// "~package.name" shows only its last component
+fn interpreted_text(role: &str, content: &str, links: &mut Vec<DocumentElement>) -> String {
+    let role = role.rsplit(':').next().unwrap_or_default();
+    if role == "math" {
+        return format!("${}$", content);
+    }
+
+    if let Some(open) = content.rfind('<')
+        && let Some(target) = content[open + 1..].strip_suffix('>') {
+        let title = content[..open].trim();
+        let text = if title.is_empty() { target } else { title };
+        if target.contains("://") || target.starts_with("mailto:") {
+            links.push(DocumentElement::Link { text: text.to_string(), url: target.to_string() });
+        }
+        return text.to_string();
+    }
+
+    match content.strip_prefix('~') {
+        Some(path) => path.rsplit('.').next().unwrap_or(path).to_string(),
+        None => content.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::ParseEvent;
+
+    const SOURCE: &str = "=====
+Guide
+=====
+
+Install
+=======
+
+Run this:
+
+.. code-block:: python
+
+   import os
+   print(os.name)
+
+.. note::
+   Needs Python 3.
+
+* one
+* two
+
+#. first
+#. second
+
+=====  =====
+Name   Size
+=====  =====
+a      1
+=====  =====
+
+Usage
+-----
+
+Literal::
+
+    $ run
+";
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn sections_directives_lists_and_tables() {
+        assert_eq!(parse_rst_str(SOURCE).to_elements(), vec![
+            DocumentElement::Title { text: "Guide".to_string() },
+            DocumentElement::Heading { level: 1, text: "Guide".to_string() },
+            DocumentElement::Heading { level: 2, text: "Install".to_string() },
+            DocumentElement::Paragraph { text: "Run this:".to_string() },
+            DocumentElement::Code { code: "import os\nprint(os.name)".to_string(), language: Some("python".to_string()), inline: false },
+            DocumentElement::Blockquote { text: "Note: Needs Python 3.".to_string() },
+            DocumentElement::List { items: strings(&["one", "two"]), ordered: false },
+            DocumentElement::List { items: strings(&["first", "second"]), ordered: true },
+            DocumentElement::Table { headers: strings(&["Name", "Size"]), rows: vec![strings(&["a", "1"])] },
+            DocumentElement::Heading { level: 3, text: "Usage".to_string() },
+            DocumentElement::Paragraph { text: "Literal:".to_string() },
+            DocumentElement::Code { code: "$ run".to_string(), language: None, inline: false },
+        ]);
+    }
+
+    #[test]
+    fn latin1_files_are_reported() {
+        let source = b"Caf\xe9 cr\xe8me.\n";
+        let path = std::env::temp_dir().join(format!("oxidoc-latin1-{}.rst", std::process::id()));
+        fs::write(&path, source).unwrap();
+
+        let warnings = std::cell::RefCell::new(Vec::new());
+        let sink = |event: ParseEvent| if let ParseEvent::Warning { path, .. } = event {
+            warnings.borrow_mut().push(path);
+        };
+        let from_path = RstParser.parse_path(&path, &sink);
+        fs::remove_file(&path).unwrap();
+        let from_bytes = RstParser.parse_bytes(source, &sink).unwrap();
+
+        // Only the filename title differs
+        assert_eq!(from_path.unwrap()[1..], from_bytes[..]);
+        assert_eq!(from_bytes, vec![DocumentElement::Paragraph { text: "Café crème.".to_string() }]);
+        assert_eq!(*warnings.borrow(), vec![Some(path), None]);
+    }
+}
@@ -0,0 +1,825 @@
+// rtf_parser.rs
+
+use encoding_rs::Encoding;
+use std::collections::HashMap;
+use std::fs;
+use std::mem;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::EventSink;
+use crate::parsers::documents_types::{fallback_title, DocumentElement};
+use crate::parsers::parser::Parser;
+
+// Code page of documents that do not declare one (\ansicpg)
+const DEFAULT_CODEPAGE: i32 = 1252;
+
+// Main parsing function
+pub fn parse_rtf_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    // Read the RTF file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+
+    let mut elements = parse_rtf_bytes(&bytes).map_err(|error| error.with_path(path))?;
+
+    fallback_title(&mut elements, path);
+
+    Ok(elements)
+}
+
+// Parse an in-memory RTF document: \info properties first, then the body in reading order
+pub fn parse_rtf_bytes(bytes: &[u8]) -> Result<Vec<DocumentElement>> {
+    if !bytes.trim_ascii_start().starts_with(b"{\\rtf") {
+        return Err(OxidocError::malformed("RTF document does not start with {\\rtf"));
+    }
+
+    let mut reader = RtfReader::new();
+    reader.read(bytes);
+    Ok(reader.finish())
+}
+
+// RTF implementation of the common Parser trait
+pub struct RtfParser;
+
+impl Parser for RtfParser {
+    fn name(&self) -> &str {
+        "rtf"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["rtf"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/rtf", "text/rtf"]
+    }
+
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(b"{\\rtf")
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], _events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_rtf_bytes(bytes)
+    }
+
+    fn parse_path(&self, path: &Path, _events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_rtf_file(path)
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to tokens
+//-------------------------------------------------
+
+enum Token<'a> {
+    GroupStart,
+    GroupEnd,
+    // \word or \word-12
+    Control(&'a str, Option<i32>),
+    // \{, \~, \*...
+    Symbol(u8),
+    // \'e9: one byte in the current code page
+    Hex(u8),
+    Byte(u8),
+}
+
+struct Lexer<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn next_token(&mut self) -> Option<Token<'a>> {
+        let byte = *self.bytes.get(self.position)?;
+        self.position += 1;
+
+        match byte {
+            b'{' => Some(Token::GroupStart),
+            b'}' => Some(Token::GroupEnd),
+            b'\\' => {
+                let Some(&next) = self.bytes.get(self.position) else {
+                    return Some(Token::Symbol(b'\\'));
+                };
+                if next.is_ascii_alphabetic() {
+                    Some(self.control_word())
+                } else if next == b'\'' {
+                    let hex = self.bytes.get(self.position + 1..self.position + 3)
+                        .and_then(|hex| std::str::from_utf8(hex).ok())
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                    match hex {
+                        Some(value) => {
+                            self.position += 3;
+                            Some(Token::Hex(value))
+                        },
+                        None => {
+                            self.position += 1;
+                            Some(Token::Symbol(b'\''))
+                        },
+                    }
+                } else {
+                    self.position += 1;
+                    Some(Token::Symbol(next))
+                }
+            },
+            _ => Some(Token::Byte(byte)),
+        }
+    }
+
+    // Letters, an optional signed number, and an optional space that belongs to the control word
+    fn control_word(&mut self) -> Token<'a> {
+        let start = self.position;
+        while self.bytes.get(self.position).is_some_and(|byte| byte.is_ascii_alphabetic()) {
+            self.position += 1;
+        }
+        let word = std::str::from_utf8(&self.bytes[start..self.position]).unwrap_or("");
+
+        let number_start = self.position;
+        if self.bytes.get(self.position) == Some(&b'-')
+            && self.bytes.get(self.position + 1).is_some_and(|byte| byte.is_ascii_digit()) {
+            self.position += 1;
+        }
+        while self.bytes.get(self.position).is_some_and(|byte| byte.is_ascii_digit()) {
+            self.position += 1;
+        }
+        let parameter = std::str::from_utf8(&self.bytes[number_start..self.position]).ok()
+            .filter(|number| !number.is_empty())
+            .and_then(|number| number.parse::<i64>().ok())
+            .map(|number| number.clamp(i32::MIN as i64, i32::MAX as i64) as i32);
+
+        if self.bytes.get(self.position) == Some(&b' ') {
+            self.position += 1;
+        }
+        Token::Control(word, parameter)
+    }
+
+    // \binN is followed by N raw bytes
+    fn skip(&mut self, count: usize) {
+        self.position = (self.position + count).min(self.bytes.len());
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to the document
+//-------------------------------------------------
+
+// Where the text of a group goes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Destination {
+    Body,
+    FontTable,
+    StyleSheet,
+    Info(InfoField),
+    FieldInstruction,
+    // Marker text of a list paragraph (\listtext, \pntext)
+    ListText,
+    // Pictures, headers, footnotes, unknown \* destinations...
+    Skip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InfoField {
+    None,
+    Title,
+    Author,
+    Subject,
+    Keywords,
+    Comment,
+    Generator,
+    Created,
+    Revised,
+}
+
+// Destinations whose content is not part of the document text
+const SKIPPED_DESTINATIONS: &[&str] = &[
+    "colortbl", "pict", "object", "header", "headerl", "headerr", "headerf", "footer", "footerl",
+    "footerr", "footerf", "footnote", "annotation", "atnid", "atnauthor", "themedata",
+    "colorschememapping", "latentstyles", "datastore", "listtable", "listoverridetable", "rsidtbl",
+    "xmlnstbl", "mmathPr", "pgdsctbl", "revtbl", "filetbl", "bkmkstart", "bkmkend", "fldtype", "pn",
+    "pntxta", "pntxtb", "xe", "tc", "txe", "shpinst", "nonshppict", "template", "private",
+    "operator", "company", "manager", "category", "comment", "hlinkbase",
+];
+
+// Formatting state, saved at every "{" and restored at the matching "}"
+#[derive(Debug, Clone)]
+struct GroupState {
+    destination: Destination,
+    // Font of the text (\fN), whose charset selects the code page
+    font: Option<i32>,
+    // Fallback characters that follow a \uN escape (\ucN)
+    unicode_skip: usize,
+    hidden: bool,
+    // Inside the result of a field, whose text is also the link text
+    field_result: bool,
+    // Paragraph properties, reset by \pard
+    style: i32,
+    outline_level: Option<i32>,
+    in_table: bool,
+}
+
+struct StyleDefinition {
+    // Lowercased style name ("heading 1", "title")
+    name: String,
+    outline_level: Option<i32>,
+}
+
+// A {\field{\*\fldinst HYPERLINK "..."}{\fldrslt ...}} being read
+struct Field {
+    // Group depth of the \field group
+    depth: usize,
+    instruction: String,
+    result: String,
+}
+
+struct RtfReader {
+    stack: Vec<GroupState>,
+    codepage: i32,
+    default_font: Option<i32>,
+    // Code page of every font with a non-default charset
+    font_codepages: HashMap<i32, i32>,
+    font_definition: Option<i32>,
+    styles: HashMap<i32, StyleDefinition>,
+    style_definition: (Option<i32>, String, Option<i32>),
+    info: Vec<(InfoField, String)>,
+    info_text: String,
+    // Year, month, day, hour, minute of \creatim or \revtim
+    date_parts: [i32; 5],
+    fields: Vec<Field>,
+    // Bytes waiting to be decoded together (double-byte code pages split characters across \'hh)
+    pending_bytes: Vec<u8>,
+    // Fallback characters still to skip after a \uN
+    skip_characters: usize,
+    high_surrogate: Option<u16>,
+    ignorable: bool,
+    paragraph: String,
+    list_marker: Option<String>,
+    links: Vec<DocumentElement>,
+    row: Vec<String>,
+    rows: Vec<Vec<String>>,
+    elements: Vec<DocumentElement>,
+}
+
+impl RtfReader {
+    fn new() -> RtfReader {
+        RtfReader {
+            stack: vec![GroupState {
+                destination: Destination::Body,
+                font: None,
+                unicode_skip: 1,
+                hidden: false,
+                field_result: false,
+                style: 0,
+                outline_level: None,
+                in_table: false,
+            }],
+            codepage: DEFAULT_CODEPAGE,
+            default_font: None,
+            font_codepages: HashMap::new(),
+            font_definition: None,
+            styles: HashMap::new(),
+            style_definition: (Some(0), String::new(), None),
+            info: Vec::new(),
+            info_text: String::new(),
+            date_parts: [0; 5],
+            fields: Vec::new(),
+            pending_bytes: Vec::new(),
+            skip_characters: 0,
+            high_surrogate: None,
+            ignorable: false,
+            paragraph: String::new(),
+            list_marker: None,
+            links: Vec::new(),
+            row: Vec::new(),
+            rows: Vec::new(),
+            elements: Vec::new(),
+        }
+    }
+
+    fn state(&self) -> &GroupState {
+        self.stack.last().expect("the document state is never popped")
+    }
+
+    fn state_mut(&mut self) -> &mut GroupState {
+        self.stack.last_mut().expect("the document state is never popped")
+    }
+
+    fn read(&mut self, bytes: &[u8]) {
+        let mut lexer = Lexer { bytes, position: 0 };
+
+        while let Some(token) = lexer.next_token() {
+            match token {
+                Token::GroupStart => {
+                    self.flush_bytes();
+                    self.skip_characters = 0;
+                    if self.state().destination == Destination::StyleSheet {
+                        self.style_definition = (Some(0), String::new(), None);
+                    }
+                    let state = self.state().clone();
+                    self.stack.push(state);
+                },
+                Token::GroupEnd => {
+                    self.flush_bytes();
+                    self.skip_characters = 0;
+                    self.end_group();
+                },
+                // Line breaks in the source are not text
+                Token::Byte(b'\r' | b'\n') => {},
+                _ if self.skip_characters > 0 => self.skip_characters -= 1,
+                Token::Byte(b';') if self.state().destination == Destination::StyleSheet => {
+                    self.flush_bytes();
+                    self.end_style_definition();
+                },
+                Token::Byte(byte) | Token::Hex(byte) => self.pending_bytes.push(byte),
+                Token::Symbol(symbol) => {
+                    self.flush_bytes();
+                    self.symbol(symbol);
+                },
+                Token::Control("bin", parameter) => lexer.skip(parameter.unwrap_or(0).max(0) as usize),
+                Token::Control(word, parameter) => {
+                    self.flush_bytes();
+                    self.control(word, parameter);
+                },
+            }
+        }
+        self.flush_bytes();
+    }
+
+    fn symbol(&mut self, symbol: u8) {
+        match symbol {
+            b'*' => self.ignorable = true,
+            b'{' | b'}' | b'\\' => self.push_text(&(symbol as char).to_string()),
+            b'~' => self.push_text(" "),
+            b'_' => self.push_text("-"),
+            // "\" followed by a line break is a paragraph break
+            b'\r' | b'\n' => self.end_paragraph(),
+            // Optional hyphens and formula characters are not text
+            _ => {},
+        }
+    }
+
+    fn control(&mut self, word: &str, parameter: Option<i32>) {
+        let ignorable = mem::take(&mut self.ignorable);
+        let destination = self.state().destination;
+        if destination == Destination::Skip {
+            return;
+        }
+
+        match word {
+            // Code pages
+            "ansicpg" => self.codepage = parameter.unwrap_or(DEFAULT_CODEPAGE),
+            "mac" => self.codepage = 10000,
+            "pc" => self.codepage = 437,
+            "pca" => self.codepage = 850,
+            "deff" => self.default_font = parameter,
+
+            // Destinations
+            "fonttbl" => self.state_mut().destination = Destination::FontTable,
+            "stylesheet" => self.state_mut().destination = Destination::StyleSheet,
+            "info" => self.state_mut().destination = Destination::Info(InfoField::None),
+            "title" | "author" | "subject" | "keywords" | "doccomm" | "creatim" | "revtim"
+                if matches!(destination, Destination::Info(_)) => {
+                self.info_text.clear();
+                self.date_parts = [0; 5];
+                self.state_mut().destination = Destination::Info(match word {
+                    "title" => InfoField::Title,
+                    "author" => InfoField::Author,
+                    "subject" => InfoField::Subject,
+                    "keywords" => InfoField::Keywords,
+                    "doccomm" => InfoField::Comment,
+                    "creatim" => InfoField::Created,
+                    _ => InfoField::Revised,
+                });
+            },
+            "generator" => {
+                self.info_text.clear();
+                self.state_mut().destination = Destination::Info(InfoField::Generator);
+            },
+            "yr" | "mo" | "dy" | "hr" | "min" if matches!(destination, Destination::Info(InfoField::Created | InfoField::Revised)) => {
+                let index = ["yr", "mo", "dy", "hr", "min"].iter().position(|part| *part == word).unwrap_or(0);
+                self.date_parts[index] = parameter.unwrap_or(0);
+            },
+            "field" => {
+                let depth = self.stack.len();
+                self.fields.push(Field { depth, instruction: String::new(), result: String::new() });
+            },
+            "fldinst" => self.state_mut().destination = Destination::FieldInstruction,
+            "fldrslt" => self.state_mut().field_result = true,
+            "listtext" | "pntext" => {
+                self.list_marker.get_or_insert_with(String::new);
+                self.state_mut().destination = Destination::ListText;
+            },
+            _ if SKIPPED_DESTINATIONS.contains(&word) => self.state_mut().destination = Destination::Skip,
+            // Unknown destinations marked \* can be ignored as a whole
+            _ if ignorable => self.state_mut().destination = Destination::Skip,
+
+            // Font and style tables
+            "f" if destination == Destination::FontTable => self.font_definition = parameter,
+            "fcharset" if destination == Destination::FontTable => {
+                if let (Some(font), Some(codepage)) = (self.font_definition, parameter.and_then(charset_codepage)) {
+                    self.font_codepages.insert(font, codepage);
+                }
+            },
+            "cpg" if destination == Destination::FontTable => {
+                if let (Some(font), Some(codepage)) = (self.font_definition, parameter) {
+                    self.font_codepages.insert(font, codepage);
+                }
+            },
+            "s" if destination == Destination::StyleSheet => self.style_definition.0 = parameter,
+            // Character, section and table styles are numbered separately from paragraph styles
+            "cs" | "ds" | "ts" if destination == Destination::StyleSheet => self.style_definition.0 = None,
+            "outlinelevel" if destination == Destination::StyleSheet => self.style_definition.2 = parameter,
+
+            // Character formatting
+            "f" => self.state_mut().font = parameter,
+            "uc" => self.state_mut().unicode_skip = parameter.unwrap_or(1).max(0) as usize,
+            "v" | "deleted" => self.state_mut().hidden = parameter != Some(0),
+            "u" => self.unicode(parameter.unwrap_or(0)),
+
+            // Paragraph formatting
+            "pard" => {
+                let state = self.state_mut();
+                state.style = 0;
+                state.outline_level = None;
+                state.in_table = false;
+            },
+            "s" => self.state_mut().style = parameter.unwrap_or(0),
+            "outlinelevel" => self.state_mut().outline_level = parameter,
+            "intbl" => self.state_mut().in_table = true,
+            "itap" => self.state_mut().in_table = parameter.unwrap_or(1) > 0,
+
+            // Breaks and special characters
+            "par" | "sect" => self.end_paragraph(),
+            "cell" | "nestcell" => self.end_cell(),
+            "row" | "nestrow" => self.end_row(),
+            "line" => self.push_text("\n"),
+            "tab" => self.push_text("\t"),
+            "emdash" => self.push_text("—"),
+            "endash" => self.push_text("–"),
+            "bullet" => self.push_text("•"),
+            "lquote" => self.push_text("‘"),
+            "rquote" => self.push_text("’"),
+            "ldblquote" => self.push_text("“"),
+            "rdblquote" => self.push_text("”"),
+            "emspace" | "enspace" | "qmspace" => self.push_text(" "),
+            _ => {},
+        }
+    }
+
+    // \uN is a signed 16-bit UTF-16 code unit, followed by fallback characters for older readers
+    fn unicode(&mut self, value: i32) {
+        let unit = if value < 0 { (value + 65536) as u16 } else { value as u16 };
+        self.skip_characters = self.state().unicode_skip;
+
+        match unit {
+            0xD800..=0xDBFF => self.high_surrogate = Some(unit),
+            0xDC00..=0xDFFF => {
+                if let Some(high) = self.high_surrogate.take() {
+                    let text = String::from_utf16_lossy(&[high, unit]);
+                    self.push_text(&text);
+                }
+            },
+            _ => {
+                self.high_surrogate = None;
+                if let Some(character) = char::from_u32(unit as u32) {
+                    self.push_text(&character.to_string());
+                }
+            },
+        }
+    }
+
+    // Decode the pending bytes with the code page of the current font
+    fn flush_bytes(&mut self) {
+        if self.pending_bytes.is_empty() {
+            return;
+        }
+        let bytes = mem::take(&mut self.pending_bytes);
+        let codepage = self.state().font
+            .or(self.default_font)
+            .and_then(|font| self.font_codepages.get(&font).copied())
+            .unwrap_or(self.codepage);
+
+        let (text, _) = codepage_encoding(codepage).decode_without_bom_handling(&bytes);
+        self.push_text(&text);
+    }
+
+    fn push_text(&mut self, text: &str) {
+        let state = self.state();
+        match state.destination {
+            Destination::Body if !state.hidden => {
+                if state.field_result
+                    && let Some(field) = self.fields.last_mut() {
+                    field.result.push_str(text);
+                }
+                self.paragraph.push_str(text);
+            },
+            Destination::StyleSheet => self.style_definition.1.push_str(text),
+            Destination::Info(_) => self.info_text.push_str(text),
+            Destination::FieldInstruction => {
+                if let Some(field) = self.fields.last_mut() {
+                    field.instruction.push_str(text);
+                }
+            },
+            Destination::ListText => self.list_marker.get_or_insert_with(String::new).push_str(text),
+            _ => {},
+        }
+    }
+
+    fn end_group(&mut self) {
+        // Unbalanced braces never pop the document state
+        if self.stack.len() <= 1 {
+            return;
+        }
+
+        if self.fields.last().is_some_and(|field| field.depth == self.stack.len()) {
+            let field = self.fields.pop().expect("checked above");
+            if let Some(url) = hyperlink_target(&field.instruction)
+                && !field.result.trim().is_empty() {
+                self.links.push(DocumentElement::Link { text: field.result.trim().to_string(), url });
+            }
+        }
+
+        let closing = self.stack.pop().expect("checked above");
+        if let Destination::Info(field) = closing.destination
+            && field != InfoField::None
+            && self.state().destination != closing.destination {
+            let value = match field {
+                InfoField::Created | InfoField::Revised => {
+                    let [year, month, day, hour, minute] = self.date_parts;
+                    (year > 0).then(|| format!("{:04}-{:02}-{:02}T{:02}:{:02}:00", year, month.max(1), day.max(1), hour, minute))
+                },
+                _ => Some(self.info_text.trim().trim_end_matches(';').trim().to_string()),
+            };
+            if let Some(value) = value.filter(|value| !value.is_empty()) {
+                self.info.push((field, value));
+            }
+            self.info_text.clear();
+        }
+    }
+
+    fn end_style_definition(&mut self) {
+        let (id, name, outline_level) = mem::replace(&mut self.style_definition, (Some(0), String::new(), None));
+        if let Some(id) = id {
+            self.styles.insert(id, StyleDefinition { name: name.trim().to_lowercase(), outline_level });
+        }
+    }
+
+
+    //-------------------------------------------------
+    // Paragraphs and tables
+    //-------------------------------------------------
+
+    fn end_paragraph(&mut self) {
+        // Paragraphs inside a table cell are lines of the cell
+        if self.state().in_table {
+            self.paragraph.push('\n');
+            self.list_marker = None;
+            return;
+        }
+        self.flush_table();
+
+        let text = mem::take(&mut self.paragraph).trim().to_string();
+        let marker = self.list_marker.take();
+        if !text.is_empty() {
+            let state = self.state();
+            let style = self.styles.get(&state.style);
+            let kind = style.and_then(|style| style_name_kind(&style.name))
+                .or_else(|| state.outline_level.or(style.and_then(|style| style.outline_level)).and_then(outline_heading));
+
+            match (kind, marker) {
+                (Some(element), _) => self.push_styled(element, text),
+                // Numbered markers ("1.", "a)") have letters or digits, bullets do not
+                (None, Some(marker)) => {
+                    let ordered = marker.chars().any(|character| character.is_alphanumeric());
+                    match self.elements.last_mut() {
+                        Some(DocumentElement::List { items, ordered: list_ordered }) if *list_ordered == ordered => items.push(text),
+                        _ => self.elements.push(DocumentElement::List { items: vec![text], ordered }),
+                    }
+                },
+                (None, None) => self.elements.push(DocumentElement::Paragraph { text }),
+            }
+        }
+
+        // Hyperlinks follow the paragraph holding them
+        self.elements.append(&mut self.links);
+    }
+
+    fn push_styled(&mut self, element: StyledParagraph, text: String) {
+        match element {
+            StyledParagraph::Title => self.elements.push(DocumentElement::Title { text }),
+            StyledParagraph::Heading(level) => self.elements.push(DocumentElement::Heading { level, text }),
+            StyledParagraph::Quote => self.elements.push(DocumentElement::Blockquote { text }),
+            // Code is written one paragraph per line: consecutive lines form one block
+            StyledParagraph::Code => match self.elements.last_mut() {
+                Some(DocumentElement::Code { code, inline: false, .. }) => {
+                    code.push('\n');
+                    code.push_str(&text);
+                },
+                _ => self.elements.push(DocumentElement::Code { code: text, language: None, inline: false }),
+            },
+        }
+    }
+
+    fn end_cell(&mut self) {
+        let text = mem::take(&mut self.paragraph);
+        self.row.push(text.trim().to_string());
+        self.list_marker = None;
+    }
+
+    fn end_row(&mut self) {
+        let row = mem::take(&mut self.row);
+        if !row.is_empty() {
+            self.rows.push(row);
+        }
+    }
+
+    // The first row of a table is taken as the header row
+    fn flush_table(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let mut rows = mem::take(&mut self.rows);
+        let headers = rows.remove(0);
+        self.elements.push(DocumentElement::Table { headers, rows });
+        self.elements.append(&mut self.links);
+    }
+
+    // Properties first, then the body. A paragraph styled "Title" is the document title
+    // unless \info already has one.
+    fn finish(mut self) -> Vec<DocumentElement> {
+        self.end_row();
+        if !self.paragraph.trim().is_empty() {
+            self.state_mut().in_table = false;
+            self.end_paragraph();
+        }
+        self.flush_table();
+
+        let property = |field: InfoField| self.info.iter().find(|(name, _)| *name == field).map(|(_, value)| value.clone());
+        let mut elements = Vec::new();
+        if let Some(title) = property(InfoField::Title) {
+            elements.push(DocumentElement::Title { text: title });
+        }
+        if let Some(author) = property(InfoField::Author) {
+            elements.push(DocumentElement::Author { text: author });
+        }
+        if let Some(description) = property(InfoField::Comment).or_else(|| property(InfoField::Subject)) {
+            elements.push(DocumentElement::Description { text: description });
+        }
+        if let Some(keywords) = property(InfoField::Keywords) {
+            elements.push(DocumentElement::Keywords { text: keywords });
+        }
+        if let Some(generator) = property(InfoField::Generator) {
+            elements.push(DocumentElement::Creator { text: generator });
+        }
+        if let Some(created) = property(InfoField::Created) {
+            elements.push(DocumentElement::CreationDate { text: created });
+        }
+        if let Some(revised) = property(InfoField::Revised) {
+            elements.push(DocumentElement::ModificationDate { text: revised });
+        }
+
+        let has_title = elements.iter().any(|element| matches!(element, DocumentElement::Title { .. }));
+        for element in self.elements {
+            match element {
+                DocumentElement::Title { text } if has_title => elements.push(DocumentElement::Heading { level: 1, text }),
+                DocumentElement::Title { text } => elements.insert(0, DocumentElement::Title { text }),
+                other => elements.push(other),
+            }
+        }
+
+        elements
+    }
+}
+
+// What a paragraph style makes of its paragraphs
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StyledParagraph {
+    Title,
+    Heading(u8),
+    Quote,
+    Code,
+}
+
+// Built-in style names are the same in every language ("heading 1", "Title", "Quote")
+fn style_name_kind(name: &str) -> Option<StyledParagraph> {
+    let compact: String = name.chars().filter(|c| !c.is_whitespace()).collect();
+
+    if compact == "title" {
+        return Some(StyledParagraph::Title);
+    }
+    if let Some(level) = compact.strip_prefix("heading").and_then(|level| level.parse::<u8>().ok()) {
+        return Some(StyledParagraph::Heading(level.clamp(1, 6)));
+    }
+    if compact == "quote" || compact == "intensequote" || compact == "blocktext" {
+        return Some(StyledParagraph::Quote);
+    }
+    if compact.contains("code") || compact == "htmlpreformatted" || compact == "plaintext" {
+        return Some(StyledParagraph::Code);
+    }
+    None
+}
+
+// \outlinelevel0 is a level 1 heading; level 9 is body text
+fn outline_heading(level: i32) -> Option<StyledParagraph> {
+    (0..9).contains(&level).then(|| StyledParagraph::Heading((level + 1).min(6) as u8))
+}
+
+// HYPERLINK "https://example.com" or HYPERLINK \l "bookmark"
+fn hyperlink_target(instruction: &str) -> Option<String> {
+    let arguments = instruction.trim().strip_prefix("HYPERLINK")?;
+    let bookmark = arguments.contains("\\l");
+    let target = match arguments.split_once('"') {
+        Some((_, rest)) => rest.split('"').next().unwrap_or(""),
+        None => arguments.split_whitespace().find(|argument| !argument.starts_with('\\')).unwrap_or(""),
+    };
+
+    if target.is_empty() {
+        return None;
+    }
+    Some(if bookmark { format!("#{}", target) } else { target.to_string() })
+}
+
+// Windows code page of an RTF font charset; None for the ANSI and symbol charsets
+fn charset_codepage(charset: i32) -> Option<i32> {
+    match charset {
+        77 => Some(10000),
+        128 => Some(932),
+        129 => Some(949),
+        134 => Some(936),
+        136 => Some(950),
+        161 => Some(1253),
+        162 => Some(1254),
+        163 => Some(1258),
+        177 => Some(1255),
+        178 => Some(1256),
+        186 => Some(1257),
+        204 => Some(1251),
+        222 => Some(874),
+        238 => Some(1250),
+        _ => None,
+    }
+}
+
+// Code pages without a WHATWG encoding (DOS 437, 850) fall back to Windows-1252
+fn codepage_encoding(codepage: i32) -> &'static Encoding {
+    let label = match codepage {
+        932 => "shift_jis".to_string(),
+        936 => "gbk".to_string(),
+        949 => "euc-kr".to_string(),
+        950 => "big5".to_string(),
+        866 => "ibm866".to_string(),
+        10000 => "macintosh".to_string(),
+        20866 => "koi8-r".to_string(),
+        65001 => "utf-8".to_string(),
+        codepage => format!("windows-{}", codepage),
+    };
+    Encoding::for_label(label.as_bytes()).unwrap_or(encoding_rs::WINDOWS_1252)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paragraph(text: &str) -> DocumentElement {
+        DocumentElement::Paragraph { text: text.to_string() }
+    }
+
+    #[test]
+    fn hex_bytes_follow_the_font_code_page() {
+        let rtf = br"{\rtf1\ansi\ansicpg1252\deff0{\fonttbl{\f0\fcharset0 Arial;}{\f1\fcharset204 Arial Cyr;}}
+            Caf\'e9 cr\'e8me\par
+            {\f1 \'cf\'f0\'e8\'e2\'e5\'f2}\par}";
+
+        assert_eq!(parse_rtf_bytes(rtf).unwrap(), vec![paragraph("Café crème"), paragraph("Привет")]);
+    }
+
+    #[test]
+    fn unicode_escapes_skip_their_fallback() {
+        let rtf = br"{\rtf1\ansi \u8364?5 {\uc2\u26085\'93\'fa\u26412??} \u-10179?\u-8576?\par}";
+
+        assert_eq!(parse_rtf_bytes(rtf).unwrap(), vec![paragraph("€5 日本 🚀")]);
+    }
+
+    #[test]
+    fn styles_lists_and_tables() {
+        let rtf = br"{\rtf1\ansi{\stylesheet{\s0 Normal;}{\s1\outlinelevel0 heading 1;}}
+            {\info{\title Report}}
+            {\pard\s1 Scope\par}
+            {\pard{\listtext 1.\tab}First\par}
+            {\pard{\listtext 2.\tab}Second\par}
+            {\pard{\listtext \'95\tab}Loose\par}
+            \trowd\pard\intbl Name\cell Size\cell\row
+            \trowd\pard\intbl a.txt\cell 12\cell\row
+            \pard After\par}";
+
+        assert_eq!(parse_rtf_bytes(rtf).unwrap(), vec![
+            DocumentElement::Title { text: "Report".to_string() },
+            DocumentElement::Heading { level: 1, text: "Scope".to_string() },
+            DocumentElement::List { items: vec!["First".to_string(), "Second".to_string()], ordered: true },
+            DocumentElement::List { items: vec!["Loose".to_string()], ordered: false },
+            DocumentElement::Table {
+                headers: vec!["Name".to_string(), "Size".to_string()],
+                rows: vec![vec!["a.txt".to_string(), "12".to_string()]],
+            },
+            paragraph("After"),
+        ]);
+    }
+}
@@ -0,0 +1,528 @@
+// subtitle_parser.rs
+
+// Subtitles and transcripts: SubRip (.srt), WebVTT (.vtt) and Advanced SubStation Alpha (.ass/.ssa).
+// Cues are cleaned of their styling tags and merged into paragraphs: a paragraph runs on while
+// the speaker stays the same, and ends at the end of a sentence followed by a pause (or once it
+// is long enough). Every paragraph is introduced by a Cue marker with its start and end times
+// and its speaker (VTT voice spans, ASS actor names, "NAME:" labels).
+
+use std::fs;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::EventSink;
+use crate::parsers::documents_types::{fallback_title, DocumentElement};
+use crate::parsers::parser::Parser;
+use crate::parsers::txt_parser::{decode_text, warn_if_latin1};
+
+// Silence after which a finished sentence ends the paragraph
+const PARAGRAPH_PAUSE_MS: u64 = 2000;
+// Length after which a finished sentence ends the paragraph, pause or not
+const PARAGRAPH_MAX_LEN: usize = 600;
+// Longest "NAME:" prefix taken as a speaker label
+const SPEAKER_LABEL_MAX_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+    Ass,
+}
+
+impl SubtitleFormat {
+    fn from_extension(extension: &str) -> Option<SubtitleFormat> {
+        match extension.to_lowercase().as_str() {
+            "srt" => Some(SubtitleFormat::Srt),
+            "vtt" | "webvtt" => Some(SubtitleFormat::Vtt),
+            "ass" | "ssa" => Some(SubtitleFormat::Ass),
+            _ => None,
+        }
+    }
+
+    // WebVTT and ASS files announce themselves, anything else is read as SubRip
+    fn detect(text: &str) -> SubtitleFormat {
+        let start = text.trim_start_matches('\u{feff}').trim_start();
+        if start.starts_with("WEBVTT") {
+            SubtitleFormat::Vtt
+        } else if start.to_lowercase().starts_with("[script info]") {
+            SubtitleFormat::Ass
+        } else {
+            SubtitleFormat::Srt
+        }
+    }
+}
+
+// One subtitle as displayed: times in milliseconds from the start of the media
+#[derive(Debug, Clone, PartialEq)]
+struct Cue {
+    start_ms: u64,
+    end_ms: u64,
+    speaker: Option<String>,
+    text: String,
+}
+
+// Main parsing function
+pub fn parse_subtitle_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    parse_subtitle_path(path, &())
+}
+
+fn parse_subtitle_path(path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    // Read the subtitle file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+    let (text, encoding) = decode_text(&bytes);
+    warn_if_latin1(encoding, Some(path), events);
+
+    let format = path.extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(SubtitleFormat::from_extension)
+        .unwrap_or_else(|| SubtitleFormat::detect(&text));
+    let mut elements = parse_subtitle_str(&text, format);
+
+    fallback_title(&mut elements, path);
+
+    Ok(elements)
+}
+
+// Parse in-memory subtitles, detecting their format from the content
+pub fn parse_subtitle_bytes(bytes: &[u8]) -> Result<Vec<DocumentElement>> {
+    parse_subtitle_bytes_with(bytes, &())
+}
+
+pub fn parse_subtitle_bytes_with(bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    let (text, encoding) = decode_text(bytes);
+    warn_if_latin1(encoding, None, events);
+    Ok(parse_subtitle_str(&text, SubtitleFormat::detect(&text)))
+}
+
+pub fn parse_subtitle_str(text: &str, format: SubtitleFormat) -> Vec<DocumentElement> {
+    let text = text.trim_start_matches('\u{feff}').replace("\r\n", "\n").replace('\r', "\n");
+    let (mut elements, cues) = match format {
+        SubtitleFormat::Srt => (Vec::new(), srt_cues(&text)),
+        SubtitleFormat::Vtt => vtt_cues(&text),
+        SubtitleFormat::Ass => ass_cues(&text),
+    };
+
+    for paragraph in merge_cues(drop_repeated_lines(cues)) {
+        elements.push(DocumentElement::Cue {
+            start_ms: paragraph.start_ms,
+            end_ms: paragraph.end_ms,
+            speaker: paragraph.speaker,
+        });
+        elements.push(DocumentElement::Paragraph { text: paragraph.text });
+    }
+
+    elements
+}
+
+// Subtitle implementation of the common Parser trait
+pub struct SubtitleParser;
+
+impl Parser for SubtitleParser {
+    fn name(&self) -> &str {
+        "subtitle"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["srt", "vtt", "webvtt", "ass", "ssa"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/x-subrip", "text/vtt", "text/x-ssa"]
+    }
+
+    // "WEBVTT", "[Script Info]", or a SubRip cue number followed by its timing line
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        let prefix = String::from_utf8_lossy(&bytes[..bytes.len().min(256)]);
+        let prefix = prefix.trim_start_matches('\u{feff}').trim_start();
+        if prefix.starts_with("WEBVTT") || prefix.to_lowercase().starts_with("[script info]") {
+            return true;
+        }
+        let mut lines = prefix.lines();
+        lines.next().is_some_and(|line| !line.trim().is_empty() && line.trim().bytes().all(|byte| byte.is_ascii_digit()))
+            && lines.next().is_some_and(|line| parse_timing(line).is_some())
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_subtitle_bytes_with(bytes, events)
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_subtitle_path(path, events)
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to formats
+//-------------------------------------------------
+
+// Blocks separated by blank lines: an optional number, "00:00:01,000 --> 00:00:04,000", the text
+fn srt_cues(text: &str) -> Vec<Cue> {
+    blocks(text)
+        .into_iter()
+        .flat_map(|block| {
+            let Some(timing) = block.iter().position(|line| line.contains("-->")) else {
+                return Vec::new();
+            };
+            let Some((start_ms, end_ms)) = parse_timing(block[timing]) else {
+                return Vec::new();
+            };
+            text_cues(start_ms, end_ms, &block[timing + 1..])
+        })
+        .collect()
+}
+
+// "WEBVTT" and its header, then cues like SubRip ones; NOTE, STYLE and REGION blocks are skipped.
+// YouTube and other tools put "Language: en" in the header.
+fn vtt_cues(text: &str) -> (Vec<DocumentElement>, Vec<Cue>) {
+    let mut metadata = Vec::new();
+    let mut cues = Vec::new();
+
+    for (index, block) in blocks(text).into_iter().enumerate() {
+        if index == 0 && block[0].starts_with("WEBVTT") {
+            for line in &block[1..] {
+                if let Some((name, value)) = line.split_once(':')
+                    && name.trim().eq_ignore_ascii_case("language")
+                    && !value.trim().is_empty() {
+                    metadata.push(DocumentElement::Language { text: value.trim().to_string() });
+                }
+            }
+            continue;
+        }
+        let Some(timing) = block.iter().position(|line| line.contains("-->")) else {
+            continue;
+        };
+        if let Some((start_ms, end_ms)) = parse_timing(block[timing]) {
+            cues.extend(text_cues(start_ms, end_ms, &block[timing + 1..]));
+        }
+    }
+
+    (metadata, cues)
+}
+
+// "[Script Info]" holds the title, "[Events]" a Format line naming the fields of the Dialogue lines
+fn ass_cues(text: &str) -> (Vec<DocumentElement>, Vec<Cue>) {
+    let mut metadata = Vec::new();
+    let mut cues = Vec::new();
+    let mut section = String::new();
+    let mut format: Vec<String> = ["layer", "start", "end", "style", "name", "marginl", "marginr", "marginv", "effect", "text"]
+        .iter()
+        .map(|field| field.to_string())
+        .collect();
+
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line.to_lowercase();
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+
+        match (section.as_str(), key.trim().to_lowercase().as_str()) {
+            ("[script info]", "title") if !value.is_empty() => metadata.push(DocumentElement::Title { text: value.to_string() }),
+            ("[script info]", "original script") if !value.is_empty() => metadata.push(DocumentElement::Author { text: value.to_string() }),
+            ("[events]", "format") => format = value.split(',').map(|field| field.trim().to_lowercase()).collect(),
+            ("[events]", "dialogue") => {
+                // The text is the last field and may contain commas
+                let fields: Vec<&str> = value.splitn(format.len(), ',').collect();
+                let field = |name: &str| format.iter().position(|field| field == name).and_then(|index| fields.get(index)).map(|value| value.trim());
+                let (Some(start_ms), Some(end_ms)) = (field("start").and_then(parse_timestamp), field("end").and_then(parse_timestamp)) else {
+                    continue;
+                };
+                let Some(text) = field("text").and_then(clean_ass_text) else {
+                    continue;
+                };
+                let speaker = field("name").filter(|name| !name.is_empty()).map(|name| name.to_string());
+                cues.push(Cue { start_ms, end_ms, speaker, text });
+            },
+            _ => {},
+        }
+    }
+
+    // Events are not always stored in display order
+    cues.sort_by_key(|cue| cue.start_ms);
+    (metadata, cues)
+}
+
+fn blocks(text: &str) -> Vec<Vec<&str>> {
+    let mut blocks = Vec::new();
+    let mut block = Vec::new();
+
+    for line in text.lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            if !block.is_empty() {
+                blocks.push(std::mem::take(&mut block));
+            }
+        } else {
+            block.push(line);
+        }
+    }
+    if !block.is_empty() {
+        blocks.push(block);
+    }
+
+    blocks
+}
+
+// "00:00:01,000 --> 00:00:04,000 X1:40" / "01:02.500 --> 01:04.000 align:start"
+fn parse_timing(line: &str) -> Option<(u64, u64)> {
+    let (start, rest) = line.split_once("-->")?;
+    let end = rest.split_whitespace().next()?;
+    Some((parse_timestamp(start.trim())?, parse_timestamp(end)?))
+}
+
+// "01:02:03,500", "02:03.500" or ASS "1:02:03.50" in milliseconds
+fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let (clock, fraction) = match timestamp.rsplit_once(['.', ',']) {
+        Some((clock, fraction)) => (clock, fraction),
+        None => (timestamp, ""),
+    };
+    if !fraction.bytes().all(|byte| byte.is_ascii_digit()) || fraction.len() > 3 {
+        return None;
+    }
+    let milliseconds = format!("{:0<3}", fraction).parse::<u64>().ok()?;
+
+    let parts: Vec<u64> = clock.split(':').map(|part| part.trim().parse::<u64>().ok()).collect::<Option<_>>()?;
+    let seconds = match parts.as_slice() {
+        [minutes, seconds] => minutes * 60 + seconds,
+        [hours, minutes, seconds] => hours * 3600 + minutes * 60 + seconds,
+        _ => return None,
+    };
+    Some(seconds * 1000 + milliseconds)
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to cue text
+//-------------------------------------------------
+
+// Text lines of a SubRip or WebVTT cue, without tags and with the speaker taken out.
+// A cue where several people speak is split at every change of speaker; the parts share its times.
+fn text_cues(start_ms: u64, end_ms: u64, lines: &[&str]) -> Vec<Cue> {
+    let mut cues = Vec::new();
+    let mut speaker = None;
+    let mut cleaned: Vec<String> = Vec::new();
+
+    for line in lines {
+        let (line, voice) = strip_tags(line);
+        // ">> " marks a change of speaker in broadcast captions, "- " a line of a dialogue
+        let line = line.trim().trim_start_matches(">>").trim();
+        let line = line.strip_prefix("- ").unwrap_or(line);
+        let (line_speaker, line) = match speaker_label(line) {
+            Some((label, rest)) => (Some(label), rest),
+            None => (voice, line),
+        };
+
+        // Lines naming nobody go on with the current speaker
+        if let Some(line_speaker) = line_speaker
+            && speaker.as_ref() != Some(&line_speaker) {
+            if !cleaned.is_empty() {
+                cues.push(Cue { start_ms, end_ms, speaker: speaker.take(), text: cleaned.join("\n") });
+                cleaned.clear();
+            }
+            speaker = Some(line_speaker);
+        }
+        if !line.is_empty() {
+            cleaned.push(line.to_string());
+        }
+    }
+
+    cues.push(Cue { start_ms, end_ms, speaker, text: cleaned.join("\n") });
+    cues
+}
+
+// Removes "<i>", "<font ...>", "<c.yellow>", "<00:00:01.000>"... and "{\an8}" overrides,
+// returning the voice of a "<v Name>" span
+fn strip_tags(line: &str) -> (String, Option<String>) {
+    let mut text = String::new();
+    let mut voice = None;
+    let mut rest = line;
+
+    while let Some(character) = rest.chars().next() {
+        let closing = match character {
+            '<' => rest.find('>'),
+            '{' if rest.starts_with("{\\") => rest.find('}'),
+            _ => None,
+        };
+        match closing {
+            Some(end) => {
+                let tag = &rest[1..end];
+                if let Some(name) = tag.strip_prefix('v').filter(|name| name.starts_with([' ', '.'])) {
+                    // "<v.loud Esme Smith>": classes come before the name
+                    let name = name.split_once(' ').map_or("", |(_, name)| name).trim();
+                    if !name.is_empty() {
+                        voice = Some(name.to_string());
+                    }
+                }
+                rest = &rest[end + 1..];
+            },
+            None => {
+                text.push(character);
+                rest = &rest[character.len_utf8()..];
+            },
+        }
+    }
+
+    (decode_entities(&text), voice)
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&lrm;", "")
+        .replace("&rlm;", "")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// "JOHN: text", "DR. SMITH: text" or "Speaker 2: text" (as written by speech recognition)
+fn speaker_label(line: &str) -> Option<(String, &str)> {
+    let (label, rest) = line.split_once(':')?;
+    let label = label.trim().trim_start_matches('-').trim();
+    if label.is_empty() || label.len() > SPEAKER_LABEL_MAX_LEN || rest.trim().is_empty() {
+        return None;
+    }
+
+    let shouted = label.chars().any(char::is_alphabetic)
+        && label.chars().all(|character| character.is_uppercase() || matches!(character, ' ' | '.' | '\'' | '-'));
+    let numbered = label.to_lowercase().strip_prefix("speaker")
+        .is_some_and(|number| number.trim().chars().all(|character| character.is_ascii_alphanumeric()));
+    (shouted || numbered).then(|| (label.to_string(), rest.trim()))
+}
+
+// ASS text: "{...}" override blocks removed, "\N" line breaks; drawings ("{\p1}") are not text
+fn clean_ass_text(text: &str) -> Option<String> {
+    if text.contains("\\p1") || text.contains("\\p2") || text.contains("\\p3") || text.contains("\\p4") {
+        return None;
+    }
+    let (text, _) = strip_tags(text);
+    let text = text.replace("\\N", "\n").replace("\\n", "\n").replace("\\h", " ");
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to paragraphs
+//-------------------------------------------------
+
+// Roll-up captions (YouTube, broadcast) repeat the previous line at the top of every cue
+fn drop_repeated_lines(cues: Vec<Cue>) -> Vec<Cue> {
+    let mut kept: Vec<Cue> = Vec::new();
+
+    for mut cue in cues {
+        if let Some(previous) = kept.last_mut() {
+            if cue.text == previous.text && cue.speaker == previous.speaker {
+                previous.end_ms = previous.end_ms.max(cue.end_ms);
+                continue;
+            }
+            let last_line = previous.text.lines().last().unwrap_or("").to_string();
+            if let Some(rest) = cue.text.strip_prefix(&last_line).filter(|_| !last_line.is_empty())
+                && (rest.is_empty() || rest.starts_with('\n')) {
+                cue.text = rest.trim_start().to_string();
+            }
+        }
+        if !cue.text.is_empty() {
+            kept.push(cue);
+        }
+    }
+
+    kept
+}
+
+fn merge_cues(cues: Vec<Cue>) -> Vec<Cue> {
+    let mut paragraphs: Vec<Cue> = Vec::new();
+
+    for cue in cues {
+        let text = cue.text.split_whitespace().collect::<Vec<&str>>().join(" ");
+        if let Some(paragraph) = paragraphs.last_mut() {
+            let same_speaker = cue.speaker.is_none() || cue.speaker == paragraph.speaker;
+            let pause = cue.start_ms.saturating_sub(paragraph.end_ms) >= PARAGRAPH_PAUSE_MS;
+            let finished = ends_sentence(&paragraph.text) && (pause || paragraph.text.len() >= PARAGRAPH_MAX_LEN);
+            if same_speaker && !finished {
+                paragraph.text.push(' ');
+                paragraph.text.push_str(&text);
+                paragraph.end_ms = paragraph.end_ms.max(cue.end_ms);
+                continue;
+            }
+        }
+        paragraphs.push(Cue { text, ..cue });
+    }
+
+    paragraphs
+}
+
+fn ends_sentence(text: &str) -> bool {
+    let text = text.trim_end_matches(['"', '\'', ')', ']', '»', '”', '’', '♪', ' ']);
+    text.ends_with(['.', '!', '?', '…', '。', '！', '？'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::ParseEvent;
+
+    // (speaker, text) of every paragraph
+    fn spoken(text: &str, format: SubtitleFormat) -> Vec<(Option<String>, String)> {
+        let mut spoken = Vec::new();
+        let mut speaker = None;
+        for element in parse_subtitle_str(text, format) {
+            match element {
+                DocumentElement::Cue { speaker: cue_speaker, .. } => speaker = cue_speaker,
+                DocumentElement::Paragraph { text } => spoken.push((speaker.clone(), text)),
+                _ => {},
+            }
+        }
+        spoken
+    }
+
+    fn line(speaker: &str, text: &str) -> (Option<String>, String) {
+        (Some(speaker.to_string()), text.to_string())
+    }
+
+    #[test]
+    fn vtt_voices_in_one_cue_are_split() {
+        let vtt = "WEBVTT\n\n00:00:01.000 --> 00:00:03.000\n<v Alice>Are you coming?</v>\n<v Bob>No.</v>\n";
+        assert_eq!(spoken(vtt, SubtitleFormat::Vtt), vec![line("Alice", "Are you coming?"), line("Bob", "No.")]);
+    }
+
+    #[test]
+    fn srt_speaker_labels_in_one_cue_are_split() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,000\nJOHN: Hello.\nMARY: Hi.\n\n2\n00:00:02,500 --> 00:00:04,000\nHow are you?\n";
+        assert_eq!(spoken(srt, SubtitleFormat::Srt), vec![line("JOHN", "Hello."), line("MARY", "Hi. How are you?")]);
+    }
+
+    #[test]
+    fn cues_merge_until_a_sentence_ends_at_a_pause() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,000\n<i>The quick</i>\n\n\
+2\n00:00:02,000 --> 00:00:03,000\nbrown fox.\n\n\
+3\n00:00:06,000 --> 00:00:07,000\nJumps.\n";
+        assert_eq!(spoken(srt, SubtitleFormat::Srt), vec![
+            (None, "The quick brown fox.".to_string()),
+            (None, "Jumps.".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn latin1_subtitles_are_reported() {
+        let srt = b"1\n00:00:01,000 --> 00:00:02,500\nCaf\xe9 cr\xe8me.\n";
+        let path = std::env::temp_dir().join(format!("oxidoc-latin1-{}.srt", std::process::id()));
+        fs::write(&path, srt).unwrap();
+
+        let warnings = std::cell::RefCell::new(Vec::new());
+        let sink = |event: ParseEvent| if let ParseEvent::Warning { path, .. } = event {
+            warnings.borrow_mut().push(path);
+        };
+        let from_path = SubtitleParser.parse_path(&path, &sink);
+        fs::remove_file(&path).unwrap();
+        let from_bytes = SubtitleParser.parse_bytes(srt, &sink).unwrap();
+
+        // Only the filename title differs
+        assert_eq!(from_path.unwrap()[1..], from_bytes[..]);
+        assert!(from_bytes.contains(&DocumentElement::Paragraph { text: "Café crème.".to_string() }));
+        assert_eq!(*warnings.borrow(), vec![Some(path), None]);
+    }
+}
@@ -0,0 +1,280 @@
+// txt_parser.rs
+
+use std::fs;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::{EventSink, ParseEvent};
+use crate::parsers::documents_types::DocumentElement;
+use crate::parsers::parser::Parser;
+
+// Longest line still taken as an ALL-CAPS heading
+const CAPS_HEADING_MAX_CHARS: usize = 80;
+
+// Encoding a text file was decoded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+// Main parsing function
+pub fn parse_txt_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    parse_txt_path(path, &())
+}
+
+fn parse_txt_path(path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    // Read the text file (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+    let (text, encoding) = decode_text(&bytes);
+    warn_if_latin1(encoding, Some(path), events);
+
+    let mut elements = parse_txt_str(&text);
+
+    // Plain text has no metadata, so the filename is the title
+    if let Some(title_str) = path.file_stem().and_then(|filename| filename.to_str()) {
+        elements.insert(0, DocumentElement::Title {
+            text: title_str.to_string()
+        });
+    }
+
+    Ok(elements)
+}
+
+// Decode text by its byte order mark, as UTF-8 when valid, and as Latin-1 otherwise.
+// Latin-1 maps every byte to a character, so decoding never fails.
+pub fn decode_text(bytes: &[u8]) -> (String, TextEncoding) {
+    let utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| from_bytes([pair[0], pair[1]])).collect();
+        String::from_utf16_lossy(&units)
+    };
+
+    if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        return (String::from_utf8_lossy(rest).into_owned(), TextEncoding::Utf8);
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+        return (utf16(rest, u16::from_le_bytes), TextEncoding::Utf16Le);
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+        return (utf16(rest, u16::from_be_bytes), TextEncoding::Utf16Be);
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => (text.to_string(), TextEncoding::Utf8),
+        Err(_) => (bytes.iter().map(|byte| *byte as char).collect(), TextEncoding::Latin1),
+    }
+}
+
+// Split plain text into headings, lists and paragraphs
+pub fn parse_txt_str(text: &str) -> Vec<DocumentElement> {
+    // Normalize Windows and old Mac line endings
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+
+    // Blocks are separated by blank lines
+    let mut elements = Vec::new();
+    let mut block: Vec<&str> = Vec::new();
+    for line in text.lines().map(|line| line.trim_end()) {
+        if line.is_empty() {
+            parse_block(&block, &mut elements);
+            block.clear();
+        } else {
+            block.push(line);
+        }
+    }
+    parse_block(&block, &mut elements);
+
+    elements
+}
+
+// Plain text implementation of the common Parser trait
+pub struct TxtParser;
+
+impl Parser for TxtParser {
+    fn name(&self) -> &str {
+        "txt"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["txt", "text"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["text/plain"]
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        let (text, encoding) = decode_text(bytes);
+        warn_if_latin1(encoding, None, events);
+        Ok(parse_txt_str(&text))
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_txt_path(path, events)
+    }
+}
+
+// Report text that was not valid UTF-8 (nor marked by a BOM) and was read as Latin-1
+pub fn warn_if_latin1(encoding: TextEncoding, path: Option<&Path>, events: &dyn EventSink) {
+    if encoding == TextEncoding::Latin1 {
+        events.emit(ParseEvent::Warning {
+            path: path.map(|path| path.to_path_buf()),
+            message: "text is not valid UTF-8, decoded as Latin-1".to_string(),
+        });
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to text structure
+//-------------------------------------------------
+
+// Classify the lines of one blank-line separated block
+fn parse_block(lines: &[&str], elements: &mut Vec<DocumentElement>) {
+    let Some(first) = lines.first() else {
+        return;
+    };
+
+    // Setext style heading: a line underlined with "===" or "---"
+    if let Some(underline) = lines.get(1)
+        && let Some(level) = underline_level(underline) {
+        elements.push(DocumentElement::Heading { level, text: first.trim().to_string() });
+        parse_block(&lines[2..], elements);
+        return;
+    }
+
+    // A lone "-----" is a horizontal rule
+    if lines.len() == 1 && underline_level(first).is_some() {
+        return;
+    }
+
+    if lines.len() == 1 && is_caps_heading(first) {
+        let text = first.trim().trim_end_matches(':').trim_end().to_string();
+        elements.push(DocumentElement::Heading { level: 2, text });
+        return;
+    }
+
+    // Lines before the first list marker form a paragraph, then each marker opens an item.
+    // Indented lines continue the current item; an unindented one ends the list.
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut items: Vec<String> = Vec::new();
+    let mut ordered = false;
+
+    for line in lines {
+        if let Some((item_ordered, rest)) = list_marker(line) {
+            if !items.is_empty() && item_ordered != ordered {
+                elements.push(DocumentElement::List { items: std::mem::take(&mut items), ordered });
+            }
+            push_paragraph(&mut paragraph, elements);
+            ordered = item_ordered;
+            items.push(rest.trim().to_string());
+        } else if let Some(item) = items.last_mut()
+            && line.starts_with(char::is_whitespace) {
+            item.push(' ');
+            item.push_str(line.trim());
+        } else {
+            if !items.is_empty() {
+                elements.push(DocumentElement::List { items: std::mem::take(&mut items), ordered });
+            }
+            paragraph.push(line.trim());
+        }
+    }
+
+    if !items.is_empty() {
+        elements.push(DocumentElement::List { items, ordered });
+    }
+    push_paragraph(&mut paragraph, elements);
+}
+
+// Hard-wrapped lines are one paragraph
+fn push_paragraph(lines: &mut Vec<&str>, elements: &mut Vec<DocumentElement>) {
+    if !lines.is_empty() {
+        elements.push(DocumentElement::Paragraph { text: lines.join(" ") });
+        lines.clear();
+    }
+}
+
+// "====" underlines a level 1 heading, "----" a level 2 heading
+fn underline_level(line: &str) -> Option<u8> {
+    let line = line.trim();
+    if line.chars().count() < 3 {
+        return None;
+    }
+    if line.chars().all(|c| c == '=') {
+        Some(1)
+    } else if line.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+// A short line whose letters are all capitals, such as "INTRODUCTION" or "RUST:"
+fn is_caps_heading(line: &str) -> bool {
+    let line = line.trim();
+    let mut letters = line.chars().filter(|c| c.is_alphabetic()).peekable();
+
+    line.chars().count() <= CAPS_HEADING_MAX_CHARS
+        && letters.peek().is_some()
+        && letters.all(|c| c.is_uppercase())
+        && !line.ends_with('.')
+}
+
+// Recognize a bullet ("•", "-", "*"...) or numbering ("1.", "2)", "a)", "(iv)") at the start of a line.
+// Returns whether the list is ordered and the text after the marker.
+pub fn list_marker(text: &str) -> Option<(bool, &str)> {
+    let text = text.trim_start();
+    let (marker, rest) = text.split_once(char::is_whitespace)?;
+    let rest = rest.trim_start();
+    if rest.is_empty() {
+        return None;
+    }
+
+    const BULLETS: &[&str] = &["•", "◦", "▪", "▫", "‣", "∙", "●", "○", "■", "□", "–", "-", "*", "+", "·"];
+    if BULLETS.contains(&marker) {
+        return Some((false, rest));
+    }
+
+    let numbering = marker.strip_prefix('(').and_then(|inner| inner.strip_suffix(')'))
+        .or_else(|| marker.strip_suffix('.'))
+        .or_else(|| marker.strip_suffix(')'))?;
+    let is_number = !numbering.is_empty() && numbering.len() <= 3 && numbering.chars().all(|c| c.is_ascii_digit());
+    let is_letter = numbering.len() == 1 && numbering.chars().all(|c| c.is_ascii_lowercase());
+    let is_roman = !numbering.is_empty() && numbering.len() <= 4 && numbering.chars().all(|c| "ivx".contains(c));
+
+    (is_number || is_letter || is_roman).then_some((true, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_become_headings_lists_and_paragraphs() {
+        let text = "Title\r\n=====\r\n\r\nINTRODUCTION:\r\n\r\nThis paragraph is\r\nhard-wrapped.\r\n\r\nSteps:\r\n1. Open\r\n   the lid\r\n2) Pour\r\n- loose\r\n";
+        assert_eq!(parse_txt_str(text), vec![
+            DocumentElement::Heading { level: 1, text: "Title".to_string() },
+            DocumentElement::Heading { level: 2, text: "INTRODUCTION".to_string() },
+            DocumentElement::Paragraph { text: "This paragraph is hard-wrapped.".to_string() },
+            DocumentElement::Paragraph { text: "Steps:".to_string() },
+            DocumentElement::List { items: vec!["Open the lid".to_string(), "Pour".to_string()], ordered: true },
+            DocumentElement::List { items: vec!["loose".to_string()], ordered: false },
+        ]);
+    }
+
+    #[test]
+    fn text_is_decoded_by_byte_order_mark_then_utf8_then_latin1() {
+        assert_eq!(decode_text(b"\xFF\xFEh\x00i\x00"), ("hi".to_string(), TextEncoding::Utf16Le));
+        assert_eq!(decode_text("café".as_bytes()), ("café".to_string(), TextEncoding::Utf8));
+        assert_eq!(decode_text(b"caf\xE9"), ("café".to_string(), TextEncoding::Latin1));
+    }
+
+    #[test]
+    fn list_markers_need_text_after_them() {
+        assert_eq!(list_marker("• item"), Some((false, "item")));
+        assert_eq!(list_marker("(iv) item"), Some((true, "item")));
+        assert_eq!(list_marker("12. item"), Some((true, "item")));
+        assert_eq!(list_marker("-"), None);
+        assert_eq!(list_marker("2024. was a year"), None);
+    }
+}
@@ -0,0 +1,477 @@
+// warc_parser.rs
+
+// Web archives: WARC (.warc, .warc.gz) and the older ARC (.arc, .arc.gz) format. Archives are read
+// as a stream, one record at a time (gzipped archives member by member), so crawls of any size never
+// sit in memory. Every successful (2xx) HTTP response and every resource record holding HTML or a
+// PDF becomes a Capture marker carrying its target URI and capture date, followed by the elements
+// the HTML or PDF parser extracts from the payload. Chunked transfer encoding and gzip or deflate
+// content encoding are undone first; other records (requests, metadata, DNS, images...) are skipped.
+
+use flate2::read::{DeflateDecoder, GzDecoder, MultiGzDecoder, ZlibDecoder};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::{EventSink, ParseEvent};
+use crate::parsers::documents_types::DocumentElement;
+use crate::parsers::html_parser::{decode_html, parse_html_str, HtmlParser};
+use crate::parsers::parser::Parser;
+use crate::parsers::pdf_parser::PdfParser;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+// Header names (lowercased) and values, in record order
+type Headers = Vec<(String, String)>;
+
+// What a payload is sent to
+enum Payload {
+    Html,
+    Pdf,
+}
+
+// Main parsing function
+pub fn parse_warc_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    parse_warc_path(path, &())
+}
+
+fn parse_warc_path(path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    // Open the archive (a missing file is an error)
+    let file = File::open(path).map_err(|source| OxidocError::io(path, source))?;
+
+    let mut elements = Vec::new();
+    parse_warc_archive(&mut BufReader::new(file), events, &mut |capture| {
+        elements.extend(capture);
+        Ok(())
+    }).map_err(|error| error.with_path(path))?;
+    Ok(elements)
+}
+
+// Stream the records of a WARC or ARC archive, gzipped or not, handing the elements of each
+// capture to `capture` as soon as it is read
+pub fn parse_warc_archive(
+    reader: &mut dyn Read,
+    events: &dyn EventSink,
+    capture: &mut dyn FnMut(Vec<DocumentElement>) -> Result<()>,
+) -> Result<()> {
+    let mut reader = BufReader::new(reader);
+    if reader.fill_buf()?.starts_with(GZIP_MAGIC) {
+        // Every record is a gzip member of its own
+        archive_records(&mut BufReader::new(MultiGzDecoder::new(reader)), events, capture)
+    } else {
+        archive_records(&mut reader, events, capture)
+    }
+}
+
+// WARC implementation of the common Parser trait
+pub struct WarcParser;
+
+impl Parser for WarcParser {
+    fn name(&self) -> &str {
+        "warc"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["warc", "arc"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/warc", "application/x-internet-archive"]
+    }
+
+    // "WARC/1.0" or an ARC "filedesc://" header, possibly gzipped (.warc.gz, .arc.gz)
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        if bytes.starts_with(GZIP_MAGIC) {
+            // The prefix is a truncated gzip stream: inflate what it holds
+            let mut start = Vec::new();
+            let _ = GzDecoder::new(bytes).take(16).read_to_end(&mut start);
+            return is_archive(&start);
+        }
+        is_archive(bytes)
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        let mut elements = Vec::new();
+        parse_warc_archive(&mut Cursor::new(bytes), events, &mut |capture| {
+            elements.extend(capture);
+            Ok(())
+        })?;
+        Ok(elements)
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_warc_path(path, events)
+    }
+
+    // Archives are streamed capture by capture
+    fn parse_records(
+        &self,
+        reader: &mut dyn Read,
+        events: &dyn EventSink,
+        record: &mut dyn FnMut(Vec<DocumentElement>) -> Result<()>,
+    ) -> Result<()> {
+        parse_warc_archive(reader, events, record)
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to archive records
+//-------------------------------------------------
+
+fn is_archive(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"WARC/") || bytes.starts_with(b"filedesc://")
+}
+
+fn archive_records(
+    reader: &mut dyn BufRead,
+    events: &dyn EventSink,
+    capture: &mut dyn FnMut(Vec<DocumentElement>) -> Result<()>,
+) -> Result<()> {
+    let mut line = Vec::new();
+    loop {
+        // Records are separated by blank lines
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        let header_line = String::from_utf8_lossy(&line).trim().to_string();
+        if header_line.is_empty() {
+            continue;
+        }
+
+        let elements = if header_line.starts_with("WARC/") {
+            warc_record(reader, events)?
+        } else {
+            arc_record(&header_line, reader, events)?
+        };
+        if let Some(elements) = elements {
+            capture(elements)?;
+        }
+    }
+}
+
+// A WARC record: header fields, then a block of Content-Length bytes
+fn warc_record(reader: &mut dyn BufRead, events: &dyn EventSink) -> Result<Option<Vec<DocumentElement>>> {
+    let headers = read_headers(reader)?;
+    let length = header(&headers, "content-length")
+        .and_then(|length| length.parse().ok())
+        .ok_or_else(|| OxidocError::malformed("WARC record without a valid Content-Length"))?;
+    let mut block = reader.take(length);
+
+    let uri = header(&headers, "warc-target-uri")
+        .map(|uri| uri.trim_start_matches('<').trim_end_matches('>').to_string());
+    let date = header(&headers, "warc-date").map(str::to_string);
+    let elements = match (header(&headers, "warc-type"), uri) {
+        (Some("response"), Some(uri)) if is_http(&uri) => http_capture(&mut block, uri, date, events)?,
+        // Resource records hold the payload itself, described by the record's Content-Type
+        (Some("resource"), Some(uri)) => {
+            let content_type = header(&headers, "content-type").unwrap_or_default().to_string();
+            payload_capture(&mut block, &content_type, uri, date, events)?
+        },
+        _ => None,
+    };
+
+    skip_block(&mut block)?;
+    Ok(elements)
+}
+
+// An ARC record: "URL IP-address date content-type [...] length" on one line, then the block
+fn arc_record(header_line: &str, reader: &mut dyn BufRead, events: &dyn EventSink) -> Result<Option<Vec<DocumentElement>>> {
+    let fields: Vec<&str> = header_line.split_whitespace().collect();
+    let length = fields.last()
+        .filter(|_| fields.len() >= 5)
+        .and_then(|length| length.parse().ok())
+        .ok_or_else(|| OxidocError::malformed(format!("not a WARC or ARC record: {:?}", header_line)))?;
+    let mut block = reader.take(length);
+
+    // The "filedesc://" record describing the archive itself is skipped like any non-HTTP record
+    let elements = if is_http(fields[0]) {
+        http_capture(&mut block, fields[0].to_string(), arc_date(fields[2]), events)?
+    } else {
+        None
+    };
+
+    skip_block(&mut block)?;
+    Ok(elements)
+}
+
+// Consume what is left of a record's block; a block cut short means the archive is truncated
+fn skip_block(block: &mut io::Take<&mut dyn BufRead>) -> Result<()> {
+    io::copy(block, &mut io::sink())?;
+    if block.limit() > 0 {
+        return Err(OxidocError::malformed("web archive ends inside a record"));
+    }
+    Ok(())
+}
+
+fn is_http(uri: &str) -> bool {
+    let uri = uri.to_ascii_lowercase();
+    uri.starts_with("http://") || uri.starts_with("https://")
+}
+
+// "20050614070159" -> "2005-06-14T07:01:59Z"
+fn arc_date(date: &str) -> Option<String> {
+    if date.len() < 14 || !date.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}T{}:{}:{}Z",
+        &date[0..4], &date[4..6], &date[6..8], &date[8..10], &date[10..12], &date[12..14],
+    ))
+}
+
+// "Name: value" lines up to a blank line; lines starting with a space continue the previous value
+fn read_headers(reader: &mut dyn BufRead) -> Result<Headers> {
+    let mut headers: Headers = Vec::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(headers);
+        }
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim_end_matches(['\r', '\n']);
+        if text.trim().is_empty() {
+            return Ok(headers);
+        }
+
+        if text.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(text.trim());
+            }
+        } else if let Some((name, value)) = text.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to HTTP payloads
+//-------------------------------------------------
+
+// An archived HTTP response: status line, headers, then the (possibly encoded) body
+fn http_capture(block: &mut dyn BufRead, uri: String, date: Option<String>, events: &dyn EventSink) -> Result<Option<Vec<DocumentElement>>> {
+    let mut status_line = Vec::new();
+    block.read_until(b'\n', &mut status_line)?;
+    let status_line = String::from_utf8_lossy(&status_line);
+    let status = status_line.strip_prefix("HTTP/")
+        .and_then(|rest| rest.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok());
+    // Redirects, errors and non-HTTP blocks hold nothing worth extracting
+    if !status.is_some_and(|status| (200..300).contains(&status)) {
+        return Ok(None);
+    }
+
+    let headers = read_headers(block)?;
+    let content_type = header(&headers, "content-type").unwrap_or_default();
+    let Some(payload) = payload_kind(content_type) else {
+        return Ok(None);
+    };
+
+    let mut body = Vec::new();
+    block.read_to_end(&mut body)?;
+    if header(&headers, "transfer-encoding").is_some_and(|encoding| encoding.to_lowercase().contains("chunked")) {
+        body = dechunk(&body);
+    }
+    if let Some(encoding) = header(&headers, "content-encoding") {
+        body = match decode_content(body, encoding) {
+            Ok(body) => body,
+            Err(message) => {
+                events.emit(ParseEvent::Warning { path: None, message: format!("{}: {}, capture skipped", uri, message) });
+                return Ok(None);
+            },
+        };
+    }
+
+    capture_elements(payload, &body, content_type, uri, date, events)
+}
+
+// A payload stored as is (resource records)
+fn payload_capture(block: &mut dyn BufRead, content_type: &str, uri: String, date: Option<String>, events: &dyn EventSink) -> Result<Option<Vec<DocumentElement>>> {
+    let Some(payload) = payload_kind(content_type) else {
+        return Ok(None);
+    };
+    let mut body = Vec::new();
+    block.read_to_end(&mut body)?;
+    capture_elements(payload, &body, content_type, uri, date, events)
+}
+
+// HTML and PDF payloads; anything else is skipped without being read
+fn payload_kind(content_type: &str) -> Option<Payload> {
+    let mime_type = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+    if HtmlParser.mime_types().contains(&mime_type.as_str()) {
+        Some(Payload::Html)
+    } else if PdfParser::new().mime_types().contains(&mime_type.as_str()) {
+        Some(Payload::Pdf)
+    } else {
+        None
+    }
+}
+
+fn capture_elements(
+    payload: Payload,
+    body: &[u8],
+    content_type: &str,
+    uri: String,
+    date: Option<String>,
+    events: &dyn EventSink,
+) -> Result<Option<Vec<DocumentElement>>> {
+    let content = match payload {
+        Payload::Html => parse_html_str(&decode_html(body, Some(content_type), None, events)).to_elements(),
+        Payload::Pdf => match PdfParser::new().parse_bytes(body, events) {
+            Ok(elements) => elements,
+            // Crawlers often cut large PDFs short; one unreadable capture does not stop the archive
+            Err(error) => {
+                events.emit(ParseEvent::Warning { path: None, message: format!("{}: {}, capture skipped", uri, error) });
+                return Ok(None);
+            },
+        },
+    };
+
+    let mut elements = vec![DocumentElement::Capture { uri, date }];
+    elements.extend(content);
+    Ok(Some(elements))
+}
+
+// Body of a "Transfer-Encoding: chunked" response: hex chunk sizes, each followed by that many
+// bytes, up to a zero size. A body that does not follow the syntax is returned unchanged.
+fn dechunk(body: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(body.len());
+    let mut rest = body;
+    loop {
+        let Some(line_end) = rest.iter().position(|byte| *byte == b'\n') else {
+            return body.to_vec();
+        };
+        let size_line = String::from_utf8_lossy(&rest[..line_end]);
+        let size = size_line.split(';').next().unwrap_or_default().trim();
+        let Ok(size) = usize::from_str_radix(size, 16) else {
+            return body.to_vec();
+        };
+        rest = &rest[line_end + 1..];
+        if size == 0 {
+            return decoded;
+        }
+
+        // A truncated last chunk keeps what was captured
+        let chunk = &rest[..size.min(rest.len())];
+        decoded.extend_from_slice(chunk);
+        rest = &rest[chunk.len()..];
+        rest = rest.strip_prefix(b"\r\n").or_else(|| rest.strip_prefix(b"\n")).unwrap_or(rest);
+        if rest.is_empty() {
+            return decoded;
+        }
+    }
+}
+
+// Undo "Content-Encoding: gzip" or "deflate". Some servers announce an encoding they did not apply,
+// so bodies without the gzip magic bytes are kept as they are.
+fn decode_content(body: Vec<u8>, encoding: &str) -> std::result::Result<Vec<u8>, String> {
+    let mut decoded = Vec::new();
+    match encoding.trim().to_lowercase().as_str() {
+        "gzip" | "x-gzip" if body.starts_with(GZIP_MAGIC) => {
+            MultiGzDecoder::new(body.as_slice()).read_to_end(&mut decoded)
+                .map_err(|error| format!("invalid gzip body ({})", error))?;
+        },
+        "gzip" | "x-gzip" | "identity" | "" => return Ok(body),
+        // "deflate" is meant to be zlib-wrapped, but raw deflate is common
+        "deflate" => {
+            if ZlibDecoder::new(body.as_slice()).read_to_end(&mut decoded).is_err() {
+                decoded.clear();
+                DeflateDecoder::new(body.as_slice()).read_to_end(&mut decoded)
+                    .map_err(|error| format!("invalid deflate body ({})", error))?;
+            }
+        },
+        other => return Err(format!("unsupported content encoding {:?}", other)),
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn record(warc_type: &str, uri: &str, date: &str, content_type: &str, block: &[u8]) -> Vec<u8> {
+        let mut record = format!(
+            "WARC/1.0\r\nWARC-Type: {}\r\nWARC-Target-URI: <{}>\r\nWARC-Date: {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+            warc_type, uri, date, content_type, block.len(),
+        ).into_bytes();
+        record.extend_from_slice(block);
+        record.extend_from_slice(b"\r\n\r\n");
+        record
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    // A request, a gzipped and chunked HTML response, a 404 and an HTML resource
+    fn records() -> Vec<Vec<u8>> {
+        let body = gzip(b"<html><body><p>Hello archive</p></body></html>");
+        let mut chunked = format!("{:x}\r\n", 10).into_bytes();
+        chunked.extend_from_slice(&body[..10]);
+        chunked.extend_from_slice(format!("\r\n{:x}\r\n", body.len() - 10).as_bytes());
+        chunked.extend_from_slice(&body[10..]);
+        chunked.extend_from_slice(b"\r\n0\r\n\r\n");
+        let mut response = b"HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nTransfer-Encoding: chunked\r\nContent-Encoding: gzip\r\n\r\n".to_vec();
+        response.extend_from_slice(&chunked);
+
+        vec![
+            record("request", "http://a.example/", "2024-01-01T00:00:00Z", "application/http; msgtype=request", b"GET / HTTP/1.1\r\n\r\n"),
+            record("response", "http://a.example/", "2024-01-01T00:00:01Z", "application/http; msgtype=response", &response),
+            record("response", "http://a.example/missing", "2024-01-01T00:00:02Z", "application/http; msgtype=response",
+                b"HTTP/1.1 404 Not Found\r\nContent-Type: text/html\r\n\r\n<p>Not here</p>"),
+            record("resource", "https://b.example/page", "2024-01-02T00:00:00Z", "text/html", b"<p>Stored as is</p>"),
+        ]
+    }
+
+    fn captures(archive: &[u8]) -> Vec<Vec<DocumentElement>> {
+        let mut captures = Vec::new();
+        parse_warc_archive(&mut Cursor::new(archive), &(), &mut |capture| {
+            captures.push(capture);
+            Ok(())
+        }).unwrap();
+        captures
+    }
+
+    #[test]
+    fn successful_html_captures_carry_their_uri_and_date() {
+        let captures = captures(&records().concat());
+
+        assert_eq!(captures, vec![
+            vec![
+                DocumentElement::Capture { uri: "http://a.example/".to_string(), date: Some("2024-01-01T00:00:01Z".to_string()) },
+                DocumentElement::Paragraph { text: "Hello archive".to_string() },
+            ],
+            vec![
+                DocumentElement::Capture { uri: "https://b.example/page".to_string(), date: Some("2024-01-02T00:00:00Z".to_string()) },
+                DocumentElement::Paragraph { text: "Stored as is".to_string() },
+            ],
+        ]);
+    }
+
+    #[test]
+    fn gzipped_archives_are_read_member_by_member() {
+        let records = records();
+        let archive: Vec<u8> = records.iter().flat_map(|record| gzip(record)).collect();
+
+        assert!(WarcParser.matches_magic(&archive));
+        assert_eq!(captures(&archive), captures(&records.concat()));
+    }
+
+    #[test]
+    fn bodies_that_are_not_chunked_are_kept() {
+        assert_eq!(dechunk(b"5\r\nHello\r\n6\r\n world\r\n0\r\n\r\n"), b"Hello world");
+        assert_eq!(dechunk(b"<html>"), b"<html>");
+    }
+}
@@ -0,0 +1,704 @@
+// xlsx_parser.rs
+
+use calamine::{open_workbook_auto_from_rs, Data, Reader};
+use roxmltree::Node;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use crate::error::{OxidocError, Result};
+use crate::events::{EventSink, ParseEvent};
+use crate::parsers::documents_types::{fallback_title, DocumentElement};
+use crate::parsers::odt_parser::odf_metadata;
+use crate::parsers::ooxml::{
+    attribute, child, children, document_properties, is_named, open_package, parse_xml, read_part, relationship_id,
+    relationships, Package,
+};
+use crate::parsers::parser::Parser;
+
+// Rows per Table unless the parser is told otherwise, so one sheet does not become one huge record
+pub const DEFAULT_CHUNK_ROWS: usize = 500;
+
+// How much of each sheet is read, and how it is split
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SheetLimits {
+    // Data rows read per sheet (the header row not included); the rest is dropped with a warning
+    pub max_rows: Option<usize>,
+    // Data rows per Table; every chunk repeats the header row
+    pub chunk_rows: Option<usize>,
+}
+
+impl Default for SheetLimits {
+    fn default() -> SheetLimits {
+        SheetLimits { max_rows: None, chunk_rows: Some(DEFAULT_CHUNK_ROWS) }
+    }
+}
+
+// Main parsing function
+pub fn parse_xlsx_file(path: &Path) -> Result<Vec<DocumentElement>> {
+    parse_xlsx_file_with(path, SheetLimits::default(), &())
+}
+
+pub fn parse_xlsx_file_with(path: &Path, limits: SheetLimits, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    // Read the spreadsheet (a missing file is an error)
+    let bytes = fs::read(path).map_err(|source| OxidocError::io(path, source))?;
+
+    let mut elements = parse_xlsx_bytes_with(&bytes, limits, events).map_err(|error| error.with_path(path))?;
+
+    fallback_title(&mut elements, path);
+
+    Ok(elements)
+}
+
+// Parse an in-memory workbook (XLSX, XLSM, XLSB, XLS or ODS)
+pub fn parse_xlsx_bytes(bytes: &[u8]) -> Result<Vec<DocumentElement>> {
+    parse_xlsx_bytes_with(bytes, SheetLimits::default(), &())
+}
+
+// Each sheet becomes its name as a Heading and its cells as one or more Tables,
+// every Table introduced by a Sheet marker with the cell range it covers
+pub fn parse_xlsx_bytes_with(bytes: &[u8], limits: SheetLimits, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes))
+        .map_err(|error| OxidocError::malformed(format!("spreadsheet could not be read: {}", error)))?;
+
+    // Office Open XML workbooks carry the same document properties as DOCX and PPTX,
+    // OpenDocument spreadsheets the same meta.xml as ODT. calamine only applies date formats,
+    // so the other number formats are read from the package too (binary XLS and XLSB keep raw values).
+    let (mut elements, formats) = match open_package(bytes) {
+        Ok(mut package) if package.file_names().any(|name| name.starts_with("xl/")) => {
+            (document_properties(&mut package)?, xlsx_number_formats(&mut package)?)
+        },
+        Ok(mut package) if package.file_names().any(|name| name == "content.xml") => {
+            (odf_metadata(&mut package)?, ods_number_formats(&mut package)?)
+        },
+        _ => (Vec::new(), HashMap::new()),
+    };
+
+    for name in workbook.sheet_names() {
+        let range = workbook.worksheet_range(&name)
+            .map_err(|error| OxidocError::malformed(format!("sheet {:?} could not be read: {}", name, error)))?;
+        elements.extend(sheet_elements(&name, &range, formats.get(&name), limits, events));
+    }
+
+    Ok(elements)
+}
+
+// Spreadsheet implementation of the common Parser trait
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XlsxParser {
+    pub limits: SheetLimits,
+}
+
+impl XlsxParser {
+    pub fn new() -> XlsxParser {
+        XlsxParser::default()
+    }
+
+    // Only read the first `max_rows` data rows of every sheet
+    pub fn with_max_rows(mut self, max_rows: usize) -> XlsxParser {
+        self.limits.max_rows = Some(max_rows);
+        self
+    }
+
+    // Split sheets into Tables of `chunk_rows` data rows; None keeps each sheet in one Table
+    pub fn with_chunk_rows(mut self, chunk_rows: Option<usize>) -> XlsxParser {
+        self.limits.chunk_rows = chunk_rows;
+        self
+    }
+}
+
+impl Parser for XlsxParser {
+    fn name(&self) -> &str {
+        "xlsx"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["xlsx", "xlsm", "xlsb", "xls", "ods"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &[
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "application/vnd.ms-excel",
+            "application/vnd.oasis.opendocument.spreadsheet",
+        ]
+    }
+
+    // A zip whose first entries name Excel parts, or an ODS whose mimetype entry comes first
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(b"PK\x03\x04")
+            && (bytes.windows(3).any(|window| window == b"xl/")
+                || bytes.windows(46).any(|window| window == b"application/vnd.oasis.opendocument.spreadsheet"))
+    }
+
+    fn parse_bytes(&self, bytes: &[u8], events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_xlsx_bytes_with(bytes, self.limits, events)
+    }
+
+    fn parse_path(&self, path: &Path, events: &dyn EventSink) -> Result<Vec<DocumentElement>> {
+        parse_xlsx_file_with(path, self.limits, events)
+    }
+}
+
+
+//-------------------------------------------------
+// Block of Functions related to sheets
+//-------------------------------------------------
+
+fn sheet_elements(
+    name: &str,
+    range: &calamine::Range<Data>,
+    formats: Option<&CellFormats>,
+    limits: SheetLimits,
+    events: &dyn EventSink,
+) -> Vec<DocumentElement> {
+    let Some((first_row, first_column)) = range.start() else {
+        // Empty sheet
+        return Vec::new();
+    };
+
+    // Keep the sheet row number of every non-empty row
+    let mut rows: Vec<(u32, Vec<String>)> = range.rows()
+        .enumerate()
+        .map(|(index, row)| {
+            let row_number = first_row + index as u32;
+            let cells = row.iter()
+                .enumerate()
+                .map(|(column, cell)| format_cell(cell, formats.and_then(|formats| formats.get(&(row_number, first_column + column as u32)))))
+                .collect::<Vec<String>>();
+            (row_number, cells)
+        })
+        .filter(|(_, cells)| cells.iter().any(|cell| !cell.is_empty()))
+        .collect();
+    if rows.is_empty() {
+        return Vec::new();
+    }
+
+    // Drop columns that are empty in every row (formatting often stretches the used range)
+    let width = rows.iter()
+        .filter_map(|(_, cells)| cells.iter().rposition(|cell| !cell.is_empty()))
+        .max()
+        .map_or(0, |last| last + 1);
+    for (_, cells) in &mut rows {
+        cells.truncate(width);
+    }
+    let last_column = first_column + width.saturating_sub(1) as u32;
+
+    let (header_row, headers) = rows.remove(0);
+    if let Some(max_rows) = limits.max_rows
+        && rows.len() > max_rows {
+        events.emit(ParseEvent::Warning {
+            path: None,
+            message: format!("sheet {:?}: only the first {} of {} rows were read", name, max_rows, rows.len()),
+        });
+        rows.truncate(max_rows);
+    }
+
+    let chunk_rows = limits.chunk_rows.filter(|size| *size > 0).unwrap_or(rows.len().max(1));
+    let mut elements = Vec::new();
+
+    let chunks: Vec<&[(u32, Vec<String>)]> = if rows.is_empty() { vec![&[]] } else { rows.chunks(chunk_rows).collect() };
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        // The first chunk's range starts at the header row
+        let start_row = if index == 0 { header_row } else { chunk[0].0 };
+        let end_row = chunk.last().map_or(header_row, |(row, _)| *row);
+        elements.push(DocumentElement::Sheet {
+            name: name.to_string(),
+            range: format!("{}{}:{}{}", column_name(first_column), start_row + 1, column_name(last_column), end_row + 1),
+        });
+        if index == 0 {
+            elements.push(DocumentElement::Heading { level: 1, text: name.to_string() });
+        }
+        elements.push(DocumentElement::Table {
+            headers: headers.clone(),
+            rows: chunk.iter().map(|(_, cells)| cells.clone()).collect(),
+        });
+    }
+
+    elements
+}
+
+// Cell values as displayed: dates as ISO 8601, numbers in their number format ("25%", "$1,234.50")
+// or without float noise when they have none ("0.30000000000000004" -> "0.3")
+fn format_cell(cell: &Data, format: Option<&NumberFormat>) -> String {
+    match cell {
+        Data::Float(number) => format.map_or_else(|| format_number(*number), |format| format.apply(*number)),
+        Data::Int(number) => format.map_or_else(|| number.to_string(), |format| format.apply(*number as f64)),
+        Data::DateTime(date) if date.is_duration() => format_number(date.as_f64()),
+        Data::DateTime(date) => match date.as_datetime() {
+            Some(datetime) if datetime.time() == chrono::NaiveTime::MIN => datetime.date().to_string(),
+            // Times without a date are stored on the 1899-12-31 epoch
+            Some(datetime) if datetime.date() < chrono::NaiveDate::from_ymd_opt(1900, 1, 1).unwrap() => datetime.time().to_string(),
+            Some(datetime) => datetime.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            None => format_number(date.as_f64()),
+        },
+        Data::String(text) => text.trim().to_string(),
+        other => other.to_string(),
+    }
+}
+
+// Shortest text of the number once rounded to the 15 significant digits Excel keeps,
+// so tiny values ("1e-12") survive and binary noise does not
+fn format_number(number: f64) -> String {
+    if !number.is_finite() {
+        return number.to_string();
+    }
+    let rounded: f64 = format!("{:.14e}", number).parse().unwrap_or(number);
+    match rounded.abs() {
+        0.0 => "0".to_string(),
+        // Scientific notation past the point where Excel itself switches to it
+        magnitude if !(1e-15..1e21).contains(&magnitude) => format!("{:e}", rounded),
+        _ => rounded.to_string(),
+    }
+}
+
+// "B3" -> (2, 1): zero-based row and column
+fn cell_position(reference: &str) -> Option<(u32, u32)> {
+    let split = reference.find(|character: char| character.is_ascii_digit())?;
+    let (letters, digits) = reference.split_at(split);
+    if letters.is_empty() || !letters.bytes().all(|byte| byte.is_ascii_alphabetic()) {
+        return None;
+    }
+    let column = letters.bytes().fold(0u32, |column, byte| column * 26 + (byte.to_ascii_uppercase() - b'A' + 1) as u32);
+    let row: u32 = digits.parse().ok()?;
+    Some((row.checked_sub(1)?, column - 1))
+}
+
+// 0 -> "A", 25 -> "Z", 26 -> "AA"
+fn column_name(index: u32) -> String {
+    let mut name = String::new();
+    let mut index = index + 1;
+    while index > 0 {
+        let remainder = (index - 1) % 26;
+        name.insert(0, (b'A' + remainder as u8) as char);
+        index = (index - 1) / 26;
+    }
+    name
+}
+
+//-------------------------------------------------
+// Block of Functions related to number formats
+//-------------------------------------------------
+
+// Number format of every formatted cell of a sheet, by zero-based (row, column)
+type CellFormats = HashMap<(u32, u32), NumberFormat>;
+
+// How a number is displayed. Dates are left to calamine; negative sections, colours and
+// conditions of a format code are ignored.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct NumberFormat {
+    // Digits after the decimal point; None keeps the shortest text of the value
+    decimals: Option<usize>,
+    // "1,234,567"
+    thousands: bool,
+    // Shown as a share of 100: 0.25 -> "25%"
+    percent: bool,
+    // "1.23E+04"
+    scientific: bool,
+    // Literal text around the digits ("$", " €", "%")
+    prefix: String,
+    suffix: String,
+}
+
+impl NumberFormat {
+    fn apply(&self, number: f64) -> String {
+        if !number.is_finite() {
+            return format_number(number);
+        }
+        let value = if self.percent { number * 100.0 } else { number };
+
+        let digits = if self.scientific {
+            let decimals = self.decimals.unwrap_or(2);
+            let text = format!("{:.*e}", decimals, value.abs());
+            let (mantissa, exponent) = text.split_once('e').unwrap_or((&text, "0"));
+            let exponent: i32 = exponent.parse().unwrap_or(0);
+            format!("{}E{}{:02}", mantissa, if exponent < 0 { '-' } else { '+' }, exponent.abs())
+        } else {
+            let text = match self.decimals {
+                Some(decimals) => format!("{:.*}", decimals, value.abs()),
+                None => format_number(value.abs()),
+            };
+            if self.thousands { group_thousands(&text) } else { text }
+        };
+
+        // No minus sign on a value that rounds to zero
+        let sign = if value < 0.0 && digits.bytes().any(|byte| (b'1'..=b'9').contains(&byte)) { "-" } else { "" };
+        // Codes such as "#,##0 " pad the digits with spaces
+        format!("{}{}{}{}", sign, self.prefix, digits, self.suffix).trim().to_string()
+    }
+}
+
+// "1234567.5" -> "1,234,567.5"
+fn group_thousands(text: &str) -> String {
+    let integer_end = text.find(|character: char| !character.is_ascii_digit()).unwrap_or(text.len());
+    let (integer, rest) = text.split_at(integer_end);
+    if rest.starts_with(['e', 'E']) {
+        return text.to_string();
+    }
+
+    let mut grouped = String::with_capacity(text.len() + integer.len() / 3);
+    for (index, digit) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped.push_str(rest);
+    grouped
+}
+
+// Format codes of the built-in number formats (ECMA-376 18.8.30) that are not dates. Currency
+// formats 5 to 8 depend on the locale of the reader; "$" is the specification's default.
+fn builtin_format_code(id: u32) -> Option<&'static str> {
+    match id {
+        1 => Some("0"),
+        2 => Some("0.00"),
+        3 => Some("#,##0"),
+        4 => Some("#,##0.00"),
+        5 | 6 => Some("$#,##0_);($#,##0)"),
+        7 | 8 => Some("$#,##0.00_);($#,##0.00)"),
+        9 => Some("0%"),
+        10 => Some("0.00%"),
+        11 => Some("0.00E+00"),
+        37 | 38 => Some("#,##0 ;(#,##0)"),
+        39 | 40 => Some("#,##0.00;(#,##0.00)"),
+        48 => Some("##0.0E+0"),
+        _ => None,
+    }
+}
+
+// Read the section of a format code for positive numbers. None for "General", text ("@"),
+// date and time formats, and codes without any digit placeholder.
+fn parse_format_code(code: &str) -> Option<NumberFormat> {
+    let mut format = NumberFormat::default();
+    let mut placeholders = 0;
+    let mut decimal_point = false;
+    let mut decimals = 0;
+    let mut pending_comma = false;
+    let mut characters = code.chars().peekable();
+
+    // Literal text goes before the digits until the first placeholder, after them from then on
+    let literal = |format: &mut NumberFormat, placeholders: usize, text: &str| {
+        if placeholders == 0 { format.prefix.push_str(text) } else { format.suffix.push_str(text) }
+    };
+
+    while let Some(character) = characters.next() {
+        match character {
+            ';' => break,
+            '"' => {
+                let text: String = characters.by_ref().take_while(|character| *character != '"').collect();
+                literal(&mut format, placeholders, &text);
+            },
+            '\\' => {
+                if let Some(escaped) = characters.next() {
+                    literal(&mut format, placeholders, &escaped.to_string());
+                }
+            },
+            // Padding to the width of a character, and fill characters
+            '_' | '*' => {
+                characters.next();
+            },
+            '[' => {
+                let bracket: String = characters.by_ref().take_while(|character| *character != ']').collect();
+                if let Some(currency) = bracket.strip_prefix('$') {
+                    literal(&mut format, placeholders, currency.split('-').next().unwrap_or(""));
+                } else if bracket.chars().all(|character| matches!(character.to_ascii_lowercase(), 'h' | 'm' | 's')) {
+                    // Elapsed time ([h]:mm)
+                    return None;
+                }
+                // Colours ([Red]) and conditions ([>=100]) change nothing here
+            },
+            '@' => return None,
+            '0' | '#' | '?' => {
+                placeholders += 1;
+                if pending_comma && !decimal_point {
+                    format.thousands = true;
+                }
+                pending_comma = false;
+                if decimal_point && !format.scientific {
+                    decimals += 1;
+                }
+            },
+            '.' if placeholders > 0 || characters.peek().is_some_and(|next| matches!(next, '0' | '#' | '?')) => decimal_point = true,
+            ',' if placeholders > 0 => pending_comma = true,
+            '%' => {
+                format.percent = true;
+                literal(&mut format, placeholders, "%");
+            },
+            'E' | 'e' if placeholders > 0 && characters.peek().is_some_and(|next| matches!(next, '+' | '-')) => {
+                characters.next();
+                format.scientific = true;
+            },
+            character if character.is_alphabetic() => {
+                // "General", and the letters of dates and times (yyyy-mm-dd, hh:mm AM/PM)
+                return None;
+            },
+            character => literal(&mut format, placeholders, &character.to_string()),
+        }
+    }
+
+    if placeholders == 0 {
+        return None;
+    }
+    format.decimals = Some(decimals);
+    Some(format)
+}
+
+// Number formats of the cells of every sheet, from the cell styles of "xl/styles.xml"
+fn xlsx_number_formats(package: &mut Package) -> Result<HashMap<String, CellFormats>> {
+    let mut sheets = HashMap::new();
+    let Some(styles_xml) = read_part(package, "xl/styles.xml")? else {
+        return Ok(sheets);
+    };
+    let styles = parse_xml(&styles_xml, "xl/styles.xml")?;
+    let root = styles.root_element();
+
+    let custom_codes: HashMap<u32, &str> = child(root, "numFmts")
+        .into_iter()
+        .flat_map(|formats| children(formats, "numFmt"))
+        .filter_map(|format| Some((attribute(format, "numFmtId")?.parse().ok()?, attribute(format, "formatCode")?)))
+        .collect();
+    // Cells refer to their style by its index in cellXfs
+    let style_formats: Vec<Option<NumberFormat>> = child(root, "cellXfs")
+        .into_iter()
+        .flat_map(|styles| children(styles, "xf"))
+        .map(|style| {
+            let id: u32 = attribute(style, "numFmtId")?.parse().ok()?;
+            parse_format_code(custom_codes.get(&id).copied().or_else(|| builtin_format_code(id))?)
+        })
+        .collect();
+    if style_formats.iter().all(Option::is_none) {
+        return Ok(sheets);
+    }
+
+    let Some(workbook_xml) = read_part(package, "xl/workbook.xml")? else {
+        return Ok(sheets);
+    };
+    let workbook = parse_xml(&workbook_xml, "xl/workbook.xml")?;
+    let parts = relationships(package, "xl/workbook.xml")?;
+
+    for sheet in workbook.descendants().filter(|node| is_named(*node, "sheet")) {
+        let (Some(name), Some(relationship)) = (attribute(sheet, "name"), relationship_id(sheet, "id").and_then(|id| parts.get(id))) else {
+            continue;
+        };
+        let Some(sheet_xml) = read_part(package, &relationship.target)? else {
+            continue;
+        };
+        let document = parse_xml(&sheet_xml, &relationship.target)?;
+
+        let mut formats = CellFormats::new();
+        for cell in document.descendants().filter(|node| is_named(*node, "c")) {
+            let format = attribute(cell, "s")
+                .and_then(|style| style.parse::<usize>().ok())
+                .and_then(|style| style_formats.get(style))
+                .and_then(Option::as_ref);
+            if let (Some(format), Some(position)) = (format, attribute(cell, "r").and_then(cell_position)) {
+                formats.insert(position, format.clone());
+            }
+        }
+        sheets.insert(name.to_string(), formats);
+    }
+
+    Ok(sheets)
+}
+
+// OpenDocument cells say their value type: percentages and currencies are displayed as such
+fn ods_number_formats(package: &mut Package) -> Result<HashMap<String, CellFormats>> {
+    let mut sheets = HashMap::new();
+    let Some(content_xml) = read_part(package, "content.xml")? else {
+        return Ok(sheets);
+    };
+    let content = parse_xml(&content_xml, "content.xml")?;
+
+    for table in content.descendants().filter(|node| is_named(*node, "table")) {
+        let Some(name) = attribute(table, "name") else {
+            continue;
+        };
+        let mut formats = CellFormats::new();
+        let mut row = 0;
+        ods_rows(table, &mut row, &mut formats);
+        sheets.insert(name.to_string(), formats);
+    }
+
+    Ok(sheets)
+}
+
+// Rows can be grouped (header rows, row groups), and rows and cells repeated
+fn ods_rows(parent: Node, row: &mut u32, formats: &mut CellFormats) {
+    let repeated = |node: Node, name: &str| attribute(node, name).and_then(|count| count.parse::<u32>().ok()).unwrap_or(1).max(1);
+
+    for node in parent.children().filter(|node| node.is_element()) {
+        match node.tag_name().name() {
+            "table-header-rows" | "table-rows" | "table-row-group" => ods_rows(node, row, formats),
+            "table-row" => {
+                let rows = repeated(node, "number-rows-repeated");
+                let mut column = 0;
+                for cell in node.children().filter(|cell| is_named(*cell, "table-cell") || is_named(*cell, "covered-table-cell")) {
+                    let columns = repeated(cell, "number-columns-repeated");
+                    if let Some(format) = ods_cell_format(cell) {
+                        for offset_row in 0..rows {
+                            for offset_column in 0..columns {
+                                formats.insert((*row + offset_row, column + offset_column), format.clone());
+                            }
+                        }
+                    }
+                    column += columns;
+                }
+                *row += rows;
+            },
+            _ => {},
+        }
+    }
+}
+
+fn ods_cell_format(cell: Node) -> Option<NumberFormat> {
+    match attribute(cell, "value-type")? {
+        "percentage" => Some(NumberFormat { percent: true, suffix: "%".to_string(), ..NumberFormat::default() }),
+        "currency" => {
+            let (prefix, suffix) = match attribute(cell, "currency").unwrap_or("") {
+                "USD" => ("$".to_string(), String::new()),
+                "GBP" => ("£".to_string(), String::new()),
+                "EUR" => (String::new(), " €".to_string()),
+                "JPY" => ("¥".to_string(), String::new()),
+                "" => (String::new(), String::new()),
+                code => (String::new(), format!(" {}", code)),
+            };
+            Some(NumberFormat { decimals: Some(2), thousands: true, prefix, suffix, ..NumberFormat::default() })
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    #[test]
+    fn numbers_keep_small_values_and_drop_float_noise() {
+        assert_eq!(format_number(1e-12), "0.000000000001");
+        assert_eq!(format_number(0.1 + 0.2), "0.3");
+        assert_eq!(format_number(-0.0), "0");
+        assert_eq!(format_number(42.0), "42");
+        assert_eq!(format_number(1234567.891), "1234567.891");
+        assert_eq!(format_number(-2.5e-7), "-0.00000025");
+        assert_eq!(format_number(6.02e300), "6.02e300");
+    }
+
+    #[test]
+    fn cells_are_displayed_as_text() {
+        assert_eq!(format_cell(&Data::String("  padded ".to_string()), None), "padded");
+        assert_eq!(format_cell(&Data::Int(7), None), "7");
+        assert_eq!(format_cell(&Data::Empty, None), "");
+    }
+
+    #[test]
+    fn column_names_run_past_z() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(701), "ZZ");
+    }
+
+    #[test]
+    fn format_codes_are_applied() {
+        let apply = |code: &str, number: f64| parse_format_code(code).map(|format| format.apply(number));
+        assert_eq!(apply("0.00%", 0.1234), Some("12.34%".to_string()));
+        assert_eq!(apply("#,##0", -1234567.4), Some("-1,234,567".to_string()));
+        assert_eq!(apply("$#,##0.00_);($#,##0.00)", 1234.5), Some("$1,234.50".to_string()));
+        assert_eq!(apply("[$€-407] #,##0.00", 1234.5), Some("€ 1,234.50".to_string()));
+        assert_eq!(apply("#,##0.00 \"kg\"", 2.0), Some("2.00 kg".to_string()));
+        assert_eq!(apply("0.00E+00", 12345.0), Some("1.23E+04".to_string()));
+        assert_eq!(apply("0.0", -0.01), Some("0.0".to_string()));
+        assert_eq!(apply("General", 1.0), None);
+        assert_eq!(apply("yyyy-mm-dd", 1.0), None);
+        assert_eq!(apply("@", 1.0), None);
+    }
+
+    fn package(parts: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in parts {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn budget_table(elements: &[DocumentElement]) -> Option<&Vec<Vec<String>>> {
+        elements.iter().find_map(|element| match element {
+            DocumentElement::Table { rows, .. } => Some(rows),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn xlsx_percent_and_currency_cells_use_their_style() {
+        let workbook = r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"
+            xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+            <sheets><sheet name="Budget" sheetId="1" r:id="rId1"/></sheets></workbook>"#;
+        let relationships = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+            <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/>
+            <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+            </Relationships>"#;
+        let styles = r#"<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+            <numFmts count="1"><numFmt numFmtId="164" formatCode="&quot;$&quot;#,##0.00"/></numFmts>
+            <cellXfs count="3"><xf numFmtId="0"/><xf numFmtId="9" applyNumberFormat="1"/><xf numFmtId="164" applyNumberFormat="1"/></cellXfs>
+            </styleSheet>"#;
+        let sheet = r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>
+            <row r="1"><c r="A1" t="inlineStr"><is><t>Item</t></is></c><c r="B1" t="inlineStr"><is><t>Share</t></is></c><c r="C1" t="inlineStr"><is><t>Cost</t></is></c></row>
+            <row r="2"><c r="A2" t="inlineStr"><is><t>Rent</t></is></c><c r="B2" s="1"><v>0.25</v></c><c r="C2" s="2"><v>1234.5</v></c></row>
+            <row r="3"><c r="A3" t="inlineStr"><is><t>Food</t></is></c><c r="B3"><v>0.75</v></c><c r="C3" s="0"><v>80</v></c></row>
+            </sheetData></worksheet>"#;
+        let content_types = r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+            <Default Extension="xml" ContentType="application/xml"/>
+            <Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>
+            </Types>"#;
+        let package_relationships = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+            <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>
+            </Relationships>"#;
+        let bytes = package(&[
+            ("[Content_Types].xml", content_types),
+            ("_rels/.rels", package_relationships),
+            ("xl/workbook.xml", workbook),
+            ("xl/_rels/workbook.xml.rels", relationships),
+            ("xl/styles.xml", styles),
+            ("xl/worksheets/sheet1.xml", sheet),
+        ]);
+
+        let elements = parse_xlsx_bytes(&bytes).unwrap();
+        assert_eq!(budget_table(&elements), Some(&vec![
+            vec!["Rent".to_string(), "25%".to_string(), "$1,234.50".to_string()],
+            vec!["Food".to_string(), "0.75".to_string(), "80".to_string()],
+        ]));
+    }
+
+    #[test]
+    fn ods_percent_and_currency_cells_use_their_value_type() {
+        let cell = |attributes: &str, text: &str| format!("<table:table-cell {}><text:p>{}</text:p></table:table-cell>", attributes, text);
+        let string = |text: &str| cell(r#"office:value-type="string""#, text);
+        // calamine expects the cells of a row without whitespace between them
+        let content = format!(
+            r#"<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+            xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+            <office:body><office:spreadsheet><table:table table:name="Budget"><table:table-row>{}{}{}</table:table-row><table:table-row>{}{}{}</table:table-row></table:table></office:spreadsheet></office:body></office:document-content>"#,
+            string("Item"), string("Share"), string("Cost"), string("Rent"),
+            cell(r#"office:value-type="percentage" office:value="0.25""#, "25 %"),
+            cell(r#"office:value-type="currency" office:currency="EUR" office:value="1234.5""#, "1.234,50 €"),
+        );
+        let manifest = r#"<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0">
+            <manifest:file-entry manifest:full-path="/" manifest:media-type="application/vnd.oasis.opendocument.spreadsheet"/>
+            <manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+            </manifest:manifest>"#;
+        let bytes = package(&[
+            ("mimetype", "application/vnd.oasis.opendocument.spreadsheet"),
+            ("META-INF/manifest.xml", manifest),
+            ("content.xml", &content),
+        ]);
+
+        let elements = parse_xlsx_bytes(&bytes).unwrap();
+        assert_eq!(budget_table(&elements), Some(&vec![
+            vec!["Rent".to_string(), "25%".to_string(), "1,234.50 €".to_string()],
+        ]));
+    }
+}